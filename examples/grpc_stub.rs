@@ -0,0 +1,371 @@
+use std::{env, time::Duration};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use h2::{
+    server::{self, SendResponse},
+    RecvStream,
+};
+use http::{HeaderMap, Request, Response};
+use prost::Message;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+    FileDescriptorProto, MethodDescriptorProto, ServiceDescriptorProto, Timestamp,
+};
+use tokio::net::{TcpListener, TcpStream};
+
+#[derive(Clone, PartialEq, Message)]
+struct HelloRequest {
+    #[prost(string, tag = "1")]
+    name: String,
+    #[prost(int32, tag = "2")]
+    count: i32,
+    #[prost(int32, tag = "3")]
+    mood: i32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct HelloReply {
+    #[prost(string, tag = "1")]
+    message: String,
+    #[prost(message, optional, tag = "2")]
+    sent_at: Option<Timestamp>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ServerReflectionRequest {
+    #[prost(string, tag = "1")]
+    host: String,
+    #[prost(oneof = "ReflectionRequest", tags = "3, 4, 7")]
+    message_request: Option<ReflectionRequest>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum ReflectionRequest {
+    #[prost(string, tag = "3")]
+    FileByFilename(String),
+    #[prost(string, tag = "4")]
+    FileContainingSymbol(String),
+    #[prost(string, tag = "7")]
+    ListServices(String),
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ServerReflectionResponse {
+    #[prost(oneof = "ReflectionResponse", tags = "4, 6, 7")]
+    message_response: Option<ReflectionResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum ReflectionResponse {
+    #[prost(message, tag = "4")]
+    FileDescriptors(FileDescriptorResponse),
+    #[prost(message, tag = "6")]
+    ListServices(ListServiceResponse),
+    #[prost(message, tag = "7")]
+    Error(ErrorResponse),
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct FileDescriptorResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    file_descriptor_proto: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ListServiceResponse {
+    #[prost(message, repeated, tag = "1")]
+    service: Vec<ServiceResponse>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ServiceResponse {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct ErrorResponse {
+    #[prost(int32, tag = "1")]
+    error_code: i32,
+    #[prost(string, tag = "2")]
+    error_message: String,
+}
+
+/// A tiny local gRPC server for trying out gRPC requests.
+///
+///     cargo run --example grpc_stub -- 50051
+///
+/// Serves `demo.Greeter` from `examples/greeter.proto` over plaintext
+/// HTTP/2, with server reflection (`v1alpha`) so the definitions can be
+/// loaded either way. `SayHello` is unary, `StreamHellos` sends `count`
+/// replies half a second apart. Request paths and headers are printed.
+fn main() {
+    let port = env::args().nth(1).unwrap_or_else(|| String::from("50051"));
+    let listener =
+        std::net::TcpListener::bind(format!("127.0.0.1:{}", port)).expect("Could not bind port");
+    println!("gRPC stub listening on grpc://127.0.0.1:{}", port);
+    run(listener);
+}
+
+/// Serves connections on `listener` until the process exits.
+pub fn run(listener: std::net::TcpListener) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Could not start runtime");
+
+    runtime.block_on(async move {
+        listener
+            .set_nonblocking(true)
+            .expect("Could not configure listener");
+        let listener = TcpListener::from_std(listener).expect("Could not register listener");
+
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(serve(stream));
+        }
+    });
+}
+
+async fn serve(stream: TcpStream) {
+    let mut connection = match server::handshake(stream).await {
+        Ok(connection) => connection,
+        Err(err) => {
+            println!("Handshake failed: {}", err);
+            return;
+        }
+    };
+
+    while let Some(Ok((request, respond))) = connection.accept().await {
+        tokio::spawn(handle(request, respond));
+    }
+}
+
+async fn handle(request: Request<RecvStream>, mut respond: SendResponse<Bytes>) {
+    let path = request.uri().path().to_string();
+    println!("{} {}", request.method(), path);
+    for (key, value) in request.headers() {
+        println!("  {}: {}", key, value.to_str().unwrap_or("<binary>"));
+    }
+
+    let mut body = request.into_body();
+    let mut buffer = BytesMut::new();
+    let response = Response::builder()
+        .header("content-type", "application/grpc")
+        .body(())
+        .unwrap();
+
+    match path.as_str() {
+        "/demo.Greeter/SayHello" | "/demo.Greeter/StreamHellos" => {
+            let hello = match read_message(&mut body, &mut buffer).await {
+                Some(bytes) => HelloRequest::decode(bytes).unwrap_or_default(),
+                None => HelloRequest::default(),
+            };
+            println!("  request: {:?}", hello);
+
+            let greeting = if hello.mood == 1 { "Hi there" } else { "Hello" };
+            let name = if hello.name.is_empty() {
+                "stranger"
+            } else {
+                hello.name.as_str()
+            };
+            let count = if path.ends_with("SayHello") {
+                1
+            } else {
+                hello.count.max(1)
+            };
+
+            let mut stream = match respond.send_response(response, false) {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            for index in 0..count {
+                if index > 0 {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                let reply = HelloReply {
+                    message: format!("{}, {}! ({}/{})", greeting, name, index + 1, count),
+                    sent_at: Some(now()),
+                };
+                if stream
+                    .send_data(frame(&reply.encode_to_vec()), false)
+                    .is_err()
+                {
+                    return;
+                }
+            }
+            let _ = stream.send_trailers(status(0, ""));
+        }
+        "/grpc.reflection.v1alpha.ServerReflection/ServerReflectionInfo" => {
+            let mut stream = match respond.send_response(response, false) {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            while let Some(bytes) = read_message(&mut body, &mut buffer).await {
+                let request = ServerReflectionRequest::decode(bytes).unwrap_or_default();
+                println!("  reflection: {:?}", request.message_request);
+                let reply = ServerReflectionResponse {
+                    message_response: Some(reflect(request.message_request)),
+                };
+                if stream
+                    .send_data(frame(&reply.encode_to_vec()), false)
+                    .is_err()
+                {
+                    return;
+                }
+            }
+            let _ = stream.send_trailers(status(0, ""));
+        }
+        _ => {
+            // Trailers-only, as real servers answer unknown methods.
+            let mut response = response;
+            response
+                .headers_mut()
+                .extend(status(12, "Method not found"));
+            let _ = respond.send_response(response, true);
+        }
+    }
+}
+
+fn reflect(request: Option<ReflectionRequest>) -> ReflectionResponse {
+    match request {
+        Some(ReflectionRequest::ListServices(_)) => {
+            ReflectionResponse::ListServices(ListServiceResponse {
+                service: ["demo.Greeter", "grpc.reflection.v1alpha.ServerReflection"]
+                    .iter()
+                    .map(|name| ServiceResponse {
+                        name: name.to_string(),
+                    })
+                    .collect(),
+            })
+        }
+        Some(ReflectionRequest::FileContainingSymbol(symbol)) if symbol.starts_with("demo.") => {
+            ReflectionResponse::FileDescriptors(FileDescriptorResponse {
+                file_descriptor_proto: vec![greeter_file().encode_to_vec()],
+            })
+        }
+        Some(ReflectionRequest::FileByFilename(name)) if name == "greeter.proto" => {
+            ReflectionResponse::FileDescriptors(FileDescriptorResponse {
+                file_descriptor_proto: vec![greeter_file().encode_to_vec()],
+            })
+        }
+        _ => ReflectionResponse::Error(ErrorResponse {
+            error_code: 5,
+            error_message: String::from("not found"),
+        }),
+    }
+}
+
+/// What protoc would produce for `examples/greeter.proto`.
+fn greeter_file() -> FileDescriptorProto {
+    let field =
+        |name: &str, number: i32, kind: Type, type_name: Option<&str>| FieldDescriptorProto {
+            name: Some(name.to_string()),
+            number: Some(number),
+            label: Some(Label::Optional as i32),
+            r#type: Some(kind as i32),
+            type_name: type_name.map(str::to_string),
+            json_name: None,
+            ..Default::default()
+        };
+    let method = |name: &str, server_streaming: bool| MethodDescriptorProto {
+        name: Some(name.to_string()),
+        input_type: Some(String::from(".demo.HelloRequest")),
+        output_type: Some(String::from(".demo.HelloReply")),
+        server_streaming: Some(server_streaming),
+        ..Default::default()
+    };
+
+    FileDescriptorProto {
+        name: Some(String::from("greeter.proto")),
+        package: Some(String::from("demo")),
+        dependency: vec![String::from("google/protobuf/timestamp.proto")],
+        syntax: Some(String::from("proto3")),
+        message_type: vec![
+            DescriptorProto {
+                name: Some(String::from("HelloRequest")),
+                field: vec![
+                    field("name", 1, Type::String, None),
+                    field("count", 2, Type::Int32, None),
+                    field("mood", 3, Type::Enum, Some(".demo.HelloRequest.Mood")),
+                ],
+                enum_type: vec![EnumDescriptorProto {
+                    name: Some(String::from("Mood")),
+                    value: ["NEUTRAL", "CHEERFUL"]
+                        .iter()
+                        .enumerate()
+                        .map(|(number, name)| EnumValueDescriptorProto {
+                            name: Some(name.to_string()),
+                            number: Some(number as i32),
+                            ..Default::default()
+                        })
+                        .collect(),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            },
+            DescriptorProto {
+                name: Some(String::from("HelloReply")),
+                field: vec![
+                    field("message", 1, Type::String, None),
+                    field(
+                        "sent_at",
+                        2,
+                        Type::Message,
+                        Some(".google.protobuf.Timestamp"),
+                    ),
+                ],
+                ..Default::default()
+            },
+        ],
+        service: vec![ServiceDescriptorProto {
+            name: Some(String::from("Greeter")),
+            method: vec![method("SayHello", false), method("StreamHellos", true)],
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+fn now() -> Timestamp {
+    let elapsed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    Timestamp {
+        seconds: elapsed.as_secs() as i64,
+        nanos: elapsed.subsec_nanos() as i32,
+    }
+}
+
+fn status(code: u32, message: &str) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("grpc-status", code.into());
+    if !message.is_empty() {
+        headers.insert("grpc-message", message.parse().unwrap());
+    }
+    headers
+}
+
+fn frame(message: &[u8]) -> Bytes {
+    let mut framed = BytesMut::with_capacity(message.len() + 5);
+    framed.put_u8(0);
+    framed.put_u32(message.len() as u32);
+    framed.put_slice(message);
+    framed.freeze()
+}
+
+async fn read_message(body: &mut RecvStream, buffer: &mut BytesMut) -> Option<Bytes> {
+    loop {
+        if buffer.len() >= 5 {
+            let length = u32::from_be_bytes([buffer[1], buffer[2], buffer[3], buffer[4]]) as usize;
+            if buffer.len() >= length + 5 {
+                buffer.advance(5);
+                return Some(buffer.split_to(length).freeze());
+            }
+        }
+
+        let chunk = body.data().await?.ok()?;
+        let _ = body.flow_control().release_capacity(chunk.len());
+        buffer.extend_from_slice(&chunk);
+    }
+}
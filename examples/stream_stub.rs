@@ -0,0 +1,110 @@
+use std::{
+    env,
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::Duration,
+};
+
+/// A tiny local server for trying out streamed responses.
+///
+///     cargo run --example stream_stub -- 8080
+///
+/// * `/sse`    - an endless `text/event-stream`, one event every 500 ms
+/// * `/ndjson` - ten `application/x-ndjson` records, then the body ends
+/// * `/logs`   - endless plain text log lines (send with `S` to stream it)
+fn main() {
+    let port = env::args().nth(1).unwrap_or_else(|| String::from("8080"));
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).expect("Could not bind port");
+    println!("Streaming stub listening on http://127.0.0.1:{}", port);
+    run(listener);
+}
+
+/// Serves connections on `listener`, one thread each, until the process
+/// exits.
+pub fn run(listener: TcpListener) {
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            // Writes fail once the client hangs up, which ends the handler.
+            let _ = handle(stream);
+        });
+    }
+}
+
+fn handle(mut stream: TcpStream) -> std::io::Result<()> {
+    let mut request_line = String::new();
+    let mut reader = BufReader::new(stream.try_clone()?);
+    reader.read_line(&mut request_line)?;
+
+    // Skip the request headers.
+    let mut line = String::new();
+    while reader.read_line(&mut line)? > 2 {
+        line.clear();
+    }
+
+    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+    let route = path.split('?').next().unwrap_or(path);
+
+    match route {
+        "/sse" => {
+            start(&mut stream, "text/event-stream")?;
+            write_chunk(&mut stream, ": connected to the stub server\n\n")?;
+            for id in 1.. {
+                let event = match id % 3 {
+                    0 => format!("event: tick\nid: {}\ndata: {{\"tick\": {}}}\n\n", id, id),
+                    1 => format!("id: {}\ndata: message {}\n\n", id, id),
+                    _ => format!(
+                        "event: multiline\nid: {}\nretry: 3000\ndata: first line\ndata: second line\n\n",
+                        id
+                    ),
+                };
+                write_chunk(&mut stream, &event)?;
+                thread::sleep(Duration::from_millis(500));
+            }
+        }
+        "/ndjson" => {
+            start(&mut stream, "application/x-ndjson")?;
+            for id in 1..=10 {
+                write_chunk(
+                    &mut stream,
+                    &format!("{{\"id\": {}, \"status\": \"ok\"}}\n", id),
+                )?;
+                thread::sleep(Duration::from_millis(300));
+            }
+            write_chunk(&mut stream, "")?;
+        }
+        "/logs" => {
+            start(&mut stream, "text/plain")?;
+            for line in 1.. {
+                write_chunk(&mut stream, &format!("INFO log line {}\n", line))?;
+                thread::sleep(Duration::from_millis(250));
+            }
+        }
+        _ => {
+            let body = "Try /sse, /ndjson or /logs\n";
+            write!(
+                stream,
+                "HTTP/1.1 404 Not Found\r\ncontent-type: text/plain\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn start(stream: &mut TcpStream, content_type: &str) -> std::io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\ncontent-type: {}\r\ncache-control: no-cache\r\ntransfer-encoding: chunked\r\nconnection: close\r\n\r\n",
+        content_type
+    )?;
+    stream.flush()
+}
+
+/// Writes one chunk of a chunked body. An empty chunk ends the body.
+fn write_chunk(stream: &mut TcpStream, data: &str) -> std::io::Result<()> {
+    write!(stream, "{:x}\r\n{}\r\n", data.len(), data)?;
+    stream.flush()
+}
@@ -0,0 +1,53 @@
+use std::{env, net::TcpListener, thread};
+
+use tungstenite::{
+    accept_hdr,
+    handshake::server::{ErrorResponse, Request, Response},
+    Message,
+};
+
+/// A tiny local WebSocket server for trying out WebSocket requests.
+///
+///     cargo run --example websocket_echo -- 9001
+///
+/// Every text and binary message is sent straight back. The handshake
+/// headers are printed so header rows can be checked as well.
+fn main() {
+    let port = env::args().nth(1).unwrap_or_else(|| String::from("9001"));
+    let listener = TcpListener::bind(format!("127.0.0.1:{}", port)).expect("Could not bind port");
+    println!("WebSocket echo listening on ws://127.0.0.1:{}", port);
+
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            let mut socket = match accept_hdr(stream, print_handshake) {
+                Ok(socket) => socket,
+                Err(err) => {
+                    println!("Handshake failed: {}", err);
+                    return;
+                }
+            };
+
+            loop {
+                match socket.read() {
+                    Ok(message @ Message::Text(_)) | Ok(message @ Message::Binary(_)) => {
+                        if socket.send(message).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(Message::Close(_)) | Err(_) => return,
+                    Ok(_) => (),
+                }
+            }
+        });
+    }
+}
+
+// The error type is tungstenite's, however large it is.
+#[allow(clippy::result_large_err)]
+fn print_handshake(request: &Request, response: Response) -> Result<Response, ErrorResponse> {
+    println!("Handshake for {}", request.uri());
+    for (key, value) in request.headers() {
+        println!("  {}: {}", key, value.to_str().unwrap_or("<binary>"));
+    }
+    Ok(response)
+}
@@ -0,0 +1 @@
+drop table tls_config;
@@ -0,0 +1,12 @@
+create table tls_config (
+    id                      integer     not null    primary key     autoincrement,
+    api_id                  integer     not null    unique,
+    ca_bundles              text        not null    default '',
+    client_certificate      text,
+    client_key              text,
+    client_password         text,
+    min_tls_version         text        check(min_tls_version in ('tls10', 'tls11', 'tls12', 'tls13')),
+    accept_invalid_certs    boolean     not null    default 0,
+
+    foreign key (api_id) references api (id)
+);
@@ -0,0 +1,30 @@
+-- The old table only holds five methods. Rather than losing the requests
+-- using any other, going back stops until they are changed or deleted.
+create temp table method_check (request_id integer);
+
+create temp trigger method_check_abort before insert on method_check
+begin
+    select raise(abort, 'Change or delete the requests using HEAD, OPTIONS, TRACE, CONNECT or custom methods first');
+end;
+
+insert into method_check
+select id from request where method not in ('get', 'post', 'delete', 'put', 'patch') limit 1;
+
+drop table method_check;
+
+create table request_old (
+    id              integer     not null    primary key     autoincrement,
+    route           text        not null,
+    method          text        not null    check(method in ('get', 'post', 'delete', 'put', 'patch')),
+    body            text,
+    resource_id     integer     not null,
+
+    foreign key (resource_id) references resource (id)
+);
+
+insert into request_old (id, route, method, body, resource_id)
+select id, route, method, body, resource_id from request;
+
+drop table request;
+
+alter table request_old rename to request;
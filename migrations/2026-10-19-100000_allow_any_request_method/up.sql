@@ -0,0 +1,19 @@
+create table request_new (
+    id              integer     not null    primary key     autoincrement,
+    route           text        not null,
+    method          text        not null    check(
+                                                method in ('get', 'post', 'delete', 'put', 'patch', 'head', 'options', 'trace', 'connect')
+                                                or (method = upper(method) and length(method) > 0)
+                                            ),
+    body            text,
+    resource_id     integer     not null,
+
+    foreign key (resource_id) references resource (id)
+);
+
+insert into request_new (id, route, method, body, resource_id)
+select id, route, method, body, resource_id from request;
+
+drop table request;
+
+alter table request_new rename to request;
@@ -0,0 +1 @@
+drop table form_part;
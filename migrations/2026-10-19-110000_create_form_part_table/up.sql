@@ -0,0 +1,10 @@
+create table form_part (
+    id              integer     not null    primary key     autoincrement,
+    name            text        not null,
+    kind            text        not null    check(kind in ('text', 'file')),
+    value           text        not null,
+    content_type    text,
+    request_id      integer     not null,
+
+    foreign key (request_id) references request (id)
+);
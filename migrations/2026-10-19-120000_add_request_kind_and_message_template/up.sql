@@ -0,0 +1,11 @@
+alter table request add column kind text not null default 'http';
+
+create table message_template (
+    id          integer     not null    primary key     autoincrement,
+    name        text        not null,
+    body        text        not null,
+    binary      boolean     not null    default 0,
+    request_id  integer     not null,
+
+    foreign key (request_id) references request (id)
+);
@@ -0,0 +1,2 @@
+drop table graphql_schema;
+drop table graphql_query;
@@ -0,0 +1,18 @@
+create table graphql_query (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null    unique,
+    query           text        not null    default '',
+    variables       text        not null    default '',
+    operation_name  text,
+
+    foreign key (request_id) references request (id)
+);
+
+create table graphql_schema (
+    id              integer     not null    primary key     autoincrement,
+    api_id          integer     not null    unique,
+    introspection   text        not null,
+    fetched_at      text        not null,
+
+    foreign key (api_id) references api (id)
+);
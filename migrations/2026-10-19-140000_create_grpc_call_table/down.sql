@@ -0,0 +1 @@
+drop table grpc_call;
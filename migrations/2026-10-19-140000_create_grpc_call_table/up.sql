@@ -0,0 +1,9 @@
+create table grpc_call (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null    unique,
+    proto_files     text        not null    default '',
+    method          text,
+    message         text        not null    default '{}',
+
+    foreign key (request_id) references request (id)
+);
@@ -0,0 +1,12 @@
+create table import_snapshot (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null    unique,
+    source_key      text        not null,
+    route           text        not null,
+    method          text        not null,
+    body            text,
+    headers         text        not null    default '[]',
+    params          text        not null    default '[]',
+
+    foreign key (request_id) references request (id)
+);
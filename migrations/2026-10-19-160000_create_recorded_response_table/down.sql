@@ -0,0 +1 @@
+drop table recorded_response;
@@ -0,0 +1,11 @@
+create table recorded_response (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null,
+    status          integer     not null,
+    content_type    text,
+    body            text        not null,
+    recorded_at     text        not null,
+
+    unique (request_id, status),
+    foreign key (request_id) references request (id)
+);
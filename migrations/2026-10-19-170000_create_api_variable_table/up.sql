@@ -0,0 +1,9 @@
+create table api_variable (
+    id          integer     not null    primary key     autoincrement,
+    api_id      integer     not null,
+    key         text        not null,
+    value       text        not null    default '',
+
+    unique (api_id, key),
+    foreign key (api_id) references api (id)
+);
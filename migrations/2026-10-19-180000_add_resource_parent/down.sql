@@ -0,0 +1,26 @@
+-- Nested folders are flattened back into names like `Pets / Photos`.
+with recursive path (id, name, parent_id) as (
+    select id, name, parent_id from resource
+    union all
+    select path.id, parent.name || ' / ' || path.name, parent.parent_id
+    from path
+    join resource parent on parent.id = path.parent_id
+)
+update resource set name = (
+    select path.name from path where path.id = resource.id and path.parent_id is null
+);
+
+create table resource_old (
+    id              integer     not null    primary key     autoincrement,
+    name            text        not null,
+    api_id          integer     not null,
+
+    foreign key (api_id) references api (id)
+);
+
+insert into resource_old (id, name, api_id)
+select id, name, api_id from resource;
+
+drop table resource;
+
+alter table resource_old rename to resource;
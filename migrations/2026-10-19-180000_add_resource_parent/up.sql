@@ -0,0 +1,51 @@
+alter table resource add column parent_id integer references resource (id);
+
+-- Importers used to flatten folders into names like `Pets / Photos`. Every
+-- leading part of such a name becomes a folder of its own, created when
+-- missing, and the resource moves into it under its last part. Only the
+-- resources holding imported requests are split, so a name typed in like
+-- `Read / Write` is left alone.
+create table resource_path as
+with recursive split (api_id, parent, path, rest) as (
+    select  api_id,
+            null,
+            substr(name, 1, instr(name || ' / ', ' / ') - 1),
+            substr(name || ' / ', instr(name || ' / ', ' / ') + 3)
+    from resource
+    where exists (
+        select 1 from request
+        join import_snapshot on import_snapshot.request_id = request.id
+        where request.resource_id = resource.id
+    )
+    union all
+    select  api_id,
+            path,
+            path || ' / ' || substr(rest, 1, instr(rest, ' / ') - 1),
+            substr(rest, instr(rest, ' / ') + 3)
+    from split
+    where rest <> ''
+)
+select distinct api_id, parent, path from split;
+
+insert into resource (name, api_id)
+select path, api_id from resource_path
+where not exists (
+    select 1 from resource
+    where resource.api_id = resource_path.api_id and resource.name = resource_path.path
+)
+order by length(path);
+
+update resource set parent_id = (
+    select min(parent.id) from resource_path
+    join resource parent
+        on parent.api_id = resource_path.api_id and parent.name = resource_path.parent
+    where resource_path.api_id = resource.api_id and resource_path.path = resource.name
+);
+
+update resource set name = substr(name, length((
+    select resource_path.parent from resource_path
+    where resource_path.api_id = resource.api_id and resource_path.path = resource.name
+)) + 4)
+where parent_id is not null;
+
+drop table resource_path;
@@ -0,0 +1 @@
+drop table expanded_node;
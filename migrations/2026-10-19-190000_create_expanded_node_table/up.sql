@@ -0,0 +1,7 @@
+create table expanded_node (
+    id          integer     not null    primary key     autoincrement,
+    kind        text        not null    check(kind in ('api', 'resource')),
+    node_id     integer     not null,
+
+    unique (kind, node_id)
+);
@@ -0,0 +1,4 @@
+alter table request drop column description;
+alter table request drop column name;
+alter table resource drop column description;
+alter table api drop column description;
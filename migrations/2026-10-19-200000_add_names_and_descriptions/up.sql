@@ -0,0 +1,19 @@
+alter table api add column description text;
+alter table resource add column description text;
+alter table request add column name text;
+alter table request add column description text;
+
+-- Postman collections and `.http` files gave requests names, kept so far
+-- as the last part of their import key, e.g. `Products / List products`.
+update request set name = (
+    with recursive tail (rest) as (
+        select source_key from import_snapshot where import_snapshot.request_id = request.id
+        union all
+        select substr(rest, instr(rest, ' / ') + 3) from tail where instr(rest, ' / ') > 0
+    )
+    select rest from tail where instr(rest, ' / ') = 0
+)
+where exists (
+    select 1 from import_snapshot
+    where import_snapshot.request_id = request.id and instr(source_key, ' / ') > 0
+);
@@ -0,0 +1,4 @@
+alter table request drop column last_run_at;
+alter table request drop column position;
+alter table resource drop column position;
+alter table api drop column position;
@@ -0,0 +1,6 @@
+-- Things without a position are ordered as if their id was one, so lists
+-- keep the order they were created in until something is moved.
+alter table api add column position integer;
+alter table resource add column position integer;
+alter table request add column position integer;
+alter table request add column last_run_at text;
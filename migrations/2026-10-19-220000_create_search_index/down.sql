@@ -0,0 +1,13 @@
+drop trigger recorded_response_search_delete;
+drop trigger recorded_response_search_update;
+drop trigger recorded_response_search_insert;
+drop trigger param_search_delete;
+drop trigger param_search_update;
+drop trigger param_search_insert;
+drop trigger header_search_delete;
+drop trigger header_search_update;
+drop trigger header_search_insert;
+drop trigger request_search_delete;
+drop trigger request_search_update;
+drop trigger request_search_insert;
+drop table search_index;
@@ -0,0 +1,85 @@
+-- What can be searched for: request bodies, headers, params and the
+-- responses recorded for requests, each with the request it belongs to.
+-- The rowid is made from the id of what was indexed and its source, body
+-- 0, header 1, param 2 and response 3, so the triggers find a row by its
+-- key instead of scanning the index.
+create virtual table search_index using fts5(
+    source unindexed,
+    request_id unindexed,
+    content
+);
+
+insert into search_index (rowid, source, request_id, content)
+select id * 4, 'body', id, body from request where body is not null;
+insert into search_index (rowid, source, request_id, content)
+select id * 4 + 1, 'header', request_id, key || ': ' || value from header;
+insert into search_index (rowid, source, request_id, content)
+select id * 4 + 2, 'param', request_id, key || '=' || value from param;
+insert into search_index (rowid, source, request_id, content)
+select id * 4 + 3, 'response', request_id, body from recorded_response;
+
+-- Kept up to date by triggers, so imports, restores and the files backend
+-- all leave it right.
+create trigger request_search_insert after insert on request when new.body is not null
+begin
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4, 'body', new.id, new.body);
+end;
+create trigger request_search_update after update of body on request
+begin
+    delete from search_index where rowid = old.id * 4;
+    insert into search_index (rowid, source, request_id, content)
+    select new.id * 4, 'body', new.id, new.body where new.body is not null;
+end;
+create trigger request_search_delete after delete on request
+begin
+    delete from search_index where rowid = old.id * 4;
+end;
+
+create trigger header_search_insert after insert on header
+begin
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 1, 'header', new.request_id, new.key || ': ' || new.value);
+end;
+create trigger header_search_update after update on header
+begin
+    delete from search_index where rowid = old.id * 4 + 1;
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 1, 'header', new.request_id, new.key || ': ' || new.value);
+end;
+create trigger header_search_delete after delete on header
+begin
+    delete from search_index where rowid = old.id * 4 + 1;
+end;
+
+create trigger param_search_insert after insert on param
+begin
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 2, 'param', new.request_id, new.key || '=' || new.value);
+end;
+create trigger param_search_update after update on param
+begin
+    delete from search_index where rowid = old.id * 4 + 2;
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 2, 'param', new.request_id, new.key || '=' || new.value);
+end;
+create trigger param_search_delete after delete on param
+begin
+    delete from search_index where rowid = old.id * 4 + 2;
+end;
+
+create trigger recorded_response_search_insert after insert on recorded_response
+begin
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 3, 'response', new.request_id, new.body);
+end;
+create trigger recorded_response_search_update after update on recorded_response
+begin
+    delete from search_index where rowid = old.id * 4 + 3;
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 3, 'response', new.request_id, new.body);
+end;
+create trigger recorded_response_search_delete after delete on recorded_response
+begin
+    delete from search_index where rowid = old.id * 4 + 3;
+end;
@@ -0,0 +1,2 @@
+drop table tag;
+alter table request drop column favourite;
@@ -0,0 +1,10 @@
+alter table request add column favourite boolean not null default false;
+
+create table tag (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null,
+    name            text        not null,
+
+    unique (request_id, name),
+    foreign key (request_id) references request (id)
+);
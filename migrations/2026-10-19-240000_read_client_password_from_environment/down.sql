@@ -0,0 +1,21 @@
+-- The dropped passwords cannot be restored and have to be entered again.
+create table tls_config_old (
+    id                      integer     not null    primary key     autoincrement,
+    api_id                  integer     not null    unique,
+    ca_bundles              text        not null    default '',
+    client_certificate      text,
+    client_key              text,
+    client_password         text,
+    min_tls_version         text        check(min_tls_version in ('tls10', 'tls11', 'tls12', 'tls13')),
+    accept_invalid_certs    boolean     not null    default 0,
+
+    foreign key (api_id) references api (id)
+);
+
+insert into tls_config_old (id, api_id, ca_bundles, client_certificate, client_key, min_tls_version, accept_invalid_certs)
+select id, api_id, ca_bundles, client_certificate, client_key, min_tls_version, accept_invalid_certs
+from tls_config;
+
+drop table tls_config;
+
+alter table tls_config_old rename to tls_config;
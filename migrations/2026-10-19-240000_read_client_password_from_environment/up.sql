@@ -0,0 +1,25 @@
+-- Certificate passwords are no longer stored. The column now names the
+-- environment variable holding one, so saved passwords are dropped.
+-- native-tls cannot require TLS 1.3, so that minimum becomes TLS 1.2.
+create table tls_config_new (
+    id                      integer     not null    primary key     autoincrement,
+    api_id                  integer     not null    unique,
+    ca_bundles              text        not null    default '',
+    client_certificate      text,
+    client_key              text,
+    client_password_env     text,
+    min_tls_version         text        check(min_tls_version in ('tls10', 'tls11', 'tls12')),
+    accept_invalid_certs    boolean     not null    default 0,
+
+    foreign key (api_id) references api (id)
+);
+
+insert into tls_config_new (id, api_id, ca_bundles, client_certificate, client_key, min_tls_version, accept_invalid_certs)
+select id, api_id, ca_bundles, client_certificate, client_key,
+       case min_tls_version when 'tls13' then 'tls12' else min_tls_version end,
+       accept_invalid_certs
+from tls_config;
+
+drop table tls_config;
+
+alter table tls_config_new rename to tls_config;
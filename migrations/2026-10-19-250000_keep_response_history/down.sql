@@ -0,0 +1,42 @@
+-- Keeps the latest textual response per status.
+create table recorded_response_old (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null,
+    status          integer     not null,
+    content_type    text,
+    body            text        not null,
+    recorded_at     text        not null,
+
+    unique (request_id, status),
+    foreign key (request_id) references request (id)
+);
+
+insert into recorded_response_old (id, request_id, status, content_type, body, recorded_at)
+select id, request_id, status, content_type, body, recorded_at
+from recorded_response
+where id in (
+    select max(id) from recorded_response where not body_base64 group by request_id, status
+);
+
+delete from search_index
+where source = 'response' and rowid not in (select id * 4 + 3 from recorded_response_old);
+
+drop table recorded_response;
+
+alter table recorded_response_old rename to recorded_response;
+
+create trigger recorded_response_search_insert after insert on recorded_response
+begin
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 3, 'response', new.request_id, new.body);
+end;
+create trigger recorded_response_search_update after update on recorded_response
+begin
+    delete from search_index where rowid = old.id * 4 + 3;
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 3, 'response', new.request_id, new.body);
+end;
+create trigger recorded_response_search_delete after delete on recorded_response
+begin
+    delete from search_index where rowid = old.id * 4 + 3;
+end;
@@ -0,0 +1,50 @@
+-- Every exchange is kept, not one response per status: the response
+-- headers, how long it took, binary bodies as base64, and the request as it
+-- was sent. Older rows have none of those, so they get empty ones.
+create table recorded_response_new (
+    id              integer     not null    primary key     autoincrement,
+    request_id      integer     not null,
+    status          integer     not null,
+    content_type    text,
+    body            text        not null,
+    recorded_at     text        not null,
+    headers         text        not null    default '[]',
+    body_base64     boolean     not null    default 0,
+    duration_ms     integer     not null    default 0,
+    method          text        not null    default '',
+    url             text        not null    default '',
+    request_headers text        not null    default '[]',
+    request_body    text,
+
+    foreign key (request_id) references request (id)
+);
+
+insert into recorded_response_new (id, request_id, status, content_type, body, recorded_at)
+select id, request_id, status, content_type, body, recorded_at
+from recorded_response;
+
+drop table recorded_response;
+
+alter table recorded_response_new rename to recorded_response;
+
+create index recorded_response_request on recorded_response (request_id, id);
+
+-- Dropping the table dropped its search triggers. Binary bodies are not
+-- worth searching.
+create trigger recorded_response_search_insert after insert on recorded_response
+when not new.body_base64
+begin
+    insert into search_index (rowid, source, request_id, content)
+    values (new.id * 4 + 3, 'response', new.request_id, new.body);
+end;
+create trigger recorded_response_search_update after update on recorded_response
+begin
+    delete from search_index where rowid = old.id * 4 + 3;
+    insert into search_index (rowid, source, request_id, content)
+    select new.id * 4 + 3, 'response', new.request_id, new.body
+    where not new.body_base64;
+end;
+create trigger recorded_response_search_delete after delete on recorded_response
+begin
+    delete from search_index where rowid = old.id * 4 + 3;
+end;
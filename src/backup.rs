@@ -0,0 +1,528 @@
+use std::{fmt, fs};
+
+use chrono::Local;
+use serde_json::{Map, Value};
+use serde_yaml::Mapping;
+use thiserror::Error;
+
+use crate::{
+    storage,
+    storage::{
+        document::{self, insert, push_document, LoadedRow, Nested, API_TABLES, REQUEST_TABLES},
+        StorageError,
+    },
+};
+
+const FORMAT: &str = "reqstr-backup";
+/// Raised when a backup could not be restored by older versions.
+const VERSION: i64 = 1;
+/// Columns that say how a row is used here rather than what it is, so two
+/// machines differing in them do not conflict.
+const VOLATILE: [&str; 3] = ["last_run_at", "position", "favourite"];
+
+#[derive(Debug, Error)]
+pub enum BackupError {
+    #[error("Could not read {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+    #[error("Not a valid backup: {0}")]
+    Syntax(String),
+    #[error("The backup is version {0}, only version {1} and older can be restored")]
+    Version(i64, i64),
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum RestoreMode {
+    /// Everything is deleted, then the backup is restored with its ids.
+    Replace,
+    /// APIs and resources are matched by name, requests by method and
+    /// route, and variables by key. What is missing is added, what differs
+    /// is kept as it is and reported.
+    Merge,
+}
+
+#[derive(Default)]
+pub struct RestoreSummary {
+    pub added: usize,
+    pub unchanged: usize,
+    pub conflicts: Vec<String>,
+}
+
+impl fmt::Display for RestoreSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} unchanged, {} conflicting",
+            self.added,
+            self.unchanged,
+            self.conflicts.len()
+        )
+    }
+}
+
+impl RestoreSummary {
+    /// Counts a row of the backup as unchanged when it is the same as one of
+    /// the rows it matches here, or as a conflict. Ids, `VOLATILE` columns,
+    /// the `skip` keys and local tables are not compared.
+    fn compare(
+        &mut self,
+        restored: &Map<String, Value>,
+        here: &[&Map<String, Value>],
+        skip: &[&str],
+        nested: &[Nested],
+        name: String,
+    ) {
+        let comparable = |row: &Map<String, Value>| {
+            let mut row = row.clone();
+            for key in skip.iter().chain(&VOLATILE) {
+                row.remove(*key);
+            }
+            for nested in nested.iter().filter(|nested| nested.local) {
+                row.remove(nested.key);
+            }
+            strip_ids(&mut row);
+            row
+        };
+
+        let restored = comparable(restored);
+        if here.iter().any(|here| comparable(here) == restored) {
+            self.unchanged += 1;
+        } else {
+            self.conflicts.push(name);
+        }
+    }
+}
+
+/// What a restored row belongs to: a row restored with it, or one that
+/// was already there.
+#[derive(Clone, Copy)]
+enum Parent {
+    Restored(usize),
+    Existing(i64),
+}
+
+/// The whole database as a backup, one versioned document that `restore`
+/// puts back over everything or next to what is already there. Rows are
+/// written with every column and recorded responses are included, so
+/// nothing is lost on the way.
+pub fn export() -> Result<Mapping, BackupError> {
+    let apis = storage()
+        .dump()?
+        .into_iter()
+        .map(serde_yaml::Value::Mapping)
+        .collect();
+
+    let mut backup = Mapping::new();
+    insert(&mut backup, "format", serde_yaml::Value::from(FORMAT));
+    insert(&mut backup, "version", serde_yaml::Value::from(VERSION));
+    insert(
+        &mut backup,
+        "exported_at",
+        serde_yaml::Value::from(Local::now().to_rfc3339()),
+    );
+    insert(&mut backup, "apis", serde_yaml::Value::Sequence(apis));
+    Ok(backup)
+}
+
+pub fn export_to_file(path: &str) -> Result<(), BackupError> {
+    let backup = export()?;
+    let text = serde_json::to_string_pretty(&backup)
+        .map_err(|err| BackupError::Syntax(err.to_string()))?;
+
+    fs::write(path, text).map_err(|err| BackupError::Write(path.to_string(), err))
+}
+
+/// Restores a backup written by `export_to_file`, or by an older version.
+pub fn restore(path: &str, mode: RestoreMode) -> Result<RestoreSummary, BackupError> {
+    let source =
+        fs::read_to_string(path).map_err(|err| BackupError::Read(path.to_string(), err))?;
+    let backup: Value =
+        serde_json::from_str(&source).map_err(|err| BackupError::Syntax(err.to_string()))?;
+    let mut backup = match backup {
+        Value::Object(backup) if backup.get("format") == Some(&Value::from(FORMAT)) => backup,
+        _ => return Err(BackupError::Syntax(String::from("no reqstr backup format"))),
+    };
+    let version = backup
+        .get("version")
+        .and_then(Value::as_i64)
+        .ok_or_else(|| BackupError::Syntax(String::from("no version")))?;
+    if version > VERSION {
+        return Err(BackupError::Version(version, VERSION));
+    }
+
+    let apis = take_objects(&mut backup, "apis");
+    match mode {
+        RestoreMode::Replace => replace(apis),
+        RestoreMode::Merge => merge(apis),
+    }
+}
+
+fn replace(apis: Vec<Map<String, Value>>) -> Result<RestoreSummary, BackupError> {
+    let mut summary = RestoreSummary::default();
+    let mut rows = vec![];
+    for api in apis {
+        summary.added += push_api(&mut rows, api);
+    }
+
+    storage().restore(rows, true)?;
+    Ok(summary)
+}
+
+fn merge(apis: Vec<Map<String, Value>>) -> Result<RestoreSummary, BackupError> {
+    let existing: Vec<Map<String, Value>> = storage()
+        .dump()?
+        .into_iter()
+        .filter_map(|api| match serde_json::to_value(api) {
+            Ok(Value::Object(api)) => Some(api),
+            _ => None,
+        })
+        .collect();
+
+    let mut summary = RestoreSummary::default();
+    let mut rows = vec![];
+    for mut api in apis {
+        let api_name = text(&api, "name").to_string();
+        let here = match existing.iter().find(|here| text(here, "name") == api_name) {
+            Some(here) => here,
+            None => {
+                strip_ids(&mut api);
+                summary.added += push_api(&mut rows, api);
+                continue;
+            }
+        };
+        let api_id = here.get("id").and_then(Value::as_i64).unwrap_or(0);
+
+        let variables = take_objects(&mut api, "variables");
+        let resources = take_objects(&mut api, "resources");
+        summary.compare(
+            &api,
+            &[here],
+            &["variables", "resources"],
+            &API_TABLES,
+            format!("API {}", api_name),
+        );
+
+        let here_variables = objects(here.get("variables"));
+        for mut variable in variables {
+            let key = text(&variable, "key").to_string();
+            match here_variables.iter().find(|here| text(here, "key") == key) {
+                Some(here) => summary.compare(
+                    &variable,
+                    &[here],
+                    &[],
+                    &[],
+                    format!("variable {} of {}", key, api_name),
+                ),
+                None => {
+                    strip_ids(&mut variable);
+                    variable.insert(String::from("api_id"), Value::from(api_id));
+                    rows.push(LoadedRow {
+                        table: "api_variable",
+                        row: variable,
+                        parents: vec![],
+                    });
+                    summary.added += 1;
+                }
+            }
+        }
+
+        merge_resources(
+            &mut rows,
+            &mut summary,
+            resources,
+            objects(here.get("resources")),
+            api_id,
+            None,
+            &api_name,
+        );
+    }
+
+    storage().restore(rows, false)?;
+    Ok(summary)
+}
+
+/// Merges resources of the backup into the resources of an API or folder
+/// here, `path` naming it in conflicts.
+fn merge_resources(
+    rows: &mut Vec<LoadedRow>,
+    summary: &mut RestoreSummary,
+    resources: Vec<Map<String, Value>>,
+    here_resources: Vec<Map<String, Value>>,
+    api_id: i64,
+    parent_id: Option<i64>,
+    path: &str,
+) {
+    for mut resource in resources {
+        let path = format!("{} / {}", path, text(&resource, "name"));
+        let here = match here_resources
+            .iter()
+            .find(|here| text(here, "name") == text(&resource, "name"))
+        {
+            Some(here) => here,
+            None => {
+                strip_ids(&mut resource);
+                summary.added += push_resource(
+                    rows,
+                    resource,
+                    Parent::Existing(api_id),
+                    parent_id.map(Parent::Existing),
+                );
+                continue;
+            }
+        };
+        let resource_id = here.get("id").and_then(Value::as_i64).unwrap_or(0);
+
+        let requests = take_objects(&mut resource, "requests");
+        let children = take_objects(&mut resource, "resources");
+        summary.compare(
+            &resource,
+            &[here],
+            &["requests", "resources"],
+            &[],
+            format!("resource {}", path),
+        );
+
+        let here_requests = objects(here.get("requests"));
+        for mut request in requests {
+            let (method, route) = (text(&request, "method"), text(&request, "route"));
+            let found: Vec<_> = here_requests
+                .iter()
+                .filter(|here| text(here, "method") == method && text(here, "route") == route)
+                .collect();
+            if found.is_empty() {
+                strip_ids(&mut request);
+                push_request(rows, request, Parent::Existing(resource_id));
+                summary.added += 1;
+            } else {
+                let name = format!("{} {} in {}", method, route, path);
+                summary.compare(&request, &found, &[], &REQUEST_TABLES, name);
+            }
+        }
+
+        merge_resources(
+            rows,
+            summary,
+            children,
+            objects(here.get("resources")),
+            api_id,
+            Some(resource_id),
+            &path,
+        );
+    }
+}
+
+/// Adds an API with everything in it, returning how many APIs, resources
+/// and requests were added.
+fn push_api(rows: &mut Vec<LoadedRow>, mut api: Map<String, Value>) -> usize {
+    let resources = take_objects(&mut api, "resources");
+    let index = push_document(rows, "api", api, &[], &document::nested(&API_TABLES, true));
+
+    1 + resources
+        .into_iter()
+        .map(|resource| push_resource(rows, resource, Parent::Restored(index), None))
+        .sum::<usize>()
+}
+
+/// Adds a resource with its requests and the resources in it, returning
+/// how many of those were added.
+fn push_resource(
+    rows: &mut Vec<LoadedRow>,
+    mut resource: Map<String, Value>,
+    api: Parent,
+    folder: Option<Parent>,
+) -> usize {
+    let requests = take_objects(&mut resource, "requests");
+    let children = take_objects(&mut resource, "resources");
+    let mut parents = vec![];
+    attach(&mut resource, &mut parents, api, "api_id");
+    if let Some(folder) = folder {
+        attach(&mut resource, &mut parents, folder, "parent_id");
+    }
+    let index = push_document(rows, "resource", resource, &parents, &[]);
+
+    let mut count = 1 + requests.len();
+    for request in requests {
+        push_request(rows, request, Parent::Restored(index));
+    }
+    for child in children {
+        count += push_resource(rows, child, api, Some(Parent::Restored(index)));
+    }
+    count
+}
+
+fn push_request(rows: &mut Vec<LoadedRow>, mut request: Map<String, Value>, resource: Parent) {
+    let mut parents = vec![];
+    attach(&mut request, &mut parents, resource, "resource_id");
+    push_document(
+        rows,
+        "request",
+        request,
+        &parents,
+        &document::nested(&REQUEST_TABLES, true),
+    );
+}
+
+/// Links a row to its parent: rows restored with it are linked once they
+/// have ids, rows already here right away.
+fn attach(
+    row: &mut Map<String, Value>,
+    parents: &mut Vec<(usize, &'static str)>,
+    parent: Parent,
+    column: &'static str,
+) {
+    match parent {
+        Parent::Restored(index) => parents.push((index, column)),
+        Parent::Existing(id) => {
+            row.insert(column.to_string(), Value::from(id));
+        }
+    }
+}
+
+/// Removes the ids of a row and the rows nested in it, so they are given
+/// new ones.
+fn strip_ids(row: &mut Map<String, Value>) {
+    row.remove("id");
+    for value in row.values_mut() {
+        match value {
+            Value::Object(child) => strip_ids(child),
+            Value::Array(children) => {
+                for child in children {
+                    if let Value::Object(child) = child {
+                        strip_ids(child);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+fn take_objects(document: &mut Map<String, Value>, key: &str) -> Vec<Map<String, Value>> {
+    objects(document.remove(key).as_ref())
+}
+
+fn objects(value: Option<&Value>) -> Vec<Map<String, Value>> {
+    value
+        .and_then(Value::as_array)
+        .map(|children| {
+            children
+                .iter()
+                .filter_map(|child| child.as_object().cloned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn text<'a>(row: &'a Map<String, Value>, key: &str) -> &'a str {
+    row.get(key).and_then(Value::as_str).unwrap_or("")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{NewApi, NewRequest, NewResource, Request},
+        services::{api_service, request_service, resource_service},
+        types::{HttpMethod, RequestKind},
+    };
+
+    fn backup_path(name: &str) -> String {
+        std::env::temp_dir()
+            .join(format!(
+                "reqstr-backup-test-{}-{}.json",
+                name,
+                std::process::id()
+            ))
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// Starts from an empty database, as tests may share one.
+    fn clear() {
+        replace(vec![]).unwrap();
+    }
+
+    /// Adds a `Shop` API with an `Orders` folder, returning the folder id.
+    fn add_shop() -> i32 {
+        api_service::create_new_api(NewApi { name: "Shop" });
+        let api = api_service::get_all_apis().pop().unwrap();
+        resource_service::create_new_resource(NewResource {
+            name: "Orders",
+            api_id: api.id,
+            parent_id: None,
+        });
+        resource_service::get_root_resources(api.id)
+            .pop()
+            .unwrap()
+            .id
+    }
+
+    fn add_request(resource_id: i32, method: HttpMethod, route: &str) -> Request {
+        request_service::create_request(NewRequest {
+            resource_id,
+            route,
+            method,
+            body: None,
+            kind: RequestKind::for_route(route),
+        })
+    }
+
+    #[test]
+    fn replaces_everything_keeping_ids() {
+        clear();
+        let orders = add_shop();
+        let listed = add_request(orders, HttpMethod::Get, "/orders");
+        let path = backup_path("replace");
+        export_to_file(&path).unwrap();
+
+        api_service::create_new_api(NewApi { name: "Other" });
+        let summary = restore(&path, RestoreMode::Replace).unwrap();
+        assert_eq!(summary.added, 3);
+
+        let apis = api_service::get_all_apis();
+        assert_eq!(apis.len(), 1);
+        assert_eq!(apis[0].name, "Shop");
+        let requests = request_service::get_requests_for_resource(orders).unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].id, listed.id);
+    }
+
+    #[test]
+    fn merges_and_reports_conflicts() {
+        clear();
+        let orders = add_shop();
+        let listed = add_request(orders, HttpMethod::Get, "/orders");
+        let deleted = add_request(orders, HttpMethod::Delete, "/orders/{id}");
+        let before = backup_path("merge-before");
+        export_to_file(&before).unwrap();
+        add_request(orders, HttpMethod::Post, "/orders");
+        let after = backup_path("merge-after");
+        export_to_file(&after).unwrap();
+        restore(&before, RestoreMode::Replace).unwrap();
+
+        // Using a request here is no conflict, editing it is.
+        request_service::mark_run(listed.id);
+        request_service::update_request_favourite(&listed, true);
+        request_service::move_request(&listed, false);
+        request_service::update_request_description(&deleted, Some("Cancels an order"));
+
+        let summary = restore(&after, RestoreMode::Merge).unwrap();
+        assert_eq!(summary.added, 1);
+        assert_eq!(summary.unchanged, 3);
+        assert_eq!(
+            summary.conflicts,
+            vec!["delete /orders/{id} in Shop / Orders"]
+        );
+
+        let requests = request_service::get_requests_for_resource(orders).unwrap();
+        assert_eq!(requests.len(), 3);
+        let kept = requests.iter().find(|request| request.id == deleted.id);
+        assert_eq!(
+            kept.and_then(|request| request.description.as_deref()),
+            Some("Cancels an order")
+        );
+    }
+}
@@ -0,0 +1,780 @@
+use std::path::Path;
+
+use thiserror::Error;
+use url::Url;
+
+use crate::{
+    graphql::{self, GraphqlError},
+    http::{self, HOSTNAME},
+    services::{
+        form_part_service, graphql_service, header_service, param_service, request_service,
+    },
+    types::{FormPartKind, HttpMethod, RequestKind},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Language {
+    Curl,
+    Httpie,
+    Reqwest,
+    Python,
+    Fetch,
+}
+
+impl Language {
+    /// In the order they are offered.
+    pub const ALL: [Language; 5] = [
+        Language::Curl,
+        Language::Httpie,
+        Language::Reqwest,
+        Language::Python,
+        Language::Fetch,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::Curl => "curl",
+            Language::Httpie => "HTTPie",
+            Language::Reqwest => "reqwest",
+            Language::Python => "Python",
+            Language::Fetch => "fetch",
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum CodegenError {
+    #[error("Request {0} does not exist")]
+    MissingRequest(i32),
+    #[error("Only HTTP and GraphQL requests can be copied as code")]
+    Unsupported,
+    #[error(transparent)]
+    Graphql(#[from] GraphqlError),
+}
+
+/// A request with its variables filled in, the way `http::make_request`
+/// would send it.
+struct Call {
+    method: HttpMethod,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: CallBody,
+    tls: CallTls,
+}
+
+enum CallBody {
+    None,
+    Text(String),
+    Form(Vec<CallPart>),
+}
+
+struct CallPart {
+    name: String,
+    kind: FormPartKind,
+    value: String,
+    content_type: Option<String>,
+}
+
+/// The TLS settings of the request's API that a snippet can carry over.
+#[derive(Default)]
+struct CallTls {
+    insecure: bool,
+    ca_bundles: Vec<String>,
+    client_certificate: Option<String>,
+    client_key: Option<String>,
+    /// Whether the certificate is a PKCS#12 archive rather than PEM.
+    pkcs12: bool,
+    /// The environment variable holding the PKCS#12 password.
+    password_env: Option<String>,
+}
+
+/// Writes a snippet that sends the request the same way this app would,
+/// for someone who does not use it.
+pub fn generate(request_id: i32, language: Language) -> Result<String, CodegenError> {
+    let call = resolve(request_id)?;
+
+    Ok(match language {
+        Language::Curl => curl(&call),
+        Language::Httpie => httpie(&call),
+        Language::Reqwest => reqwest(&call),
+        Language::Python => python(&call),
+        Language::Fetch => fetch(&call),
+    })
+}
+
+fn resolve(request_id: i32) -> Result<Call, CodegenError> {
+    let request = request_service::get_request_by_id(request_id)
+        .ok_or(CodegenError::MissingRequest(request_id))?;
+    if !matches!(request.kind, RequestKind::Http | RequestKind::Graphql) {
+        return Err(CodegenError::Unsupported);
+    }
+    let variables = http::variables_for_resource(request.resource_id);
+    let expand = |text: &str| http::expand_variables(text, &variables);
+
+    let mut headers: Vec<(String, String)> = header_service::get_headers_for_request(request.id)
+        .iter()
+        .map(|header| http::expand_header(header, &variables))
+        .collect();
+    let mut query: Vec<(String, String)> = param_service::get_params_for_request(request.id)
+        .iter()
+        .map(|param| (expand(&param.key), expand(&param.value)))
+        .collect();
+    let form_parts = form_part_service::get_form_parts_for_request(request.id);
+
+    let body = if request.kind == RequestKind::Graphql {
+        let query_text =
+            graphql_service::get_query_for_request(request.id).ok_or(GraphqlError::MissingQuery)?;
+        if request.method == HttpMethod::Get {
+            query.extend(graphql::query_params(&query_text)?);
+            CallBody::None
+        } else {
+            if !has_header(&headers, "content-type") {
+                headers.push((
+                    String::from("Content-Type"),
+                    String::from("application/json"),
+                ));
+            }
+            CallBody::Text(graphql::envelope(&query_text)?.to_string())
+        }
+    } else if !form_parts.is_empty() {
+        // The multipart boundary is chosen by whatever sends the form.
+        headers.retain(|(key, _)| !key.eq_ignore_ascii_case("content-type"));
+        CallBody::Form(
+            form_parts
+                .iter()
+                .map(|part| CallPart {
+                    name: part.name.clone(),
+                    kind: part.kind,
+                    value: match part.kind {
+                        FormPartKind::Text => expand(&part.value),
+                        FormPartKind::File => part.value.clone(),
+                    },
+                    content_type: part.content_type.clone(),
+                })
+                .collect(),
+        )
+    } else {
+        match request.body {
+            Some(body) => CallBody::Text(expand(&body)),
+            None => CallBody::None,
+        }
+    };
+
+    let tls = http::tls_config_for_resource(request.resource_id)
+        .map(|tls| CallTls {
+            insecure: tls.accept_invalid_certs,
+            ca_bundles: tls
+                .ca_bundle_paths()
+                .into_iter()
+                .map(str::to_string)
+                .collect(),
+            client_key: tls.client_key.clone(),
+            pkcs12: tls
+                .client_certificate
+                .as_deref()
+                .is_some_and(http::is_pkcs12),
+            password_env: tls.client_password_env.clone(),
+            client_certificate: tls.client_certificate,
+        })
+        .unwrap_or_default();
+
+    Ok(Call {
+        method: request.method,
+        url: with_query(http::request_url(HOSTNAME, &expand(&request.route)), &query),
+        headers,
+        body,
+        tls,
+    })
+}
+
+fn has_header(headers: &[(String, String)], name: &str) -> bool {
+    headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case(name))
+}
+
+/// Appends the params encoded the way reqwest encodes them when sending.
+fn with_query(url: String, query: &[(String, String)]) -> String {
+    if query.is_empty() {
+        return url;
+    }
+
+    match Url::parse(&url) {
+        Ok(mut parsed) => {
+            parsed.query_pairs_mut().extend_pairs(query);
+            parsed.to_string()
+        }
+        // A placeholder without a variable can leave the URL unparsable.
+        Err(_) => {
+            let encoded = url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(query)
+                .finish();
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!("{}{}{}", url, separator, encoded)
+        }
+    }
+}
+
+fn file_name(path: &str) -> String {
+    Path::new(path).file_name().map_or_else(
+        || path.to_string(),
+        |name| name.to_string_lossy().to_string(),
+    )
+}
+
+/// Quotes a word for a POSIX shell.
+fn shell_quote(text: &str) -> String {
+    let safe = !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c));
+    if safe {
+        text.to_string()
+    } else {
+        format!("'{}'", text.replace('\'', r"'\''"))
+    }
+}
+
+/// A string literal in Python or JavaScript; JSON strings are valid in both.
+fn string_literal(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+/// Joins shell words, one option per line.
+fn shell_command(words: Vec<String>) -> String {
+    words.join(" \\\n  ")
+}
+
+fn curl(call: &Call) -> String {
+    let command = match call.method {
+        HttpMethod::Get => String::from("curl"),
+        HttpMethod::Head => String::from("curl --head"),
+        ref method => format!("curl -X {}", shell_quote(method.as_str())),
+    };
+    let mut words = vec![format!("{} {}", command, shell_quote(&call.url))];
+
+    for (key, value) in &call.headers {
+        words.push(format!(
+            "-H {}",
+            shell_quote(&format!("{}: {}", key, value))
+        ));
+    }
+    match &call.body {
+        CallBody::None => (),
+        CallBody::Text(body) => words.push(format!("--data-raw {}", shell_quote(body))),
+        CallBody::Form(parts) => {
+            for part in parts {
+                let mut value = match part.kind {
+                    FormPartKind::Text => format!("{}={}", part.name, part.value),
+                    FormPartKind::File => format!("{}=@{}", part.name, part.value),
+                };
+                if let Some(content_type) = &part.content_type {
+                    value.push_str(&format!(";type={}", content_type));
+                }
+                let flag = match part.kind {
+                    FormPartKind::Text if part.content_type.is_none() => "--form-string",
+                    _ => "-F",
+                };
+                words.push(format!("{} {}", flag, shell_quote(&value)));
+            }
+        }
+    }
+
+    if call.tls.insecure {
+        words.push(String::from("-k"));
+    }
+    for path in &call.tls.ca_bundles {
+        words.push(format!("--cacert {}", shell_quote(path)));
+    }
+    match &call.tls.client_certificate {
+        Some(certificate) if call.tls.pkcs12 => {
+            // curl reads the password after a colon in the same argument.
+            let password = match &call.tls.password_env {
+                Some(name) => format!(":\"${}\"", name),
+                None => String::new(),
+            };
+            words.push(String::from("--cert-type P12"));
+            words.push(format!("--cert {}{}", shell_quote(certificate), password));
+        }
+        Some(certificate) => {
+            words.push(format!("--cert {}", shell_quote(certificate)));
+            if let Some(key) = &call.tls.client_key {
+                words.push(format!("--key {}", shell_quote(key)));
+            }
+        }
+        None => (),
+    }
+
+    shell_command(words)
+}
+
+fn httpie(call: &Call) -> String {
+    let mut comments = String::new();
+    let mut words = vec![format!(
+        "http {} {}",
+        shell_quote(call.method.as_str()),
+        shell_quote(&call.url)
+    )];
+    if let CallBody::Form(_) = call.body {
+        words.push(String::from("--multipart"));
+    }
+    if let CallBody::Text(body) = &call.body {
+        words.push(format!("--raw {}", shell_quote(body)));
+    }
+    if call.tls.insecure {
+        words.push(String::from("--verify=no"));
+    } else if let Some(path) = call.tls.ca_bundles.first() {
+        // HTTPie takes a single bundle.
+        words.push(format!("--verify={}", shell_quote(path)));
+    }
+    match &call.tls.client_certificate {
+        Some(certificate) if call.tls.pkcs12 => {
+            // HTTPie only reads PEM certificates.
+            comments = format!(
+                "# Convert the PKCS#12 certificate to PEM first:\n# openssl pkcs12 -in {} -out client.pem -nodes\n",
+                shell_quote(certificate)
+            );
+            words.push(String::from("--cert=client.pem"));
+        }
+        Some(certificate) => {
+            words.push(format!("--cert={}", shell_quote(certificate)));
+            if let Some(key) = &call.tls.client_key {
+                words.push(format!("--cert-key={}", shell_quote(key)));
+            }
+        }
+        None => (),
+    }
+
+    for (key, value) in &call.headers {
+        words.push(shell_quote(&format!("{}:{}", key, value)));
+    }
+    if let CallBody::Form(parts) = &call.body {
+        for part in parts {
+            let mut item = match part.kind {
+                FormPartKind::Text => format!("{}={}", part.name, part.value),
+                FormPartKind::File => format!("{}@{}", part.name, part.value),
+            };
+            if let (FormPartKind::File, Some(content_type)) = (part.kind, &part.content_type) {
+                item.push_str(&format!(";type={}", content_type));
+            }
+            words.push(shell_quote(&item));
+        }
+    }
+
+    comments + &shell_command(words)
+}
+
+fn reqwest(call: &Call) -> String {
+    let mut lines = vec![
+        String::from("use reqwest::{blocking::Client, Method};"),
+        String::new(),
+        String::from("fn main() -> Result<(), Box<dyn std::error::Error>> {"),
+    ];
+
+    let mut builder = vec![];
+    if call.tls.insecure {
+        builder.push(String::from("        .danger_accept_invalid_certs(true)"));
+    }
+    for path in &call.tls.ca_bundles {
+        builder.push(format!(
+            "        .add_root_certificate(reqwest::Certificate::from_pem(&std::fs::read({:?})?)?)",
+            path
+        ));
+    }
+    match &call.tls.client_certificate {
+        Some(certificate) if call.tls.pkcs12 => {
+            let password = match &call.tls.password_env {
+                Some(name) => format!("&std::env::var({:?})?", name),
+                None => String::from("\"\""),
+            };
+            builder.push(format!(
+                "        .identity(reqwest::Identity::from_pkcs12_der(&std::fs::read({:?})?, {})?)",
+                certificate, password
+            ));
+        }
+        Some(certificate) => {
+            let key = call.tls.client_key.as_deref().unwrap_or(certificate);
+            builder.push(format!(
+                "        .identity(reqwest::Identity::from_pkcs8_pem(&std::fs::read({:?})?, &std::fs::read({:?})?)?)",
+                certificate, key
+            ));
+        }
+        None => (),
+    }
+    if builder.is_empty() {
+        lines.push(String::from("    let client = Client::new();"));
+    } else {
+        lines.push(String::from("    let client = Client::builder()"));
+        lines.extend(builder);
+        lines.push(String::from("        .build()?;"));
+    }
+
+    let method = match HttpMethod::standard().contains(&call.method) {
+        true => format!("Method::{}", call.method.as_str()),
+        false => format!("Method::from_bytes(b{:?})?", call.method.as_str()),
+    };
+    lines.push(String::from("    let response = client"));
+    lines.push(format!("        .request({}, {:?})", method, call.url));
+    for (key, value) in &call.headers {
+        lines.push(format!("        .header({:?}, {:?})", key, value));
+    }
+    match &call.body {
+        CallBody::None => (),
+        CallBody::Text(body) => lines.push(format!("        .body({:?})", body)),
+        CallBody::Form(parts) => {
+            lines.push(String::from("        .multipart("));
+            lines.push(String::from(
+                "            reqwest::blocking::multipart::Form::new()",
+            ));
+            for part in parts {
+                let value = match part.kind {
+                    FormPartKind::Text => {
+                        format!("reqwest::blocking::multipart::Part::text({:?})", part.value)
+                    }
+                    FormPartKind::File => format!(
+                        "reqwest::blocking::multipart::Part::file({:?})?",
+                        part.value
+                    ),
+                };
+                let value = match &part.content_type {
+                    Some(content_type) => format!("{}.mime_str({:?})?", value, content_type),
+                    None => value,
+                };
+                lines.push(format!("                .part({:?}, {})", part.name, value));
+            }
+            lines.push(String::from("        )"));
+        }
+    }
+    lines.push(String::from("        .send()?;"));
+    lines.push(String::new());
+    lines.push(String::from("    println!(\"{}\", response.status());"));
+    lines.push(String::from("    println!(\"{}\", response.text()?);"));
+    lines.push(String::from("    Ok(())"));
+    lines.push(String::from("}"));
+
+    lines.join("\n")
+}
+
+fn python(call: &Call) -> String {
+    // requests has no PKCS#12 support, the requests_pkcs12 package adds it.
+    let pkcs12 = call.tls.client_certificate.is_some() && call.tls.pkcs12;
+    let mut lines = match (pkcs12, &call.tls.password_env) {
+        (true, Some(_)) => vec![
+            String::from("import os"),
+            String::from("import requests_pkcs12"),
+        ],
+        (true, None) => vec![String::from("import requests_pkcs12")],
+        (false, _) => vec![String::from("import requests")],
+    };
+    lines.extend([
+        String::new(),
+        format!(
+            "response = {}.request(",
+            if pkcs12 {
+                "requests_pkcs12"
+            } else {
+                "requests"
+            }
+        ),
+        format!("    {},", string_literal(call.method.as_str())),
+        format!("    {},", string_literal(&call.url)),
+    ]);
+
+    if !call.headers.is_empty() {
+        lines.push(String::from("    headers={"));
+        for (key, value) in &call.headers {
+            lines.push(format!(
+                "        {}: {},",
+                string_literal(key),
+                string_literal(value)
+            ));
+        }
+        lines.push(String::from("    },"));
+    }
+    match &call.body {
+        CallBody::None => (),
+        CallBody::Text(body) => lines.push(format!("    data={},", string_literal(body))),
+        CallBody::Form(parts) => {
+            // requests only sends multipart when there are files, so text
+            // parts go in `files` too, without a file name.
+            lines.push(String::from("    files=["));
+            for part in parts {
+                let file_name = match part.kind {
+                    FormPartKind::Text => String::from("None"),
+                    FormPartKind::File => string_literal(&file_name(&part.value)),
+                };
+                let value = match part.kind {
+                    FormPartKind::Text => string_literal(&part.value),
+                    FormPartKind::File => format!("open({}, \"rb\")", string_literal(&part.value)),
+                };
+                let content_type = match &part.content_type {
+                    Some(content_type) => format!(", {}", string_literal(content_type)),
+                    None => String::new(),
+                };
+                lines.push(format!(
+                    "        ({}, ({}, {}{})),",
+                    string_literal(&part.name),
+                    file_name,
+                    value,
+                    content_type
+                ));
+            }
+            lines.push(String::from("    ],"));
+        }
+    }
+
+    if call.tls.insecure {
+        lines.push(String::from("    verify=False,"));
+    } else if let Some(path) = call.tls.ca_bundles.first() {
+        lines.push(format!("    verify={},", string_literal(path)));
+    }
+    match (&call.tls.client_certificate, &call.tls.client_key) {
+        (Some(certificate), _) if pkcs12 => {
+            lines.push(format!(
+                "    pkcs12_filename={},",
+                string_literal(certificate)
+            ));
+            if let Some(name) = &call.tls.password_env {
+                lines.push(format!(
+                    "    pkcs12_password=os.environ[{}],",
+                    string_literal(name)
+                ));
+            }
+        }
+        (Some(certificate), Some(key)) => lines.push(format!(
+            "    cert=({}, {}),",
+            string_literal(certificate),
+            string_literal(key)
+        )),
+        (Some(certificate), None) => {
+            lines.push(format!("    cert={},", string_literal(certificate)))
+        }
+        _ => (),
+    }
+    lines.push(String::from(")"));
+    lines.push(String::new());
+    lines.push(String::from("print(response.status_code)"));
+    lines.push(String::from("print(response.text)"));
+
+    lines.join("\n")
+}
+
+fn fetch(call: &Call) -> String {
+    let mut lines = vec![];
+
+    if call.tls.insecure {
+        lines.push(String::from(
+            "// Certificate checks are off for this API. Browsers cannot do that,",
+        ));
+        lines.push(String::from(
+            "// run Node with NODE_TLS_REJECT_UNAUTHORIZED=0 instead.",
+        ));
+    }
+    if let CallBody::Form(parts) = &call.body {
+        if parts.iter().any(|part| part.kind == FormPartKind::File) {
+            lines.push(String::from("import fs from \"node:fs\";"));
+            lines.push(String::new());
+        }
+        lines.push(String::from("const form = new FormData();"));
+        for part in parts {
+            match part.kind {
+                FormPartKind::Text => lines.push(format!(
+                    "form.append({}, {});",
+                    string_literal(&part.name),
+                    string_literal(&part.value)
+                )),
+                FormPartKind::File => {
+                    let options = match &part.content_type {
+                        Some(content_type) => {
+                            format!(", {{ type: {} }}", string_literal(content_type))
+                        }
+                        None => String::new(),
+                    };
+                    lines.push(format!(
+                        "form.append({}, await fs.openAsBlob({}{}), {});",
+                        string_literal(&part.name),
+                        string_literal(&part.value),
+                        options,
+                        string_literal(&file_name(&part.value))
+                    ));
+                }
+            }
+        }
+        lines.push(String::new());
+    }
+
+    lines.push(format!(
+        "const response = await fetch({}, {{",
+        string_literal(&call.url)
+    ));
+    lines.push(format!(
+        "  method: {},",
+        string_literal(call.method.as_str())
+    ));
+    if !call.headers.is_empty() {
+        lines.push(String::from("  headers: {"));
+        for (key, value) in &call.headers {
+            lines.push(format!(
+                "    {}: {},",
+                string_literal(key),
+                string_literal(value)
+            ));
+        }
+        lines.push(String::from("  },"));
+    }
+    match &call.body {
+        CallBody::None => (),
+        CallBody::Text(body) => lines.push(format!("  body: {},", string_literal(body))),
+        CallBody::Form(_) => lines.push(String::from("  body: form,")),
+    }
+    lines.push(String::from("});"));
+    lines.push(String::new());
+    lines.push(String::from("console.log(response.status);"));
+    lines.push(String::from("console.log(await response.text());"));
+
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(body: CallBody) -> Call {
+        Call {
+            method: HttpMethod::Post,
+            url: String::from("https://api.example.com/items?q=a+b"),
+            headers: vec![(String::from("X-Note"), String::from("it's"))],
+            body,
+            tls: CallTls::default(),
+        }
+    }
+
+    fn with_certificate(mut call: Call, certificate: &str, pkcs12: bool) -> Call {
+        call.tls = CallTls {
+            client_certificate: Some(certificate.to_string()),
+            client_key: (!pkcs12).then(|| String::from("client.key")),
+            pkcs12,
+            password_env: pkcs12.then(|| String::from("CERT_PASSWORD")),
+            ..Default::default()
+        };
+        call
+    }
+
+    fn call_with_form() -> Call {
+        call(CallBody::Form(vec![
+            CallPart {
+                name: String::from("title"),
+                kind: FormPartKind::Text,
+                value: String::from("Hello"),
+                content_type: None,
+            },
+            CallPart {
+                name: String::from("upload"),
+                kind: FormPartKind::File,
+                value: String::from("/tmp/a b.png"),
+                content_type: Some(String::from("image/png")),
+            },
+        ]))
+    }
+
+    #[test]
+    fn quotes_for_the_shell() {
+        assert_eq!(shell_quote("plain-word_1.txt"), "plain-word_1.txt");
+        assert_eq!(shell_quote(""), "''");
+        assert_eq!(shell_quote("it's here"), r"'it'\''s here'");
+    }
+
+    #[test]
+    fn appends_query_params() {
+        let params = vec![(String::from("a b"), String::from("1&2"))];
+        assert_eq!(
+            with_query(String::from("https://x.test/p?z=1"), &params),
+            "https://x.test/p?z=1&a+b=1%262"
+        );
+        assert_eq!(
+            with_query(String::from("{{host}}/p"), &params),
+            "{{host}}/p?a+b=1%262"
+        );
+    }
+
+    #[test]
+    fn writes_curl() {
+        let call = call(CallBody::Text(String::from("{\"a\": 1}")));
+        assert_eq!(
+            curl(&call),
+            "curl -X POST 'https://api.example.com/items?q=a+b' \\\n  -H 'X-Note: it'\\''s' \\\n  --data-raw '{\"a\": 1}'"
+        );
+
+        let form = call_with_form();
+        let snippet = curl(&form);
+        assert!(snippet.contains("--form-string title=Hello"));
+        assert!(snippet.contains("-F 'upload=@/tmp/a b.png;type=image/png'"));
+    }
+
+    #[test]
+    fn branches_on_the_certificate_format_in_curl() {
+        let pem = curl(&with_certificate(call(CallBody::None), "client.pem", false));
+        assert!(pem.contains("--cert client.pem \\\n  --key client.key"));
+        assert!(!pem.contains("--cert-type"));
+
+        let p12 = curl(&with_certificate(call(CallBody::None), "client.p12", true));
+        assert!(p12.contains("--cert-type P12 \\\n  --cert client.p12:\"$CERT_PASSWORD\""));
+        assert!(!p12.contains("--key"));
+    }
+
+    #[test]
+    fn branches_on_the_certificate_format_in_reqwest() {
+        let pem = reqwest(&with_certificate(call(CallBody::None), "client.pem", false));
+        assert!(pem.contains(
+            ".identity(reqwest::Identity::from_pkcs8_pem(&std::fs::read(\"client.pem\")?, &std::fs::read(\"client.key\")?)?)"
+        ));
+
+        let p12 = reqwest(&with_certificate(call(CallBody::None), "client.p12", true));
+        assert!(p12.contains(
+            ".identity(reqwest::Identity::from_pkcs12_der(&std::fs::read(\"client.p12\")?, &std::env::var(\"CERT_PASSWORD\")?)?)"
+        ));
+        assert!(!p12.contains("from_pkcs8_pem"));
+    }
+
+    #[test]
+    fn branches_on_the_certificate_format_in_python_and_httpie() {
+        let p12 = with_certificate(call(CallBody::None), "client.p12", true);
+        let snippet = python(&p12);
+        assert!(snippet.starts_with(
+            "import os\nimport requests_pkcs12\n\nresponse = requests_pkcs12.request("
+        ));
+        assert!(snippet.contains("    pkcs12_filename=\"client.p12\",\n    pkcs12_password=os.environ[\"CERT_PASSWORD\"],"));
+
+        let snippet = httpie(&p12);
+        assert!(snippet.starts_with("# Convert the PKCS#12 certificate to PEM first:\n"));
+        assert!(snippet.contains("--cert=client.pem"));
+
+        let pem = python(&with_certificate(call(CallBody::None), "client.pem", false));
+        assert!(pem.contains("    cert=(\"client.pem\", \"client.key\"),"));
+    }
+
+    #[test]
+    fn writes_forms_for_every_language() {
+        let form = call_with_form();
+
+        assert!(httpie(&form).contains("--multipart"));
+        assert!(reqwest(&form)
+            .contains(".part(\"upload\", reqwest::blocking::multipart::Part::file(\"/tmp/a b.png\")?.mime_str(\"image/png\")?)"));
+        assert!(python(&form).contains(
+            "(\"upload\", (\"a b.png\", open(\"/tmp/a b.png\", \"rb\"), \"image/png\")),"
+        ));
+        let snippet = fetch(&form);
+        assert!(snippet.starts_with("import fs from \"node:fs\";"));
+        assert!(snippet.contains("  body: form,"));
+    }
+
+    #[test]
+    fn writes_custom_methods() {
+        let mut call = call(CallBody::None);
+        call.method = "PURGE".parse().unwrap();
+
+        assert!(curl(&call).starts_with("curl -X PURGE "));
+        assert!(reqwest(&call).contains(".request(Method::from_bytes(b\"PURGE\")?, "));
+        assert!(fetch(&call).contains("  method: \"PURGE\","));
+    }
+}
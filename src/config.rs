@@ -2,9 +2,27 @@ use std::env;
 
 use serde::{Deserialize, Serialize};
 
+/// Where collections are kept: a SQLite database file, or a directory of
+/// YAML files that can be committed next to the code, mirrored from a
+/// SQLite cache the queries run on.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageBackend {
+    #[default]
+    Sqlite,
+    Files,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ReqstrConfig {
+    /// The database file, or the directory for `files`.
     pub storage_location: String,
+    #[serde(default)]
+    pub storage_backend: StorageBackend,
+    /// Whether the left pane starts as a tree of every collection rather
+    /// than a list of one level at a time.
+    #[serde(default)]
+    pub tree_view: bool,
 }
 
 impl Default for ReqstrConfig {
@@ -12,6 +30,8 @@ impl Default for ReqstrConfig {
         let path: String = format!("{}/data/database.sqlite3", env::args().next().unwrap());
         Self {
             storage_location: path,
+            storage_backend: StorageBackend::default(),
+            tree_view: false,
         }
     }
 }
@@ -0,0 +1,619 @@
+use std::fs;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use thiserror::Error;
+use url::form_urlencoded;
+
+use crate::{
+    models::{NewFormPart, NewHeader, NewParam, NewRequest, NewTlsConfig},
+    services::{
+        form_part_service, header_service, import_service::ImportedFormPart, param_service,
+        request_service, resource_service, tls_service,
+    },
+    types::{FormPartKind, HttpMethod, RequestKind},
+};
+
+/// Flags that only change what curl prints or how it behaves locally, and
+/// mean nothing for the stored request.
+const NO_OP_FLAGS: [&str; 16] = [
+    "-s",
+    "--silent",
+    "-S",
+    "--show-error",
+    "-v",
+    "--verbose",
+    "-i",
+    "--include",
+    "-L",
+    "--location",
+    "-f",
+    "--fail",
+    "-#",
+    "--progress-bar",
+    "--compressed",
+    "--no-buffer",
+];
+
+/// Unsupported flags that take a value, so the value is skipped with them.
+const FLAGS_WITH_VALUE: [&str; 30] = [
+    "-o",
+    "--output",
+    "-x",
+    "--proxy",
+    "-U",
+    "--proxy-user",
+    "-E",
+    "--cert",
+    "--key",
+    "--cacert",
+    "--capath",
+    "-m",
+    "--max-time",
+    "--connect-timeout",
+    "--retry",
+    "-w",
+    "--write-out",
+    "-T",
+    "--upload-file",
+    "-r",
+    "--range",
+    "--resolve",
+    "-K",
+    "--config",
+    "-c",
+    "--cookie-jar",
+    "--limit-rate",
+    "-z",
+    "--time-cond",
+    "--max-redirs",
+];
+
+/// Short flags that take a value, which may be attached, as in `-XPOST`.
+const SHORT_FLAGS_WITH_VALUE: &str = "XHdFuAebxomwEUTrKcz";
+
+/// Short flags without a value, which may be bundled, as in `-sSk`.
+const SHORT_FLAGS: &str = "sSvikLfG#I";
+
+#[derive(Debug, Error)]
+pub enum CurlError {
+    #[error("Unclosed quote")]
+    UnclosedQuote,
+    #[error("{0} needs a value")]
+    MissingValue(String),
+    #[error("No URL given")]
+    MissingUrl,
+    #[error("Could not read {0}: {1}")]
+    Read(String, std::io::Error),
+}
+
+/// A request described by a curl command line.
+pub struct CurlCommand {
+    pub route: String,
+    pub method: HttpMethod,
+    pub headers: Vec<(String, String)>,
+    pub params: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub form_parts: Vec<ImportedFormPart>,
+    /// `-k`: the server certificate should not be checked.
+    pub insecure: bool,
+    /// Flags that were not understood, and left out of the request.
+    pub ignored: Vec<String>,
+}
+
+/// Parses a curl command as copied from a terminal, a browser's devtools
+/// or a bug report, with or without the leading `curl`. Data given as
+/// `@file` is read right away.
+pub fn parse(command: &str) -> Result<CurlCommand, CurlError> {
+    let mut words = split_words(command)?.into_iter().peekable();
+    if words.peek().map(String::as_str) == Some("curl") {
+        words.next();
+    }
+
+    let mut url = None;
+    let mut method = None;
+    let mut headers = vec![];
+    let mut data: Vec<String> = vec![];
+    let mut form_parts = vec![];
+    let mut user = None;
+    let mut get = false;
+    let mut insecure = false;
+    let mut ignored = vec![];
+
+    while let Some(word) = words.next() {
+        for (flag, attached) in split_flag(&word) {
+            let mut value = |flag: &str| match &attached {
+                Some(value) => Ok(value.clone()),
+                None => words
+                    .next()
+                    .ok_or_else(|| CurlError::MissingValue(flag.to_string())),
+            };
+
+            match flag.as_str() {
+                "-X" | "--request" => method = Some(value(&flag)?),
+                "-H" | "--header" => {
+                    let header = value(&flag)?;
+                    if let Some((key, value)) = header.split_once(':') {
+                        headers.push((key.trim().to_string(), value.trim().to_string()));
+                    }
+                }
+                "-d" | "--data" | "--data-ascii" => data.push(read_data(&value(&flag)?, true)?),
+                "--data-binary" => data.push(read_data(&value(&flag)?, false)?),
+                "--data-raw" => data.push(value(&flag)?),
+                "--data-urlencode" => data.push(url_encode_data(&value(&flag)?)?),
+                "-F" | "--form" | "--form-string" => {
+                    form_parts.push(parse_form_part(&value(&flag)?, flag == "--form-string")?)
+                }
+                "-u" | "--user" => user = Some(value(&flag)?),
+                "--oauth2-bearer" => {
+                    headers.push((
+                        String::from("Authorization"),
+                        format!("Bearer {}", value(&flag)?),
+                    ));
+                }
+                "-A" | "--user-agent" => headers.push((String::from("User-Agent"), value(&flag)?)),
+                "-e" | "--referer" => headers.push((String::from("Referer"), value(&flag)?)),
+                "-b" | "--cookie" => headers.push((String::from("Cookie"), value(&flag)?)),
+                "-G" | "--get" => get = true,
+                "-I" | "--head" => method = Some(String::from("HEAD")),
+                "-k" | "--insecure" => insecure = true,
+                "--url" => url = Some(value(&flag)?),
+                flag if NO_OP_FLAGS.contains(&flag) => (),
+                flag if FLAGS_WITH_VALUE.contains(&flag) => {
+                    value(flag)?;
+                    ignored.push(flag.to_string());
+                }
+                flag if flag.starts_with('-') && flag.len() > 1 => ignored.push(flag.to_string()),
+                _ => url = Some(word.clone()),
+            }
+        }
+    }
+
+    let url = url.ok_or(CurlError::MissingUrl)?;
+    let (route, query) = match url.split_once('?') {
+        Some((route, query)) => (route.to_string(), query.to_string()),
+        None => (url, String::new()),
+    };
+    let mut params: Vec<(String, String)> = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+
+    if let Some(user) = user {
+        headers.push((
+            String::from("Authorization"),
+            format!("Basic {}", STANDARD.encode(user)),
+        ));
+    }
+
+    let has_content_type = headers
+        .iter()
+        .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+    let mut body = None;
+    if get {
+        for data in &data {
+            params.extend(form_urlencoded::parse(data.as_bytes()).into_owned());
+        }
+    } else if !data.is_empty() {
+        body = Some(data.join("&"));
+        if !has_content_type {
+            headers.push((
+                String::from("Content-Type"),
+                String::from("application/x-www-form-urlencoded"),
+            ));
+        }
+    }
+
+    // A method the client cannot send is left out like an unknown flag.
+    let method = method.and_then(|method| match method.parse::<HttpMethod>() {
+        Ok(method) => Some(method),
+        Err(_) => {
+            ignored.push(format!("-X {}", method));
+            None
+        }
+    });
+    // Like curl, sending data or a form makes the request a POST unless a
+    // method was given.
+    let method = match method {
+        Some(method) => method,
+        None if body.is_some() || !form_parts.is_empty() => HttpMethod::Post,
+        None => HttpMethod::Get,
+    };
+
+    Ok(CurlCommand {
+        route,
+        method,
+        headers,
+        params,
+        body,
+        form_parts,
+        insecure,
+        ignored,
+    })
+}
+
+/// Creates the request under a resource and returns its id. `-k` turns
+/// off certificate checks for the whole API, as that is where TLS
+/// settings live.
+pub fn save(resource_id: i32, command: &CurlCommand) -> i32 {
+    let request = request_service::create_request(NewRequest {
+        resource_id,
+        route: &command.route,
+        method: command.method.clone(),
+        body: command.body.as_deref(),
+        kind: RequestKind::for_route(&command.route),
+    });
+
+    for (key, value) in &command.headers {
+        header_service::create_new_header(NewHeader {
+            key,
+            value,
+            request_id: request.id,
+        });
+    }
+    for (key, value) in &command.params {
+        param_service::create_new_param(NewParam {
+            key,
+            value,
+            request_id: request.id,
+        });
+    }
+    for part in &command.form_parts {
+        form_part_service::create_new_form_part(NewFormPart {
+            name: &part.name,
+            kind: part.kind,
+            value: &part.value,
+            content_type: part.content_type.as_deref(),
+            request_id: request.id,
+        });
+    }
+
+    if command.insecure {
+        if let Some(resource) = resource_service::get_resource_by_id(resource_id) {
+            let existing = tls_service::get_tls_config_for_api(resource.api_id);
+            let mut changes = NewTlsConfig::for_api(resource.api_id);
+            if let Some(existing) = &existing {
+                changes = NewTlsConfig {
+                    ca_bundles: &existing.ca_bundles,
+                    client_certificate: existing.client_certificate.as_deref(),
+                    client_key: existing.client_key.as_deref(),
+                    client_password_env: existing.client_password_env.as_deref(),
+                    min_tls_version: existing.min_tls_version,
+                    ..changes
+                };
+            }
+            tls_service::save_tls_config(&NewTlsConfig {
+                accept_invalid_certs: true,
+                ..changes
+            });
+        }
+    }
+
+    request.id
+}
+
+/// Splits `-XPOST` into the flag and its value, and bundled short flags
+/// like `-sSk` or `-kXPOST` into each of them. A word with a flag that is
+/// not known is returned as it is, to be counted as ignored.
+fn split_flag(word: &str) -> Vec<(String, Option<String>)> {
+    if word.starts_with("--") || !word.starts_with('-') || word.len() <= 2 {
+        return vec![(word.to_string(), None)];
+    }
+
+    let mut flags = vec![];
+    for (index, c) in word.char_indices().skip(1) {
+        let flag = format!("-{}", c);
+        if SHORT_FLAGS_WITH_VALUE.contains(c) {
+            let rest = &word[index + c.len_utf8()..];
+            let value = Some(rest.to_string()).filter(|rest| !rest.is_empty());
+            flags.push((flag, value));
+            return flags;
+        }
+        if !SHORT_FLAGS.contains(c) {
+            return vec![(word.to_string(), None)];
+        }
+        flags.push((flag, None));
+    }
+    flags
+}
+
+/// `@file` reads the data from a file. `-d` strips newlines from it the
+/// way curl does, `--data-binary` keeps them.
+fn read_data(data: &str, strip_newlines: bool) -> Result<String, CurlError> {
+    let path = match data.strip_prefix('@') {
+        Some(path) => path,
+        None => return Ok(data.to_string()),
+    };
+    let content = fs::read_to_string(path).map_err(|err| CurlError::Read(path.to_string(), err))?;
+
+    Ok(if strip_newlines {
+        content.replace(['\r', '\n'], "")
+    } else {
+        content
+    })
+}
+
+/// `--data-urlencode name=value` encodes only the value, `@file` and
+/// `name@file` read it from a file first.
+fn url_encode_data(data: &str) -> Result<String, CurlError> {
+    let encode = |text: &str| form_urlencoded::byte_serialize(text.as_bytes()).collect::<String>();
+
+    if let Some((name, value)) = data.split_once('=') {
+        return Ok(if name.is_empty() {
+            encode(value)
+        } else {
+            format!("{}={}", name, encode(value))
+        });
+    }
+    match data.split_once('@') {
+        Some((name, path)) => {
+            let content =
+                fs::read_to_string(path).map_err(|err| CurlError::Read(path.to_string(), err))?;
+            Ok(if name.is_empty() {
+                encode(&content)
+            } else {
+                format!("{}={}", name, encode(&content))
+            })
+        }
+        None => Ok(encode(data)),
+    }
+}
+
+/// `name=value`, `name=@path` for a file upload, or `name=<path` for a
+/// text field read from a file, optionally followed by `;type=...`.
+fn parse_form_part(form: &str, literal: bool) -> Result<ImportedFormPart, CurlError> {
+    let (name, value) = form.split_once('=').unwrap_or((form, ""));
+    let (value, content_type) = match value.split_once(";type=") {
+        Some((value, content_type)) if !literal => (value, Some(content_type.to_string())),
+        _ => (value, None),
+    };
+
+    let (kind, value) = match value.strip_prefix('@') {
+        Some(path) if !literal => (FormPartKind::File, path.to_string()),
+        _ => match value.strip_prefix('<') {
+            Some(path) if !literal => (
+                FormPartKind::Text,
+                fs::read_to_string(path).map_err(|err| CurlError::Read(path.to_string(), err))?,
+            ),
+            _ => (FormPartKind::Text, value.to_string()),
+        },
+    };
+
+    Ok(ImportedFormPart {
+        name: name.to_string(),
+        kind,
+        value,
+        content_type,
+    })
+}
+
+/// Splits a command line into words the way a POSIX shell would, including
+/// `$'...'` strings and backslash line continuations.
+fn split_words(command: &str) -> Result<Vec<String>, CurlError> {
+    let mut words = vec![];
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some('\n') | Some('\r') => (),
+                Some(escaped) => {
+                    word.push(escaped);
+                    in_word = true;
+                }
+                None => (),
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err(CurlError::UnclosedQuote),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped @ ('"' | '\\' | '$' | '`')) => word.push(escaped),
+                            Some('\n') => (),
+                            Some(other) => {
+                                word.push('\\');
+                                word.push(other);
+                            }
+                            None => return Err(CurlError::UnclosedQuote),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(CurlError::UnclosedQuote),
+                    }
+                }
+            }
+            '$' if chars.peek() == Some(&'\'') => {
+                chars.next();
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => word.push('\n'),
+                            Some('t') => word.push('\t'),
+                            Some('r') => word.push('\r'),
+                            Some(escaped) => word.push(escaped),
+                            None => return Err(CurlError::UnclosedQuote),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err(CurlError::UnclosedQuote),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn param(command: &CurlCommand, key: &str) -> Option<String> {
+        command
+            .params
+            .iter()
+            .find(|(name, _)| name == key)
+            .map(|(_, value)| value.clone())
+    }
+
+    #[test]
+    fn parses_a_devtools_command() {
+        let command = parse(
+            "curl 'https://api.example.com/pets?limit=10' \\\n  -H 'Accept: application/json' \\\n  -H \"Authorization: Bearer abc\" \\\n  --data-raw '{\"name\":\"Rex\"}' --compressed",
+        )
+        .unwrap();
+        assert_eq!(command.route, "https://api.example.com/pets");
+        assert_eq!(command.method, HttpMethod::Post);
+        assert_eq!(param(&command, "limit").as_deref(), Some("10"));
+        assert_eq!(command.body.as_deref(), Some("{\"name\":\"Rex\"}"));
+        assert!(command
+            .headers
+            .contains(&(String::from("Authorization"), String::from("Bearer abc"))));
+        assert!(command.ignored.is_empty());
+    }
+
+    #[test]
+    fn bundled_short_flags_all_count() {
+        for flags in ["-kG", "-Gk", "-sSGk"] {
+            let command = parse(&format!("curl {} -d q=rust http://a.test/search", flags)).unwrap();
+            assert!(command.insecure, "{}", flags);
+            assert_eq!(command.method, HttpMethod::Get, "{}", flags);
+            assert_eq!(command.body, None, "{}", flags);
+            assert_eq!(param(&command, "q").as_deref(), Some("rust"), "{}", flags);
+        }
+    }
+
+    #[test]
+    fn bundled_flags_may_end_with_one_taking_a_value() {
+        let command = parse("curl -kXPUT http://a.test/ -sH 'X-Id: 1'").unwrap();
+        assert!(command.insecure);
+        assert_eq!(command.method, HttpMethod::Put);
+        assert_eq!(
+            command.headers,
+            vec![(String::from("X-Id"), String::from("1"))]
+        );
+
+        let command = parse("curl -kX DELETE http://a.test/").unwrap();
+        assert_eq!(command.method, HttpMethod::Delete);
+    }
+
+    #[test]
+    fn unknown_flags_are_ignored() {
+        let command = parse("curl --http2 -sZ -o out.txt http://a.test/").unwrap();
+        assert_eq!(command.ignored, vec!["--http2", "-sZ", "-o"]);
+        assert_eq!(command.route, "http://a.test/");
+    }
+
+    #[test]
+    fn unknown_methods_are_ignored() {
+        let command = parse("curl -X 'GET/1' -d q=1 http://a.test/").unwrap();
+        assert_eq!(command.ignored, vec!["-X GET/1"]);
+        assert_eq!(command.method, HttpMethod::Post);
+
+        let command = parse("curl -X PURGE http://a.test/").unwrap();
+        assert!(command.ignored.is_empty());
+        assert_eq!(command.method.as_str(), "PURGE");
+    }
+
+    #[test]
+    fn user_and_head() {
+        let command = parse("curl -I -u me:secret http://a.test/").unwrap();
+        assert_eq!(command.method, HttpMethod::Head);
+        assert!(command.headers.contains(&(
+            String::from("Authorization"),
+            String::from("Basic bWU6c2VjcmV0")
+        )));
+    }
+
+    #[test]
+    fn form_parts() {
+        let path = std::env::temp_dir().join("reqstr-curl-form-test.txt");
+        fs::write(&path, "from a file").unwrap();
+
+        let command = parse(&format!(
+            "curl -F 'photo=@cat.png;type=image/png' -F 'note=<{}' --form-string 'raw=@not-a-file' http://a.test/",
+            path.display()
+        ))
+        .unwrap();
+        fs::remove_file(&path).ok();
+
+        let parts: Vec<_> = command
+            .form_parts
+            .iter()
+            .map(|part| {
+                (
+                    part.name.as_str(),
+                    part.kind,
+                    part.value.as_str(),
+                    part.content_type.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            parts,
+            vec![
+                ("photo", FormPartKind::File, "cat.png", Some("image/png")),
+                ("note", FormPartKind::Text, "from a file", None),
+                ("raw", FormPartKind::Text, "@not-a-file", None),
+            ]
+        );
+        assert_eq!(command.method, HttpMethod::Post);
+    }
+
+    #[test]
+    fn unreadable_form_files_are_errors() {
+        let result = parse("curl -F 'note=</no/such/file' http://a.test/");
+        assert!(matches!(result, Err(CurlError::Read(path, _)) if path == "/no/such/file"));
+    }
+
+    #[test]
+    fn shell_quoting() {
+        assert_eq!(
+            split_words("a 'b c' \"d \\\"e\\\"\" $'f\\ng' h\\ i").unwrap(),
+            vec!["a", "b c", "d \"e\"", "f\ng", "h i"]
+        );
+        assert!(matches!(
+            split_words("curl 'http://a.test"),
+            Err(CurlError::UnclosedQuote)
+        ));
+        assert!(matches!(parse("curl -s"), Err(CurlError::MissingUrl)));
+        assert!(matches!(
+            parse("curl http://a.test -H"),
+            Err(CurlError::MissingValue(flag)) if flag == "-H"
+        ));
+    }
+
+    #[test]
+    fn url_encoded_data() {
+        let command = parse("curl --data-urlencode 'q=a b&c' http://a.test/").unwrap();
+        assert_eq!(command.body.as_deref(), Some("q=a+b%26c"));
+    }
+}
@@ -0,0 +1,421 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use ::http::StatusCode;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::{
+    markdown::{self, escape, LineKind},
+    models::{Api, Request, Resource},
+    services::{
+        api_service, form_part_service, graphql_service, header_service, param_service,
+        recorded_response_service, request_service, resource_service, variable_service,
+    },
+    types::RequestKind,
+};
+
+const INDEX: &str = "index.html";
+
+const STYLE: &str = "
+body { margin: 0; font: 15px/1.5 -apple-system, 'Segoe UI', Helvetica, Arial, sans-serif; color: #1f2328; }
+nav { position: fixed; top: 0; bottom: 0; left: 0; width: 280px; overflow-y: auto; padding: 16px; box-sizing: border-box; background: #f6f8fa; border-right: 1px solid #d0d7de; font-size: 14px; }
+nav ul { list-style: none; margin: 0; padding-left: 14px; }
+nav > ul { padding-left: 0; }
+nav a { display: block; padding: 2px 4px; color: #1f2328; text-decoration: none; border-radius: 4px; overflow: hidden; text-overflow: ellipsis; white-space: nowrap; }
+nav a:hover, nav a.current { background: #ddf4ff; }
+nav .api { font-weight: 600; font-size: 16px; margin-bottom: 8px; }
+nav .folder { font-weight: 600; }
+main { margin-left: 280px; padding: 24px 40px; max-width: 960px; }
+section { border-top: 1px solid #d0d7de; padding-top: 8px; margin-top: 32px; }
+.breadcrumb { color: #656d76; }
+.method { display: inline-block; min-width: 48px; padding: 0 6px; border-radius: 4px; font: 600 12px/20px monospace; text-align: center; color: #fff; background: #6e7781; }
+.get { background: #1a7f37; } .post { background: #0969da; } .put, .patch { background: #9a6700; } .delete { background: #cf222e; }
+code, pre { font-family: ui-monospace, SFMono-Regular, Menlo, monospace; font-size: 13px; }
+pre { background: #f6f8fa; padding: 12px; border-radius: 6px; overflow-x: auto; }
+table { border-collapse: collapse; margin: 8px 0; }
+th, td { border: 1px solid #d0d7de; padding: 4px 10px; text-align: left; vertical-align: top; }
+blockquote { margin: 0; padding-left: 12px; border-left: 4px solid #d0d7de; color: #656d76; }
+";
+
+#[derive(Debug, Error)]
+pub enum DocsError {
+    #[error("API {0} not found")]
+    UnknownApi(i32),
+    #[error("No API called {0}")]
+    UnknownApiName(String),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+}
+
+/// A folder of the API with the page it is documented on.
+struct Folder {
+    resource: Resource,
+    page: String,
+    requests: Vec<Request>,
+    children: Vec<Folder>,
+}
+
+/// Writes static HTML documentation of an API to a directory: an overview
+/// page and a page per folder, each with the whole collection in a
+/// sidebar. The pages need nothing but themselves, so the directory can be
+/// published as it is. Returns how many pages were written.
+pub fn export_to_dir(api_id: i32, dir: &str) -> Result<usize, DocsError> {
+    let api = api_service::get_api_by_id(api_id).ok_or(DocsError::UnknownApi(api_id))?;
+    let mut pages = HashSet::from([String::from(INDEX)]);
+    let folders = folders(resource_service::get_root_resources(api_id), "", &mut pages);
+
+    fs::create_dir_all(dir).map_err(|err| DocsError::Write(dir.to_string(), err))?;
+    write_page(
+        dir,
+        INDEX,
+        &page(&api, &api.name, INDEX, &folders, &index(&api, &folders)),
+    )?;
+    let mut written = 1;
+    let mut stack: Vec<(&Folder, Vec<&Folder>)> =
+        folders.iter().map(|folder| (folder, vec![])).collect();
+    while let Some((folder, parents)) = stack.pop() {
+        let content = folder_page(&api, &parents, folder);
+        let title = format!("{} - {}", folder.resource.name, api.name);
+        write_page(
+            dir,
+            &folder.page,
+            &page(&api, &title, &folder.page, &folders, &content),
+        )?;
+        written += 1;
+
+        for child in &folder.children {
+            let mut path = parents.clone();
+            path.push(folder);
+            stack.push((child, path));
+        }
+    }
+    Ok(written)
+}
+
+/// Writes the documentation of the API with the given name.
+pub fn export_named_to_dir(name: &str, dir: &str) -> Result<usize, DocsError> {
+    let api = api_service::get_all_apis()
+        .into_iter()
+        .find(|api| api.name == name)
+        .ok_or_else(|| DocsError::UnknownApiName(name.to_string()))?;
+    export_to_dir(api.id, dir)
+}
+
+fn write_page(dir: &str, file: &str, html: &str) -> Result<(), DocsError> {
+    let path = Path::new(dir).join(file);
+    fs::write(&path, html).map_err(|err| DocsError::Write(path.display().to_string(), err))
+}
+
+fn folders(resources: Vec<Resource>, prefix: &str, pages: &mut HashSet<String>) -> Vec<Folder> {
+    resources
+        .into_iter()
+        .map(|resource| {
+            let name = format!("{}{}", prefix, slug(&resource.name));
+            let mut page = format!("{}.html", name);
+            if !pages.insert(page.clone()) {
+                page = format!("{}-{}.html", name, resource.id);
+                pages.insert(page.clone());
+            }
+            let children = folders(
+                resource_service::get_child_resources(resource.id),
+                &format!("{}-", name),
+                pages,
+            );
+            Folder {
+                requests: request_service::get_requests_for_resource(resource.id)
+                    .unwrap_or_default(),
+                resource,
+                page,
+                children,
+            }
+        })
+        .collect()
+}
+
+fn slug(name: &str) -> String {
+    let slug = name
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        String::from("folder")
+    } else {
+        slug
+    }
+}
+
+fn page(api: &Api, title: &str, current: &str, folders: &[Folder], content: &str) -> String {
+    let mut sidebar = format!(
+        "<a class=\"api{}\" href=\"{}\">{}</a>\n",
+        if current == INDEX { " current" } else { "" },
+        INDEX,
+        escape(&api.name)
+    );
+    if !folders.is_empty() {
+        sidebar.push_str("<ul>\n");
+        sidebar_folders(&mut sidebar, folders, current);
+        sidebar.push_str("</ul>\n");
+    }
+
+    format!(
+        "<!DOCTYPE html>
+<html lang=\"en\">
+<head>
+<meta charset=\"utf-8\">
+<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">
+<title>{}</title>
+<style>{}</style>
+</head>
+<body>
+<nav>
+{}</nav>
+<main>
+{}</main>
+</body>
+</html>
+",
+        escape(title),
+        STYLE,
+        sidebar,
+        content
+    )
+}
+
+/// The items of folders in the sidebar, their folders and requests nested
+/// under them.
+fn sidebar_folders(html: &mut String, folders: &[Folder], current: &str) {
+    for folder in folders {
+        html.push_str(&format!(
+            "<li><a class=\"folder{}\" href=\"{}\">{}/</a>\n",
+            if folder.page == current {
+                " current"
+            } else {
+                ""
+            },
+            folder.page,
+            escape(&folder.resource.name)
+        ));
+        if !folder.children.is_empty() || !folder.requests.is_empty() {
+            html.push_str("<ul>\n");
+            sidebar_folders(html, &folder.children, current);
+            for request in &folder.requests {
+                html.push_str(&format!(
+                    "<li><a href=\"{}#request-{}\">{} {}</a></li>\n",
+                    folder.page,
+                    request.id,
+                    method_badge(request),
+                    escape(&request_name(request))
+                ));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</li>\n");
+    }
+}
+
+fn index(api: &Api, folders: &[Folder]) -> String {
+    let mut html = format!("<h1>{}</h1>\n", escape(&api.name));
+    if let Some(description) = &api.description {
+        html.push_str(&markdown::to_html(description));
+    }
+
+    let variables: Vec<(String, String)> = variable_service::get_variables_for_api(api.id)
+        .into_iter()
+        .map(|variable| (format!("{{{{{}}}}}", variable.key), variable.value))
+        .collect();
+    html.push_str(&table("Variables", ["Variable", "Value"], &variables));
+
+    html.push_str(&folder_list("Folders", folders));
+    html
+}
+
+fn folder_list(title: &str, folders: &[Folder]) -> String {
+    if folders.is_empty() {
+        return String::new();
+    }
+    let mut html = format!("<h2>{}</h2>\n<ul>\n", title);
+    for folder in folders {
+        let summary = folder
+            .resource
+            .description
+            .as_deref()
+            .and_then(|description| {
+                markdown::lines(description)
+                    .into_iter()
+                    .find(|(kind, _)| !matches!(kind, LineKind::Blank | LineKind::Code))
+            })
+            .map(|(_, line)| format!(" - {}", markdown::inline_html(line)))
+            .unwrap_or_default();
+        html.push_str(&format!(
+            "<li><a href=\"{}\">{}</a>{}</li>\n",
+            folder.page,
+            escape(&folder.resource.name),
+            summary
+        ));
+    }
+    html.push_str("</ul>\n");
+    html
+}
+
+fn folder_page(api: &Api, parents: &[&Folder], folder: &Folder) -> String {
+    let mut breadcrumb = format!("<a href=\"{}\">{}</a>", INDEX, escape(&api.name));
+    for parent in parents {
+        breadcrumb.push_str(&format!(
+            " / <a href=\"{}\">{}</a>",
+            parent.page,
+            escape(&parent.resource.name)
+        ));
+    }
+
+    let mut html = format!(
+        "<p class=\"breadcrumb\">{}</p>\n<h1>{}</h1>\n",
+        breadcrumb,
+        escape(&folder.resource.name)
+    );
+    if let Some(description) = &folder.resource.description {
+        html.push_str(&markdown::to_html(description));
+    }
+    html.push_str(&folder_list("Folders", &folder.children));
+
+    for request in &folder.requests {
+        html.push_str(&request_section(request));
+    }
+    html
+}
+
+fn request_section(request: &Request) -> String {
+    let mut html = format!(
+        "<section id=\"request-{}\">\n<h2>{}</h2>\n<p>{} <code>{}</code></p>\n",
+        request.id,
+        escape(&request_name(request)),
+        method_badge(request),
+        escape(&request.route)
+    );
+    if let Some(description) = &request.description {
+        html.push_str(&markdown::to_html(description));
+    }
+
+    let headers: Vec<(String, String)> = header_service::get_headers_for_request(request.id)
+        .into_iter()
+        .map(|header| (header.key, header.value))
+        .collect();
+    html.push_str(&table("Headers", ["Name", "Value"], &headers));
+
+    let params: Vec<(String, String)> = param_service::get_params_for_request(request.id)
+        .into_iter()
+        .map(|param| (param.key, param.value))
+        .collect();
+    html.push_str(&table("Params", ["Name", "Value"], &params));
+
+    let form_parts: Vec<(String, String)> =
+        form_part_service::get_form_parts_for_request(request.id)
+            .into_iter()
+            .map(|part| {
+                let value = match part.content_type {
+                    Some(content_type) => {
+                        format!("{} ({}, {})", part.value, part.kind, content_type)
+                    }
+                    None => format!("{} ({})", part.value, part.kind),
+                };
+                (part.name, value)
+            })
+            .collect();
+    html.push_str(&table("Form", ["Name", "Value"], &form_parts));
+
+    let query = match request.kind {
+        RequestKind::Graphql => graphql_service::get_query_for_request(request.id),
+        _ => None,
+    };
+    if let Some(query) = query {
+        html.push_str(&code_block("Query", &query.query));
+        if !query.variables.trim().is_empty() {
+            html.push_str(&code_block("Variables", &pretty(&query.variables)));
+        }
+    } else if let Some(body) = request
+        .body
+        .as_deref()
+        .filter(|body| !body.trim().is_empty())
+    {
+        html.push_str(&code_block("Body", &pretty(body)));
+    }
+
+    let responses = recorded_response_service::get_examples_for_request(request.id);
+    if !responses.is_empty() {
+        html.push_str("<h3>Example responses</h3>\n");
+    }
+    for response in responses {
+        let reason = StatusCode::from_u16(response.status as u16)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("");
+        html.push_str(&format!("<h4>{} {}", response.status, reason));
+        if let Some(content_type) = &response.content_type {
+            html.push_str(&format!(" <code>{}</code>", escape(content_type)));
+        }
+        html.push_str(&format!(
+            "</h4>\n<pre><code>{}</code></pre>\n",
+            escape(&pretty(&response.body))
+        ));
+    }
+
+    html.push_str("</section>\n");
+    html
+}
+
+fn table(title: &str, headings: [&str; 2], rows: &[(String, String)]) -> String {
+    if rows.is_empty() {
+        return String::new();
+    }
+    let mut html = format!(
+        "<h3>{}</h3>\n<table>\n<tr><th>{}</th><th>{}</th></tr>\n",
+        title, headings[0], headings[1]
+    );
+    for (name, value) in rows {
+        html.push_str(&format!(
+            "<tr><td><code>{}</code></td><td><code>{}</code></td></tr>\n",
+            escape(name),
+            escape(value)
+        ));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+fn code_block(title: &str, code: &str) -> String {
+    format!(
+        "<h3>{}</h3>\n<pre><code>{}</code></pre>\n",
+        title,
+        escape(code)
+    )
+}
+
+/// JSON laid out to be read, anything else as it is.
+fn pretty(text: &str) -> String {
+    serde_json::from_str::<Value>(text)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| text.to_string())
+}
+
+fn request_name(request: &Request) -> String {
+    match &request.name {
+        Some(name) if !name.is_empty() => name.clone(),
+        _ => request.route.clone(),
+    }
+}
+
+fn method_badge(request: &Request) -> String {
+    let method = match request.kind {
+        RequestKind::Http => request.method.as_str().to_string(),
+        RequestKind::Graphql => String::from("GQL"),
+        RequestKind::Websocket => String::from("WS"),
+        RequestKind::Grpc => String::from("GRPC"),
+    };
+    format!(
+        "<span class=\"method {}\">{}</span>",
+        method.to_lowercase(),
+        escape(&method)
+    )
+}
@@ -0,0 +1,226 @@
+use std::{collections::HashMap, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    models::Request,
+    services::{recorded_response_service, request_service, tag_service},
+};
+
+#[derive(Debug, Error)]
+pub enum FilterError {
+    #[error("Unknown filter {0}:, try tag:, method:, status: or is:favourite")]
+    UnknownKey(String),
+    #[error("{0} is not a status, try 404 or 5xx")]
+    Status(String),
+    #[error("is:{0} is not a filter, try is:favourite")]
+    Is(String),
+}
+
+#[derive(Debug, PartialEq)]
+enum Term {
+    Tag(String),
+    Method(String),
+    /// A status from the first to the second, of the latest response
+    /// recorded for a request, streamed ones included once they ended.
+    Status(i32, i32),
+    Favourite,
+    Text(String),
+}
+
+/// A filter like `tag:auth method:POST status:5xx` narrowing down the
+/// requests of every API. A request has to match every part, and words
+/// without a key are looked for in its name and route.
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+impl FromStr for Filter {
+    type Err = FilterError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut terms = vec![];
+        for word in text.split_whitespace() {
+            let term = match word.split_once(':') {
+                Some(("tag", name)) => Term::Tag(name.to_lowercase()),
+                Some(("method", method)) => Term::Method(method.to_uppercase()),
+                Some(("status", status)) => status_range(status)
+                    .map(|(low, high)| Term::Status(low, high))
+                    .ok_or_else(|| FilterError::Status(status.to_string()))?,
+                Some(("is", "fav" | "favourite" | "favorite" | "starred")) => Term::Favourite,
+                Some(("is", other)) => return Err(FilterError::Is(other.to_string())),
+                // Unless it is part of a URL.
+                Some((key, rest))
+                    if !key.is_empty()
+                        && key.chars().all(char::is_alphabetic)
+                        && !rest.starts_with("//") =>
+                {
+                    return Err(FilterError::UnknownKey(key.to_string()))
+                }
+                _ => Term::Text(word.to_lowercase()),
+            };
+            terms.push(term);
+        }
+        Ok(Self { terms })
+    }
+}
+
+impl Filter {
+    /// The requests of every API matching the filter.
+    pub fn requests(&self) -> Vec<Request> {
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        for tag in tag_service::get_all_tags() {
+            tags.entry(tag.request_id)
+                .or_default()
+                .push(tag.name.to_lowercase());
+        }
+        let statuses: HashMap<i32, i32> = recorded_response_service::get_latest_statuses()
+            .into_iter()
+            .collect();
+
+        request_service::get_all_requests()
+            .into_iter()
+            .filter(|request| {
+                let tags = tags.get(&request.id).map(Vec::as_slice).unwrap_or_default();
+                let status = statuses.get(&request.id);
+                self.terms.iter().all(|term| match term {
+                    Term::Tag(name) => tags.contains(name),
+                    Term::Method(method) => request.method.as_str() == method,
+                    Term::Status(low, high) => {
+                        status.is_some_and(|status| (low..=high).contains(&status))
+                    }
+                    Term::Favourite => request.favourite,
+                    Term::Text(text) => {
+                        request.title().to_lowercase().contains(text)
+                            || request.route.to_lowercase().contains(text)
+                    }
+                })
+            })
+            .collect()
+    }
+}
+
+/// The statuses meant by `404`, or by a class like `5xx`.
+fn status_range(status: &str) -> Option<(i32, i32)> {
+    let status = status.to_lowercase();
+    match status.strip_suffix("xx") {
+        Some(class) if class.len() == 1 => {
+            let class: i32 = class.parse().ok()?;
+            Some((class * 100, class * 100 + 99))
+        }
+        _ if status.len() == 3 => {
+            let code = status.parse().ok()?;
+            Some((code, code))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{NewApi, NewRecordedResponse, NewRequest, NewResource},
+        services::{api_service, resource_service},
+        types::{HttpMethod, RequestKind},
+    };
+
+    #[test]
+    fn reads_every_kind_of_term() {
+        let filter: Filter = "tag:Auth method:post status:5xx is:fav Login https://shop.test"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            filter.terms,
+            vec![
+                Term::Tag(String::from("auth")),
+                Term::Method(String::from("POST")),
+                Term::Status(500, 599),
+                Term::Favourite,
+                Term::Text(String::from("login")),
+                Term::Text(String::from("https://shop.test")),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_statuses_and_classes() {
+        assert_eq!(status_range("404"), Some((404, 404)));
+        assert_eq!(status_range("2XX"), Some((200, 299)));
+        assert_eq!(status_range("xx"), None);
+        assert_eq!(status_range("40"), None);
+        assert_eq!(status_range("abc"), None);
+    }
+
+    #[test]
+    fn rejects_unknown_filters() {
+        assert!(matches!(
+            "colour:red".parse::<Filter>(),
+            Err(FilterError::UnknownKey(key)) if key == "colour"
+        ));
+        assert!(matches!(
+            "status:teapot".parse::<Filter>(),
+            Err(FilterError::Status(status)) if status == "teapot"
+        ));
+        assert!(matches!(
+            "is:broken".parse::<Filter>(),
+            Err(FilterError::Is(name)) if name == "broken"
+        ));
+    }
+
+    #[test]
+    fn matches_the_latest_status() {
+        api_service::create_new_api(NewApi { name: "Filtered" });
+        let api = api_service::get_all_apis().pop().unwrap();
+        resource_service::create_new_resource(NewResource {
+            name: "Orders",
+            api_id: api.id,
+            parent_id: None,
+        });
+        let resource = resource_service::get_root_resources(api.id).pop().unwrap();
+
+        let mut ids = vec![];
+        for (route, statuses) in [
+            ("/filtered/fixed", [500, 200]),
+            ("/filtered/broken", [200, 503]),
+        ] {
+            let request = request_service::create_request(NewRequest {
+                resource_id: resource.id,
+                route,
+                method: HttpMethod::Get,
+                body: None,
+                kind: RequestKind::Http,
+            });
+            for status in statuses {
+                recorded_response_service::record_response(&NewRecordedResponse {
+                    request_id: request.id,
+                    status,
+                    content_type: None,
+                    body: "",
+                    recorded_at: "2024-01-01T00:00:00+00:00",
+                    headers: "[]",
+                    body_base64: false,
+                    duration_ms: 0,
+                    method: "GET",
+                    url: route,
+                    request_headers: "[]",
+                    request_body: None,
+                });
+            }
+            ids.push(request.id);
+        }
+
+        let found = |filter: &str| -> Vec<i32> {
+            filter
+                .parse::<Filter>()
+                .unwrap()
+                .requests()
+                .iter()
+                .map(|request| request.id)
+                .collect()
+        };
+        assert_eq!(found("/filtered status:5xx"), [ids[1]]);
+        assert_eq!(found("/filtered status:200"), [ids[0]]);
+    }
+}
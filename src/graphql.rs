@@ -0,0 +1,974 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+};
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+use crate::{
+    http::{self, HttpError},
+    models::{GraphqlQuery, Request},
+    services::header_service,
+};
+
+/// Asks the endpoint for every type with its fields and their arguments.
+/// Type references are unwrapped seven levels deep, which covers anything
+/// like `[[Item!]!]!`.
+pub const INTROSPECTION_QUERY: &str = "query IntrospectionQuery {
+  __schema {
+    queryType { name }
+    mutationType { name }
+    subscriptionType { name }
+    types {
+      kind
+      name
+      fields(includeDeprecated: true) {
+        name
+        args { name type { ...TypeRef } }
+        type { ...TypeRef }
+      }
+    }
+  }
+}
+
+fragment TypeRef on __Type {
+  kind name
+  ofType { kind name ofType { kind name ofType { kind name ofType { kind name
+    ofType { kind name ofType { kind name ofType { kind name } } } } } } }
+}";
+
+#[derive(Debug, Error)]
+pub enum GraphqlError {
+    #[error("Variables are not valid JSON: {0}")]
+    Variables(serde_json::Error),
+    #[error("Variables must be a JSON object")]
+    VariablesNotObject,
+    #[error("The request has no GraphQL query yet")]
+    MissingQuery,
+    #[error("Invalid introspection result: {0}")]
+    Introspection(String),
+}
+
+/// The JSON envelope sent as the body of a POST request.
+pub fn envelope(query: &GraphqlQuery) -> Result<Value, GraphqlError> {
+    let mut body = Map::new();
+    body.insert(String::from("query"), Value::String(query.query.clone()));
+
+    if let Some(variables) = parse_variables(&query.variables)? {
+        body.insert(String::from("variables"), variables);
+    }
+    if let Some(operation_name) = &query.operation_name {
+        body.insert(
+            String::from("operationName"),
+            Value::String(operation_name.clone()),
+        );
+    }
+
+    Ok(Value::Object(body))
+}
+
+/// The same fields as `envelope`, encoded as query parameters for GET
+/// requests.
+pub fn query_params(query: &GraphqlQuery) -> Result<Vec<(String, String)>, GraphqlError> {
+    let mut params = vec![(String::from("query"), query.query.clone())];
+
+    if let Some(variables) = parse_variables(&query.variables)? {
+        params.push((String::from("variables"), variables.to_string()));
+    }
+    if let Some(operation_name) = &query.operation_name {
+        params.push((String::from("operationName"), operation_name.clone()));
+    }
+
+    Ok(params)
+}
+
+pub fn parse_variables(variables: &str) -> Result<Option<Value>, GraphqlError> {
+    if variables.trim().is_empty() {
+        return Ok(None);
+    }
+
+    match serde_json::from_str(variables).map_err(GraphqlError::Variables)? {
+        Value::Object(map) => Ok(Some(Value::Object(map))),
+        Value::Null => Ok(None),
+        _ => Err(GraphqlError::VariablesNotObject),
+    }
+}
+
+/// Runs the introspection query against the endpoint of a request, with
+/// its headers and the API's variables expanded as when it is sent, and
+/// returns the raw result, which is what gets cached.
+pub fn introspect(request: &Request, hostname: &str) -> Result<String, HttpError> {
+    let tls = http::tls_config_for_resource(request.resource_id);
+    let variables = http::variables_for_resource(request.resource_id);
+    let expand = |text: &str| http::expand_variables(text, &variables);
+
+    let client = http::build_client(tls.as_ref())?;
+    let mut builder = client
+        .post(http::request_url(hostname, &expand(&request.route)))
+        .header("content-type", "application/json")
+        .body(json!({ "query": INTROSPECTION_QUERY }).to_string());
+
+    for header in header_service::get_headers_for_request(request.id) {
+        if !header.key.eq_ignore_ascii_case("content-type") {
+            let (key, value) = http::expand_header(&header, &variables);
+            builder = builder.header(key, value);
+        }
+    }
+
+    let response = http::OpenResponse::send(builder)?;
+    response.response.error_for_status_ref()?;
+    let text = String::from_utf8_lossy(&response.bytes()?).into_owned();
+    Schema::parse(&text)?;
+
+    Ok(text)
+}
+
+pub struct Field {
+    pub name: String,
+    /// The named type once lists and non-null wrappers are removed.
+    pub type_name: String,
+    /// The full type as written in SDL, e.g. `[User!]!`.
+    pub type_display: String,
+    pub args: Vec<(String, String)>,
+}
+
+pub struct SchemaType {
+    pub kind: String,
+    pub fields: Vec<Field>,
+}
+
+impl SchemaType {
+    fn is_leaf(&self) -> bool {
+        self.kind == "SCALAR" || self.kind == "ENUM"
+    }
+
+    fn field(&self, name: &str) -> Option<&Field> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+}
+
+/// A schema as far as completion and validation need it.
+pub struct Schema {
+    pub query_type: Option<String>,
+    pub mutation_type: Option<String>,
+    pub subscription_type: Option<String>,
+    pub types: HashMap<String, SchemaType>,
+}
+
+/// Where the end of a partially typed query is.
+enum Context {
+    Definitions,
+    Selection(Option<String>),
+    Arguments(Option<String>, String),
+}
+
+pub struct Completion {
+    pub prefix: String,
+    /// Candidate names with a short description, e.g. `("id", "ID!")`.
+    pub candidates: Vec<(String, String)>,
+}
+
+impl Schema {
+    pub fn parse(introspection: &str) -> Result<Self, GraphqlError> {
+        let invalid = |reason: &str| GraphqlError::Introspection(reason.to_string());
+
+        let value: Value =
+            serde_json::from_str(introspection).map_err(|err| invalid(&err.to_string()))?;
+        let schema = value
+            .pointer("/data/__schema")
+            .ok_or_else(|| invalid("no data.__schema in the response"))?;
+        let root_name = |key: &str| {
+            schema
+                .pointer(&format!("/{}/name", key))
+                .and_then(Value::as_str)
+                .map(String::from)
+        };
+
+        let mut types = HashMap::new();
+        for schema_type in schema
+            .get("types")
+            .and_then(Value::as_array)
+            .ok_or_else(|| invalid("no types in the schema"))?
+        {
+            let name = match schema_type.get("name").and_then(Value::as_str) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let kind = schema_type
+                .get("kind")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            let fields = schema_type
+                .get("fields")
+                .and_then(Value::as_array)
+                .map(|fields| fields.iter().filter_map(parse_field).collect())
+                .unwrap_or_default();
+
+            types.insert(name, SchemaType { kind, fields });
+        }
+
+        Ok(Self {
+            query_type: root_name("queryType"),
+            mutation_type: root_name("mutationType"),
+            subscription_type: root_name("subscriptionType"),
+            types,
+        })
+    }
+
+    /// Suggests names for the word at the end of `query`: fields of the
+    /// enclosing selection set, arguments inside parentheses and operation
+    /// keywords at the top level.
+    pub fn complete(&self, query: &str) -> Completion {
+        let prefix_start = query
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+            .last()
+            .map(|(index, _)| index)
+            .unwrap_or(query.len());
+        let prefix = &query[prefix_start..];
+
+        let (tokens, _) = tokenize(&query[..prefix_start]);
+        let mut walker = Walker::new(self, &tokens);
+        walker.run();
+
+        let mut candidates: Vec<(String, String)> = match walker.context {
+            Context::Definitions => ["query", "mutation", "subscription", "fragment"]
+                .iter()
+                .map(|keyword| (keyword.to_string(), String::from("keyword")))
+                .collect(),
+            Context::Selection(Some(type_name)) => match self.types.get(&type_name) {
+                Some(schema_type) => schema_type
+                    .fields
+                    .iter()
+                    .map(|field| (field.name.clone(), field.type_display.clone()))
+                    .chain([(String::from("__typename"), String::from("String!"))])
+                    .collect(),
+                None => vec![],
+            },
+            Context::Arguments(Some(type_name), field_name) => self
+                .types
+                .get(&type_name)
+                .and_then(|schema_type| schema_type.field(&field_name))
+                .map(|field| field.args.clone())
+                .unwrap_or_default(),
+            _ => vec![],
+        };
+        candidates.retain(|(name, _)| name.starts_with(prefix));
+
+        Completion {
+            prefix: prefix.to_string(),
+            candidates,
+        }
+    }
+
+    /// Checks that every selected field exists on its type and that leaf
+    /// and object fields are selected correctly.
+    pub fn validate(&self, query: &str) -> Vec<String> {
+        let (tokens, mut problems) = tokenize(query);
+        let mut walker = Walker::new(self, &tokens);
+        walker.run();
+        problems.append(&mut walker.problems);
+
+        if !walker.stack.is_empty() {
+            problems.push(String::from("Missing closing }"));
+        }
+
+        problems
+    }
+
+    fn root_type(&self, operation: &str) -> Option<&String> {
+        match operation {
+            "mutation" => self.mutation_type.as_ref(),
+            "subscription" => self.subscription_type.as_ref(),
+            _ => self.query_type.as_ref(),
+        }
+    }
+}
+
+fn parse_field(field: &Value) -> Option<Field> {
+    let (type_name, type_display) = type_ref(field.get("type")?)?;
+    let args = field
+        .get("args")
+        .and_then(Value::as_array)
+        .map(|args| {
+            args.iter()
+                .filter_map(|arg| {
+                    let name = arg.get("name")?.as_str()?.to_string();
+                    let (_, display) = type_ref(arg.get("type")?)?;
+                    Some((name, display))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(Field {
+        name: field.get("name")?.as_str()?.to_string(),
+        type_name,
+        type_display,
+        args,
+    })
+}
+
+/// Unwraps an introspection type reference into its named type and its
+/// SDL notation.
+fn type_ref(value: &Value) -> Option<(String, String)> {
+    match value.get("kind")?.as_str()? {
+        "NON_NULL" => {
+            let (name, display) = type_ref(value.get("ofType")?)?;
+            Some((name, format!("{}!", display)))
+        }
+        "LIST" => {
+            let (name, display) = type_ref(value.get("ofType")?)?;
+            Some((name, format!("[{}]", display)))
+        }
+        _ => {
+            let name = value.get("name")?.as_str()?.to_string();
+            Some((name.clone(), name))
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Punctuator(char),
+    Spread,
+    Value,
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Token::Name(name) => write!(f, "{}", name),
+            Token::Punctuator(c) => write!(f, "{}", c),
+            Token::Spread => write!(f, "..."),
+            Token::Value => write!(f, "value"),
+        }
+    }
+}
+
+/// Splits a document into tokens with their line numbers. String and
+/// number literals are kept only as placeholders.
+fn tokenize(document: &str) -> (Vec<(Token, usize)>, Vec<String>) {
+    let mut tokens = vec![];
+    let mut problems = vec![];
+    let mut chars = document.chars().peekable();
+    let mut line = 1;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            '#' => {
+                while chars.peek().is_some_and(|&next| next != '\n') {
+                    chars.next();
+                }
+            }
+            '"' if chars.next_if_eq(&'"').is_some() => {
+                // `""` is an empty string, `"""` starts a block string,
+                // which ends at the next `"""` not escaped as `\"""`.
+                if chars.next_if_eq(&'"').is_some() {
+                    let start = line;
+                    let mut quotes = 0;
+                    while quotes < 3 {
+                        match chars.next() {
+                            Some('"') => quotes += 1,
+                            Some('\\') => {
+                                quotes = 0;
+                                if chars.peek() == Some(&'"') {
+                                    for _ in 0..3 {
+                                        chars.next_if_eq(&'"');
+                                    }
+                                }
+                            }
+                            Some(next) => {
+                                quotes = 0;
+                                if next == '\n' {
+                                    line += 1;
+                                }
+                            }
+                            None => break,
+                        }
+                    }
+                    if quotes < 3 {
+                        problems.push(format!("Unterminated block string (line {})", start));
+                    }
+                    tokens.push((Token::Value, start));
+                } else {
+                    tokens.push((Token::Value, line));
+                }
+            }
+            '"' => {
+                let start = line;
+                let mut closed = false;
+                while let Some(next) = chars.next() {
+                    match next {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => {
+                            closed = true;
+                            break;
+                        }
+                        '\n' => line += 1,
+                        _ => (),
+                    }
+                }
+                if !closed {
+                    problems.push(format!("Unterminated string (line {})", start));
+                }
+                tokens.push((Token::Value, start));
+            }
+            '.' => {
+                if chars.next_if_eq(&'.').is_some() && chars.next_if_eq(&'.').is_some() {
+                    tokens.push((Token::Spread, line));
+                } else {
+                    problems.push(format!("Unexpected . (line {})", line));
+                }
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut name = String::from(c);
+                while let Some(next) = chars.next_if(|next| next.is_alphanumeric() || *next == '_')
+                {
+                    name.push(next);
+                }
+                tokens.push((Token::Name(name), line));
+            }
+            c if c.is_ascii_digit() || c == '-' => {
+                while chars
+                    .next_if(|next| {
+                        next.is_ascii_alphanumeric() || *next == '.' || *next == '+' || *next == '-'
+                    })
+                    .is_some()
+                {}
+                tokens.push((Token::Value, line));
+            }
+            c if c.is_whitespace() || c == ',' => (),
+            c => tokens.push((Token::Punctuator(c), line)),
+        }
+    }
+
+    (tokens, problems)
+}
+
+/// Walks the tokens of a document, keeping track of the type of every
+/// selection set it is in.
+struct Walker<'a> {
+    schema: &'a Schema,
+    tokens: &'a [(Token, usize)],
+    position: usize,
+    stack: Vec<Option<String>>,
+    context: Context,
+    problems: Vec<String>,
+}
+
+impl<'a> Walker<'a> {
+    fn new(schema: &'a Schema, tokens: &'a [(Token, usize)]) -> Self {
+        Self {
+            schema,
+            tokens,
+            position: 0,
+            stack: vec![],
+            context: Context::Definitions,
+            problems: vec![],
+        }
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position.saturating_sub(1))
+            .map(|(_, line)| *line)
+            .unwrap_or(1)
+    }
+
+    fn next_name(&mut self) -> Option<&'a str> {
+        match self.peek() {
+            Some(Token::Name(name)) => {
+                self.position += 1;
+                Some(name)
+            }
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, punctuator: char) -> bool {
+        if self.peek() == Some(&Token::Punctuator(punctuator)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Skips a parenthesised list. Returns false if the document ends
+    /// inside it.
+    fn skip_parentheses(&mut self) -> bool {
+        if !self.eat('(') {
+            return true;
+        }
+
+        let mut depth = 1;
+        while let Some(token) = self.next() {
+            match token {
+                Token::Punctuator('(') => depth += 1,
+                Token::Punctuator(')') => depth -= 1,
+                _ => (),
+            }
+            if depth == 0 {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn skip_directives(&mut self) {
+        while self.eat('@') {
+            self.next_name();
+            self.skip_parentheses();
+        }
+    }
+
+    fn current_type(&self) -> Option<&'a SchemaType> {
+        let name = self.stack.last()?.as_ref()?;
+        self.schema.types.get(name)
+    }
+
+    fn known_type(&mut self, name: &str) -> Option<String> {
+        if self.schema.types.contains_key(name) {
+            Some(name.to_string())
+        } else {
+            let line = self.line();
+            self.problems
+                .push(format!("Unknown type {} (line {})", name, line));
+            None
+        }
+    }
+
+    fn run(&mut self) {
+        while self.peek().is_some() {
+            if self.stack.is_empty() {
+                self.definition();
+            } else {
+                self.selection();
+            }
+        }
+
+        if !matches!(self.context, Context::Arguments(_, _)) {
+            self.context = match self.stack.last() {
+                None => Context::Definitions,
+                Some(type_name) => Context::Selection(type_name.clone()),
+            };
+        }
+    }
+
+    fn definition(&mut self) {
+        let line = self.tokens[self.position].1;
+
+        match self.next() {
+            Some(Token::Punctuator('{')) => {
+                let root = self.schema.root_type("query").cloned();
+                self.stack.push(root);
+            }
+            Some(Token::Name(keyword)) if keyword == "fragment" => {
+                self.next_name();
+                if self.next_name() != Some("on") {
+                    self.problems
+                        .push(format!("Fragment without a type condition (line {})", line));
+                }
+                let type_name = self.next_name().and_then(|name| self.known_type(name));
+                self.skip_directives();
+                if self.eat('{') {
+                    self.stack.push(type_name);
+                }
+            }
+            Some(Token::Name(operation))
+                if ["query", "mutation", "subscription"].contains(&operation.as_str()) =>
+            {
+                let root = self.schema.root_type(operation).cloned();
+                if root.is_none() {
+                    self.problems.push(format!(
+                        "The schema has no {} type (line {})",
+                        operation, line
+                    ));
+                }
+                self.next_name();
+                self.skip_parentheses();
+                self.skip_directives();
+                if self.eat('{') {
+                    self.stack.push(root);
+                }
+            }
+            Some(Token::Punctuator('}')) => {
+                self.problems.push(format!("Unexpected }} (line {})", line));
+            }
+            Some(_) => {
+                self.problems
+                    .push(format!("Expected an operation or fragment (line {})", line));
+            }
+            None => (),
+        }
+    }
+
+    fn selection(&mut self) {
+        let line = self.tokens[self.position].1;
+
+        match self.next() {
+            Some(Token::Punctuator('}')) => {
+                self.stack.pop();
+            }
+            Some(Token::Spread) => {
+                if self.peek() == Some(&Token::Name(String::from("on"))) {
+                    self.position += 1;
+                    let type_name = self.next_name().and_then(|name| self.known_type(name));
+                    self.skip_directives();
+                    if self.eat('{') {
+                        self.stack.push(type_name);
+                    }
+                } else if self.next_name().is_some() {
+                    self.skip_directives();
+                } else {
+                    self.skip_directives();
+                    if self.eat('{') {
+                        let current = self.stack.last().cloned().flatten();
+                        self.stack.push(current);
+                    }
+                }
+            }
+            Some(Token::Name(name)) => {
+                let name = if self.eat(':') {
+                    self.next_name().unwrap_or(name)
+                } else {
+                    name
+                };
+                self.field(name, line);
+            }
+            Some(token) => {
+                self.problems
+                    .push(format!("Unexpected {} in selection (line {})", token, line));
+            }
+            None => (),
+        }
+    }
+
+    fn field(&mut self, name: &str, line: usize) {
+        let parent = self.current_type();
+        let parent_name = self.stack.last().cloned().flatten();
+        let field = parent.and_then(|parent| parent.field(name));
+
+        if let (Some(parent_name), None) = (&parent_name, field) {
+            if parent.is_some() && name != "__typename" {
+                self.problems.push(format!(
+                    "Unknown field {} on {} (line {})",
+                    name, parent_name, line
+                ));
+            }
+        }
+
+        if !self.skip_parentheses() {
+            self.context = Context::Arguments(parent_name, name.to_string());
+            return;
+        }
+        self.skip_directives();
+
+        let field_type = field.and_then(|field| self.schema.types.get(&field.type_name));
+        let has_selection = self.eat('{');
+
+        match (field, field_type) {
+            (Some(field), Some(field_type)) if has_selection && field_type.is_leaf() => {
+                self.problems.push(format!(
+                    "{} is a {} and cannot have a selection (line {})",
+                    name, field.type_display, line
+                ));
+            }
+            (Some(field), Some(field_type)) if !has_selection && !field_type.is_leaf() => {
+                self.problems.push(format!(
+                    "{} is a {} and needs a selection of subfields (line {})",
+                    name, field.type_display, line
+                ));
+            }
+            _ => (),
+        }
+
+        if has_selection {
+            // Nothing is checked inside a selection a leaf cannot have.
+            let selected = match field_type {
+                Some(field_type) if field_type.is_leaf() => None,
+                _ => field.map(|field| field.type_name.clone()),
+            };
+            self.stack.push(selected);
+        }
+    }
+}
+
+/// The `data` and `errors` members of a GraphQL response, formatted for
+/// display. Returns `None` when the body is not a GraphQL response.
+pub fn split_response(body: &str) -> Option<(Option<String>, Vec<String>)> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    let object = value.as_object()?;
+
+    if !object.contains_key("data") && !object.contains_key("errors") {
+        return None;
+    }
+
+    let data = object
+        .get("data")
+        .filter(|data| !data.is_null())
+        .and_then(|data| serde_json::to_string_pretty(data).ok());
+
+    let errors = object
+        .get("errors")
+        .and_then(Value::as_array)
+        .map(|errors| errors.iter().map(format_error).collect())
+        .unwrap_or_default();
+
+    Some((data, errors))
+}
+
+fn format_error(error: &Value) -> String {
+    let mut text = error
+        .get("message")
+        .and_then(Value::as_str)
+        .unwrap_or("Unknown error")
+        .to_string();
+
+    if let Some(path) = error.get("path").and_then(Value::as_array) {
+        let path: Vec<_> = path
+            .iter()
+            .map(|segment| match segment {
+                Value::String(name) => name.clone(),
+                other => other.to_string(),
+            })
+            .collect();
+        text.push_str(&format!(" at {}", path.join(".")));
+    }
+
+    if let Some(location) = error
+        .get("locations")
+        .and_then(Value::as_array)
+        .and_then(|locations| locations.first())
+    {
+        let line = location.get("line").and_then(Value::as_u64).unwrap_or(0);
+        let column = location.get("column").and_then(Value::as_u64).unwrap_or(0);
+        text.push_str(&format!(" (line {}, column {})", line, column));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn named(kind: &str, name: &str) -> Value {
+        json!({ "kind": kind, "name": name })
+    }
+
+    fn wrapped(kind: &str, of_type: Value) -> Value {
+        json!({ "kind": kind, "name": null, "ofType": of_type })
+    }
+
+    fn field(name: &str, field_type: Value, args: Vec<(&str, Value)>) -> Value {
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|(name, arg_type)| json!({ "name": name, "type": arg_type }))
+            .collect();
+        json!({ "name": name, "args": args, "type": field_type })
+    }
+
+    fn schema() -> Schema {
+        let user = || named("OBJECT", "User");
+        let id = || wrapped("NON_NULL", named("SCALAR", "ID"));
+        let introspection = json!({ "data": { "__schema": {
+            "queryType": { "name": "Query" },
+            "mutationType": { "name": "Mutation" },
+            "subscriptionType": null,
+            "types": [
+                { "kind": "OBJECT", "name": "Query", "fields": [
+                    field("user", user(), vec![("id", id())]),
+                    field("users", wrapped("NON_NULL", wrapped("LIST", wrapped("NON_NULL", user()))), vec![]),
+                ] },
+                { "kind": "OBJECT", "name": "Mutation", "fields": [
+                    field("rename", user(), vec![("id", id()), ("name", named("SCALAR", "String"))]),
+                ] },
+                { "kind": "OBJECT", "name": "User", "fields": [
+                    field("id", id(), vec![]),
+                    field("name", named("SCALAR", "String"), vec![]),
+                    field("role", named("ENUM", "Role"), vec![]),
+                    field("friends", wrapped("LIST", user()), vec![]),
+                ] },
+                { "kind": "ENUM", "name": "Role", "fields": null },
+                { "kind": "SCALAR", "name": "ID", "fields": null },
+                { "kind": "SCALAR", "name": "String", "fields": null },
+            ],
+        } } });
+        Schema::parse(&introspection.to_string()).unwrap()
+    }
+
+    fn tokens(document: &str) -> Vec<Token> {
+        let (tokens, problems) = tokenize(document);
+        assert!(problems.is_empty(), "{:?}", problems);
+        tokens.into_iter().map(|(token, _)| token).collect()
+    }
+
+    fn name(name: &str) -> Token {
+        Token::Name(name.to_string())
+    }
+
+    #[test]
+    fn tokenizes_names_punctuators_and_values() {
+        assert_eq!(
+            tokens("query($n: Int = -1.5e3) { ...F # comment\n a: b(x: \"s\\\"}\") }"),
+            vec![
+                name("query"),
+                Token::Punctuator('('),
+                Token::Punctuator('$'),
+                name("n"),
+                Token::Punctuator(':'),
+                name("Int"),
+                Token::Punctuator('='),
+                Token::Value,
+                Token::Punctuator(')'),
+                Token::Punctuator('{'),
+                Token::Spread,
+                name("F"),
+                name("a"),
+                Token::Punctuator(':'),
+                name("b"),
+                Token::Punctuator('('),
+                name("x"),
+                Token::Punctuator(':'),
+                Token::Value,
+                Token::Punctuator(')'),
+                Token::Punctuator('}'),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokenizes_block_strings() {
+        assert_eq!(
+            tokens("f(a: \"\"\"say \"hi\" and \\\"\"\" }\"\"\", b: \"\")"),
+            vec![
+                name("f"),
+                Token::Punctuator('('),
+                name("a"),
+                Token::Punctuator(':'),
+                Token::Value,
+                name("b"),
+                Token::Punctuator(':'),
+                Token::Value,
+                Token::Punctuator(')'),
+            ]
+        );
+
+        let (tokens, _) = tokenize("\"\"\"one\ntwo\nthree\"\"\" x");
+        assert_eq!(tokens.last(), Some(&(name("x"), 3)));
+    }
+
+    #[test]
+    fn reports_unterminated_strings() {
+        let (_, problems) = tokenize("{ user(id: \"1) { id } }");
+        assert_eq!(problems, vec!["Unterminated string (line 1)"]);
+
+        let (_, problems) = tokenize("\n{ user(id: \"\"\"1\") { id } }");
+        assert_eq!(problems, vec!["Unterminated block string (line 2)"]);
+    }
+
+    #[test]
+    fn accepts_valid_documents() {
+        let schema = schema();
+        for query in [
+            "{ users { id name role friends { id } } }",
+            "query One($id: ID!) { user(id: $id) @include(if: true) { ...Names } }\nfragment Names on User { name }",
+            "mutation { renamed: rename(id: 1, name: \"\"\"Rex \"the\" dog\"\"\") { id __typename } }",
+            "{ user(id: 1) { ... on User { id } ... { name } } }",
+        ] {
+            assert_eq!(schema.validate(query), Vec::<String>::new(), "{}", query);
+        }
+    }
+
+    #[test]
+    fn reports_problems_with_their_lines() {
+        let schema = schema();
+        assert_eq!(
+            schema.validate("{\n users { nickname }\n}"),
+            vec!["Unknown field nickname on User (line 2)"]
+        );
+        assert_eq!(
+            schema.validate("{ users }"),
+            vec!["users is a [User!]! and needs a selection of subfields (line 1)"]
+        );
+        assert_eq!(
+            schema.validate("{ users { role { id } } }"),
+            vec!["role is a Role and cannot have a selection (line 1)"]
+        );
+        assert_eq!(schema.validate("{ users { id }"), vec!["Missing closing }"]);
+        assert_eq!(
+            schema.validate("subscription { users { id } }"),
+            vec!["The schema has no subscription type (line 1)"]
+        );
+        assert_eq!(
+            schema.validate("fragment F on Pet { id }"),
+            vec!["Unknown type Pet (line 1)"]
+        );
+    }
+
+    #[test]
+    fn completes_fields_arguments_and_keywords() {
+        let schema = schema();
+        let names = |query: &str| -> Vec<String> {
+            schema
+                .complete(query)
+                .candidates
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect()
+        };
+
+        assert_eq!(names("{ us"), vec!["user", "users"]);
+        assert_eq!(names("{ user(id: 1) { f"), vec!["friends"]);
+        assert_eq!(names("mutation { rename("), vec!["id", "name"]);
+        assert_eq!(names("qu"), vec!["query"]);
+        assert_eq!(schema.complete("{ users { na").prefix, "na");
+    }
+
+    #[test]
+    fn builds_envelopes_and_reads_responses() {
+        let query = GraphqlQuery {
+            id: 1,
+            request_id: 1,
+            query: String::from("{ users { id } }"),
+            variables: String::from("{\"a\": 1}"),
+            operation_name: None,
+        };
+        assert_eq!(
+            envelope(&query).unwrap(),
+            json!({ "query": "{ users { id } }", "variables": { "a": 1 } })
+        );
+        assert!(matches!(
+            parse_variables("[1]"),
+            Err(GraphqlError::VariablesNotObject)
+        ));
+        assert_eq!(parse_variables(" ").unwrap(), None);
+
+        let (data, errors) = split_response(
+            r#"{"data": null, "errors": [{"message": "Nope", "path": ["user", 0], "locations": [{"line": 2, "column": 3}]}]}"#,
+        )
+        .unwrap();
+        assert_eq!(data, None);
+        assert_eq!(errors, vec!["Nope at user.0 (line 2, column 3)"]);
+        assert!(split_response("{\"id\": 1}").is_none());
+    }
+}
@@ -0,0 +1,774 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Display, Formatter},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+use ::http::{HeaderMap, Request, Uri};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use chrono::{DateTime, Local};
+use h2::{
+    client::{ResponseFuture, SendRequest},
+    RecvStream, SendStream,
+};
+use prost::Message;
+use prost_reflect::{
+    DescriptorPool, DynamicMessage, MessageDescriptor, MethodDescriptor, SerializeOptions,
+};
+use prost_types::FileDescriptorProto;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::TcpStream,
+    runtime::Runtime,
+};
+
+use crate::{
+    http::{native_tls_builder, HttpError},
+    models::TlsConfig,
+    proto::ProtoError,
+};
+
+const REFLECTION_SERVICES: [&str; 2] = [
+    "grpc.reflection.v1alpha.ServerReflection",
+    "grpc.reflection.v1.ServerReflection",
+];
+
+#[derive(Debug, Error)]
+pub enum GrpcError {
+    #[error("Invalid URL {0}")]
+    Url(String),
+    #[error("Invalid header {0}")]
+    Header(String),
+    #[error("Method {0} not found, load the service definitions first")]
+    UnknownMethod(String),
+    #[error("{0} is client-streaming, only unary and server-streaming methods can be called")]
+    ClientStreaming(String),
+    #[error("Invalid request message: {0}")]
+    Message(String),
+    #[error("Could not decode the response: {0}")]
+    Decode(#[from] prost::DecodeError),
+    #[error("Compressed responses are not supported")]
+    Compressed,
+    #[error(transparent)]
+    Proto(#[from] ProtoError),
+    #[error("Invalid service definitions: {0}")]
+    Descriptor(#[from] prost_reflect::DescriptorError),
+    #[error("Connection failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("HTTP/2 error: {0}")]
+    H2(#[from] h2::Error),
+    #[error(transparent)]
+    Http(#[from] HttpError),
+    #[error("Server responded with HTTP {0}")]
+    HttpStatus(u16),
+    #[error("{0}")]
+    Status(GrpcStatus),
+    #[error("Server reflection failed: {0}")]
+    Reflection(String),
+}
+
+/// The `grpc-status` and `grpc-message` a call ended with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GrpcStatus {
+    pub code: u32,
+    pub message: String,
+}
+
+impl GrpcStatus {
+    pub fn name(&self) -> &str {
+        match self.code {
+            0 => "OK",
+            1 => "CANCELLED",
+            2 => "UNKNOWN",
+            3 => "INVALID_ARGUMENT",
+            4 => "DEADLINE_EXCEEDED",
+            5 => "NOT_FOUND",
+            6 => "ALREADY_EXISTS",
+            7 => "PERMISSION_DENIED",
+            8 => "RESOURCE_EXHAUSTED",
+            9 => "FAILED_PRECONDITION",
+            10 => "ABORTED",
+            11 => "OUT_OF_RANGE",
+            12 => "UNIMPLEMENTED",
+            13 => "INTERNAL",
+            14 => "UNAVAILABLE",
+            15 => "DATA_LOSS",
+            16 => "UNAUTHENTICATED",
+            _ => "UNKNOWN",
+        }
+    }
+
+    fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let code = headers.get("grpc-status")?.to_str().ok()?.parse().ok()?;
+        let message = headers
+            .get("grpc-message")
+            .and_then(|value| value.to_str().ok())
+            .map(percent_decode)
+            .unwrap_or_default();
+
+        Some(Self { code, message })
+    }
+}
+
+impl Display for GrpcStatus {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.message.is_empty() {
+            write!(f, "{} {}", self.code, self.name())
+        } else {
+            write!(f, "{} {}: {}", self.code, self.name(), self.message)
+        }
+    }
+}
+
+/// `grpc-message` is percent-encoded.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%')
+            .then(|| value.get(index + 1..index + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Where and how to connect. `grpc://` and `grpcs://` routes are accepted
+/// as aliases of `http://` and `https://`.
+#[derive(Clone)]
+pub struct Target {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub tls: Option<TlsConfig>,
+}
+
+impl Target {
+    pub fn new(url: &str, headers: Vec<(String, String)>, tls: Option<TlsConfig>) -> Self {
+        let url = if let Some(rest) = url.strip_prefix("grpcs://") {
+            format!("https://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("grpc://") {
+            format!("http://{}", rest)
+        } else {
+            url.to_string()
+        };
+
+        Self { url, headers, tls }
+    }
+}
+
+fn runtime() -> Result<Runtime, GrpcError> {
+    Ok(tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?)
+}
+
+async fn connect(target: &Target) -> Result<(SendRequest<Bytes>, Uri), GrpcError> {
+    let uri: Uri = target
+        .url
+        .parse()
+        .map_err(|_| GrpcError::Url(target.url.clone()))?;
+    let host = uri
+        .host()
+        .ok_or_else(|| GrpcError::Url(target.url.clone()))?
+        .to_string();
+    let secure = uri.scheme_str() == Some("https");
+    let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+    let stream = TcpStream::connect((host.as_str(), port)).await?;
+
+    let send_request = if secure {
+        let mut builder = native_tls_builder(target.tls.as_ref())?;
+        builder.request_alpns(&["h2"]);
+        let to_error = |err: native_tls::Error| HttpError::Tls(err.to_string());
+        let connector = tokio_native_tls::TlsConnector::from(builder.build().map_err(to_error)?);
+        let stream = connector.connect(&host, stream).await.map_err(to_error)?;
+        handshake(stream).await?
+    } else {
+        handshake(stream).await?
+    };
+
+    Ok((send_request, uri))
+}
+
+async fn handshake<T>(io: T) -> Result<SendRequest<Bytes>, GrpcError>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let (send_request, connection) = h2::client::handshake(io).await?;
+    tokio::spawn(async move {
+        let _ = connection.await;
+    });
+    Ok(send_request)
+}
+
+async fn start_call(
+    send_request: SendRequest<Bytes>,
+    uri: &Uri,
+    path: &str,
+    headers: &[(String, String)],
+) -> Result<(SendStream<Bytes>, ResponseFuture), GrpcError> {
+    let mut send_request = send_request.ready().await?;
+
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(format!(
+            "{}://{}{}",
+            uri.scheme_str().unwrap_or("http"),
+            uri.authority()
+                .map(|authority| authority.as_str())
+                .unwrap_or(""),
+            path
+        ))
+        .header("content-type", "application/grpc")
+        .header("te", "trailers");
+    for (key, value) in headers {
+        builder = builder.header(key.as_str(), value.as_str());
+    }
+    let request = builder
+        .body(())
+        .map_err(|err| GrpcError::Header(err.to_string()))?;
+
+    let (response, stream) = send_request.send_request(request, false)?;
+    Ok((stream, response))
+}
+
+/// Waits for the response headers. A call that fails right away carries
+/// its status in the headers ("trailers-only").
+async fn read_response(response: ResponseFuture) -> Result<RecvStream, GrpcError> {
+    let (parts, body) = response.await?.into_parts();
+
+    if parts.status != 200 {
+        return Err(GrpcError::HttpStatus(parts.status.as_u16()));
+    }
+    match GrpcStatus::from_headers(&parts.headers) {
+        Some(status) if status.code != 0 => Err(GrpcError::Status(status)),
+        _ => Ok(body),
+    }
+}
+
+/// Prefixes a message with the gRPC frame header: an uncompressed flag and
+/// the big-endian length.
+fn frame(message: &[u8]) -> Bytes {
+    let mut framed = BytesMut::with_capacity(message.len() + 5);
+    framed.put_u8(0);
+    framed.put_u32(message.len() as u32);
+    framed.put_slice(message);
+    framed.freeze()
+}
+
+/// Splits the response body back into messages.
+#[derive(Default)]
+struct FrameDecoder {
+    buffer: BytesMut,
+}
+
+impl FrameDecoder {
+    fn next(&mut self) -> Result<Option<Bytes>, GrpcError> {
+        if self.buffer.len() < 5 {
+            return Ok(None);
+        }
+        if self.buffer[0] != 0 {
+            return Err(GrpcError::Compressed);
+        }
+        let length = u32::from_be_bytes([
+            self.buffer[1],
+            self.buffer[2],
+            self.buffer[3],
+            self.buffer[4],
+        ]) as usize;
+        if self.buffer.len() < length + 5 {
+            return Ok(None);
+        }
+
+        self.buffer.advance(5);
+        Ok(Some(self.buffer.split_to(length).freeze()))
+    }
+}
+
+async fn next_message(
+    body: &mut RecvStream,
+    decoder: &mut FrameDecoder,
+) -> Result<Option<Bytes>, GrpcError> {
+    loop {
+        if let Some(message) = decoder.next()? {
+            return Ok(Some(message));
+        }
+        match body.data().await {
+            Some(chunk) => {
+                let chunk = chunk?;
+                let _ = body.flow_control().release_capacity(chunk.len());
+                decoder.buffer.extend_from_slice(&chunk);
+            }
+            None => return Ok(None),
+        }
+    }
+}
+
+async fn final_status(body: &mut RecvStream) -> Result<GrpcStatus, GrpcError> {
+    let trailers = body.trailers().await?;
+
+    Ok(trailers
+        .as_ref()
+        .and_then(GrpcStatus::from_headers)
+        .unwrap_or(GrpcStatus {
+            code: 2,
+            message: String::from("The response had no grpc-status"),
+        }))
+}
+
+/// The services and methods a pool describes, skipping server reflection
+/// itself.
+pub fn methods(pool: &DescriptorPool) -> Vec<MethodDescriptor> {
+    pool.services()
+        .filter(|service| !REFLECTION_SERVICES.contains(&service.full_name()))
+        .flat_map(|service| service.methods().collect::<Vec<_>>())
+        .collect()
+}
+
+/// The stored form of a method, e.g. `demo.Greeter/SayHello`.
+pub fn method_name(method: &MethodDescriptor) -> String {
+    format!("{}/{}", method.parent_service().full_name(), method.name())
+}
+
+pub fn find_method(pool: &DescriptorPool, name: &str) -> Option<MethodDescriptor> {
+    let (service, method) = name.split_once('/')?;
+    pool.get_service_by_name(service)?
+        .methods()
+        .find(|candidate| candidate.name() == method)
+}
+
+pub fn to_json(message: &DynamicMessage) -> String {
+    let mut json = vec![];
+    let mut serializer = serde_json::Serializer::pretty(&mut json);
+    let options = SerializeOptions::new().skip_default_fields(false);
+
+    match message.serialize_with_options(&mut serializer, &options) {
+        Ok(()) => String::from_utf8_lossy(&json).into_owned(),
+        Err(err) => format!("<{}>", err),
+    }
+}
+
+/// A request message with every field at its default value, as a starting
+/// point for editing.
+pub fn message_template(descriptor: MessageDescriptor) -> String {
+    to_json(&DynamicMessage::new(descriptor))
+}
+
+// Messages of grpc/reflection/v1alpha/reflection.proto, which v1 shares.
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServerReflectionRequest {
+    #[prost(string, tag = "1")]
+    host: String,
+    #[prost(oneof = "ReflectionRequest", tags = "3, 4, 7")]
+    message_request: Option<ReflectionRequest>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum ReflectionRequest {
+    #[prost(string, tag = "3")]
+    FileByFilename(String),
+    #[prost(string, tag = "4")]
+    FileContainingSymbol(String),
+    #[prost(string, tag = "7")]
+    ListServices(String),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServerReflectionResponse {
+    #[prost(oneof = "ReflectionResponse", tags = "4, 6, 7")]
+    message_response: Option<ReflectionResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+enum ReflectionResponse {
+    #[prost(message, tag = "4")]
+    FileDescriptors(FileDescriptorResponse),
+    #[prost(message, tag = "6")]
+    ListServices(ListServiceResponse),
+    #[prost(message, tag = "7")]
+    Error(ErrorResponse),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct FileDescriptorResponse {
+    #[prost(bytes = "vec", repeated, tag = "1")]
+    file_descriptor_proto: Vec<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ListServiceResponse {
+    #[prost(message, repeated, tag = "1")]
+    service: Vec<ServiceResponse>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ServiceResponse {
+    #[prost(string, tag = "1")]
+    name: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+struct ErrorResponse {
+    #[prost(int32, tag = "1")]
+    error_code: i32,
+    #[prost(string, tag = "2")]
+    error_message: String,
+}
+
+/// Loads the service definitions from the server reflection service,
+/// trying `v1alpha` first since more servers implement it.
+pub fn reflect(target: &Target) -> Result<DescriptorPool, GrpcError> {
+    runtime()?.block_on(async {
+        let (send_request, uri) = connect(target).await?;
+
+        let mut result = Err(GrpcError::Reflection(String::from("no reflection service")));
+        for service in REFLECTION_SERVICES {
+            result = reflect_with(send_request.clone(), &uri, target, service).await;
+            match &result {
+                Err(GrpcError::Status(status)) if status.code == 12 => continue,
+                _ => break,
+            }
+        }
+        result
+    })
+}
+
+async fn reflect_with(
+    send_request: SendRequest<Bytes>,
+    uri: &Uri,
+    target: &Target,
+    service: &str,
+) -> Result<DescriptorPool, GrpcError> {
+    let path = format!("/{}/ServerReflectionInfo", service);
+    let (mut stream, response) = start_call(send_request, uri, &path, &target.headers).await?;
+    let mut decoder = FrameDecoder::default();
+
+    let ask = |stream: &mut SendStream<Bytes>, request: ReflectionRequest| {
+        let message = ServerReflectionRequest {
+            host: String::new(),
+            message_request: Some(request),
+        };
+        stream.send_data(frame(&message.encode_to_vec()), false)
+    };
+
+    ask(&mut stream, ReflectionRequest::ListServices(String::new()))?;
+    let mut body = read_response(response).await?;
+
+    let services = match reflection_response(&mut body, &mut decoder).await? {
+        ReflectionResponse::ListServices(list) => list.service,
+        _ => return Err(GrpcError::Reflection(String::from("unexpected response"))),
+    };
+
+    let pool = DescriptorPool::global();
+    let mut files: HashMap<String, FileDescriptorProto> = HashMap::new();
+    let mut pending: Vec<ReflectionRequest> = services
+        .into_iter()
+        .filter(|service| !REFLECTION_SERVICES.contains(&service.name.as_str()))
+        .map(|service| ReflectionRequest::FileContainingSymbol(service.name))
+        .collect();
+
+    // Servers usually send a file together with its dependencies, but not
+    // all do, so anything still missing is asked for by name.
+    while !pending.is_empty() {
+        let count = pending.len();
+        for request in pending.drain(..) {
+            ask(&mut stream, request)?;
+        }
+
+        for _ in 0..count {
+            match reflection_response(&mut body, &mut decoder).await? {
+                ReflectionResponse::FileDescriptors(response) => {
+                    for bytes in response.file_descriptor_proto {
+                        let file = FileDescriptorProto::decode(bytes.as_slice())?;
+                        files.insert(file.name().to_string(), file);
+                    }
+                }
+                ReflectionResponse::Error(error) => {
+                    return Err(GrpcError::Reflection(error.error_message))
+                }
+                ReflectionResponse::ListServices(_) => (),
+            }
+        }
+
+        let mut missing: Vec<String> = files
+            .values()
+            .flat_map(|file| file.dependency.iter())
+            .filter(|name| !files.contains_key(*name) && pool.get_file_by_name(name).is_none())
+            .cloned()
+            .collect();
+        missing.sort();
+        missing.dedup();
+        pending = missing
+            .into_iter()
+            .map(ReflectionRequest::FileByFilename)
+            .collect();
+    }
+
+    let _ = stream.send_data(Bytes::new(), true);
+
+    let mut pool = pool;
+    pool.add_file_descriptor_protos(files.into_values())?;
+    Ok(pool)
+}
+
+async fn reflection_response(
+    body: &mut RecvStream,
+    decoder: &mut FrameDecoder,
+) -> Result<ReflectionResponse, GrpcError> {
+    match next_message(body, decoder).await? {
+        Some(bytes) => ServerReflectionResponse::decode(bytes)?
+            .message_response
+            .ok_or_else(|| GrpcError::Reflection(String::from("empty response"))),
+        None => Err(match final_status(body).await? {
+            status if status.code != 0 => GrpcError::Status(status),
+            _ => GrpcError::Reflection(String::from("the stream ended early")),
+        }),
+    }
+}
+
+pub struct GrpcMessage {
+    pub received_at: DateTime<Local>,
+    pub json: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CallState {
+    Running,
+    Finished(GrpcStatus),
+    Stopped,
+    Failed(String),
+}
+
+enum CallEvent {
+    Message(GrpcMessage),
+    Finished(GrpcStatus),
+    Failed(String),
+}
+
+/// A unary or server-streaming call running on a background thread. Each
+/// response message is handed over as JSON as soon as it arrives.
+pub struct CallSession {
+    pub messages: Vec<GrpcMessage>,
+    pub state: CallState,
+    receiver: Receiver<CallEvent>,
+    stop: Arc<AtomicBool>,
+}
+
+impl CallSession {
+    pub fn start(
+        target: Target,
+        method: &MethodDescriptor,
+        request_json: &str,
+    ) -> Result<Self, GrpcError> {
+        if method.is_client_streaming() {
+            return Err(GrpcError::ClientStreaming(method_name(method)));
+        }
+
+        let request_json = if request_json.trim().is_empty() {
+            "{}"
+        } else {
+            request_json
+        };
+        let mut deserializer = serde_json::Deserializer::from_str(request_json);
+        let request = DynamicMessage::deserialize(method.input(), &mut deserializer)
+            .map_err(|err| GrpcError::Message(err.to_string()))?;
+        let payload = request.encode_to_vec();
+
+        let path = format!("/{}", method_name(method));
+        let output = method.output();
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        thread::spawn(move || {
+            let result = runtime().and_then(|runtime| {
+                runtime.block_on(run_call(&target, &path, payload, output, &tx, &stopped))
+            });
+            let event = match result {
+                Ok(Some(status)) => CallEvent::Finished(status),
+                Ok(None) => return,
+                Err(GrpcError::Status(status)) => CallEvent::Finished(status),
+                Err(err) => CallEvent::Failed(err.to_string()),
+            };
+            let _ = tx.send(event);
+        });
+
+        Ok(Self {
+            messages: vec![],
+            state: CallState::Running,
+            receiver: rx,
+            stop,
+        })
+    }
+
+    /// Moves everything received since the last call into `messages`.
+    pub fn poll(&mut self) {
+        if self.state != CallState::Running {
+            return;
+        }
+
+        loop {
+            match self.receiver.try_recv() {
+                Ok(CallEvent::Message(message)) => self.messages.push(message),
+                Ok(CallEvent::Finished(status)) => {
+                    self.state = CallState::Finished(status);
+                    break;
+                }
+                Ok(CallEvent::Failed(err)) => {
+                    self.state = CallState::Failed(err);
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.state = CallState::Failed(String::from("The call ended unexpectedly"));
+                    break;
+                }
+            }
+        }
+    }
+
+    /// Stops listening. The connection is dropped once the next message
+    /// arrives.
+    pub fn stop(&mut self) {
+        if self.state == CallState::Running {
+            self.stop.store(true, Ordering::Relaxed);
+            self.state = CallState::Stopped;
+        }
+    }
+}
+
+impl Drop for CallSession {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Returns the final status, or `None` if the call was stopped.
+async fn run_call(
+    target: &Target,
+    path: &str,
+    payload: Vec<u8>,
+    output: MessageDescriptor,
+    events: &Sender<CallEvent>,
+    stopped: &AtomicBool,
+) -> Result<Option<GrpcStatus>, GrpcError> {
+    let (send_request, uri) = connect(target).await?;
+    let (mut stream, response) = start_call(send_request, &uri, path, &target.headers).await?;
+    stream.send_data(frame(&payload), true)?;
+
+    let mut body = read_response(response).await?;
+    let mut decoder = FrameDecoder::default();
+
+    while let Some(bytes) = next_message(&mut body, &mut decoder).await? {
+        if stopped.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+
+        let message = DynamicMessage::decode(output.clone(), bytes)?;
+        let message = GrpcMessage {
+            received_at: Local::now(),
+            json: to_json(&message),
+        };
+        if events.send(CallEvent::Message(message)).is_err() {
+            return Ok(None);
+        }
+    }
+
+    Ok(Some(final_status(&mut body).await?))
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "../examples/grpc_stub.rs"]
+mod grpc_stub;
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+
+    fn start_stub() -> Target {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || grpc_stub::run(listener));
+        Target::new(&format!("grpc://127.0.0.1:{}", port), vec![], None)
+    }
+
+    fn wait(session: &mut CallSession) {
+        let started = Instant::now();
+        while session.state == CallState::Running && started.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+            session.poll();
+        }
+    }
+
+    #[test]
+    fn lists_services_through_reflection() {
+        let target = start_stub();
+        let pool = reflect(&target).unwrap();
+
+        let names: Vec<String> = methods(&pool).iter().map(method_name).collect();
+        assert_eq!(
+            names,
+            vec!["demo.Greeter/SayHello", "demo.Greeter/StreamHellos"]
+        );
+        let method = find_method(&pool, "demo.Greeter/SayHello").unwrap();
+        assert!(message_template(method.input()).contains("\"name\""));
+    }
+
+    #[test]
+    fn calls_a_unary_method() {
+        let target = start_stub();
+        let pool = reflect(&target).unwrap();
+        let method = find_method(&pool, "demo.Greeter/SayHello").unwrap();
+
+        let mut session =
+            CallSession::start(target, &method, r#"{"name": "Ada", "mood": "CHEERFUL"}"#).unwrap();
+        wait(&mut session);
+
+        match &session.state {
+            CallState::Finished(status) => assert_eq!(status.code, 0),
+            other => panic!("unexpected state {:?}", other),
+        }
+        assert_eq!(session.messages.len(), 1);
+        assert!(session.messages[0]
+            .json
+            .contains("\"message\": \"Hi there, Ada! (1/1)\""));
+    }
+
+    #[test]
+    fn streams_a_method_loaded_from_a_proto_file() {
+        let target = start_stub();
+        let pool = crate::proto::load_files(&["examples/greeter.proto"]).unwrap();
+        let method = find_method(&pool, "demo.Greeter/StreamHellos").unwrap();
+
+        let mut session = CallSession::start(target, &method, r#"{"count": 2}"#).unwrap();
+        wait(&mut session);
+
+        assert!(matches!(session.state, CallState::Finished(_)));
+        let messages: Vec<&str> = session.messages.iter().map(|m| m.json.as_str()).collect();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].contains("Hello, stranger! (2/2)"));
+    }
+}
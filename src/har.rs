@@ -0,0 +1,519 @@
+use std::fs;
+
+use ::http::StatusCode;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chrono::{DateTime, Duration};
+use serde_json::{json, Value};
+use thiserror::Error;
+use url::{form_urlencoded, Url};
+
+use crate::{
+    models::RecordedResponse,
+    services::{
+        api_service,
+        import_service::{ImportedApi, ImportedFormPart, ImportedRequest, ImportedResource},
+        recorded_response_service, request_service, resource_service,
+    },
+    types::{FormPartKind, HttpMethod},
+};
+
+/// Headers the client works out for itself when a request is sent.
+const SKIPPED_HEADERS: [&str; 4] = ["host", "content-length", "connection", "accept-encoding"];
+
+#[derive(Debug, Error)]
+pub enum HarError {
+    #[error("Could not read {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Not a valid HAR file: {0}")]
+    Syntax(String),
+    #[error("API {0} not found")]
+    UnknownApi(i32),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+}
+
+/// A request captured in a HAR file, with the status it got.
+pub struct HarEntry {
+    pub status: i64,
+    pub request: ImportedRequest,
+}
+
+/// The entries of a HAR file that can be sent again.
+pub struct HarLog {
+    pub entries: Vec<HarEntry>,
+    /// Entries left out because their method cannot be sent, as
+    /// `METHOD url`.
+    pub skipped: Vec<String>,
+}
+
+pub fn is_har_file(path: &str) -> bool {
+    path.ends_with(".har")
+}
+
+/// Reads the entries of a HAR file, in the order they were captured.
+pub fn load(path: &str) -> Result<HarLog, HarError> {
+    let source = fs::read_to_string(path).map_err(|err| HarError::Read(path.to_string(), err))?;
+    let document: Value =
+        serde_json::from_str(&source).map_err(|err| HarError::Syntax(err.to_string()))?;
+    let entries = document
+        .pointer("/log/entries")
+        .and_then(Value::as_array)
+        .ok_or_else(|| HarError::Syntax(String::from("no log.entries")))?;
+
+    let mut log = HarLog {
+        entries: vec![],
+        skipped: vec![],
+    };
+    for entry in entries.iter().filter_map(parse_entry) {
+        match entry {
+            Ok(entry) => log.entries.push(entry),
+            Err(skipped) => log.skipped.push(skipped),
+        }
+    }
+    Ok(log)
+}
+
+/// Entries without a request are passed over; one whose method cannot be
+/// sent is given back as `METHOD url`.
+fn parse_entry(entry: &Value) -> Option<Result<HarEntry, String>> {
+    let request = entry.get("request")?;
+    let url = request.get("url").and_then(Value::as_str)?;
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .unwrap_or("GET");
+    let method: HttpMethod = match method.parse() {
+        Ok(method) => method,
+        Err(_) => return Some(Err(format!("{} {}", method, url))),
+    };
+    let route = url.split_once('?').map_or(url, |(route, _)| route);
+
+    let pairs = |list: Option<&Value>| -> Vec<(String, String)> {
+        list.and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|pair| {
+                let name = pair.get("name").and_then(Value::as_str)?;
+                let value = pair.get("value").and_then(Value::as_str).unwrap_or("");
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect()
+    };
+
+    let mut headers: Vec<(String, String)> = pairs(request.get("headers"))
+        .into_iter()
+        .filter(|(name, _)| {
+            !name.starts_with(':') && !SKIPPED_HEADERS.contains(&name.to_lowercase().as_str())
+        })
+        .collect();
+    let params = match request.get("queryString") {
+        Some(query) => pairs(Some(query)),
+        None => Url::parse(url)
+            .map(|url| url.query_pairs().into_owned().collect())
+            .unwrap_or_default(),
+    };
+
+    let mut body = None;
+    let mut form_parts = vec![];
+    if let Some(post_data) = request.get("postData") {
+        let mime_type = post_data
+            .get("mimeType")
+            .and_then(Value::as_str)
+            .unwrap_or("");
+        let text = post_data.get("text").and_then(Value::as_str).unwrap_or("");
+        let params = post_data.get("params").and_then(Value::as_array);
+
+        if let (true, Some(params)) = (mime_type.starts_with("multipart/form-data"), params) {
+            // The boundary of the captured body would not match the parts
+            // sent again.
+            headers.retain(|(name, _)| !name.eq_ignore_ascii_case("content-type"));
+            form_parts = params.iter().map(parse_form_part).collect();
+        } else if !text.is_empty() {
+            body = Some(text.to_string());
+        } else if let Some(params) = params {
+            let mut encoded = form_urlencoded::Serializer::new(String::new());
+            for param in params {
+                encoded.append_pair(
+                    param.get("name").and_then(Value::as_str).unwrap_or(""),
+                    param.get("value").and_then(Value::as_str).unwrap_or(""),
+                );
+            }
+            body = Some(encoded.finish());
+        }
+
+        let has_content_type = headers
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case("content-type"));
+        if body.is_some() && !has_content_type && !mime_type.is_empty() {
+            headers.push((String::from("Content-Type"), mime_type.to_string()));
+        }
+    }
+
+    Some(Ok(HarEntry {
+        status: entry
+            .pointer("/response/status")
+            .and_then(Value::as_i64)
+            .unwrap_or(0),
+        request: ImportedRequest {
+            key: format!("{} {}", method, route),
+            name: None,
+            description: None,
+            route: route.to_string(),
+            method,
+            body,
+            headers,
+            params,
+            form_parts,
+        },
+    }))
+}
+
+/// Files uploaded in the captured request are expected next to where the
+/// app runs, under the name they were sent with.
+fn parse_form_part(param: &Value) -> ImportedFormPart {
+    let text = |key: &str| param.get(key).and_then(Value::as_str).map(str::to_string);
+
+    let (kind, value) = match text("fileName") {
+        Some(file_name) => (FormPartKind::File, file_name),
+        None => (FormPartKind::Text, text("value").unwrap_or_default()),
+    };
+    ImportedFormPart {
+        name: text("name").unwrap_or_default(),
+        kind,
+        value,
+        content_type: text("contentType"),
+    }
+}
+
+/// Turns the chosen entries into an API with a resource per host. Repeated
+/// calls to the same method and URL become a single request.
+pub fn to_api(name: &str, entries: Vec<HarEntry>) -> ImportedApi {
+    let mut resources: Vec<ImportedResource> = vec![];
+
+    for entry in entries {
+        let request = entry.request;
+        let host = Url::parse(&request.route)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_string))
+            .unwrap_or_else(|| String::from("default"));
+
+        let resource = match resources.iter().position(|resource| resource.name == host) {
+            Some(index) => &mut resources[index],
+            None => {
+                resources.push(ImportedResource {
+                    name: host,
+                    requests: vec![],
+                });
+                resources.last_mut().unwrap()
+            }
+        };
+        if !resource
+            .requests
+            .iter()
+            .any(|existing| existing.key == request.key)
+        {
+            resource.requests.push(request);
+        }
+    }
+
+    ImportedApi {
+        name: name.to_string(),
+        description: None,
+        resources,
+        variables: vec![],
+        complete: false,
+    }
+}
+
+/// Builds a HAR 1.2 log from the exchanges recorded for an API's requests,
+/// oldest first, each with the request as it was sent. Exchanges recorded
+/// before requests were kept with them are left out.
+pub fn export(api_id: i32) -> Result<Value, HarError> {
+    api_service::get_api_by_id(api_id).ok_or(HarError::UnknownApi(api_id))?;
+
+    let mut entries = vec![];
+    for resource in resource_service::get_resources_for_api(api_id) {
+        for request in request_service::get_requests_for_resource(resource.id).unwrap_or_default() {
+            entries.extend(
+                recorded_response_service::get_responses_for_request(request.id)
+                    .iter()
+                    .filter_map(export_entry),
+            );
+        }
+    }
+    entries.sort_by(|left, right| {
+        left["startedDateTime"]
+            .as_str()
+            .cmp(&right["startedDateTime"].as_str())
+    });
+
+    Ok(json!({
+        "log": {
+            "version": "1.2",
+            "creator": {
+                "name": env!("CARGO_PKG_NAME"),
+                "version": env!("CARGO_PKG_VERSION"),
+            },
+            "entries": entries,
+        }
+    }))
+}
+
+pub fn export_to_file(api_id: i32, path: &str) -> Result<(), HarError> {
+    let log = export(api_id)?;
+    let text =
+        serde_json::to_string_pretty(&log).map_err(|err| HarError::Syntax(err.to_string()))?;
+
+    fs::write(path, text).map_err(|err| HarError::Write(path.to_string(), err))
+}
+
+/// Only the total time of an exchange is measured, so it is all reported
+/// as waiting for the server, and the HTTP version is not known.
+fn export_entry(response: &RecordedResponse) -> Option<Value> {
+    if response.url.is_empty() {
+        return None;
+    }
+    let started = DateTime::parse_from_rfc3339(&response.recorded_at).ok()?
+        - Duration::milliseconds(i64::from(response.duration_ms));
+
+    let pairs = |pairs: Vec<(String, String)>| -> Vec<Value> {
+        pairs
+            .into_iter()
+            .map(|(name, value)| json!({ "name": name, "value": value }))
+            .collect()
+    };
+    let query = Url::parse(&response.url)
+        .map(|url| url.query_pairs().into_owned().collect())
+        .unwrap_or_default();
+
+    let sent_headers = response.sent_headers();
+    let sent_type = sent_headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map_or("", |(_, value)| value.as_str());
+    let mut request = json!({
+        "method": response.method,
+        "url": response.url,
+        "httpVersion": "",
+        "cookies": [],
+        "headers": pairs(sent_headers.clone()),
+        "queryString": pairs(query),
+        "headersSize": -1,
+        "bodySize": response.request_body.as_ref().map_or(0, String::len),
+    });
+    if let Some(body) = &response.request_body {
+        request["postData"] = json!({ "mimeType": sent_type, "text": body });
+    } else if !sent_type.is_empty() {
+        // A multipart form, which is streamed and not kept.
+        request["bodySize"] = json!(-1);
+    }
+
+    let size = if response.body_base64 {
+        STANDARD.decode(&response.body).map_or(0, |body| body.len())
+    } else {
+        response.body.len()
+    };
+    let mut content = json!({
+        "size": size,
+        "mimeType": response.content_type.as_deref().unwrap_or(""),
+        "text": response.body,
+    });
+    if response.body_base64 {
+        content["encoding"] = json!("base64");
+    }
+    let status_text = u16::try_from(response.status)
+        .ok()
+        .and_then(|code| StatusCode::from_u16(code).ok())
+        .and_then(|code| code.canonical_reason())
+        .unwrap_or("");
+
+    Some(json!({
+        "startedDateTime": started.to_rfc3339(),
+        "time": response.duration_ms,
+        "request": request,
+        "response": {
+            "status": response.status,
+            "statusText": status_text,
+            "httpVersion": "",
+            "cookies": [],
+            "headers": pairs(response.response_headers()),
+            "content": content,
+            "redirectURL": "",
+            "headersSize": -1,
+            "bodySize": size,
+        },
+        "cache": {},
+        "timings": { "send": 0, "wait": response.duration_ms, "receive": 0 },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exchange() -> RecordedResponse {
+        RecordedResponse {
+            id: 1,
+            request_id: 1,
+            status: 201,
+            content_type: Some(String::from("application/json")),
+            body: String::from("{\"id\":7}"),
+            recorded_at: String::from("2026-10-19T10:00:01.500+00:00"),
+            headers: String::from(
+                "[[\"content-type\",\"application/json\"],[\"x-trace\",\"abc\"]]",
+            ),
+            body_base64: false,
+            duration_ms: 1500,
+            method: String::from("POST"),
+            url: String::from("https://shop.test/orders?note=a%20b&tag=x%26y"),
+            request_headers: String::from("[[\"content-type\",\"application/json\"]]"),
+            request_body: Some(String::from("{\"item\":1}")),
+        }
+    }
+
+    #[test]
+    fn exports_the_exchange_as_it_was_sent() {
+        let entry = export_entry(&exchange()).unwrap();
+
+        assert_eq!(entry["startedDateTime"], "2026-10-19T10:00:00+00:00");
+        assert_eq!(entry["time"], 1500);
+        assert_eq!(
+            entry["request"]["url"],
+            "https://shop.test/orders?note=a%20b&tag=x%26y"
+        );
+        assert_eq!(
+            entry["request"]["queryString"],
+            json!([{ "name": "note", "value": "a b" }, { "name": "tag", "value": "x&y" }])
+        );
+        assert_eq!(
+            entry["request"]["postData"],
+            json!({ "mimeType": "application/json", "text": "{\"item\":1}" })
+        );
+        assert_eq!(entry["response"]["statusText"], "Created");
+        assert_eq!(
+            entry["response"]["headers"][1],
+            json!({ "name": "x-trace", "value": "abc" })
+        );
+    }
+
+    #[test]
+    fn exports_binary_bodies_as_base64() {
+        let mut response = exchange();
+        response.content_type = Some(String::from("image/png"));
+        response.body = STANDARD.encode(b"\x89PNG\r\n\x1a\n");
+        response.body_base64 = true;
+
+        let entry = export_entry(&response).unwrap();
+        assert_eq!(entry["response"]["content"]["encoding"], "base64");
+        assert_eq!(entry["response"]["content"]["size"], 8);
+    }
+
+    #[test]
+    fn leaves_out_exchanges_without_the_request() {
+        let mut response = exchange();
+        response.url = String::new();
+        assert!(export_entry(&response).is_none());
+    }
+
+    fn captured(request: Value) -> HarEntry {
+        parse_entry(&json!({ "request": request, "response": { "status": 200 } }))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn skips_methods_that_cannot_be_sent() {
+        let entry = json!({
+            "request": { "method": "GET/1", "url": "https://shop.test/orders" },
+            "response": { "status": 200 }
+        });
+        assert_eq!(
+            parse_entry(&entry).unwrap().err().as_deref(),
+            Some("GET/1 https://shop.test/orders")
+        );
+    }
+
+    #[test]
+    fn reads_captured_requests() {
+        let entry = captured(json!({
+            "method": "POST",
+            "url": "https://shop.test/orders?page=2",
+            "headers": [
+                { "name": ":authority", "value": "shop.test" },
+                { "name": "Host", "value": "shop.test" },
+                { "name": "Authorization", "value": "Bearer abc" }
+            ],
+            "postData": {
+                "mimeType": "application/x-www-form-urlencoded",
+                "params": [{ "name": "note", "value": "a b" }]
+            }
+        }));
+
+        assert_eq!(entry.status, 200);
+        let request = entry.request;
+        assert_eq!(request.key, "POST https://shop.test/orders");
+        assert_eq!(request.route, "https://shop.test/orders");
+        assert_eq!(
+            request.params,
+            vec![(String::from("page"), String::from("2"))]
+        );
+        assert_eq!(
+            request.headers,
+            vec![
+                (String::from("Authorization"), String::from("Bearer abc")),
+                (
+                    String::from("Content-Type"),
+                    String::from("application/x-www-form-urlencoded")
+                )
+            ]
+        );
+        assert_eq!(request.body.as_deref(), Some("note=a+b"));
+    }
+
+    #[test]
+    fn turns_multipart_bodies_into_form_parts() {
+        let entry = captured(json!({
+            "method": "POST",
+            "url": "https://shop.test/upload",
+            "headers": [
+                { "name": "Content-Type", "value": "multipart/form-data; boundary=xyz" }
+            ],
+            "postData": {
+                "mimeType": "multipart/form-data; boundary=xyz",
+                "params": [
+                    { "name": "title", "value": "Logo" },
+                    { "name": "file", "fileName": "logo.png", "contentType": "image/png" }
+                ]
+            }
+        }));
+
+        let request = entry.request;
+        assert!(request.headers.is_empty());
+        assert!(request.body.is_none());
+        assert_eq!(request.form_parts.len(), 2);
+        assert_eq!(request.form_parts[0].kind, FormPartKind::Text);
+        assert_eq!(request.form_parts[0].value, "Logo");
+        assert_eq!(request.form_parts[1].kind, FormPartKind::File);
+        assert_eq!(request.form_parts[1].value, "logo.png");
+        assert_eq!(
+            request.form_parts[1].content_type.as_deref(),
+            Some("image/png")
+        );
+    }
+
+    #[test]
+    fn groups_requests_by_host() {
+        let entries = vec![
+            captured(json!({ "method": "GET", "url": "https://shop.test/orders?page=1" })),
+            captured(json!({ "method": "GET", "url": "https://shop.test/orders?page=2" })),
+            captured(json!({ "method": "GET", "url": "https://cdn.test/logo.png" })),
+        ];
+
+        let api = to_api("Captured", entries);
+        assert!(!api.complete);
+        assert_eq!(api.resources.len(), 2);
+        assert_eq!(api.resources[0].name, "shop.test");
+        assert_eq!(api.resources[0].requests.len(), 1);
+        assert_eq!(api.resources[1].name, "cdn.test");
+    }
+}
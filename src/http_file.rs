@@ -0,0 +1,596 @@
+use std::fs;
+
+use thiserror::Error;
+use url::form_urlencoded;
+
+use crate::{
+    graphql,
+    http::HOSTNAME,
+    models::{FormPart, Request},
+    services::{
+        form_part_service, graphql_service, header_service,
+        import_service::{
+            self, ImportedApi, ImportedFormPart, ImportedRequest, ImportedResource, PATH_SEPARATOR,
+        },
+        param_service, request_service, resource_service, variable_service,
+    },
+    types::{FormPartKind, HttpMethod, RequestKind},
+};
+
+/// Separates requests in a file, optionally followed by the request name.
+const SEPARATOR: &str = "###";
+
+/// Boundary of the multipart bodies written for requests with form parts.
+const BOUNDARY: &str = "WebAppBoundary";
+
+#[derive(Debug, Error)]
+pub enum HttpFileError {
+    #[error("No requests found")]
+    NoRequests,
+    #[error("Resource {0} not found")]
+    UnknownResource(i32),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+}
+
+/// Whether a file should be read as an `.http` file, going by its name.
+pub fn is_http_file(path: &str) -> bool {
+    path.ends_with(".http") || path.ends_with(".rest")
+}
+
+/// Reads a `.http` or `.rest` file, as written for the JetBrains HTTP
+/// client or VS Code REST Client, into a single resource of `api`.
+/// `@key = value` lines become variables of the API.
+pub fn parse(source: &str, api: &str, resource: &str) -> Result<ImportedApi, HttpFileError> {
+    let mut variables = vec![];
+    let mut requests = vec![];
+
+    let mut title = "";
+    let mut block = vec![];
+    for line in source.lines() {
+        if let Some(rest) = line.strip_prefix(SEPARATOR) {
+            requests.extend(parse_block(&block, title, resource, &mut variables));
+            title = rest.trim_start_matches('#').trim();
+            block.clear();
+        } else {
+            block.push(line);
+        }
+    }
+    requests.extend(parse_block(&block, title, resource, &mut variables));
+
+    if requests.is_empty() {
+        return Err(HttpFileError::NoRequests);
+    }
+
+    Ok(ImportedApi {
+        name: api.to_string(),
+        description: None,
+        resources: vec![ImportedResource {
+            name: resource.to_string(),
+            requests,
+        }],
+        variables,
+        complete: false,
+    })
+}
+
+/// Where in a request block a line is.
+enum Section {
+    BeforeRequest,
+    Headers,
+    Body,
+    /// Inside a `> {% ... %}` response handler script.
+    Handler,
+}
+
+fn parse_block(
+    lines: &[&str],
+    title: &str,
+    resource: &str,
+    variables: &mut Vec<(String, String)>,
+) -> Option<ImportedRequest> {
+    let mut section = Section::BeforeRequest;
+    let mut name = None;
+    let mut method = HttpMethod::Get;
+    let mut url = String::new();
+    let mut headers = vec![];
+    let mut body: Vec<&str> = vec![];
+
+    for line in lines {
+        let trimmed = line.trim();
+
+        match section {
+            Section::Handler => {
+                if trimmed.ends_with("%}") {
+                    section = Section::Body;
+                }
+                continue;
+            }
+            Section::Body => {
+                if trimmed.starts_with("> {%") {
+                    if !trimmed.ends_with("%}") {
+                        section = Section::Handler;
+                    }
+                } else if !trimmed.starts_with("<> ") && !trimmed.starts_with(">> ") {
+                    body.push(line);
+                }
+                continue;
+            }
+            _ => (),
+        }
+
+        if let Some(comment) = trimmed
+            .strip_prefix('#')
+            .or_else(|| trimmed.strip_prefix("//"))
+        {
+            if let Some(request_name) = comment.trim().strip_prefix("@name") {
+                name = Some(request_name.trim().to_string());
+            }
+            continue;
+        }
+
+        match section {
+            Section::BeforeRequest if trimmed.is_empty() => (),
+            Section::BeforeRequest => {
+                if let Some(definition) = trimmed.strip_prefix('@') {
+                    if let Some((key, value)) = definition.split_once('=') {
+                        variables.push((key.trim().to_string(), value.trim().to_string()));
+                    }
+                    continue;
+                }
+
+                let tokens: Vec<&str> = trimmed.split_whitespace().collect();
+                match tokens.as_slice() {
+                    [verb, target, ..]
+                        if *verb == verb.to_uppercase() && verb.parse::<HttpMethod>().is_ok() =>
+                    {
+                        method = verb.parse().unwrap_or(HttpMethod::Get);
+                        url = target.to_string();
+                    }
+                    [target, ..] => url = target.to_string(),
+                    [] => (),
+                }
+                section = Section::Headers;
+            }
+            // Long query strings may continue on the following lines.
+            Section::Headers if trimmed.starts_with('?') || trimmed.starts_with('&') => {
+                url.push_str(trimmed);
+            }
+            Section::Headers if trimmed.is_empty() => section = Section::Body,
+            Section::Headers => {
+                if let Some((key, value)) = trimmed.split_once(':') {
+                    headers.push((key.trim().to_string(), value.trim().to_string()));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    if url.is_empty() {
+        return None;
+    }
+
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+    let body = Some(body.join("\n")).filter(|body| !body.is_empty());
+
+    let (route, query) = match url.split_once('?') {
+        Some((route, query)) => (route.to_string(), query),
+        None => (url.clone(), ""),
+    };
+    let params = form_urlencoded::parse(query.as_bytes())
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    // Multipart bodies become form parts, the boundary is picked again when
+    // the request is sent.
+    let boundary = headers.iter().position(|(key, value)| {
+        key.eq_ignore_ascii_case("content-type") && value.starts_with("multipart/form-data")
+    });
+    let (body, form_parts) = match (boundary, &body) {
+        (Some(index), Some(text)) => {
+            let (_, content_type) = headers.remove(index);
+            let boundary = content_type
+                .split(';')
+                .find_map(|part| part.trim().strip_prefix("boundary="))
+                .unwrap_or(BOUNDARY)
+                .trim_matches('"');
+            (None, parse_multipart(text, boundary))
+        }
+        _ => (body, vec![]),
+    };
+
+    let given = name.or_else(|| Some(title.to_string()).filter(|title| !title.is_empty()));
+    let name = given
+        .clone()
+        .unwrap_or_else(|| format!("{} {}", method, url));
+
+    Some(ImportedRequest {
+        key: format!("{}{}{}", resource, PATH_SEPARATOR, name),
+        name: given,
+        description: None,
+        route,
+        method,
+        body,
+        headers,
+        params,
+        form_parts,
+    })
+}
+
+/// Splits a multipart body into its parts. A part whose content is
+/// `< path` is read from that file.
+fn parse_multipart(body: &str, boundary: &str) -> Vec<ImportedFormPart> {
+    let delimiter = format!("--{}", boundary);
+    let mut parts = vec![];
+
+    for chunk in body.split(delimiter.as_str()) {
+        let chunk = chunk.trim_start_matches(['\r', '\n']);
+        if chunk.is_empty() || chunk.starts_with("--") {
+            continue;
+        }
+
+        let (head, content) = chunk
+            .split_once("\n\n")
+            .or_else(|| chunk.split_once("\r\n\r\n"))
+            .unwrap_or((chunk, ""));
+        let mut name = String::new();
+        let mut content_type = None;
+        for line in head.lines() {
+            let (key, value) = match line.split_once(':') {
+                Some(header) => header,
+                None => continue,
+            };
+            if key.trim().eq_ignore_ascii_case("content-disposition") {
+                name = value
+                    .split(';')
+                    .find_map(|field| field.trim().strip_prefix("name="))
+                    .unwrap_or("")
+                    .trim_matches('"')
+                    .to_string();
+            } else if key.trim().eq_ignore_ascii_case("content-type") {
+                content_type = Some(value.trim().to_string());
+            }
+        }
+
+        let content = content.trim_end_matches(['\r', '\n']);
+        let (kind, value) = match content.strip_prefix("< ") {
+            Some(path) => (FormPartKind::File, path.trim().to_string()),
+            None => (FormPartKind::Text, content.to_string()),
+        };
+        parts.push(ImportedFormPart {
+            name,
+            kind,
+            value,
+            content_type,
+        });
+    }
+
+    parts
+}
+
+/// Writes the requests of a resource as an `.http` file, with the API's
+/// variables defined at the top. Relative routes are made absolute with a
+/// `{{baseUrl}}` variable. WebSocket and gRPC requests are left out.
+pub fn export(resource_id: i32) -> Result<String, HttpFileError> {
+    let resource = resource_service::get_resource_by_id(resource_id)
+        .ok_or(HttpFileError::UnknownResource(resource_id))?;
+    let requests: Vec<Request> = request_service::get_requests_for_resource(resource_id)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|request| matches!(request.kind, RequestKind::Http | RequestKind::Graphql))
+        .collect();
+
+    let mut variables: Vec<(String, String)> =
+        variable_service::get_variables_for_api(resource.api_id)
+            .into_iter()
+            .map(|variable| (variable.key, variable.value))
+            .collect();
+    let relative =
+        |request: &Request| !request.route.contains("://") && !request.route.starts_with("{{");
+    if requests.iter().any(relative) && !variables.iter().any(|(key, _)| key == "baseUrl") {
+        variables.push((String::from("baseUrl"), HOSTNAME.to_string()));
+    }
+
+    let mut file = String::new();
+    for (key, value) in &variables {
+        file.push_str(&format!("@{} = {}\n", key, value));
+    }
+
+    for request in &requests {
+        if !file.is_empty() {
+            file.push('\n');
+        }
+        let name = import_service::get_request_name(request)
+            .unwrap_or_else(|| format!("{} {}", request.method, request.route));
+        file.push_str(&format!("{} {}\n", SEPARATOR, name));
+        file.push_str(&export_request(request, relative(request)));
+    }
+
+    Ok(file)
+}
+
+pub fn export_to_file(resource_id: i32, path: &str) -> Result<(), HttpFileError> {
+    let file = export(resource_id)?;
+
+    fs::write(path, file).map_err(|err| HttpFileError::Write(path.to_string(), err))
+}
+
+fn export_request(request: &Request, relative: bool) -> String {
+    let mut headers: Vec<(String, String)> = header_service::get_headers_for_request(request.id)
+        .into_iter()
+        .map(|header| (header.key, header.value))
+        .collect();
+    let params = param_service::get_params_for_request(request.id);
+    let form_parts = form_part_service::get_form_parts_for_request(request.id);
+
+    let mut url = if relative {
+        format!("{{{{baseUrl}}}}{}", request.route)
+    } else {
+        request.route.clone()
+    };
+    for (index, param) in params.iter().enumerate() {
+        let separator = if index == 0 && !url.contains('?') {
+            '?'
+        } else {
+            '&'
+        };
+        url.push_str(&format!(
+            "{}{}={}",
+            separator,
+            encode_query(&param.key),
+            encode_query(&param.value)
+        ));
+    }
+
+    let has_content_type = |headers: &[(String, String)]| {
+        headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+    };
+    let (method, body) = if request.kind == RequestKind::Graphql {
+        let body = graphql_service::get_query_for_request(request.id)
+            .and_then(|query| graphql::envelope(&query).ok())
+            .map(|envelope| serde_json::to_string_pretty(&envelope).unwrap_or_default());
+        if !has_content_type(&headers) {
+            headers.push((
+                String::from("Content-Type"),
+                String::from("application/json"),
+            ));
+        }
+        (HttpMethod::Post, body)
+    } else if !form_parts.is_empty() {
+        headers.retain(|(key, _)| !key.eq_ignore_ascii_case("content-type"));
+        headers.push((
+            String::from("Content-Type"),
+            format!("multipart/form-data; boundary={}", BOUNDARY),
+        ));
+        (request.method.clone(), Some(export_multipart(&form_parts)))
+    } else {
+        (request.method.clone(), request.body.clone())
+    };
+
+    let mut text = format!("{} {}\n", method, url);
+    for (key, value) in &headers {
+        text.push_str(&format!("{}: {}\n", key, value));
+    }
+    if let Some(body) = body {
+        text.push('\n');
+        text.push_str(&body);
+        text.push('\n');
+    }
+    text
+}
+
+/// Encodes a query param key or value, leaving `{{key}}` placeholders as
+/// they are so they are still filled in.
+fn encode_query(text: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end + 2,
+            None => break,
+        };
+        encoded.extend(form_urlencoded::byte_serialize(&rest.as_bytes()[..start]));
+        encoded.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+    encoded.extend(form_urlencoded::byte_serialize(rest.as_bytes()));
+    encoded
+}
+
+fn export_multipart(form_parts: &[FormPart]) -> String {
+    let mut body = String::new();
+
+    for part in form_parts {
+        body.push_str(&format!("--{}\n", BOUNDARY));
+        match part.kind {
+            FormPartKind::Text => {
+                body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"\n",
+                    part.name
+                ));
+            }
+            FormPartKind::File => {
+                let file_name = part.value.rsplit('/').next().unwrap_or(&part.value);
+                body.push_str(&format!(
+                    "Content-Disposition: form-data; name=\"{}\"; filename=\"{}\"\n",
+                    part.name, file_name
+                ));
+            }
+        }
+        if let Some(content_type) = &part.content_type {
+            body.push_str(&format!("Content-Type: {}\n", content_type));
+        }
+        body.push('\n');
+        match part.kind {
+            FormPartKind::Text => body.push_str(&part.value),
+            FormPartKind::File => body.push_str(&format!("< {}", part.value)),
+        }
+        body.push('\n');
+    }
+
+    body.push_str(&format!("--{}--", BOUNDARY));
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::{NewApi, NewHeader, NewParam, NewRequest, NewResource},
+        services::api_service,
+    };
+
+    #[test]
+    fn reads_requests_variables_and_names() {
+        let imported = parse(
+            "@host = https://shop.test\n\
+             \n\
+             ### List orders\n\
+             GET {{host}}/orders?page=1\n\
+             \x20   &limit=20\n\
+             Accept: application/json\n\
+             \n\
+             ###\n\
+             # @name createOrder\n\
+             POST {{host}}/orders\n\
+             Content-Type: application/json\n\
+             \n\
+             {\"item\": 1}\n\
+             \n\
+             > {% client.global.set(\"id\", response.body.id); %}\n",
+            "Shop",
+            "orders",
+        )
+        .unwrap();
+
+        assert_eq!(
+            imported.variables,
+            vec![(String::from("host"), String::from("https://shop.test"))]
+        );
+        let requests = &imported.resources[0].requests;
+        assert_eq!(requests.len(), 2);
+
+        assert_eq!(requests[0].key, "orders / List orders");
+        assert_eq!(requests[0].route, "{{host}}/orders");
+        assert_eq!(
+            requests[0].params,
+            vec![
+                (String::from("page"), String::from("1")),
+                (String::from("limit"), String::from("20"))
+            ]
+        );
+        assert_eq!(
+            requests[0].headers,
+            vec![(String::from("Accept"), String::from("application/json"))]
+        );
+
+        assert_eq!(requests[1].name.as_deref(), Some("createOrder"));
+        assert_eq!(requests[1].method, HttpMethod::Post);
+        assert_eq!(requests[1].body.as_deref(), Some("{\"item\": 1}"));
+    }
+
+    #[test]
+    fn turns_multipart_bodies_into_form_parts() {
+        let imported = parse(
+            "POST https://shop.test/upload\n\
+             Content-Type: multipart/form-data; boundary=\"edge\"\n\
+             \n\
+             --edge\n\
+             Content-Disposition: form-data; name=\"title\"\n\
+             \n\
+             Holiday\n\
+             --edge\n\
+             Content-Disposition: form-data; name=\"photo\"; filename=\"cat.png\"\n\
+             Content-Type: image/png\n\
+             \n\
+             < ./cat.png\n\
+             --edge--\n",
+            "Shop",
+            "uploads",
+        )
+        .unwrap();
+
+        let request = &imported.resources[0].requests[0];
+        assert!(request.headers.is_empty());
+        assert_eq!(request.body, None);
+        assert_eq!(request.form_parts.len(), 2);
+        assert_eq!(request.form_parts[0].name, "title");
+        assert_eq!(request.form_parts[0].value, "Holiday");
+        assert_eq!(request.form_parts[1].kind, FormPartKind::File);
+        assert_eq!(request.form_parts[1].value, "./cat.png");
+        assert_eq!(
+            request.form_parts[1].content_type.as_deref(),
+            Some("image/png")
+        );
+    }
+
+    #[test]
+    fn needs_a_request() {
+        assert!(matches!(
+            parse("# only a comment\n@key = value\n", "Shop", "empty"),
+            Err(HttpFileError::NoRequests)
+        ));
+    }
+
+    #[test]
+    fn reads_back_what_it_exports() {
+        api_service::create_new_api(NewApi { name: "Round trip" });
+        let api = api_service::get_all_apis().pop().unwrap();
+        resource_service::create_new_resource(NewResource {
+            name: "orders",
+            api_id: api.id,
+            parent_id: None,
+        });
+        let resource = resource_service::get_root_resources(api.id).pop().unwrap();
+        let request = request_service::create_request(NewRequest {
+            resource_id: resource.id,
+            route: "/orders",
+            method: HttpMethod::Post,
+            body: Some("{\"item\": 1}"),
+            kind: RequestKind::Http,
+        });
+        request_service::update_request_name(&request, Some("Search orders"));
+        let params = [("q", "a&b #c d"), ("token", "{{token}}"), ("a=b", "100%")];
+        for (key, value) in params {
+            param_service::create_new_param(NewParam {
+                key,
+                value,
+                request_id: request.id,
+            });
+        }
+        header_service::create_new_header(NewHeader {
+            key: "Content-Type",
+            value: "application/json",
+            request_id: request.id,
+        });
+
+        let exported = export(resource.id).unwrap();
+        assert!(exported.contains("?q=a%26b+%23c+d&token={{token}}&a%3Db=100%25\n"));
+
+        let imported = parse(&exported, "Round trip", "orders").unwrap();
+        assert!(imported
+            .variables
+            .contains(&(String::from("baseUrl"), HOSTNAME.to_string())));
+        let read = &imported.resources[0].requests[0];
+        assert_eq!(read.name.as_deref(), Some("Search orders"));
+        assert_eq!(read.method, HttpMethod::Post);
+        assert_eq!(read.route, "{{baseUrl}}/orders");
+        let expected: Vec<(String, String)> = params
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(read.params, expected);
+        assert_eq!(
+            read.headers,
+            vec![(
+                String::from("Content-Type"),
+                String::from("application/json")
+            )]
+        );
+        assert_eq!(read.body.as_deref(), Some("{\"item\": 1}"));
+    }
+}
@@ -0,0 +1,299 @@
+use std::{cmp::Reverse, collections::HashMap, io::Stdout};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::{left_widget::method_badge, right_widget::centered_rect};
+use crate::{
+    models::Request,
+    services::{api_service, request_service, resource_service},
+};
+
+/// A request that can be found, with the API and folders it is in.
+struct Entry {
+    request: Request,
+    /// Like `Shop / Products / Reviews`.
+    path: String,
+    /// Everything a query is matched against, in lowercase.
+    text: String,
+}
+
+pub enum FinderResult {
+    None,
+    Close,
+    Open(i32),
+}
+
+/// A popup finding any request by a few letters of its API, folders, name,
+/// method or route. The requests sent last come first.
+pub struct Finder {
+    query: String,
+    entries: Vec<Entry>,
+    /// The entries matching the query, best first.
+    matches: Vec<usize>,
+    list_state: ListState,
+}
+
+impl Finder {
+    pub fn new() -> Self {
+        let paths = resource_paths();
+        let mut entries: Vec<Entry> = request_service::get_all_requests()
+            .into_iter()
+            .filter_map(|request| {
+                let path = paths.get(&request.resource_id)?.clone();
+                let text = format!(
+                    "{} {} {} {}",
+                    path,
+                    request.title(),
+                    request.method,
+                    request.route
+                )
+                .to_lowercase();
+                Some(Entry {
+                    request,
+                    path,
+                    text,
+                })
+            })
+            .collect();
+        // The latest first, those never sent last.
+        entries.sort_by_key(|entry| Reverse(entry.request.last_run()));
+
+        let mut finder = Self {
+            query: String::new(),
+            entries,
+            matches: vec![],
+            list_state: ListState::default(),
+        };
+        finder.update_matches();
+        finder
+    }
+
+    /// Matches the entries against the query again, keeping the more
+    /// recent first among those that match equally well.
+    fn update_matches(&mut self) {
+        let query = self.query.to_lowercase();
+        let mut scored: Vec<(usize, i32)> = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter_map(|(index, entry)| {
+                query
+                    .split_whitespace()
+                    .map(|word| score(word, &entry.text))
+                    .sum::<Option<i32>>()
+                    .map(|score| (index, score))
+            })
+            .collect();
+        scored.sort_by_key(|(_, score)| Reverse(*score));
+
+        self.matches = scored.into_iter().map(|(index, _)| index).collect();
+        self.list_state.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+    }
+
+    fn select_next(&mut self, forward: bool) {
+        let count = self.matches.len();
+        if count == 0 {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(Some(if forward {
+            (selected + 1) % count
+        } else {
+            (selected + count - 1) % count
+        }));
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent) -> FinderResult {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return FinderResult::Close,
+            KeyCode::Enter => {
+                return match self
+                    .list_state
+                    .selected()
+                    .and_then(|selected| self.matches.get(selected))
+                {
+                    Some(index) => FinderResult::Open(self.entries[*index].request.id),
+                    None => FinderResult::None,
+                }
+            }
+            KeyCode::Down => self.select_next(true),
+            KeyCode::Up => self.select_next(false),
+            KeyCode::Char('n') if control => self.select_next(true),
+            KeyCode::Char('p') if control => self.select_next(false),
+            KeyCode::Char(c) if !control => {
+                self.query.push(c);
+                self.update_matches();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_matches();
+            }
+            _ => (),
+        }
+        FinderResult::None
+    }
+
+    pub fn draw(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let title = format!(
+            "Find a request - {} of {}",
+            self.matches.len(),
+            self.entries.len()
+        );
+        let list_area = draw_popup(frame, area, &title, &self.query);
+
+        let items: Vec<_> = self
+            .matches
+            .iter()
+            .map(|index| {
+                let entry = &self.entries[*index];
+                ListItem::new(Spans::from(request_spans(&entry.request, &entry.path)))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(Color::Yellow)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_stateful_widget(list, list_area, &mut self.list_state.clone());
+    }
+}
+
+/// Draws a popup in the middle of `area` with what was typed at the top,
+/// returning where to list what was found.
+pub(super) fn draw_popup(
+    frame: &mut Frame<CrosstermBackend<Stdout>>,
+    area: Rect,
+    title: &str,
+    query: &str,
+) -> Rect {
+    let popup = centered_rect(
+        area.width.saturating_sub(8).min(110),
+        area.height.saturating_sub(6).min(24),
+        area,
+    );
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Plain);
+    let inner = block.inner(popup);
+    frame.render_widget(Clear, popup);
+    frame.render_widget(block, popup);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(2), Constraint::Min(1)].as_ref())
+        .split(inner);
+
+    let query = Paragraph::new(Spans::from(vec![
+        Span::styled("> ", Style::default().fg(Color::Cyan)),
+        Span::raw(query),
+    ]));
+    frame.render_widget(query, chunks[0]);
+    chunks[1]
+}
+
+/// The API and folders of every resource by its id, like
+/// `Shop / Products / Reviews`.
+pub(super) fn resource_paths() -> HashMap<i32, String> {
+    let mut paths = HashMap::new();
+    for api in api_service::get_all_apis() {
+        let resources = resource_service::get_resources_for_api(api.id);
+        for resource in &resources {
+            let path = format!(
+                "{} / {}",
+                api.name,
+                resource_service::path_name(&resources, resource)
+            );
+            paths.insert(resource.id, path);
+        }
+    }
+    paths
+}
+
+/// A request as a line of a popup: its method, where it is and its name.
+pub(super) fn request_spans<'a>(request: &'a Request, path: &str) -> Vec<Span<'a>> {
+    let mut spans = vec![
+        method_badge(request),
+        Span::styled(format!("{} / ", path), Style::default().fg(Color::Blue)),
+    ];
+    match &request.name {
+        Some(name) if !name.is_empty() => spans.extend([
+            Span::raw(name.as_str()),
+            Span::styled(
+                format!("  {}", request.route),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        _ => spans.push(Span::raw(request.route.as_str())),
+    }
+    spans
+}
+
+/// How well `word` matches `text` when its letters are found in order,
+/// none when they are not. Letters found right after one another, or at
+/// the start of a word, count for more.
+fn score(word: &str, text: &str) -> Option<i32> {
+    let mut wanted = word.chars().peekable();
+    let mut score = 0;
+    let mut previous: Option<char> = None;
+    let mut after_match = false;
+
+    for c in text.chars() {
+        if wanted.peek() == Some(&c) {
+            wanted.next();
+            score += 1;
+            if after_match {
+                score += 4;
+            }
+            if previous.is_none_or(|previous| !previous.is_alphanumeric()) {
+                score += 3;
+            }
+            after_match = true;
+        } else {
+            after_match = false;
+        }
+        previous = Some(c);
+    }
+
+    match wanted.peek() {
+        Some(_) => None,
+        None => Some(score),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn needs_every_letter_in_order() {
+        assert_eq!(score("ord", "orders"), Some(14));
+        assert_eq!(score("ord", "dro"), None);
+        assert_eq!(score("ordx", "orders"), None);
+        assert_eq!(score("", "orders"), Some(0));
+    }
+
+    #[test]
+    fn prefers_runs_and_word_starts() {
+        let run = score("ord", "list orders").unwrap();
+        let starts = score("ord", "open-report-draft").unwrap();
+        let scattered = score("ord", "colourfield").unwrap();
+
+        assert!(run > starts);
+        assert!(starts > scattered);
+    }
+}
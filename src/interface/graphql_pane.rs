@@ -0,0 +1,332 @@
+use std::io::Stdout;
+
+use chrono::Local;
+use crossterm::event::KeyCode;
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph, Wrap},
+    Frame,
+};
+
+use super::right_widget::{optional, RightInputResult};
+use crate::{
+    graphql::{self, Schema},
+    http::HOSTNAME,
+    models::{GraphqlQuery, GraphqlSchema, NewGraphqlQuery, NewGraphqlSchema, Request},
+    services::graphql_service,
+    types::RequestKind,
+};
+
+/// Fields of the GraphQL tab, in display order.
+const GRAPHQL_FIELDS: [&str; 3] = ["Operation name", "Query", "Variables"];
+
+/// The GraphQL tab: query, variables and operation name of the request,
+/// checked against the schema cached for its Api.
+pub(super) struct GraphqlPane {
+    request_id: i32,
+    api_id: Option<i32>,
+    query: Option<GraphqlQuery>,
+    schema: Option<Schema>,
+    schema_fetched_at: Option<String>,
+    selected: usize,
+    input: Option<String>,
+    message: Option<String>,
+}
+
+impl GraphqlPane {
+    pub(super) fn new(request_id: i32, api_id: Option<i32>) -> Self {
+        let mut pane = Self {
+            request_id,
+            api_id,
+            query: graphql_service::get_query_for_request(request_id),
+            schema: None,
+            schema_fetched_at: None,
+            selected: 1,
+            input: None,
+            message: None,
+        };
+
+        if let Some(cached) = api_id.and_then(graphql_service::get_schema_for_api) {
+            pane.load_schema(&cached);
+        }
+        pane
+    }
+
+    fn load_schema(&mut self, cached: &GraphqlSchema) {
+        match Schema::parse(&cached.introspection) {
+            Ok(schema) => {
+                self.schema = Some(schema);
+                self.schema_fetched_at = Some(cached.fetched_at.clone());
+            }
+            Err(err) => {
+                self.message.replace(err.to_string());
+            }
+        }
+    }
+
+    pub(super) fn introspect(&mut self, request: &Request) {
+        let api_id = match self.api_id {
+            Some(api_id) => api_id,
+            None => return,
+        };
+
+        match graphql::introspect(request, HOSTNAME) {
+            Ok(introspection) => {
+                let cached = graphql_service::save_schema(&NewGraphqlSchema {
+                    api_id,
+                    introspection: &introspection,
+                    fetched_at: &Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+                });
+                self.message.take();
+                self.load_schema(&cached);
+            }
+            Err(err) => {
+                self.message
+                    .replace(format!("Introspection failed: {}", err));
+            }
+        }
+    }
+
+    fn value(&self, field: usize) -> &str {
+        match (&self.query, field) {
+            (Some(query), 0) => query.operation_name.as_deref().unwrap_or(""),
+            (Some(query), 1) => &query.query,
+            (Some(query), 2) => &query.variables,
+            _ => "",
+        }
+    }
+
+    fn save(&mut self, field: usize, value: &str) {
+        let (query, variables, operation_name) = match field {
+            0 => (self.value(1), self.value(2), optional(value)),
+            1 => (value, self.value(2), optional(self.value(0))),
+            _ => (self.value(1), value, optional(self.value(0))),
+        };
+
+        let saved = graphql_service::save_query(&NewGraphqlQuery {
+            request_id: self.request_id,
+            query,
+            variables,
+            operation_name,
+        });
+        self.query = Some(saved);
+    }
+
+    /// Completes the field name being typed at the end of the query.
+    fn complete(&mut self) {
+        let (schema, input) = match (&self.schema, &mut self.input) {
+            (Some(schema), Some(input)) => (schema, input),
+            (None, _) => {
+                self.message
+                    .replace(String::from("No schema cached yet, press i to introspect"));
+                return;
+            }
+            _ => return,
+        };
+
+        let completion = schema.complete(input);
+        let names: Vec<&str> = completion
+            .candidates
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+
+        let common = names.first().map(|first| {
+            names.iter().fold(first.to_string(), |common, name| {
+                common
+                    .chars()
+                    .zip(name.chars())
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect()
+            })
+        });
+        if let Some(common) = common {
+            input.push_str(&common[completion.prefix.len()..]);
+        }
+
+        self.message = match completion.candidates.len() {
+            0 => Some(String::from("No completions")),
+            1 => None,
+            _ => Some(
+                completion
+                    .candidates
+                    .iter()
+                    .map(|(name, detail)| format!("{}: {}", name, detail))
+                    .collect::<Vec<_>>()
+                    .join("  "),
+            ),
+        };
+    }
+
+    /// Handles the keys the tab owns. Anything else is left to the request
+    /// widget.
+    pub(super) fn handle_input(&mut self, key: KeyCode) -> Option<RightInputResult> {
+        let multiline = self.selected != 0;
+
+        if self.input.is_none() {
+            match key {
+                KeyCode::Char('j') => self.selected = (self.selected + 1) % GRAPHQL_FIELDS.len(),
+                KeyCode::Char('k') => {
+                    self.selected =
+                        (self.selected + GRAPHQL_FIELDS.len() - 1) % GRAPHQL_FIELDS.len()
+                }
+                KeyCode::Enter => {
+                    self.input.replace(self.value(self.selected).to_string());
+                }
+                _ => return None,
+            }
+            return Some(RightInputResult::None);
+        }
+
+        match key {
+            KeyCode::Char(character) => {
+                if let Some(input) = &mut self.input {
+                    input.push(character);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(input) = &mut self.input {
+                    input.pop();
+                }
+            }
+            KeyCode::Tab if self.selected == 1 => self.complete(),
+            KeyCode::Enter if multiline => {
+                if let Some(input) = &mut self.input {
+                    input.push('\n');
+                }
+            }
+            KeyCode::Enter => {
+                if let Some(input) = self.input.take() {
+                    self.save(self.selected, &input);
+                }
+            }
+            KeyCode::Esc if multiline => {
+                if let Some(input) = self.input.take() {
+                    self.save(self.selected, &input);
+                }
+                self.message.take();
+            }
+            KeyCode::Esc => {
+                self.input.take();
+            }
+            _ => (),
+        }
+        Some(RightInputResult::None)
+    }
+
+    fn problems(&self) -> Vec<String> {
+        let current = |field: usize| match &self.input {
+            Some(input) if self.selected == field => input.as_str(),
+            _ => self.value(field),
+        };
+
+        let mut problems = match &self.schema {
+            Some(schema) if !current(1).trim().is_empty() => schema.validate(current(1)),
+            _ => vec![],
+        };
+        if let Err(err) = graphql::parse_variables(current(2)) {
+            problems.push(err.to_string());
+        }
+        problems
+    }
+
+    fn status_lines(&self) -> Vec<Spans<'_>> {
+        let schema_line = match (&self.schema, &self.schema_fetched_at) {
+            (Some(schema), Some(fetched_at)) => format!(
+                "{} types, fetched {}, i to refresh",
+                schema.types.len(),
+                fetched_at
+            ),
+            _ => String::from("Not cached yet, i to introspect"),
+        };
+        let mut lines = vec![Spans::from(Span::styled(
+            schema_line,
+            Style::default().fg(Color::DarkGray),
+        ))];
+
+        if let Some(message) = &self.message {
+            lines.push(Spans::from(Span::styled(
+                message.as_str(),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+
+        let problems = self.problems();
+        if problems.is_empty() && self.schema.is_some() && !self.value(1).trim().is_empty() {
+            lines.push(Spans::from(Span::styled(
+                "Query is valid",
+                Style::default().fg(Color::Green),
+            )));
+        }
+        lines.extend(
+            problems
+                .into_iter()
+                .map(|problem| Spans::from(Span::styled(problem, Style::default().fg(Color::Red)))),
+        );
+
+        lines
+    }
+
+    pub(super) fn draw(
+        &self,
+        frame: &mut Frame<CrosstermBackend<Stdout>>,
+        rect: Rect,
+        kind: RequestKind,
+    ) {
+        if kind != RequestKind::Graphql {
+            let hint = Paragraph::new(
+                "This is not a GraphQL request. Switch its kind on the Details tab to edit a query.",
+            )
+            .wrap(Wrap { trim: true })
+            .block(Block::default().title("GraphQL").borders(Borders::ALL));
+            frame.render_widget(hint, rect);
+            return;
+        }
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(6),
+                Constraint::Length(7),
+                Constraint::Length(8),
+            ])
+            .split(rect);
+
+        for (field, title) in GRAPHQL_FIELDS.iter().enumerate() {
+            let editing = self.selected == field && self.input.is_some();
+            let text = match &self.input {
+                Some(input) if editing => input.as_str(),
+                _ => self.value(field),
+            };
+            let title = match (editing, field) {
+                (true, 0) => format!("{} (Enter to save, Esc to cancel)", title),
+                (true, 1) => format!("{} (Esc to save, Tab to complete)", title),
+                (true, _) => format!("{} (Esc to save)", title),
+                (false, _) => title.to_string(),
+            };
+            let border_style = match (editing, self.selected == field) {
+                (true, _) => Style::default().fg(Color::Cyan),
+                (false, true) => Style::default().fg(Color::Yellow),
+                (false, false) => Style::default(),
+            };
+
+            let para = Paragraph::new(text).block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(border_style),
+            );
+            frame.render_widget(para, chunks[field]);
+        }
+
+        let status = Paragraph::new(self.status_lines())
+            .wrap(Wrap { trim: false })
+            .block(Block::default().title("Schema").borders(Borders::ALL));
+        frame.render_widget(status, chunks[3]);
+    }
+}
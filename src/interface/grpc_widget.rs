@@ -0,0 +1,500 @@
+use std::io::Stdout;
+
+use crossterm::event::KeyCode;
+use prost_reflect::{DescriptorPool, MethodDescriptor};
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::right_widget::{
+    centered_rect, complete_path, draw_insecure_banner, optional, Drawable, RightInputResult,
+};
+use crate::{
+    grpc::{self, CallSession, CallState, GrpcError, Target},
+    http::{self, HOSTNAME},
+    models::{GrpcCall, Header, NewGrpcCall, Request, TlsConfig},
+    proto,
+    services::{grpc_service, request_service},
+};
+
+const GRPC_FIELDS: [&str; 4] = ["URL", "Proto files", "Method", "Message"];
+
+pub(super) struct GrpcWidget {
+    request: Request,
+    headers: Vec<Header>,
+    tls: Option<TlsConfig>,
+    call: Option<GrpcCall>,
+    pool: Option<DescriptorPool>,
+    methods: Vec<MethodDescriptor>,
+    selected: usize,
+    input: Option<String>,
+    method_picker: Option<ListState>,
+    session: Option<CallSession>,
+    status_message: Option<String>,
+}
+
+impl GrpcWidget {
+    pub(super) fn new(request: Request, headers: Vec<Header>, tls: Option<TlsConfig>) -> Self {
+        let call = grpc_service::get_call_for_request(request.id);
+        let mut widget = Self {
+            request,
+            headers,
+            tls,
+            call,
+            pool: None,
+            methods: vec![],
+            selected: 3,
+            input: None,
+            method_picker: None,
+            session: None,
+            status_message: None,
+        };
+
+        // Local files are cheap to parse, reflection waits for `l`.
+        if !widget.proto_paths().is_empty() {
+            widget.load_definitions();
+        }
+        widget
+    }
+
+    fn url(&self) -> String {
+        let variables = http::variables_for_resource(self.request.resource_id);
+        http::request_url(
+            HOSTNAME,
+            &http::expand_variables(&self.request.route, &variables),
+        )
+    }
+
+    fn target(&self) -> Target {
+        let headers = self
+            .headers
+            .iter()
+            .map(|header| (header.key.clone(), header.value.clone()))
+            .collect();
+
+        Target::new(&self.url(), headers, self.tls.clone())
+    }
+
+    fn proto_paths(&self) -> Vec<&str> {
+        self.call
+            .as_ref()
+            .map(GrpcCall::proto_paths)
+            .unwrap_or_default()
+    }
+
+    fn value(&self, field: usize) -> &str {
+        match (&self.call, field) {
+            (Some(call), 1) => &call.proto_files,
+            (Some(call), 2) => call.method.as_deref().unwrap_or(""),
+            (Some(call), 3) => &call.message,
+            (None, 3) => "{}",
+            _ => "",
+        }
+    }
+
+    fn save(&mut self, field: usize, value: &str) {
+        let (proto_files, method, message) = match field {
+            1 => (value, optional(self.value(2)), self.value(3)),
+            2 => (self.value(1), optional(value), self.value(3)),
+            _ => (self.value(1), optional(self.value(2)), value),
+        };
+
+        let saved = grpc_service::save_call(&NewGrpcCall {
+            request_id: self.request.id,
+            proto_files,
+            method,
+            message,
+        });
+        self.call = Some(saved);
+    }
+
+    /// Reads the listed `.proto` files, or asks the server when there are
+    /// none.
+    fn load_definitions(&mut self) {
+        let paths = self.proto_paths();
+        let (result, source) = if paths.is_empty() {
+            (grpc::reflect(&self.target()), "server reflection")
+        } else {
+            (
+                proto::load_files(&paths).map_err(GrpcError::from),
+                "proto files",
+            )
+        };
+
+        match result {
+            Ok(pool) => {
+                self.methods = grpc::methods(&pool);
+                self.pool = Some(pool);
+                self.status_message.replace(format!(
+                    "Loaded {} methods from {}",
+                    self.methods.len(),
+                    source
+                ));
+            }
+            Err(err) => {
+                self.status_message.replace(err.to_string());
+            }
+        }
+    }
+
+    fn selected_method(&self) -> Option<MethodDescriptor> {
+        let name = optional(self.value(2))?;
+        grpc::find_method(self.pool.as_ref()?, name)
+    }
+
+    fn fill_template(&mut self) {
+        match self.selected_method() {
+            Some(method) => {
+                let template = grpc::message_template(method.input());
+                self.save(3, &template);
+            }
+            None => {
+                self.status_message
+                    .replace(String::from("Pick a method first"));
+            }
+        }
+    }
+
+    fn send(&mut self) {
+        let method = match self.selected_method() {
+            Some(method) => method,
+            None => {
+                let err = match optional(self.value(2)) {
+                    Some(name) => GrpcError::UnknownMethod(name.to_string()).to_string(),
+                    None => String::from("Pick a method first"),
+                };
+                self.status_message.replace(err);
+                return;
+            }
+        };
+
+        request_service::mark_run(self.request.id);
+        match CallSession::start(self.target(), &method, self.value(3)) {
+            Ok(session) => {
+                self.session = Some(session);
+                self.status_message.take();
+            }
+            Err(err) => {
+                self.status_message.replace(err.to_string());
+            }
+        }
+    }
+
+    pub(super) fn tick(&mut self) {
+        if let Some(session) = &mut self.session {
+            session.poll();
+        }
+    }
+
+    fn open_method_picker(&mut self) {
+        if self.methods.is_empty() {
+            self.status_message.replace(String::from(
+                "No methods loaded, l to load the service definitions",
+            ));
+            return;
+        }
+
+        let current = self.value(2);
+        let selected = self
+            .methods
+            .iter()
+            .position(|method| grpc::method_name(method) == current)
+            .unwrap_or(0);
+
+        let mut picker_state = ListState::default();
+        picker_state.select(Some(selected));
+        self.method_picker.replace(picker_state);
+    }
+
+    fn handle_method_picker_input(&mut self, key: KeyCode) -> RightInputResult {
+        let option_count = self.methods.len();
+        let picker_state = match &mut self.method_picker {
+            Some(picker_state) => picker_state,
+            None => return RightInputResult::None,
+        };
+        let selected = picker_state.selected().unwrap_or(0);
+
+        match key {
+            KeyCode::Char('j') | KeyCode::Down => {
+                picker_state.select(Some((selected + 1) % option_count));
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                picker_state.select(Some((selected + option_count - 1) % option_count));
+            }
+            KeyCode::Enter => {
+                self.method_picker.take();
+                if let Some(method) = self.methods.get(selected) {
+                    let name = grpc::method_name(method);
+                    self.save(2, &name);
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => {
+                self.method_picker.take();
+            }
+            _ => (),
+        }
+        RightInputResult::None
+    }
+
+    fn method_picker(&self) -> List<'_> {
+        let items: Vec<_> = self
+            .methods
+            .iter()
+            .map(|method| {
+                let badge = if method.is_server_streaming() {
+                    "stream "
+                } else {
+                    "unary  "
+                };
+                ListItem::new(Spans::from(vec![
+                    Span::styled(badge, Style::default().fg(Color::DarkGray)),
+                    Span::raw(grpc::method_name(method)),
+                ]))
+            })
+            .collect();
+
+        let block = Block::default()
+            .title("gRPC Method")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+
+        List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(Color::Yellow)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+    }
+
+    fn save_input(&mut self) -> RightInputResult {
+        let input = self.input.take().unwrap_or_default();
+
+        match self.selected {
+            0 => {
+                self.request = request_service::update_request_route(&self.request, &input);
+                return RightInputResult::RefreshRequests;
+            }
+            1 => {
+                self.save(1, &input);
+                if !self.proto_paths().is_empty() {
+                    self.load_definitions();
+                }
+            }
+            field => self.save(field, &input),
+        }
+        RightInputResult::None
+    }
+
+    fn handle_edit_input(&mut self, key: KeyCode) -> RightInputResult {
+        let multiline = self.selected == 3;
+
+        match key {
+            KeyCode::Char(character) => {
+                if let Some(input) = &mut self.input {
+                    input.push(character);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(input) = &mut self.input {
+                    input.pop();
+                }
+            }
+            KeyCode::Tab if self.selected == 1 => {
+                if let Some(input) = &mut self.input {
+                    let start = input.rfind(';').map(|index| index + 1).unwrap_or(0);
+                    let completed = complete_path(&input[start..]);
+                    input.truncate(start);
+                    input.push_str(&completed);
+                }
+            }
+            KeyCode::Enter if multiline => {
+                if let Some(input) = &mut self.input {
+                    input.push('\n');
+                }
+            }
+            KeyCode::Enter => return self.save_input(),
+            KeyCode::Esc if multiline => return self.save_input(),
+            KeyCode::Esc => {
+                self.input.take();
+            }
+            _ => (),
+        }
+        RightInputResult::None
+    }
+
+    pub(super) fn handle_input(&mut self, key: KeyCode) -> RightInputResult {
+        if self.method_picker.is_some() {
+            return self.handle_method_picker_input(key);
+        }
+
+        if self.input.is_some() {
+            return self.handle_edit_input(key);
+        }
+
+        match key {
+            KeyCode::Char('q') => return RightInputResult::Exit,
+            KeyCode::Char('j') => self.selected = (self.selected + 1) % GRPC_FIELDS.len(),
+            KeyCode::Char('k') => {
+                self.selected = (self.selected + GRPC_FIELDS.len() - 1) % GRPC_FIELDS.len()
+            }
+            KeyCode::Enter => match self.selected {
+                0 => {
+                    self.input.replace(self.request.route.clone());
+                }
+                2 => self.open_method_picker(),
+                field => {
+                    self.input.replace(self.value(field).to_string());
+                }
+            },
+            KeyCode::Char('l') => self.load_definitions(),
+            KeyCode::Char('t') => self.fill_template(),
+            KeyCode::Char('r') => self.send(),
+            KeyCode::Esc => {
+                if let Some(session) = &mut self.session {
+                    session.stop();
+                }
+            }
+            KeyCode::Backspace => return RightInputResult::LoseFocus,
+            _ => (),
+        }
+        RightInputResult::None
+    }
+
+    fn session_title(&self) -> String {
+        let session = match &self.session {
+            Some(session) => session,
+            None => return String::from("Response (r to call)"),
+        };
+
+        let state = match &session.state {
+            CallState::Running => String::from("running, Esc to stop"),
+            CallState::Finished(status) => status.to_string(),
+            CallState::Stopped => String::from("stopped"),
+            CallState::Failed(err) => format!("failed: {}", err),
+        };
+        format!("Response - {} messages - {}", session.messages.len(), state)
+    }
+
+    fn session_lines(&self) -> Vec<Spans<'_>> {
+        let session = match &self.session {
+            Some(session) => session,
+            None => return vec![],
+        };
+
+        let mut lines = vec![];
+        for message in &session.messages {
+            lines.push(Spans::from(Span::styled(
+                message.received_at.format("%H:%M:%S%.3f").to_string(),
+                Style::default().fg(Color::DarkGray),
+            )));
+            lines.extend(message.json.lines().map(Spans::from));
+        }
+
+        match &session.state {
+            CallState::Finished(status) if status.code != 0 => {
+                lines.push(Spans::from(Span::styled(
+                    status.to_string(),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+            CallState::Failed(err) => {
+                lines.push(Spans::from(Span::styled(
+                    err.as_str(),
+                    Style::default().fg(Color::Red),
+                )));
+            }
+            _ => (),
+        }
+        lines
+    }
+}
+
+impl Drawable for GrpcWidget {
+    fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
+        let rect = draw_insecure_banner(frame, rect, self.tls.as_ref());
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(rect);
+
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(chunks[0]);
+
+        let fields = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Length(3),
+                Constraint::Min(0),
+            ])
+            .split(columns[0]);
+
+        for (field, title) in GRPC_FIELDS.iter().enumerate() {
+            let editing = self.selected == field && self.input.is_some();
+            let text = match (&self.input, field) {
+                (Some(input), _) if editing => input.clone(),
+                (_, 0) => self.url(),
+                (_, 1) if self.value(1).is_empty() => String::from("(server reflection)"),
+                _ => self.value(field).to_string(),
+            };
+            let title = match (editing, field) {
+                (true, 1) => format!("{} (; separated, Tab to complete)", title),
+                (true, 3) => format!("{} (Esc to save)", title),
+                (true, _) => format!("{} (Enter to save, Esc to cancel)", title),
+                (false, 3) => format!("{} (t for a template)", title),
+                (false, _) => title.to_string(),
+            };
+            let border_style = match (editing, self.selected == field) {
+                (true, _) => Style::default().fg(Color::Cyan),
+                (false, true) => Style::default().fg(Color::Yellow),
+                (false, false) => Style::default(),
+            };
+
+            let para = Paragraph::new(text).block(
+                Block::default()
+                    .title(title)
+                    .borders(Borders::ALL)
+                    .border_style(border_style),
+            );
+            frame.render_widget(para, fields[field]);
+        }
+
+        let lines = self.session_lines();
+        let visible = columns[1].height.saturating_sub(2) as usize;
+        let scroll = lines.len().saturating_sub(visible) as u16;
+        let response_para = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(self.session_title())
+                    .borders(Borders::ALL),
+            )
+            .scroll((scroll, 0));
+        frame.render_widget(response_para, columns[1]);
+
+        let status_para = match &self.status_message {
+            Some(message) => Paragraph::new(message.as_str()),
+            None => Paragraph::new("l load definitions, Enter edit, t template, r call")
+                .style(Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(
+            status_para.block(Block::default().borders(Borders::ALL)),
+            chunks[1],
+        );
+
+        if let Some(picker_state) = &self.method_picker {
+            let height = self.methods.len() as u16 + 2;
+            let popup = centered_rect(60, height, columns[0]);
+            frame.render_widget(Clear, popup);
+            frame.render_stateful_widget(self.method_picker(), popup, &mut picker_state.clone());
+        }
+    }
+}
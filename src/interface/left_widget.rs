@@ -1,16 +1,32 @@
+use chrono::Local;
 use crossterm::event::KeyCode;
+use std::{cmp::Ordering, collections::HashMap, fs, mem, path::Path};
 use tui::{
     style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::{Block, BorderType, Borders, List, ListItem, ListState},
 };
 
+use super::finder::resource_paths;
 use crate::{
-    models::{Api, NewApi, NewRequest, NewResource, Request, Resource},
-    services::{api_service, request_service, resource_service},
-    types::HttpMethod,
+    backup::{self, RestoreMode},
+    curl, docs,
+    filter::{Filter, FilterError},
+    har, http_file,
+    models::{Api, ExpandedNode, NewApi, NewRequest, NewResource, Request, Resource},
+    openapi, postman,
+    services::{
+        api_service, expanded_node_service,
+        import_service::{self, ImportedApi, PATH_SEPARATOR},
+        request_service, resource_service,
+    },
+    types::{HttpMethod, RequestKind, TreeNodeKind},
 };
 
+/// Exports to a path ending in this are Postman collections, to `.har`
+/// files the recorded responses, anything else is an OpenAPI document.
+const POSTMAN_EXTENSION: &str = ".postman_collection.json";
+
 pub enum LeftType {
     Apis,
     Resources(i32),
@@ -18,9 +34,55 @@ pub enum LeftType {
 }
 
 enum LeftContent {
-    Apis(Vec<Api>),
+    /// Every API, below the requests starred as favourites.
+    Apis {
+        favourites: Vec<Request>,
+        apis: Vec<Api>,
+    },
+    /// The folders at the top of an API.
     Resources(Vec<Resource>, i32),
-    Requests(Vec<Request>, i32),
+    /// The folders in a folder, listed before its requests.
+    Folder {
+        id: i32,
+        path: String,
+        folders: Vec<Resource>,
+        requests: Vec<Request>,
+    },
+    /// Every API, with the folders and requests of those left open, below
+    /// the requests starred as favourites.
+    Tree {
+        favourites: Vec<Request>,
+        rows: Vec<TreeRow>,
+    },
+    /// The requests of every API a filter typed matches, with where they are.
+    Filtered {
+        filter: String,
+        requests: Vec<Request>,
+        paths: HashMap<i32, String>,
+    },
+}
+
+/// A line of the collection tree.
+struct TreeRow {
+    node: TreeNode,
+    depth: usize,
+    expanded: bool,
+}
+
+enum TreeNode {
+    Api(Api),
+    Resource(Resource),
+    Request(Request),
+}
+
+impl TreeNode {
+    fn item(&self) -> SelectedItem {
+        match self {
+            TreeNode::Api(api) => SelectedItem::Api(api.id),
+            TreeNode::Resource(resource) => SelectedItem::Resource(resource.id),
+            TreeNode::Request(request) => SelectedItem::Request(request.id),
+        }
+    }
 }
 
 pub enum LeftInputResult {
@@ -32,9 +94,86 @@ pub enum LeftInputResult {
     ShowNothing,
     IntoApi(i32),
     IntoResource(i32),
+    EditApi(i32),
+    EditResource(i32),
     EditRequest(i32),
+    /// Choose which entries of a HAR file to import, into the given API or
+    /// a new one.
+    ImportHar(String, Option<i32>),
+}
+
+/// How the list is ordered, switched with `o`. APIs and folders are sorted
+/// by name whenever the order is not the manual one.
+#[derive(Clone, Copy, PartialEq)]
+enum SortOrder {
+    Manual,
+    Name,
+    Method,
+    LastRun,
 }
 
+impl SortOrder {
+    fn next(self) -> Self {
+        match self {
+            SortOrder::Manual => SortOrder::Name,
+            SortOrder::Name => SortOrder::Method,
+            SortOrder::Method => SortOrder::LastRun,
+            SortOrder::LastRun => SortOrder::Manual,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SortOrder::Manual => "in manual order",
+            SortOrder::Name => "by name",
+            SortOrder::Method => "by method",
+            SortOrder::LastRun => "by last run",
+        }
+    }
+
+    fn compare_names(self, a: &str, b: &str) -> Ordering {
+        match self {
+            SortOrder::Manual => Ordering::Equal,
+            _ => a.to_lowercase().cmp(&b.to_lowercase()),
+        }
+    }
+
+    fn compare_requests(self, a: &Request, b: &Request) -> Ordering {
+        let rank = |request: &Request| {
+            HttpMethod::standard()
+                .iter()
+                .position(|method| *method == request.method)
+                .unwrap_or(usize::MAX)
+        };
+        match self {
+            SortOrder::Manual => Ordering::Equal,
+            SortOrder::Name => SortOrder::Name.compare_names(&a.title(), &b.title()),
+            SortOrder::Method => rank(a)
+                .cmp(&rank(b))
+                .then_with(|| a.method.as_str().cmp(b.method.as_str()))
+                .then_with(|| SortOrder::Name.compare_requests(a, b)),
+            // The latest first, those never sent last.
+            SortOrder::LastRun => b
+                .last_run()
+                .cmp(&a.last_run())
+                .then_with(|| SortOrder::Name.compare_requests(a, b)),
+        }
+    }
+
+    fn compare_nodes(self, a: &TreeNode, b: &TreeNode) -> Ordering {
+        match (a, b) {
+            (TreeNode::Api(a), TreeNode::Api(b)) => self.compare_names(&a.name, &b.name),
+            (TreeNode::Resource(a), TreeNode::Resource(b)) => self.compare_names(&a.name, &b.name),
+            (TreeNode::Request(a), TreeNode::Request(b)) => self.compare_requests(a, b),
+            // Folders stay before the requests next to them.
+            (TreeNode::Request(_), _) => Ordering::Greater,
+            (_, TreeNode::Request(_)) => Ordering::Less,
+            _ => Ordering::Equal,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
 pub enum SelectedItem {
     None,
     Api(i32),
@@ -43,34 +182,201 @@ pub enum SelectedItem {
 }
 
 impl LeftContent {
+    fn apis() -> Self {
+        LeftContent::Apis {
+            favourites: request_service::get_favourite_requests(),
+            apis: api_service::get_all_apis(),
+        }
+    }
+
+    fn filtered(filter: &str) -> Result<Self, FilterError> {
+        let requests = filter.parse::<Filter>()?.requests();
+        Ok(LeftContent::Filtered {
+            filter: filter.trim().to_string(),
+            requests,
+            paths: resource_paths(),
+        })
+    }
+
+    fn folder(resource_id: i32) -> Self {
+        let path = resource_service::get_resource_path(resource_id)
+            .iter()
+            .map(|resource| resource.name.as_str())
+            .collect::<Vec<_>>()
+            .join(PATH_SEPARATOR);
+
+        LeftContent::Folder {
+            id: resource_id,
+            path,
+            folders: resource_service::get_child_resources(resource_id),
+            requests: request_service::get_requests_for_resource(resource_id).unwrap_or_default(),
+        }
+    }
+
+    fn tree() -> Self {
+        let expanded = expanded_node_service::get_expanded_nodes();
+
+        let mut rows = vec![];
+        for api in api_service::get_all_apis() {
+            let api_id = api.id;
+            let open = is_expanded(&expanded, TreeNodeKind::Api, api_id);
+            rows.push(TreeRow {
+                node: TreeNode::Api(api),
+                depth: 0,
+                expanded: open,
+            });
+            if open {
+                add_folders(
+                    &mut rows,
+                    &expanded,
+                    resource_service::get_root_resources(api_id),
+                    1,
+                );
+            }
+        }
+        LeftContent::Tree {
+            favourites: request_service::get_favourite_requests(),
+            rows,
+        }
+    }
+
     fn length(&self) -> usize {
         match self {
-            LeftContent::Apis(apis) => apis.len(),
+            LeftContent::Apis { favourites, apis } => favourites.len() + apis.len(),
             LeftContent::Resources(resources, _) => resources.len(),
-            LeftContent::Requests(requests, _) => requests.len(),
+            LeftContent::Folder {
+                folders, requests, ..
+            } => folders.len() + requests.len(),
+            LeftContent::Tree { favourites, rows } => favourites.len() + rows.len(),
+            LeftContent::Filtered { requests, .. } => requests.len(),
+        }
+    }
+
+    fn item(&self, index: usize) -> SelectedItem {
+        match self {
+            LeftContent::Apis { favourites, apis } => match favourites.get(index) {
+                Some(request) => Some(SelectedItem::Request(request.id)),
+                None => apis
+                    .get(index - favourites.len())
+                    .map(|api| SelectedItem::Api(api.id)),
+            },
+            LeftContent::Resources(resources, _) => resources
+                .get(index)
+                .map(|resource| SelectedItem::Resource(resource.id)),
+            LeftContent::Folder {
+                folders, requests, ..
+            } => match folders.get(index) {
+                Some(folder) => Some(SelectedItem::Resource(folder.id)),
+                None => requests
+                    .get(index - folders.len())
+                    .map(|request| SelectedItem::Request(request.id)),
+            },
+            LeftContent::Tree { favourites, rows } => match favourites.get(index) {
+                Some(request) => Some(SelectedItem::Request(request.id)),
+                None => rows
+                    .get(index - favourites.len())
+                    .map(|row| row.node.item()),
+            },
+            LeftContent::Filtered { requests, .. } => requests
+                .get(index)
+                .map(|request| SelectedItem::Request(request.id)),
+        }
+        .unwrap_or(SelectedItem::None)
+    }
+
+    fn position(&self, item: &SelectedItem) -> Option<usize> {
+        (0..self.length()).find(|index| self.item(*index) == *item)
+    }
+
+    fn sort(&mut self, order: SortOrder) {
+        if order == SortOrder::Manual {
+            return;
+        }
+        match self {
+            LeftContent::Apis { favourites, apis } => {
+                favourites.sort_by(|a, b| order.compare_requests(a, b));
+                apis.sort_by(|a, b| order.compare_names(&a.name, &b.name));
+            }
+            LeftContent::Resources(resources, _) => {
+                resources.sort_by(|a, b| order.compare_names(&a.name, &b.name))
+            }
+            LeftContent::Folder {
+                folders, requests, ..
+            } => {
+                folders.sort_by(|a, b| order.compare_names(&a.name, &b.name));
+                requests.sort_by(|a, b| order.compare_requests(a, b));
+            }
+            LeftContent::Tree { favourites, rows } => {
+                favourites.sort_by(|a, b| order.compare_requests(a, b));
+                *rows = sort_rows(mem::take(rows), order);
+            }
+            LeftContent::Filtered { requests, .. } => {
+                requests.sort_by(|a, b| order.compare_requests(a, b))
+            }
         }
     }
 }
 
+/// What the text typed into the list is for.
+#[derive(Clone, Copy, PartialEq)]
+enum Prompt {
+    Name,
+    Import,
+    Export(i32),
+    ExportResource(i32),
+    /// The directory to write the documentation of an API to.
+    Docs(i32),
+    /// A curl command to create a request from, in the listed resource.
+    Curl(i32),
+    /// The name of a folder to create in an API, inside a folder if given.
+    Folder(i32, Option<i32>),
+    /// The route of a request to create in a folder.
+    Request(i32),
+    Backup,
+    Restore(RestoreMode),
+    /// A filter like `tag:auth method:POST` to list the requests it matches.
+    Filter,
+}
+
 pub struct LeftList {
     content: LeftContent,
     pub list_state: ListState,
     input: Option<String>,
+    prompt: Prompt,
+    status: Option<String>,
     selected_item: Option<usize>,
+    /// Whether everything is shown as one tree rather than a level at a time.
+    tree: bool,
+    sort: SortOrder,
 }
 
 impl<'a> LeftList {
     pub fn new() -> Self {
-        let apis = api_service::get_all_apis();
+        let tree = crate::CONFIG.tree_view;
+        let content = if tree {
+            LeftContent::tree()
+        } else {
+            LeftContent::apis()
+        };
 
         Self {
-            content: LeftContent::Apis(apis),
+            content,
             list_state: ListState::default(),
             input: None,
+            prompt: Prompt::Name,
+            status: None,
             selected_item: None,
+            tree,
+            sort: SortOrder::Manual,
         }
     }
 
+    /// Lists `content`, in the order picked.
+    fn show(&mut self, content: LeftContent) {
+        self.content = content;
+        self.content.sort(self.sort);
+    }
+
     fn set_selected(&mut self) {
         self.list_state.select(self.selected_item);
     }
@@ -123,40 +429,384 @@ impl<'a> LeftList {
             self.set_selected();
         }
     }
+    pub fn set_status(&mut self, status: String) {
+        self.status.replace(status);
+    }
+
     pub fn refresh(&mut self) {
         match self.content {
-            LeftContent::Apis(_) => {
-                let apis = api_service::get_all_apis();
-                self.content = LeftContent::Apis(apis);
-            }
+            LeftContent::Apis { .. } => self.show(LeftContent::apis()),
             LeftContent::Resources(_, api_id) => {
-                let resources = resource_service::get_resources_for_api(api_id);
-                self.content = LeftContent::Resources(resources, api_id);
+                let resources = resource_service::get_root_resources(api_id);
+                self.show(LeftContent::Resources(resources, api_id));
+            }
+            LeftContent::Folder { id, .. } => self.show(LeftContent::folder(id)),
+            LeftContent::Tree { .. } => {
+                let selected = self.selected();
+                self.show(LeftContent::tree());
+                self.select(self.content.position(&selected));
+            }
+            LeftContent::Filtered { ref filter, .. } => {
+                if let Ok(content) = LeftContent::filtered(filter) {
+                    self.show(content);
+                }
+            }
+        }
+    }
+
+    /// Shows the tree again, selecting `selected` if it is there.
+    fn show_tree(&mut self, selected: SelectedItem) -> LeftInputResult {
+        self.show(LeftContent::tree());
+        self.select(self.content.position(&selected));
+        self.changed_show()
+    }
+
+    /// Selects an item where it belongs: in the tree, opened down to it, or
+    /// in the list of the level it is on.
+    fn reveal(&mut self, item: SelectedItem) -> LeftInputResult {
+        if self.tree {
+            for (kind, id) in tree_ancestors(item) {
+                expanded_node_service::set_expanded(kind, id, true);
+            }
+            return self.show_tree(item);
+        }
+
+        match item {
+            SelectedItem::Request(request_id) => {
+                match request_service::get_request_by_id(request_id) {
+                    Some(request) => self.open_folder(request.resource_id, item),
+                    None => LeftInputResult::None,
+                }
+            }
+            SelectedItem::Resource(resource_id) => {
+                match resource_service::get_resource_by_id(resource_id) {
+                    Some(Resource {
+                        parent_id: Some(parent_id),
+                        ..
+                    }) => self.open_folder(parent_id, item),
+                    Some(resource) => {
+                        let resources = resource_service::get_root_resources(resource.api_id);
+                        self.show(LeftContent::Resources(resources, resource.api_id));
+                        self.select(self.content.position(&item));
+                        self.changed_show()
+                    }
+                    None => LeftInputResult::None,
+                }
+            }
+            SelectedItem::Api(_) | SelectedItem::None => {
+                self.show(LeftContent::apis());
+                self.select(self.content.position(&item));
+                self.changed_show()
+            }
+        }
+    }
+
+    /// Lists the folder a request is in, or opens it in the tree, with the
+    /// request selected.
+    pub fn show_request(&mut self, request_id: i32) {
+        self.reveal(SelectedItem::Request(request_id));
+    }
+
+    /// Switches between the tree and the list of one level, keeping what
+    /// is selected, or else the listed level, in view.
+    fn switch_view(&mut self) -> LeftInputResult {
+        let selected = match (self.selected(), &self.content) {
+            (SelectedItem::None, LeftContent::Folder { id, .. }) => SelectedItem::Resource(*id),
+            (SelectedItem::None, LeftContent::Resources(_, api_id)) => SelectedItem::Api(*api_id),
+            (selected, _) => selected,
+        };
+        self.tree = !self.tree;
+        self.reveal(selected)
+    }
+
+    /// Switches to the next order, keeping the selection.
+    fn switch_order(&mut self) -> LeftInputResult {
+        let selected = self.selected();
+        self.sort = self.sort.next();
+        self.refresh();
+        self.select(self.content.position(&selected));
+        self.status.replace(format!("Listed {}", self.sort.label()));
+        LeftInputResult::None
+    }
+
+    /// Moves the selected API, folder or request one place up or down.
+    fn move_selected(&mut self, up: bool) -> LeftInputResult {
+        if self.sort != SortOrder::Manual {
+            self.status
+                .replace(String::from("Switch to manual order (o) to move things"));
+            return LeftInputResult::None;
+        }
+
+        let selected = self.selected();
+        let moved = match selected {
+            SelectedItem::Api(api_id) => api_service::move_api(api_id, up),
+            SelectedItem::Resource(resource_id) => {
+                resource_service::get_resource_by_id(resource_id)
+                    .is_some_and(|resource| resource_service::move_resource(&resource, up))
+            }
+            SelectedItem::Request(request_id) => request_service::get_request_by_id(request_id)
+                .is_some_and(|request| request_service::move_request(&request, up)),
+            SelectedItem::None => false,
+        };
+        if moved {
+            self.refresh();
+            self.select(self.content.position(&selected));
+        }
+        LeftInputResult::None
+    }
+
+    /// Lists the requests a filter matches, or leaves the filtered list
+    /// when it is empty.
+    fn apply_filter(&mut self, filter: &str) -> LeftInputResult {
+        if filter.trim().is_empty() {
+            return match self.content {
+                LeftContent::Filtered { .. } => self.reveal(self.selected()),
+                _ => LeftInputResult::None,
+            };
+        }
+
+        match LeftContent::filtered(filter) {
+            Ok(content) => {
+                self.show(content);
+                let count = self.content.length();
+                self.status.replace(format!(
+                    "{} request{}",
+                    count,
+                    if count == 1 { "" } else { "s" }
+                ));
+                self.select(if count > 0 { Some(0) } else { None });
+                self.changed_show()
+            }
+            Err(err) => {
+                self.status.replace(err.to_string());
+                LeftInputResult::None
+            }
+        }
+    }
+
+    /// Stars the selected request, or takes its star off. Starred requests
+    /// are listed above the APIs.
+    fn toggle_favourite(&mut self) -> LeftInputResult {
+        let selected = self.selected();
+        let request = match selected {
+            SelectedItem::Request(request_id) => request_service::get_request_by_id(request_id),
+            _ => None,
+        };
+        if let Some(request) = request {
+            let request = request_service::update_request_favourite(&request, !request.favourite);
+            self.refresh();
+            self.select(self.content.position(&selected));
+            self.status.replace(String::from(if request.favourite {
+                "Starred"
+            } else {
+                "Unstarred"
+            }));
+            return self.changed_show();
+        }
+        LeftInputResult::None
+    }
+
+    /// The selected row of the tree, unless it is a pinned favourite.
+    fn selected_row(&self) -> Option<&TreeRow> {
+        match (&self.content, self.selected_item) {
+            (LeftContent::Tree { favourites, rows }, Some(selected_index)) => {
+                rows.get(selected_index.checked_sub(favourites.len())?)
             }
-            LeftContent::Requests(_, resource_id) => {
-                let requests = request_service::get_requests_for_resource(resource_id).unwrap();
-                self.content = LeftContent::Requests(requests, resource_id);
+            _ => None,
+        }
+    }
+
+    /// Opens or closes the selected API or folder of the tree, remembering
+    /// it for the next start.
+    fn set_expanded(&mut self, expanded: bool) -> LeftInputResult {
+        let selected = self.selected();
+        let (kind, id) = match selected {
+            SelectedItem::Api(api_id) => (TreeNodeKind::Api, api_id),
+            SelectedItem::Resource(resource_id) => (TreeNodeKind::Resource, resource_id),
+            _ => return LeftInputResult::None,
+        };
+        expanded_node_service::set_expanded(kind, id, expanded);
+        self.show_tree(selected)
+    }
+
+    /// Closes the selected row of the tree, or selects the row it is in
+    /// when it is closed already.
+    fn collapse_or_leave(&mut self) -> LeftInputResult {
+        let (depth, expanded) = match self.selected_row() {
+            Some(row) => (row.depth, row.expanded),
+            None => return LeftInputResult::None,
+        };
+        if expanded {
+            return self.set_expanded(false);
+        }
+
+        if let (LeftContent::Tree { favourites, rows }, Some(selected_index)) =
+            (&self.content, self.selected_item)
+        {
+            let parent = rows[..selected_index - favourites.len()]
+                .iter()
+                .rposition(|row| row.depth < depth)
+                .map(|parent| parent + favourites.len());
+            if parent.is_some() {
+                self.select(parent);
+                return self.changed_show();
             }
         }
+        LeftInputResult::None
+    }
+
+    /// The API the selected row of the tree belongs to.
+    fn tree_api_id(&self) -> Option<i32> {
+        match &self.selected_row()?.node {
+            TreeNode::Api(api) => Some(api.id),
+            TreeNode::Resource(resource) => Some(resource.api_id),
+            TreeNode::Request(request) => resource_service::get_resource_by_id(request.resource_id)
+                .map(|resource| resource.api_id),
+        }
+    }
+
+    /// Starts asking for the name of something to add next to the selected
+    /// row of the tree: an API, a folder or a request.
+    fn add_in_tree(&mut self) -> LeftInputResult {
+        self.prompt = match self.selected_row().map(|row| &row.node) {
+            Some(TreeNode::Resource(resource)) => {
+                Prompt::Folder(resource.api_id, resource.parent_id)
+            }
+            Some(TreeNode::Request(request)) => Prompt::Request(request.resource_id),
+            _ => Prompt::Name,
+        };
+        self.input.replace(String::new());
+        LeftInputResult::None
+    }
+
+    /// Lists what is in a folder, selecting `selected` if it is there.
+    fn open_folder(&mut self, resource_id: i32, selected: SelectedItem) -> LeftInputResult {
+        self.show(LeftContent::folder(resource_id));
+        self.select(self.content.position(&selected));
+        self.changed_show()
+    }
+
+    fn selected(&self) -> SelectedItem {
+        match self.selected_item {
+            Some(selected_index) => self.content.item(selected_index),
+            None => SelectedItem::None,
+        }
     }
 
     fn changed_show(&self) -> LeftInputResult {
-        if let Some(selected_index) = self.selected_item {
-            match &self.content {
-                LeftContent::Apis(apis) => LeftInputResult::ShowApi(apis[selected_index].id),
-                LeftContent::Resources(resources, _) => {
-                    LeftInputResult::ShowResource(resources[selected_index].id)
+        match self.selected() {
+            SelectedItem::Api(api_id) => LeftInputResult::ShowApi(api_id),
+            SelectedItem::Resource(resource_id) => LeftInputResult::ShowResource(resource_id),
+            SelectedItem::Request(request_id) => LeftInputResult::ShowRequest(request_id),
+            SelectedItem::None => LeftInputResult::ShowNothing,
+        }
+    }
+
+    /// Imports an OpenAPI or Swagger document, or a Postman collection, into
+    /// the API named by its title, merging with what an earlier import
+    /// created. `.http` files are imported as a resource of the listed API,
+    /// or of an API named after the file when APIs are listed. HAR files
+    /// are handed on so their entries can be chosen first.
+    fn import(&mut self, path: &str) -> LeftInputResult {
+        let path = path.trim();
+        let api_id = match self.content {
+            LeftContent::Resources(_, api_id) => Some(api_id),
+            LeftContent::Tree { .. } => self.tree_api_id(),
+            _ => None,
+        };
+        if har::is_har_file(path) {
+            return LeftInputResult::ImportHar(path.to_string(), api_id);
+        }
+
+        let summary = load_document(path, api_id).and_then(|imported| {
+            import_service::import_api(&imported).map_err(|err| err.to_string())
+        });
+
+        match summary {
+            Ok(summary) => {
+                self.status.replace(summary.to_string());
+                if self.tree {
+                    let resource = resource_service::get_root_resources(summary.api_id)
+                        .into_iter()
+                        .find(|resource| api_id.is_some() && resource.name == file_stem(path));
+                    return self.reveal(match resource {
+                        Some(resource) => SelectedItem::Resource(resource.id),
+                        None => SelectedItem::Api(summary.api_id),
+                    });
+                }
+                if api_id.is_some() {
+                    let resources = resource_service::get_root_resources(summary.api_id);
+                    let index = resources
+                        .iter()
+                        .position(|resource| resource.name == file_stem(path));
+                    self.show(LeftContent::Resources(resources, summary.api_id));
+                    self.select(index);
+                } else {
+                    self.show(LeftContent::apis());
+                    self.select(self.content.position(&SelectedItem::Api(summary.api_id)));
                 }
-                LeftContent::Requests(requests, _) => {
-                    LeftInputResult::ShowRequest(requests[selected_index].id)
+                self.changed_show()
+            }
+            Err(err) => {
+                self.status.replace(err);
+                LeftInputResult::None
+            }
+        }
+    }
+
+    /// Creates a request from a pasted curl command and selects it. Flags
+    /// that had no place in the request are listed in the status.
+    fn create_from_curl(&mut self, resource_id: i32, command: &str) -> LeftInputResult {
+        let command = match curl::parse(command) {
+            Ok(command) => command,
+            Err(err) => {
+                self.status.replace(err.to_string());
+                return LeftInputResult::None;
+            }
+        };
+
+        let request_id = curl::save(resource_id, &command);
+        let mut status = format!("Created {} {}", command.method, command.route);
+        if command.insecure {
+            status.push_str(", certificate checks off for the API");
+        }
+        if !command.ignored.is_empty() {
+            status.push_str(&format!(", ignored {}", command.ignored.join(" ")));
+        }
+        self.status.replace(status);
+
+        self.reveal(SelectedItem::Request(request_id))
+    }
+
+    /// Restores a backup and lists the APIs again. The first conflict, if
+    /// any, is named in the status.
+    fn restore(&mut self, path: &str, mode: RestoreMode) -> LeftInputResult {
+        match backup::restore(path, mode) {
+            Ok(summary) => {
+                let mut status = format!("Restored: {}", summary);
+                if let Some(conflict) = summary.conflicts.first() {
+                    status.push_str(&format!(", kept {} as it is", conflict));
                 }
+                self.status.replace(status);
             }
-        } else {
-            LeftInputResult::ShowNothing
+            Err(err) => {
+                self.status.replace(err.to_string());
+                return LeftInputResult::None;
+            }
+        }
+
+        if self.tree {
+            return self.show_tree(SelectedItem::None);
         }
+        self.show(LeftContent::apis());
+        self.select(None);
+        self.changed_show()
     }
 
     pub fn handle_input(&mut self, key: KeyCode) -> LeftInputResult {
+        self.status.take();
+        let filtered = matches!(self.content, LeftContent::Filtered { .. });
+
         match key {
             KeyCode::Char(character) => {
                 if let Some(input) = &mut self.input {
@@ -173,78 +823,315 @@ impl<'a> LeftList {
                             self.changed_show()
                         }
                         'q' => LeftInputResult::Exit,
+                        't' => self.switch_view(),
+                        'o' => self.switch_order(),
+                        'K' => self.move_selected(true),
+                        'J' => self.move_selected(false),
+                        '*' => self.toggle_favourite(),
+                        '/' => {
+                            self.input.replace(match &self.content {
+                                LeftContent::Filtered { filter, .. } => filter.clone(),
+                                _ => String::new(),
+                            });
+                            self.prompt = Prompt::Filter;
+                            LeftInputResult::None
+                        }
+                        'h' if filtered => self.reveal(self.selected()),
+                        'a' | 'l' if filtered => LeftInputResult::None,
+                        'h' if self.tree => self.collapse_or_leave(),
+                        'l' if self.tree => self.set_expanded(true),
+                        'a' if self.tree => self.add_in_tree(),
                         'a' => {
                             self.input.replace(String::new());
                             LeftInputResult::None
                         }
+                        'f' => {
+                            let (api_id, parent_id) = match (&self.content, self.selected()) {
+                                (&LeftContent::Folder { id, .. }, _)
+                                | (&LeftContent::Tree { .. }, SelectedItem::Resource(id)) => {
+                                    match resource_service::get_resource_by_id(id) {
+                                        Some(folder) => (folder.api_id, Some(folder.id)),
+                                        None => return LeftInputResult::None,
+                                    }
+                                }
+                                (LeftContent::Tree { .. }, SelectedItem::Api(api_id)) => {
+                                    (api_id, None)
+                                }
+                                _ => return LeftInputResult::None,
+                            };
+                            self.input.replace(String::new());
+                            self.prompt = Prompt::Folder(api_id, parent_id);
+                            LeftInputResult::None
+                        }
+                        'i' if !matches!(self.content, LeftContent::Folder { .. }) => {
+                            self.input.replace(String::new());
+                            self.prompt = Prompt::Import;
+                            LeftInputResult::None
+                        }
+                        'c' => {
+                            let resource_id = match (&self.content, self.selected_row()) {
+                                (LeftContent::Folder { id, .. }, _) => *id,
+                                (_, Some(row)) => match &row.node {
+                                    TreeNode::Resource(resource) => resource.id,
+                                    TreeNode::Request(request) => request.resource_id,
+                                    TreeNode::Api(_) => return LeftInputResult::None,
+                                },
+                                _ => return LeftInputResult::None,
+                            };
+                            self.input.replace(String::new());
+                            self.prompt = Prompt::Curl(resource_id);
+                            LeftInputResult::None
+                        }
+                        'x' | 'X' => match self.selected() {
+                            SelectedItem::Api(api_id) => {
+                                let api = match api_service::get_api_by_id(api_id) {
+                                    Some(api) => api,
+                                    None => return LeftInputResult::None,
+                                };
+                                let extension = if character == 'X' {
+                                    POSTMAN_EXTENSION
+                                } else {
+                                    ".yaml"
+                                };
+                                self.input.replace(format!(
+                                    "{}{}",
+                                    file_name(&api.name),
+                                    extension
+                                ));
+                                self.prompt = Prompt::Export(api.id);
+                                LeftInputResult::None
+                            }
+                            SelectedItem::Resource(resource_id) if character == 'x' => {
+                                let resource =
+                                    match resource_service::get_resource_by_id(resource_id) {
+                                        Some(resource) => resource,
+                                        None => return LeftInputResult::None,
+                                    };
+                                self.input
+                                    .replace(format!("{}.http", file_name(&resource.name)));
+                                self.prompt = Prompt::ExportResource(resource.id);
+                                LeftInputResult::None
+                            }
+                            _ => LeftInputResult::None,
+                        },
+                        'D' => {
+                            if let SelectedItem::Api(api_id) = self.selected() {
+                                if let Some(api) = api_service::get_api_by_id(api_id) {
+                                    self.input.replace(format!("{}-docs", file_name(&api.name)));
+                                    self.prompt = Prompt::Docs(api.id);
+                                }
+                            }
+                            LeftInputResult::None
+                        }
+                        'B' if matches!(
+                            self.content,
+                            LeftContent::Apis { .. } | LeftContent::Tree { .. }
+                        ) =>
+                        {
+                            self.input.replace(format!(
+                                "reqstr-backup-{}.json",
+                                Local::now().format("%Y-%m-%d")
+                            ));
+                            self.prompt = Prompt::Backup;
+                            LeftInputResult::None
+                        }
+                        'R' if matches!(
+                            self.content,
+                            LeftContent::Apis { .. } | LeftContent::Tree { .. }
+                        ) =>
+                        {
+                            self.input.replace(String::new());
+                            self.prompt = Prompt::Restore(RestoreMode::Merge);
+                            LeftInputResult::None
+                        }
+                        'e' => match self.selected() {
+                            SelectedItem::Api(api_id) => LeftInputResult::EditApi(api_id),
+                            SelectedItem::Resource(resource_id) => {
+                                LeftInputResult::EditResource(resource_id)
+                            }
+                            _ => LeftInputResult::None,
+                        },
                         _ => LeftInputResult::None,
                     }
                 }
             }
             KeyCode::Enter => {
+                // Commands pasted over several lines end each one with a
+                // backslash, which only continues the command.
+                if let (Prompt::Curl(_), Some(input)) = (self.prompt, &mut self.input) {
+                    if input.ends_with('\\') {
+                        input.pop();
+                        input.push(' ');
+                        return LeftInputResult::None;
+                    }
+                }
+
+                match std::mem::replace(&mut self.prompt, Prompt::Name) {
+                    Prompt::Import => {
+                        let path = self.input.take().unwrap_or_default();
+                        return self.import(&path);
+                    }
+                    Prompt::Export(api_id) => {
+                        let path = self.input.take().unwrap_or_default();
+                        let path = path.trim();
+                        let exported = if path.ends_with(POSTMAN_EXTENSION) {
+                            postman::export_to_file(api_id, path).map_err(|err| err.to_string())
+                        } else if har::is_har_file(path) {
+                            har::export_to_file(api_id, path).map_err(|err| err.to_string())
+                        } else {
+                            openapi::export_to_file(api_id, path).map_err(|err| err.to_string())
+                        };
+                        let status = match exported {
+                            Ok(()) => format!("Exported to {}", path),
+                            Err(err) => err,
+                        };
+                        self.status.replace(status);
+                        return LeftInputResult::None;
+                    }
+                    Prompt::ExportResource(resource_id) => {
+                        let path = self.input.take().unwrap_or_default();
+                        let path = path.trim();
+                        let status = match http_file::export_to_file(resource_id, path) {
+                            Ok(()) => format!("Exported to {}", path),
+                            Err(err) => err.to_string(),
+                        };
+                        self.status.replace(status);
+                        return LeftInputResult::None;
+                    }
+                    Prompt::Docs(api_id) => {
+                        let dir = self.input.take().unwrap_or_default();
+                        let dir = dir.trim();
+                        let status = match docs::export_to_dir(api_id, dir) {
+                            Ok(pages) => format!("Wrote {} pages to {}", pages, dir),
+                            Err(err) => err.to_string(),
+                        };
+                        self.status.replace(status);
+                        return LeftInputResult::None;
+                    }
+                    Prompt::Curl(resource_id) => {
+                        let command = self.input.take().unwrap_or_default();
+                        return self.create_from_curl(resource_id, &command);
+                    }
+                    Prompt::Backup => {
+                        let path = self.input.take().unwrap_or_default();
+                        let path = path.trim();
+                        let status = match backup::export_to_file(path) {
+                            Ok(()) => format!("Backed up to {}", path),
+                            Err(err) => err.to_string(),
+                        };
+                        self.status.replace(status);
+                        return LeftInputResult::None;
+                    }
+                    Prompt::Restore(mode) => {
+                        let path = self.input.take().unwrap_or_default();
+                        return self.restore(path.trim(), mode);
+                    }
+                    Prompt::Folder(api_id, parent_id) => {
+                        let name = self.input.take().unwrap_or_default();
+                        if name.trim().is_empty() {
+                            return LeftInputResult::None;
+                        }
+                        resource_service::create_new_resource(NewResource {
+                            name: name.trim(),
+                            api_id,
+                            parent_id,
+                        });
+                        let folders = match parent_id {
+                            Some(parent_id) => resource_service::get_child_resources(parent_id),
+                            None => resource_service::get_root_resources(api_id),
+                        };
+                        let created = folders.last().map(|folder| folder.id).unwrap_or_default();
+                        return self.reveal(SelectedItem::Resource(created));
+                    }
+                    Prompt::Request(resource_id) => {
+                        let route = self.input.take().unwrap_or_default();
+                        if route.trim().is_empty() {
+                            return LeftInputResult::None;
+                        }
+                        let request = request_service::create_request(NewRequest {
+                            route: route.trim(),
+                            method: HttpMethod::Get,
+                            body: None,
+                            resource_id,
+                            kind: RequestKind::for_route(&route),
+                        });
+                        return self.reveal(SelectedItem::Request(request.id));
+                    }
+                    Prompt::Filter => {
+                        let filter = self.input.take().unwrap_or_default();
+                        return self.apply_filter(&filter);
+                    }
+                    Prompt::Name => (),
+                }
+
                 if let Some(input) = &mut self.input {
                     match &self.content {
-                        LeftContent::Apis(_) => {
+                        LeftContent::Apis { .. } => {
                             api_service::create_new_api(NewApi { name: &input });
-                            let new_api_list = api_service::get_all_apis();
-                            self.content = LeftContent::Apis(new_api_list);
+                            self.show(LeftContent::apis());
                         }
                         LeftContent::Resources(_, api_id) => {
                             resource_service::create_new_resource(NewResource {
                                 name: &input,
                                 api_id: *api_id,
+                                parent_id: None,
                             });
-                            let new_resource_list =
-                                resource_service::get_resources_for_api(*api_id);
-                            self.content = LeftContent::Resources(new_resource_list, *api_id);
+                            let new_resource_list = resource_service::get_root_resources(*api_id);
+                            self.show(LeftContent::Resources(new_resource_list, *api_id));
                         }
-                        LeftContent::Requests(_, resource_id) => {
+                        LeftContent::Folder { id, .. } => {
                             request_service::create_new_request(NewRequest {
                                 route: &input,
                                 method: HttpMethod::Get,
                                 body: None,
-                                resource_id: *resource_id,
+                                resource_id: *id,
+                                kind: RequestKind::for_route(input),
                             });
-                            let new_request_list =
-                                request_service::get_requests_for_resource(*resource_id).unwrap();
-                            self.content = LeftContent::Requests(new_request_list, *resource_id);
+                            self.show(LeftContent::folder(*id));
+                        }
+                        LeftContent::Tree { .. } => {
+                            api_service::create_new_api(NewApi { name: input });
+                            let selected = self.selected();
+                            self.show(LeftContent::tree());
+                            self.select(self.content.position(&selected));
                         }
+                        LeftContent::Filtered { .. } => (),
                     }
                     self.input.take();
                     LeftInputResult::None
-                } else {
-                    if let Some(selected_index) = self.selected_item {
-                        match &self.content {
-                            LeftContent::Requests(requests, _) => {
-                                LeftInputResult::EditRequest(requests[selected_index].id)
-                            }
-                            LeftContent::Apis(apis) => {
-                                let selected_api = &apis[selected_index];
-                                let resources =
-                                    resource_service::get_resources_for_api(selected_api.id);
-                                let new_content =
-                                    LeftContent::Resources(resources, selected_api.id);
-                                self.content = new_content;
-
-                                self.select(None);
-                                self.changed_show()
-                            }
-                            LeftContent::Resources(resources, _) => {
-                                let selected_resource = &resources[selected_index];
-                                let requests = request_service::get_requests_for_resource(
-                                    selected_resource.id,
-                                )
-                                .unwrap();
-                                let new_content =
-                                    LeftContent::Requests(requests, selected_resource.id);
-                                self.content = new_content;
-
-                                self.select(None);
-                                self.changed_show()
+                } else if self.tree && !filtered {
+                    match self.selected_row() {
+                        Some(TreeRow {
+                            node: TreeNode::Request(request),
+                            ..
+                        }) => LeftInputResult::EditRequest(request.id),
+                        Some(row) => {
+                            let expanded = row.expanded;
+                            self.set_expanded(!expanded)
+                        }
+                        None => match self.selected() {
+                            SelectedItem::Request(request_id) => {
+                                LeftInputResult::EditRequest(request_id)
                             }
+                            _ => LeftInputResult::None,
+                        },
+                    }
+                } else {
+                    match self.selected() {
+                        SelectedItem::Request(request_id) => {
+                            LeftInputResult::EditRequest(request_id)
+                        }
+                        SelectedItem::Api(api_id) => {
+                            let resources = resource_service::get_root_resources(api_id);
+                            self.show(LeftContent::Resources(resources, api_id));
+
+                            self.select(None);
+                            self.changed_show()
+                        }
+                        SelectedItem::Resource(resource_id) => {
+                            self.open_folder(resource_id, SelectedItem::None)
                         }
-                    } else {
-                        LeftInputResult::None
+                        SelectedItem::None => LeftInputResult::None,
                     }
                 }
             }
@@ -252,27 +1139,30 @@ impl<'a> LeftList {
                 if let Some(input) = &mut self.input {
                     input.pop();
                     LeftInputResult::None
+                } else if filtered {
+                    self.reveal(self.selected())
+                } else if self.tree {
+                    self.collapse_or_leave()
                 } else {
                     match &self.content {
                         LeftContent::Resources(_, api_id) => {
-                            let apis = api_service::get_all_apis();
-                            let new_selected_index = apis.iter().position(|api| api.id == *api_id);
-                            let new_content = LeftContent::Apis(apis);
-                            self.content = new_content;
+                            let api_id = *api_id;
+                            self.show(LeftContent::apis());
 
-                            self.select(new_selected_index.to_owned());
+                            self.select(self.content.position(&SelectedItem::Api(api_id)));
                             self.changed_show()
                         }
-                        LeftContent::Requests(_, resource_id) => {
-                            let parent_resource =
-                                resource_service::get_resource_by_id(*resource_id).unwrap();
-                            let resources =
-                                resource_service::get_resources_for_api(parent_resource.api_id);
-                            let new_selected_index = resources
-                                .iter()
-                                .position(|resource| resource.id == *resource_id);
-                            self.content =
-                                LeftContent::Resources(resources, parent_resource.api_id);
+                        LeftContent::Folder { id, .. } => {
+                            let id = *id;
+                            let folder = resource_service::get_resource_by_id(id).unwrap();
+                            if let Some(parent_id) = folder.parent_id {
+                                return self.open_folder(parent_id, SelectedItem::Resource(id));
+                            }
+
+                            let resources = resource_service::get_root_resources(folder.api_id);
+                            let new_selected_index =
+                                resources.iter().position(|resource| resource.id == id);
+                            self.show(LeftContent::Resources(resources, folder.api_id));
 
                             self.select(new_selected_index.to_owned());
                             self.changed_show()
@@ -281,32 +1171,63 @@ impl<'a> LeftList {
                     }
                 }
             }
+            // Switches a restore between merging and replacing everything.
+            KeyCode::Tab => {
+                if let Prompt::Restore(mode) = self.prompt {
+                    self.prompt = Prompt::Restore(match mode {
+                        RestoreMode::Merge => RestoreMode::Replace,
+                        RestoreMode::Replace => RestoreMode::Merge,
+                    });
+                }
+                LeftInputResult::None
+            }
+            KeyCode::Left if filtered && self.input.is_none() => self.reveal(self.selected()),
+            KeyCode::Left if self.tree && self.input.is_none() => self.collapse_or_leave(),
+            KeyCode::Right if self.tree && !filtered && self.input.is_none() => {
+                self.set_expanded(true)
+            }
+            KeyCode::Esc => {
+                self.input.take();
+                self.prompt = Prompt::Name;
+                LeftInputResult::None
+            }
             _ => LeftInputResult::None,
         }
     }
 
     pub fn render(&'a self) -> List<'a> {
-        let list_title = match self.content {
-            LeftContent::Apis(_) => "APIs",
+        let list_title = match &self.content {
+            LeftContent::Apis { .. } => "APIs",
             LeftContent::Resources(_, _) => "Resources",
-            LeftContent::Requests(_, _) => "Requests",
+            LeftContent::Folder { path, .. } => path,
+            LeftContent::Tree { .. } => "Collections",
+            LeftContent::Filtered { filter, .. } => filter,
         };
 
         let left_block = Block::default()
             .borders(Borders::ALL)
             .style(Style::default().fg(Color::White))
-            .title(list_title)
+            .title(match &self.status {
+                Some(status) => format!("{} - {}", list_title, status),
+                None if self.sort != SortOrder::Manual => {
+                    format!("{} ({})", list_title, self.sort.label())
+                }
+                None => list_title.to_string(),
+            })
             .border_type(BorderType::Plain);
 
         let mut items: Vec<_> = match &self.content {
-            LeftContent::Apis(apis) => {
+            LeftContent::Apis { favourites, apis } => {
+                let favourites = favourites
+                    .iter()
+                    .map(|request| ListItem::new(Spans::from(request_spans(request))));
                 let list = apis.iter().map(|api| {
                     ListItem::new(Spans::from(vec![Span::styled(
                         api.name.clone(),
                         Style::default(),
                     )]))
                 });
-                list.collect()
+                favourites.chain(list).collect()
             }
             LeftContent::Resources(resources, _) => {
                 let list = resources.iter().map(|resource| {
@@ -317,25 +1238,87 @@ impl<'a> LeftList {
                 });
                 list.collect()
             }
-            LeftContent::Requests(requests, _) => {
-                let list = requests.iter().map(|request| {
-                    ListItem::new(Spans::from(vec![
-                        Span::styled(
-                            format!("{}    ", request.method),
-                            Style::default()
-                                .fg(Color::Green)
-                                .add_modifier(Modifier::BOLD),
-                        ),
-                        Span::styled(&request.route, Style::default()),
-                    ]))
+            LeftContent::Folder {
+                folders, requests, ..
+            } => {
+                let folders = folders.iter().map(|folder| {
+                    ListItem::new(Spans::from(vec![Span::styled(
+                        format!("{}/", folder.name),
+                        Style::default()
+                            .fg(Color::Blue)
+                            .add_modifier(Modifier::BOLD),
+                    )]))
                 });
-                list.collect()
+                let list = requests
+                    .iter()
+                    .map(|request| ListItem::new(Spans::from(request_spans(request))));
+                folders.chain(list).collect()
             }
+            LeftContent::Tree { favourites, rows } => favourites
+                .iter()
+                .map(|request| ListItem::new(Spans::from(request_spans(request))))
+                .chain(rows.iter().map(|row| {
+                    let indent = Span::raw("  ".repeat(row.depth));
+                    let marker = Span::raw(if row.expanded { "▾ " } else { "▸ " });
+                    let spans = match &row.node {
+                        TreeNode::Api(api) => vec![
+                            indent,
+                            marker,
+                            Span::styled(&api.name, Style::default().add_modifier(Modifier::BOLD)),
+                        ],
+                        TreeNode::Resource(resource) => vec![
+                            indent,
+                            marker,
+                            Span::styled(
+                                format!("{}/", resource.name),
+                                Style::default()
+                                    .fg(Color::Blue)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                        ],
+                        TreeNode::Request(request) => {
+                            let mut spans = vec![indent];
+                            spans.extend(request_spans(request));
+                            spans
+                        }
+                    };
+                    ListItem::new(Spans::from(spans))
+                }))
+                .collect(),
+            LeftContent::Filtered {
+                requests, paths, ..
+            } => requests
+                .iter()
+                .map(|request| {
+                    let mut spans = request_spans(request);
+                    if let Some(path) = paths.get(&request.resource_id) {
+                        spans.push(Span::styled(
+                            format!("  {}", path),
+                            Style::default().fg(Color::DarkGray),
+                        ));
+                    }
+                    ListItem::new(Spans::from(spans))
+                })
+                .collect(),
         };
 
         if let Some(input) = &self.input {
+            let prompt = match self.prompt {
+                Prompt::Name if self.tree => "New API: ",
+                Prompt::Name => "",
+                Prompt::Import => "Import file: ",
+                Prompt::Export(_) | Prompt::ExportResource(_) => "Export to: ",
+                Prompt::Docs(_) => "Docs to: ",
+                Prompt::Curl(_) => "curl: ",
+                Prompt::Folder(_, _) => "New folder: ",
+                Prompt::Request(_) => "New request: ",
+                Prompt::Backup => "Back up to: ",
+                Prompt::Restore(RestoreMode::Merge) => "Restore, merging (Tab): ",
+                Prompt::Restore(RestoreMode::Replace) => "Restore, replacing all (Tab): ",
+                Prompt::Filter => "Filter: ",
+            };
             items.push(
-                ListItem::new(Spans::from(vec![Span::raw(input)])).style(
+                ListItem::new(Spans::from(vec![Span::raw(prompt), Span::raw(input)])).style(
                     Style::default()
                         .fg(Color::Black)
                         .bg(Color::Cyan)
@@ -354,3 +1337,170 @@ impl<'a> LeftList {
         list
     }
 }
+
+/// Sorts the rows of a tree among those next to them, keeping what is in
+/// an API or folder under it.
+fn sort_rows(rows: Vec<TreeRow>, order: SortOrder) -> Vec<TreeRow> {
+    let depth = match rows.first() {
+        Some(row) => row.depth,
+        None => return rows,
+    };
+
+    let mut groups: Vec<(TreeRow, Vec<TreeRow>)> = vec![];
+    for row in rows {
+        match groups.last_mut() {
+            Some((_, inside)) if row.depth > depth => inside.push(row),
+            _ => groups.push((row, vec![])),
+        }
+    }
+    groups.sort_by(|(a, _), (b, _)| order.compare_nodes(&a.node, &b.node));
+
+    let mut sorted = vec![];
+    for (row, inside) in groups {
+        sorted.push(row);
+        sorted.extend(sort_rows(inside, order));
+    }
+    sorted
+}
+
+fn is_expanded(expanded: &[ExpandedNode], kind: TreeNodeKind, id: i32) -> bool {
+    expanded
+        .iter()
+        .any(|node| node.kind == kind && node.node_id == id)
+}
+
+/// Adds folders to the tree, and what is in those left open.
+fn add_folders(
+    rows: &mut Vec<TreeRow>,
+    expanded: &[ExpandedNode],
+    folders: Vec<Resource>,
+    depth: usize,
+) {
+    for folder in folders {
+        let folder_id = folder.id;
+        let open = is_expanded(expanded, TreeNodeKind::Resource, folder_id);
+        rows.push(TreeRow {
+            node: TreeNode::Resource(folder),
+            depth,
+            expanded: open,
+        });
+        if !open {
+            continue;
+        }
+
+        add_folders(
+            rows,
+            expanded,
+            resource_service::get_child_resources(folder_id),
+            depth + 1,
+        );
+        let requests = request_service::get_requests_for_resource(folder_id).unwrap_or_default();
+        rows.extend(requests.into_iter().map(|request| TreeRow {
+            node: TreeNode::Request(request),
+            depth: depth + 1,
+            expanded: false,
+        }));
+    }
+}
+
+/// The API and folders to open in the tree for an item to be seen.
+fn tree_ancestors(item: SelectedItem) -> Vec<(TreeNodeKind, i32)> {
+    let (path, inside) = match item {
+        SelectedItem::Request(request_id) => {
+            let path = request_service::get_request_by_id(request_id)
+                .map(|request| resource_service::get_resource_path(request.resource_id))
+                .unwrap_or_default();
+            let inside = path.len();
+            (path, inside)
+        }
+        SelectedItem::Resource(resource_id) => {
+            let path = resource_service::get_resource_path(resource_id);
+            let inside = path.len().saturating_sub(1);
+            (path, inside)
+        }
+        SelectedItem::Api(_) | SelectedItem::None => return vec![],
+    };
+
+    let mut ancestors: Vec<_> = path
+        .first()
+        .map(|resource| (TreeNodeKind::Api, resource.api_id))
+        .into_iter()
+        .collect();
+    ancestors.extend(
+        path[..inside]
+            .iter()
+            .map(|resource| (TreeNodeKind::Resource, resource.id)),
+    );
+    ancestors
+}
+
+/// A request by its name, with the route after it, or by its route. A
+/// star marks the favourites.
+fn request_spans(request: &Request) -> Vec<Span<'_>> {
+    let mut spans = vec![method_badge(request)];
+    if request.favourite {
+        spans.push(Span::styled("★ ", Style::default().fg(Color::Yellow)));
+    }
+    match &request.name {
+        Some(name) if !name.is_empty() => spans.extend([
+            Span::raw(name.as_str()),
+            Span::styled(
+                format!("  {}", request.route),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        _ => spans.push(Span::styled(&request.route, Style::default())),
+    }
+    spans
+}
+
+/// The method of a request, or its kind when that says more.
+pub(super) fn method_badge(request: &Request) -> Span<'static> {
+    Span::styled(
+        match request.kind {
+            RequestKind::Http => format!("{}    ", request.method),
+            RequestKind::Graphql => String::from("GQL    "),
+            RequestKind::Websocket => String::from("WS    "),
+            RequestKind::Grpc => String::from("GRPC   "),
+        },
+        Style::default()
+            .fg(Color::Green)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Reads a document to import. `.http` files are told apart by their
+/// name, Postman collections from OpenAPI and Swagger documents by their
+/// content.
+fn load_document(path: &str, api_id: Option<i32>) -> Result<ImportedApi, String> {
+    let source =
+        fs::read_to_string(path).map_err(|err| format!("Could not read {}: {}", path, err))?;
+
+    if http_file::is_http_file(path) {
+        let resource = file_stem(path);
+        let api = match api_id.and_then(api_service::get_api_by_id) {
+            Some(api) => api.name,
+            None => resource.clone(),
+        };
+        http_file::parse(&source, &api, &resource).map_err(|err| err.to_string())
+    } else if postman::is_collection(&source) {
+        postman::parse(&source).map_err(|err| err.to_string())
+    } else {
+        openapi::parse(&source).map_err(|err| err.to_string())
+    }
+}
+
+fn file_stem(path: &str) -> String {
+    Path::new(path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// A lowercase file name for exports of something called `name`.
+fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .to_lowercase()
+}
@@ -1,6 +1,8 @@
+use super::finder::{Finder, FinderResult};
 use super::left_widget::{LeftInputResult, LeftList};
 use super::right_widget::{RightInputResult, RightType, RightWidget};
-use crossterm::event::{self, Event as CEvent, KeyCode};
+use super::search::Search;
+use crossterm::event::{self, Event as CEvent, KeyCode, KeyEvent, KeyModifiers};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use std::io::{self, Stdout};
 use std::sync::mpsc;
@@ -20,12 +22,21 @@ enum Focus {
     Right,
 }
 
+/// Shown over everything else, and given every key while open.
+enum Popup {
+    /// Finding requests by their name, route and where they are, on Ctrl-P.
+    Finder(Finder),
+    /// Searching what requests send and got back, on Ctrl-F.
+    Search(Search),
+}
+
 pub struct MainWindow {
     input_mode: bool,
     terminal: Terminal<CrosstermBackend<Stdout>>,
     left: LeftList,
     right: RightWidget,
     focus: Focus,
+    popup: Option<Popup>,
 }
 
 impl<'a> MainWindow {
@@ -44,6 +55,7 @@ impl<'a> MainWindow {
             left: LeftList::new(),
             right,
             focus: Focus::Left,
+            popup: None,
         }
     }
 
@@ -75,13 +87,19 @@ impl<'a> MainWindow {
         loop {
             self.draw();
 
-            match rx.recv().unwrap() {
-                Event::Input(event) => {
-                    if self.handle_input(event.code) {
-                        break;
-                    }
+            let exit = match rx.recv().unwrap() {
+                Event::Input(event) => self.handle_key(event),
+                Event::Tick => {
+                    self.right.tick();
+                    false
                 }
-                Event::Tick => {}
+            };
+
+            if let Err(err) = crate::flush_storage() {
+                self.left.set_status(err.to_string());
+            }
+            if exit {
+                break;
             }
         }
     }
@@ -105,10 +123,53 @@ impl<'a> MainWindow {
                 let left = self.left.render();
                 self.right.draw(rect, main_chunks[1]);
                 rect.render_stateful_widget(left, main_chunks[0], &mut left_list_state.clone());
+
+                match &self.popup {
+                    Some(Popup::Finder(finder)) => finder.draw(rect, size),
+                    Some(Popup::Search(search)) => search.draw(rect, size),
+                    None => (),
+                }
             })
             .unwrap();
     }
 
+    /// Gives keys to the popup while one is open, and else to the focused
+    /// side.
+    fn handle_key(&'a mut self, key: KeyEvent) -> bool {
+        if let Some(popup) = &mut self.popup {
+            let result = match popup {
+                Popup::Finder(finder) => finder.handle_input(key),
+                Popup::Search(search) => search.handle_input(key),
+            };
+            match result {
+                FinderResult::Open(request_id) => {
+                    self.popup = None;
+                    self.left.show_request(request_id);
+                    self.right.set_type(RightType::Request(request_id));
+                    self.focus = Focus::Right;
+                }
+                FinderResult::Close => self.popup = None,
+                FinderResult::None => (),
+            }
+            return false;
+        }
+
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            match key.code {
+                KeyCode::Char('p') => {
+                    self.popup = Some(Popup::Finder(Finder::new()));
+                    return false;
+                }
+                KeyCode::Char('f') => {
+                    self.popup = Some(Popup::Search(Search::new()));
+                    return false;
+                }
+                _ => (),
+            }
+        }
+        self.handle_input(key.code)
+    }
+
     pub fn handle_input(&'a mut self, key_code: KeyCode) -> bool {
         match self.focus {
             Focus::Left => {
@@ -121,11 +182,26 @@ impl<'a> MainWindow {
                         self.set_right(result);
                         false
                     }
+                    LeftInputResult::EditApi(api_id) => {
+                        self.right.set_type(RightType::Api(api_id));
+                        self.focus = Focus::Right;
+                        false
+                    }
+                    LeftInputResult::EditResource(resource_id) => {
+                        self.right.set_type(RightType::Resource(resource_id));
+                        self.focus = Focus::Right;
+                        false
+                    }
                     LeftInputResult::EditRequest(request_id) => {
                         self.right.set_type(RightType::Request(request_id));
                         self.focus = Focus::Right;
                         false
                     }
+                    LeftInputResult::ImportHar(path, api_id) => {
+                        self.right.set_type(RightType::HarImport(path, api_id));
+                        self.focus = Focus::Right;
+                        false
+                    }
                     LeftInputResult::Exit => {
                         disable_raw_mode().unwrap();
                         self.terminal.show_cursor().unwrap();
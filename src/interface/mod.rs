@@ -1,3 +1,9 @@
+mod finder;
+mod graphql_pane;
+mod grpc_widget;
 mod left_widget;
 pub mod main_window;
 mod right_widget;
+mod search;
+mod stream_view;
+mod websocket_widget;
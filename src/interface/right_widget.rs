@@ -1,5 +1,11 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
 use crossterm::event::KeyCode;
-use std::io::Stdout;
+use std::{
+    fs,
+    io::{self, Stdout, Write},
+    path::Path,
+    time::Duration,
+};
 use tui::{backend::CrosstermBackend, Frame};
 
 use tui::{
@@ -7,24 +13,44 @@ use tui::{
     style::{Color, Modifier, Style},
     text::{Span, Spans},
     widgets::{
-        Block, BorderType, Borders, Cell, List, ListItem, Paragraph, Row, Table, TableState, Tabs,
-        Widget,
+        Block, BorderType, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table,
+        TableState, Tabs, Widget, Wrap,
     },
 };
 
+use chrono::Local;
+
+use super::{
+    graphql_pane::GraphqlPane, grpc_widget::GrpcWidget, stream_view,
+    websocket_widget::WebSocketWidget,
+};
 use crate::{
-    http,
-    models::{Api, Header, NewHeader, Request, Resource},
-    services::{api_service, header_service, request_service, resource_service},
-    types::HttpMethod,
+    codegen::{self, Language},
+    graphql,
+    har::{self, HarEntry},
+    http::{self, PeerCertificate, SentRequest, HOSTNAME},
+    markdown,
+    models::{
+        Api, ApiVariable, FormPart, Header, NewApiVariable, NewFormPart, NewHeader,
+        NewRecordedResponse, NewTlsConfig, Request, Resource, Tag, TlsConfig,
+    },
+    response::{self, HttpResponse},
+    services::{
+        api_service, form_part_service, header_service, import_service, message_template_service,
+        recorded_response_service, request_service, resource_service, tag_service, tls_service,
+        variable_service,
+    },
+    stream::{ResponseStream, StreamFormat, StreamState},
+    types::{FormPartKind, HttpMethod, RequestKind},
 };
 
-use reqwest::blocking::Response;
-
 pub enum RightType {
     Api(i32),
     Resource(i32),
     Request(i32),
+    /// Choosing entries of the HAR file at a path to import, into the given
+    /// API or a new one.
+    HarImport(String, Option<i32>),
     None, //Request(i32)
 }
 
@@ -38,31 +64,377 @@ pub enum RightInputResult {
 enum Content {
     Api(ApiWidget),
     Resource(ResourceWidget),
-    Request(RequestWidget),
+    Request(Box<RequestWidget>),
+    WebSocket(Box<WebSocketWidget>),
+    Grpc(Box<GrpcWidget>),
+    HarImport(Box<HarImportWidget>),
 }
 
-trait Drawable {
+pub(super) trait Drawable {
     fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect);
 }
 
 struct ApiWidget {
     api: Api,
     resources: Vec<Resource>,
+    variables: Vec<ApiVariable>,
+    variable_table_state: TableState,
+    tls: Option<TlsConfig>,
+    tls_table_state: TableState,
+    section: ApiSection,
+    input: Option<String>,
+    /// Whether `input` holds a new `key=value` variable rather than the
+    /// value of the selected one.
+    adding_variable: bool,
+    description_input: Option<String>,
+}
+
+#[derive(PartialEq)]
+enum ApiSection {
+    Variables,
+    Tls,
+}
+
+const TLS_ROWS: usize = 6;
+
+impl ApiWidget {
+    fn new(api: Api, resources: Vec<Resource>, tls: Option<TlsConfig>) -> Self {
+        let variables = variable_service::get_variables_for_api(api.id);
+
+        Self {
+            api,
+            resources,
+            variables,
+            variable_table_state: TableState::default(),
+            tls,
+            tls_table_state: TableState::default(),
+            section: ApiSection::Variables,
+            input: None,
+            adding_variable: false,
+            description_input: None,
+        }
+    }
+
+    fn section_state(&mut self) -> (&mut TableState, usize) {
+        match self.section {
+            ApiSection::Variables => (&mut self.variable_table_state, self.variables.len()),
+            ApiSection::Tls => (&mut self.tls_table_state, TLS_ROWS),
+        }
+    }
+
+    fn move_down(&mut self) {
+        let (state, rows) = self.section_state();
+        match state.selected() {
+            Some(current) if current + 1 < rows => state.select(Some(current + 1)),
+            _ if rows > 0 => state.select(Some(0)),
+            _ => state.select(None),
+        }
+    }
+
+    fn move_up(&mut self) {
+        let (state, rows) = self.section_state();
+        match state.selected() {
+            Some(current) if current > 0 => state.select(Some(current - 1)),
+            _ if rows > 0 => state.select(Some(rows - 1)),
+            _ => state.select(None),
+        }
+    }
+
+    fn switch_section(&mut self) {
+        self.section = match self.section {
+            ApiSection::Variables => ApiSection::Tls,
+            ApiSection::Tls => ApiSection::Variables,
+        };
+    }
+
+    /// Saves `key=value` as a new variable, or the value of the selected
+    /// one.
+    fn save_variable(&mut self, input: &str) {
+        let (key, value) = if self.adding_variable {
+            let (key, value) = input.split_once('=').unwrap_or((input, ""));
+            (key.trim().to_string(), value.trim())
+        } else {
+            match self.variable_table_state.selected() {
+                Some(row) => (self.variables[row].key.clone(), input),
+                None => return,
+            }
+        };
+        if key.is_empty() {
+            return;
+        }
+
+        variable_service::save_variable(&NewApiVariable {
+            api_id: self.api.id,
+            key: &key,
+            value,
+        });
+        self.variables = variable_service::get_variables_for_api(self.api.id);
+        let row = self
+            .variables
+            .iter()
+            .position(|variable| variable.key == key);
+        self.variable_table_state.select(row);
+    }
+
+    fn delete_variable(&mut self) {
+        if let Some(row) = self.variable_table_state.selected() {
+            variable_service::delete_variable(&self.variables[row]);
+            self.variables.remove(row);
+            if self.variables.is_empty() {
+                self.variable_table_state.select(None);
+            } else {
+                self.variable_table_state
+                    .select(Some(row.min(self.variables.len() - 1)));
+            }
+        }
+    }
+
+    fn tls_value(&self, row: usize) -> String {
+        let tls = match &self.tls {
+            Some(tls) => tls,
+            None if row == 5 => return String::from("no"),
+            None => return String::new(),
+        };
+
+        match row {
+            0 => tls.ca_bundles.clone(),
+            1 => tls.client_certificate.clone().unwrap_or_default(),
+            2 => tls.client_key.clone().unwrap_or_default(),
+            3 => tls.client_password_env.clone().unwrap_or_default(),
+            4 => tls
+                .min_tls_version
+                .map(|version| version.to_string())
+                .unwrap_or_default(),
+            _ => String::from(if tls.accept_invalid_certs {
+                "yes"
+            } else {
+                "no"
+            }),
+        }
+    }
+
+    fn save_tls_row(&mut self, row: usize, value: &str) {
+        let mut changes = match &self.tls {
+            Some(tls) => tls.changes(),
+            None => NewTlsConfig::for_api(self.api.id),
+        };
+
+        match row {
+            0 => changes.ca_bundles = value.trim(),
+            1 => changes.client_certificate = optional(value),
+            2 => changes.client_key = optional(value),
+            3 => changes.client_password_env = optional(value),
+            4 => match optional(value).map(str::parse) {
+                Some(Ok(version)) => changes.min_tls_version = Some(version),
+                Some(Err(_)) => return,
+                None => changes.min_tls_version = None,
+            },
+            _ => changes.accept_invalid_certs = !changes.accept_invalid_certs,
+        }
+
+        let saved = tls_service::save_tls_config(&changes);
+        self.tls = Some(saved);
+    }
+
+    fn handle_input(&mut self, key: KeyCode) -> RightInputResult {
+        if self.description_input.is_some() {
+            if let Some(description) = handle_description_input(&mut self.description_input, key) {
+                self.api = api_service::update_api_description(&self.api, optional(&description));
+            }
+            return RightInputResult::None;
+        }
+
+        match key {
+            KeyCode::Char(character) => {
+                if let Some(input) = &mut self.input {
+                    input.push(character);
+                    RightInputResult::None
+                } else {
+                    match character {
+                        'q' => RightInputResult::Exit,
+                        'e' => {
+                            self.description_input
+                                .replace(self.api.description.clone().unwrap_or_default());
+                            RightInputResult::None
+                        }
+                        'j' => {
+                            self.move_down();
+                            RightInputResult::None
+                        }
+                        'k' => {
+                            self.move_up();
+                            RightInputResult::None
+                        }
+                        'a' if self.section == ApiSection::Variables => {
+                            self.adding_variable = true;
+                            self.input.replace(String::new());
+                            RightInputResult::None
+                        }
+                        'd' if self.section == ApiSection::Variables => {
+                            self.delete_variable();
+                            RightInputResult::None
+                        }
+                        _ => RightInputResult::None,
+                    }
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(input) = &mut self.input {
+                    input.pop();
+                    RightInputResult::None
+                } else {
+                    RightInputResult::LoseFocus
+                }
+            }
+            KeyCode::Tab if self.input.is_none() => {
+                self.switch_section();
+                RightInputResult::None
+            }
+            KeyCode::Enter => {
+                match (self.input.take(), &self.section) {
+                    (Some(input), ApiSection::Variables) => self.save_variable(&input),
+                    (Some(input), ApiSection::Tls) => {
+                        if let Some(row) = self.tls_table_state.selected() {
+                            self.save_tls_row(row, &input);
+                        }
+                    }
+                    (None, ApiSection::Variables) => {
+                        if let Some(row) = self.variable_table_state.selected() {
+                            self.input.replace(self.variables[row].value.clone());
+                        }
+                    }
+                    (None, ApiSection::Tls) => match self.tls_table_state.selected() {
+                        Some(5) => self.save_tls_row(5, ""),
+                        Some(row) => {
+                            self.input.replace(self.tls_value(row));
+                        }
+                        None => (),
+                    },
+                }
+                self.adding_variable = false;
+                RightInputResult::None
+            }
+            KeyCode::Esc => {
+                self.input.take();
+                self.adding_variable = false;
+                RightInputResult::None
+            }
+            _ => RightInputResult::None,
+        }
+    }
+
+    fn highlight_style(&self) -> Style {
+        if self.input.is_some() {
+            Style::default().bg(Color::Cyan).fg(Color::Black)
+        } else {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        }
+    }
+
+    fn variable_details(&self) -> Table<'_> {
+        let block = Block::default()
+            .title("Variables")
+            .borders(Borders::ALL)
+            .border_style(if self.section == ApiSection::Variables {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            });
+
+        let selected = self.variable_table_state.selected();
+        let mut rows: Vec<_> = self
+            .variables
+            .iter()
+            .enumerate()
+            .map(|(row, variable)| {
+                let value = match &self.input {
+                    Some(input) if !self.adding_variable && selected == Some(row) => input.clone(),
+                    _ => variable.value.clone(),
+                };
+                Row::new([Cell::from(variable.key.clone()), Cell::from(value)])
+            })
+            .collect();
+
+        if let (true, Some(input)) = (self.adding_variable, &self.input) {
+            rows.push(
+                Row::new([Cell::from("New (key=value)"), Cell::from(input.clone())])
+                    .style(Style::default().fg(Color::Cyan)),
+            );
+        }
+
+        Table::new(rows)
+            .block(block)
+            .highlight_style(self.highlight_style())
+            .widths(&[Constraint::Percentage(30), Constraint::Percentage(70)])
+    }
+
+    fn tls_details(&self) -> Table<'_> {
+        let block = Block::default()
+            .title("TLS Settings")
+            .borders(Borders::ALL)
+            .border_style(if self.section == ApiSection::Tls {
+                Style::default().fg(Color::Yellow)
+            } else {
+                Style::default()
+            });
+
+        let labels = [
+            "CA bundles",
+            "Client certificate",
+            "Client key",
+            "Password env variable",
+            "Minimum TLS version",
+            "Accept invalid certs",
+        ];
+
+        let rows: Vec<_> = labels
+            .iter()
+            .enumerate()
+            .map(|(row, label)| {
+                let value = match &self.input {
+                    Some(input) if self.tls_table_state.selected() == Some(row) => input.clone(),
+                    _ => self.tls_value(row),
+                };
+                Row::new([Cell::from(*label), Cell::from(value)]).height(1)
+            })
+            .collect();
+
+        Table::new(rows)
+            .block(block)
+            .highlight_style(self.highlight_style())
+            .widths(&[Constraint::Percentage(30), Constraint::Percentage(70)])
+    }
 }
 
 impl Drawable for ApiWidget {
     fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
-        let right_chunk = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(100)])
-            .split(rect)[0];
+        let rect = draw_insecure_banner(frame, rect, self.tls.as_ref());
+
+        let variable_rows = self.variables.len() + usize::from(self.adding_variable);
+        let description = description_pane(
+            self.api.description.as_deref(),
+            self.description_input.as_deref(),
+        );
+        let right_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(description.as_ref().map_or(0, |(height, _)| *height)),
+                Constraint::Min(0),
+                Constraint::Length(variable_rows.clamp(1, 10) as u16 + 2),
+                Constraint::Length(TLS_ROWS as u16 + 2),
+            ])
+            .split(rect);
+
+        if let Some((_, description)) = description {
+            frame.render_widget(description, right_chunks[0]);
+        }
 
         let list: Vec<_> = self
             .resources
             .iter()
             .map(|resource| {
                 ListItem::new(Spans::from(vec![Span::styled(
-                    resource.name.clone(),
+                    resource_service::path_name(&self.resources, resource),
                     Style::default(),
                 )]))
             })
@@ -76,31 +448,293 @@ impl Drawable for ApiWidget {
 
         let widget = List::new(list).block(right_block);
 
-        frame.render_widget(widget, right_chunk);
+        // Only the focused section shows its selection.
+        let (mut variable_state, mut tls_state) = match self.section {
+            ApiSection::Variables => (self.variable_table_state.clone(), TableState::default()),
+            ApiSection::Tls => (TableState::default(), self.tls_table_state.clone()),
+        };
+
+        frame.render_widget(widget, right_chunks[1]);
+        frame.render_stateful_widget(
+            self.variable_details(),
+            right_chunks[2],
+            &mut variable_state,
+        );
+        frame.render_stateful_widget(self.tls_details(), right_chunks[3], &mut tls_state);
+    }
+}
+
+/// A `width` x `height` area in the middle of `area`, clamped to fit inside it.
+pub(super) fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Puts `text` on the clipboard with an OSC 52 escape sequence, which the
+/// terminal handles even when the app runs on another machine over SSH.
+/// Inside tmux this needs `set-clipboard on`.
+fn copy_to_clipboard(text: &str) -> io::Result<()> {
+    let mut stdout = io::stdout();
+    write!(stdout, "\x1b]52;c;{}\x07", STANDARD.encode(text))?;
+    stdout.flush()
+}
+
+/// Completes the last component of `input` against the file system, as far
+/// as all matching entries agree.
+pub(super) fn complete_path(input: &str) -> String {
+    let (directory, prefix) = match input.rfind('/') {
+        Some(index) => input.split_at(index + 1),
+        None => ("", input),
+    };
+
+    let entries = match fs::read_dir(if directory.is_empty() { "." } else { directory }) {
+        Ok(entries) => entries,
+        Err(_) => return input.to_string(),
+    };
+
+    let matches: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let mut name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) {
+                return None;
+            }
+            if entry.path().is_dir() {
+                name.push('/');
+            }
+            Some(name)
+        })
+        .collect();
+
+    let completion = match matches.split_first() {
+        Some((first, rest)) => rest.iter().fold(first.clone(), |common, name| {
+            common
+                .chars()
+                .zip(name.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect()
+        }),
+        None => return input.to_string(),
+    };
+
+    format!("{}{}", directory, completion)
+}
+
+/// The names of tags as they are typed, separated by commas.
+/// Stores an exchange in the history of a request. Bodies come as text, or
+/// as base64 when the second value is set.
+fn record_exchange(
+    request_id: i32,
+    status: &str,
+    headers: &[(String, String)],
+    (body, body_base64): (&str, bool),
+    sent: &SentRequest,
+    elapsed: Duration,
+) {
+    let status = match status
+        .split_whitespace()
+        .next()
+        .and_then(|code| code.parse().ok())
+    {
+        Some(status) => status,
+        None => return,
+    };
+    let content_type = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+        .map(|(_, value)| value.as_str());
+    let request_body = sent
+        .body
+        .as_ref()
+        .map(|body| String::from_utf8_lossy(body).into_owned());
+
+    recorded_response_service::record_response(&NewRecordedResponse {
+        request_id,
+        status,
+        content_type,
+        body,
+        recorded_at: &Local::now().to_rfc3339(),
+        headers: &serde_json::to_string(headers).unwrap_or_default(),
+        body_base64,
+        duration_ms: i32::try_from(elapsed.as_millis()).unwrap_or(i32::MAX),
+        method: &sent.method,
+        url: &sent.url,
+        request_headers: &serde_json::to_string(&sent.headers).unwrap_or_default(),
+        request_body: request_body.as_deref(),
+    });
+}
+
+fn tag_names(tags: &[Tag]) -> String {
+    tags.iter()
+        .map(|tag| tag.name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(super) fn optional(value: &str) -> Option<&str> {
+    let value = value.trim();
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Edits a description, where Enter starts a new line. Returns what was
+/// typed once Esc ends the editing.
+fn handle_description_input(input: &mut Option<String>, key: KeyCode) -> Option<String> {
+    match key {
+        KeyCode::Esc => return input.take(),
+        KeyCode::Char(character) => input.iter_mut().for_each(|input| input.push(character)),
+        KeyCode::Enter => input.iter_mut().for_each(|input| input.push('\n')),
+        KeyCode::Backspace => {
+            input.iter_mut().for_each(|input| {
+                input.pop();
+            });
+        }
+        _ => (),
+    }
+    None
+}
+
+/// The Markdown description of an API, folder or request, or the text
+/// typed while it is edited, with the height it needs. Nothing is shown
+/// for an empty description.
+fn description_pane(
+    description: Option<&str>,
+    input: Option<&str>,
+) -> Option<(u16, Paragraph<'static>)> {
+    let (title, lines, style) = match (input, description) {
+        (Some(input), _) => (
+            "Description (Esc to save)",
+            input
+                .split('\n')
+                .map(|line| Spans::from(line.to_string()))
+                .collect(),
+            Style::default().fg(Color::Cyan),
+        ),
+        (None, Some(description)) if !description.trim().is_empty() => (
+            "Description",
+            markdown::to_spans(description),
+            Style::default(),
+        ),
+        _ => return None,
+    };
+
+    let height = (lines.len() as u16).clamp(1, 12) + 2;
+    let paragraph = Paragraph::new(lines)
+        .style(style)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .wrap(Wrap { trim: false });
+    Some((height, paragraph))
+}
+
+/// Draws a warning line on top of `rect` when certificate verification is
+/// disabled for the API, and returns the area left for the widget itself.
+pub(super) fn draw_insecure_banner(
+    frame: &mut Frame<CrosstermBackend<Stdout>>,
+    rect: Rect,
+    tls: Option<&TlsConfig>,
+) -> Rect {
+    if !tls.is_some_and(|tls| tls.accept_invalid_certs) {
+        return rect;
     }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(0)])
+        .split(rect);
+
+    let banner = Paragraph::new(Span::styled(
+        " WARNING: TLS certificate verification is disabled for this API",
+        Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    ))
+    .style(Style::default().bg(Color::Red));
+    frame.render_widget(banner, chunks[0]);
+
+    chunks[1]
 }
 
 struct ResourceWidget {
     resource: Resource,
+    folders: Vec<Resource>,
     requests: Vec<Request>,
+    description_input: Option<String>,
+}
+
+impl ResourceWidget {
+    fn handle_input(&mut self, key: KeyCode) -> RightInputResult {
+        if self.description_input.is_some() {
+            if let Some(description) = handle_description_input(&mut self.description_input, key) {
+                self.resource = resource_service::update_resource_description(
+                    &self.resource,
+                    optional(&description),
+                );
+            }
+            return RightInputResult::None;
+        }
+
+        match key {
+            KeyCode::Char('q') => RightInputResult::Exit,
+            KeyCode::Char('e') => {
+                self.description_input
+                    .replace(self.resource.description.clone().unwrap_or_default());
+                RightInputResult::None
+            }
+            KeyCode::Backspace | KeyCode::Esc => RightInputResult::LoseFocus,
+            _ => RightInputResult::None,
+        }
+    }
 }
 
 impl Drawable for ResourceWidget {
     fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
-        let right_chunk = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(100)])
-            .split(rect)[0];
+        let description = description_pane(
+            self.resource.description.as_deref(),
+            self.description_input.as_deref(),
+        );
+        let right_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(description.as_ref().map_or(0, |(height, _)| *height)),
+                Constraint::Min(0),
+            ])
+            .split(rect);
 
-        let list: Vec<_> = self
-            .requests
-            .iter()
-            .map(|request| {
-                ListItem::new(Spans::from(vec![Span::styled(
-                    request.route.clone(),
-                    Style::default(),
-                )]))
-            })
+        if let Some((_, description)) = description {
+            frame.render_widget(description, right_chunks[0]);
+        }
+
+        let folders = self.folders.iter().map(|folder| {
+            ListItem::new(Spans::from(vec![Span::styled(
+                format!("{}/", folder.name),
+                Style::default().fg(Color::Blue),
+            )]))
+        });
+        let list: Vec<_> = folders
+            .chain(self.requests.iter().map(|request| {
+                ListItem::new(Spans::from(match &request.name {
+                    Some(name) if !name.is_empty() => vec![
+                        Span::raw(name.clone()),
+                        Span::styled(
+                            format!("  {}", request.route),
+                            Style::default().fg(Color::DarkGray),
+                        ),
+                    ],
+                    _ => vec![Span::raw(request.route.clone())],
+                }))
+            }))
             .collect();
 
         let right_block = Block::default()
@@ -111,7 +745,203 @@ impl Drawable for ResourceWidget {
 
         let widget = List::new(list).block(right_block);
 
-        frame.render_widget(widget, right_chunk);
+        frame.render_widget(widget, right_chunks[1]);
+    }
+}
+
+/// Lets the user pick which entries of a HAR file become requests.
+struct HarImportWidget {
+    /// The API the requests go to, named after the file unless an API was
+    /// open when importing.
+    api_name: String,
+    entries: Vec<HarEntry>,
+    chosen: Vec<bool>,
+    list_state: ListState,
+    status_message: Option<String>,
+}
+
+impl HarImportWidget {
+    fn new(path: &str, api_id: Option<i32>) -> Self {
+        let (entries, status_message) = match har::load(path) {
+            Ok(log) if !log.skipped.is_empty() => (
+                log.entries,
+                Some(format!("Left out {}", log.skipped.join(", "))),
+            ),
+            Ok(log) if log.entries.is_empty() => {
+                (log.entries, Some(String::from("No entries found")))
+            }
+            Ok(log) => (log.entries, None),
+            Err(err) => (vec![], Some(err.to_string())),
+        };
+        let api_name = match api_id.and_then(api_service::get_api_by_id) {
+            Some(api) => api.name,
+            None => Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default(),
+        };
+
+        let mut list_state = ListState::default();
+        if !entries.is_empty() {
+            list_state.select(Some(0));
+        }
+
+        Self {
+            api_name,
+            chosen: vec![false; entries.len()],
+            entries,
+            list_state,
+            status_message,
+        }
+    }
+
+    fn handle_input(&mut self, key: KeyCode) -> RightInputResult {
+        let count = self.entries.len();
+        let selected = self.list_state.selected();
+
+        match key {
+            KeyCode::Char('j') | KeyCode::Down if count > 0 => {
+                let next = selected.map_or(0, |selected| (selected + 1) % count);
+                self.list_state.select(Some(next));
+            }
+            KeyCode::Char('k') | KeyCode::Up if count > 0 => {
+                let previous = selected.map_or(0, |selected| (selected + count - 1) % count);
+                self.list_state.select(Some(previous));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(selected) = selected {
+                    self.chosen[selected] = !self.chosen[selected];
+                }
+            }
+            KeyCode::Char('a') => {
+                let all = self.chosen.iter().all(|chosen| *chosen);
+                self.chosen.iter_mut().for_each(|chosen| *chosen = !all);
+            }
+            KeyCode::Enter => return self.import(),
+            KeyCode::Char('q') => return RightInputResult::Exit,
+            KeyCode::Backspace | KeyCode::Esc => return RightInputResult::LoseFocus,
+            _ => (),
+        }
+        RightInputResult::None
+    }
+
+    fn import(&mut self) -> RightInputResult {
+        let entries: Vec<HarEntry> = std::mem::take(&mut self.entries)
+            .into_iter()
+            .zip(&self.chosen)
+            .filter_map(|(entry, chosen)| chosen.then_some(entry))
+            .collect();
+        if entries.is_empty() {
+            self.status_message
+                .replace(String::from("Nothing chosen, Space to choose entries"));
+            return RightInputResult::None;
+        }
+
+        let imported = har::to_api(&self.api_name, entries);
+        self.chosen.clear();
+        self.list_state.select(None);
+        self.status_message
+            .replace(match import_service::import_api(&imported) {
+                Ok(summary) => format!("Imported into {}: {}", self.api_name, summary),
+                Err(err) => err.to_string(),
+            });
+        RightInputResult::RefreshRequests
+    }
+}
+
+impl Drawable for HarImportWidget {
+    fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)])
+            .split(rect);
+
+        let items: Vec<_> = self
+            .entries
+            .iter()
+            .zip(&self.chosen)
+            .map(|(entry, chosen)| {
+                let request = &entry.request;
+                let mut route = request.route.clone();
+                if !request.params.is_empty() {
+                    let query: Vec<String> = request
+                        .params
+                        .iter()
+                        .map(|(key, value)| format!("{}={}", key, value))
+                        .collect();
+                    route = format!("{}?{}", route, query.join("&"));
+                }
+                ListItem::new(Spans::from(vec![
+                    Span::raw(if *chosen { "[x] " } else { "[ ] " }),
+                    Span::styled(
+                        format!("{:<7}", request.method.to_string()),
+                        Style::default()
+                            .fg(Color::Green)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(
+                        format!("{:<4}", entry.status),
+                        Style::default().fg(if entry.status >= 400 {
+                            Color::Red
+                        } else {
+                            Color::Reset
+                        }),
+                    ),
+                    Span::raw(route),
+                ]))
+            })
+            .collect();
+
+        let chosen = self.chosen.iter().filter(|chosen| **chosen).count();
+        let block = Block::default()
+            .title(format!(
+                "HAR entries ({} of {} chosen)",
+                chosen,
+                self.entries.len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Yellow));
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, chunks[0], &mut self.list_state);
+
+        let status_para = match &self.status_message {
+            Some(message) => Paragraph::new(message.as_str()),
+            None => Paragraph::new("Space choose, a choose all, Enter import, Esc cancel")
+                .style(Style::default().fg(Color::DarkGray)),
+        };
+        frame.render_widget(status_para, chunks[1]);
+    }
+}
+
+const REQUEST_TAB_COUNT: usize = 6;
+
+/// Number of body bytes shown in the hex view.
+const HEX_DUMP_LIMIT: usize = 4096;
+
+/// What the text input of the Form tab is currently editing.
+enum FormEdit {
+    NewText,
+    NewFile,
+    Name,
+    Value,
+    ContentType,
+}
+
+/// The request written as code in one of `Language::ALL`, to be copied.
+struct CopyAs {
+    language: usize,
+    snippet: Result<String, String>,
+}
+
+impl CopyAs {
+    fn new(request_id: i32, language: usize) -> Self {
+        Self {
+            language,
+            snippet: codegen::generate(request_id, Language::ALL[language])
+                .map_err(|err| err.to_string()),
+        }
     }
 }
 
@@ -122,26 +952,384 @@ struct RequestWidget {
     header_table_state: TableState,
     param_table_state: TableState,
     headers: Vec<Header>,
+    form_parts: Vec<FormPart>,
+    form_table_state: TableState,
+    form_edit: Option<FormEdit>,
+    tls: Option<TlsConfig>,
     input: Option<String>,
-    response: Option<String>,
+    response: Option<Result<HttpResponse, String>>,
+    show_hex: bool,
+    save_path: Option<String>,
+    status_message: Option<String>,
+    /// The URL the last response came from, when it was sent over TLS.
+    peer_url: Option<String>,
+    /// The certificate chain of `peer_url`, fetched once the inspector is
+    /// opened.
+    certificates: Option<Result<Vec<PeerCertificate>, String>>,
+    show_certificates: bool,
+    stream: Option<ResponseStream>,
+    /// Whether the stream was added to the history, which happens once it
+    /// ends.
+    stream_recorded: bool,
+    method_picker: Option<ListState>,
+    copy_as: Option<CopyAs>,
+    graphql: GraphqlPane,
+    description_input: Option<String>,
+    tags: Vec<Tag>,
 }
 
-impl RequestWidget {
-    fn new(request: Request, headers: Vec<Header>) -> Self {
-        Self {
-            tab_index: 0,
-            request,
-            request_details_table_state: TableState::default(),
-            header_table_state: TableState::default(),
-            param_table_state: TableState::default(),
-            headers,
-            input: None,
-            response: None,
+/// Route, method, kind, name, description, tags and whether it is starred.
+const DETAIL_ROWS: usize = 7;
+
+impl RequestWidget {
+    fn new(
+        request: Request,
+        headers: Vec<Header>,
+        form_parts: Vec<FormPart>,
+        tls: Option<TlsConfig>,
+        graphql: GraphqlPane,
+    ) -> Self {
+        Self {
+            tab_index: 0,
+            tags: tag_service::get_tags_for_request(request.id),
+            request,
+            request_details_table_state: TableState::default(),
+            header_table_state: TableState::default(),
+            param_table_state: TableState::default(),
+            headers,
+            form_parts,
+            form_table_state: TableState::default(),
+            form_edit: None,
+            tls,
+            input: None,
+            response: None,
+            show_hex: false,
+            save_path: None,
+            status_message: None,
+            peer_url: None,
+            certificates: None,
+            show_certificates: false,
+            stream: None,
+            stream_recorded: false,
+            method_picker: None,
+            copy_as: None,
+            graphql,
+            description_input: None,
+        }
+    }
+
+    /// Sends the request. Event streams and NDJSON are always streamed;
+    /// `force_stream` streams any other body line by line as well.
+    fn send(&mut self, force_stream: bool) {
+        self.stop_stream();
+        self.stream.take();
+        request_service::mark_run(self.request.id);
+
+        self.certificates = None;
+        self.peer_url = None;
+
+        match http::make_request(self.request.id, HOSTNAME) {
+            Ok(response) => {
+                let url = response.response.url();
+                if url.scheme() == "https" {
+                    self.peer_url = Some(url.to_string());
+                }
+                if self.show_certificates {
+                    self.fetch_certificates();
+                }
+
+                let content_type = response
+                    .response
+                    .headers()
+                    .get("content-type")
+                    .and_then(|value| value.to_str().ok());
+                let format = match StreamFormat::for_content_type(content_type) {
+                    Some(format) => Some(format),
+                    None if force_stream => Some(StreamFormat::Lines),
+                    None => None,
+                };
+
+                if let Some(format) = format {
+                    self.response = None;
+                    self.stream = Some(ResponseStream::start(response, format));
+                    self.stream_recorded = false;
+                } else {
+                    let response = HttpResponse::read(response).map_err(|err| err.to_string());
+                    if let Ok(response) = &response {
+                        self.record(response);
+                    }
+                    self.response = Some(response);
+                }
+            }
+            Err(err) => {
+                self.response = Some(Err(err.to_string()));
+            }
+        }
+        self.status_message.take();
+    }
+
+    /// Adds the exchange to the history of the request, which the HAR
+    /// export, the `status:` filter and the examples of the OpenAPI and
+    /// HTML exports are made from.
+    fn record(&self, response: &HttpResponse) {
+        let (body, body_base64) = match response.text() {
+            Some(text) => (text.to_string(), false),
+            None => (STANDARD.encode(&response.body), true),
+        };
+        record_exchange(
+            self.request.id,
+            &response.status,
+            &response.headers,
+            (&body, body_base64),
+            &response.sent,
+            response.elapsed,
+        );
+    }
+
+    /// Adds a stream to the history once it ended, with what was received.
+    fn record_stream(&mut self) {
+        if let Some(stream) = &self.stream {
+            if stream.state != StreamState::Receiving && !self.stream_recorded {
+                record_exchange(
+                    self.request.id,
+                    &stream.status,
+                    &stream.headers,
+                    (&stream.text(), false),
+                    &stream.sent,
+                    stream.started.elapsed(),
+                );
+                self.stream_recorded = true;
+            }
+        }
+    }
+
+    fn tick(&mut self) {
+        if let Some(stream) = &mut self.stream {
+            stream.poll();
+        }
+        self.record_stream();
+    }
+
+    fn stop_stream(&mut self) -> bool {
+        let stopped = match &mut self.stream {
+            Some(stream) if stream.state == StreamState::Receiving => {
+                stream.stop();
+                true
+            }
+            _ => false,
+        };
+        self.record_stream();
+        stopped
+    }
+
+    fn handle_save_input(&mut self, key: KeyCode) -> RightInputResult {
+        let save_path = match &mut self.save_path {
+            Some(save_path) => save_path,
+            None => return RightInputResult::None,
+        };
+
+        match key {
+            KeyCode::Char(character) => save_path.push(character),
+            KeyCode::Backspace => {
+                save_path.pop();
+            }
+            KeyCode::Tab => *save_path = complete_path(save_path),
+            KeyCode::Esc => {
+                self.save_path.take();
+            }
+            KeyCode::Enter => {
+                let save_path = self.save_path.take().unwrap_or_default();
+                if let Some(Ok(response)) = &self.response {
+                    let message = match fs::write(&save_path, &response.body) {
+                        Ok(_) => format!(
+                            "Saved {} to {}",
+                            response::human_size(response.body.len()),
+                            save_path
+                        ),
+                        Err(err) => format!("Could not save to {}: {}", save_path, err),
+                    };
+                    self.status_message.replace(message);
+                }
+            }
+            _ => (),
+        }
+
+        RightInputResult::None
+    }
+
+    fn response_title(&self) -> String {
+        match &self.response {
+            Some(Ok(response)) => format!(
+                "Response - {} - {} - {}",
+                response.status,
+                response.content_type.mime,
+                response::human_size(response.body.len())
+            ),
+            _ => String::from("Response"),
+        }
+    }
+
+    /// Shows `errors` above `data` so they are not lost in a large result.
+    fn graphql_lines(data: Option<String>, errors: Vec<String>) -> Vec<Spans<'static>> {
+        let heading = |text: String, color: Color| {
+            Spans::from(Span::styled(
+                text,
+                Style::default().fg(color).add_modifier(Modifier::BOLD),
+            ))
+        };
+        let mut lines = vec![];
+
+        if !errors.is_empty() {
+            lines.push(heading(format!("Errors ({})", errors.len()), Color::Red));
+            lines.extend(errors.into_iter().map(|error| {
+                Spans::from(Span::styled(
+                    format!("  {}", error),
+                    Style::default().fg(Color::Red),
+                ))
+            }));
+            lines.push(Spans::from(""));
+        }
+
+        lines.push(heading(String::from("Data"), Color::Green));
+        match data {
+            Some(data) => lines.extend(data.lines().map(|line| Spans::from(line.to_string()))),
+            None => lines.push(Spans::from("null")),
+        }
+
+        lines
+    }
+
+    /// Switches between plain HTTP and GraphQL. GraphQL requests sent with
+    /// GET put the query in the URL, so a GET request becomes a POST.
+    fn toggle_graphql(&mut self) -> RightInputResult {
+        let kind = match self.request.kind {
+            RequestKind::Http => RequestKind::Graphql,
+            RequestKind::Graphql => RequestKind::Http,
+            RequestKind::Websocket | RequestKind::Grpc => return RightInputResult::None,
+        };
+
+        self.request = request_service::update_request_kind(&self.request, kind);
+        if kind == RequestKind::Graphql && self.request.method == HttpMethod::Get {
+            self.request = request_service::update_request_method(&self.request, HttpMethod::Post);
+        }
+        RightInputResult::RefreshRequests
+    }
+
+    fn response_lines(&self) -> Vec<Spans<'_>> {
+        let response = match &self.response {
+            Some(Ok(response)) => response,
+            Some(Err(err)) => return vec![Spans::from(err.as_str())],
+            None => return vec![],
+        };
+
+        if let (Some(text), false) = (response.text(), self.show_hex) {
+            if self.request.kind == RequestKind::Graphql {
+                if let Some((data, errors)) = graphql::split_response(text) {
+                    return Self::graphql_lines(data, errors);
+                }
+            }
+            return text.lines().map(Spans::from).collect();
+        }
+
+        let label = |name: &str| {
+            Span::styled(
+                format!("{:<16}", name),
+                Style::default().add_modifier(Modifier::BOLD),
+            )
+        };
+
+        let mut lines = vec![
+            Spans::from(vec![
+                label("Detected type"),
+                Span::raw(&response.content_type.mime),
+            ]),
+            Spans::from(vec![
+                label("Content-Type"),
+                Span::raw(response.header("content-type").unwrap_or("-")),
+            ]),
+            Spans::from(vec![
+                label("Size"),
+                Span::raw(format!(
+                    "{} ({} bytes)",
+                    response::human_size(response.body.len()),
+                    response.body.len()
+                )),
+            ]),
+            Spans::from(""),
+        ];
+
+        let shown = &response.body[..response.body.len().min(HEX_DUMP_LIMIT)];
+        lines.extend(response::hex_dump(shown).into_iter().map(Spans::from));
+
+        if response.body.len() > HEX_DUMP_LIMIT {
+            lines.push(Spans::from(Span::styled(
+                format!(
+                    "... {} more bytes, press s to save the body to a file",
+                    response.body.len() - HEX_DUMP_LIMIT
+                ),
+                Style::default().fg(Color::Yellow),
+            )));
+        }
+
+        lines
+    }
+
+    /// Connects to the host of the last response again to read its
+    /// certificate chain, unless it was already read.
+    fn fetch_certificates(&mut self) {
+        if let (Some(url), None) = (&self.peer_url, &self.certificates) {
+            self.certificates = Some(
+                http::peer_certificate_chain(url, self.tls.as_ref()).map_err(|err| err.to_string()),
+            );
+        }
+    }
+
+    fn certificate_chain(&self) -> Vec<Spans<'_>> {
+        match &self.certificates {
+            Some(Ok(chain)) if !chain.is_empty() => chain
+                .iter()
+                .enumerate()
+                .flat_map(|(depth, certificate)| {
+                    let label = |name: &str| {
+                        Span::styled(
+                            format!("  {:<12}", name),
+                            Style::default().add_modifier(Modifier::BOLD),
+                        )
+                    };
+                    vec![
+                        Spans::from(Span::styled(
+                            format!("#{}", depth),
+                            Style::default().fg(Color::Green),
+                        )),
+                        Spans::from(vec![
+                            label("Subject"),
+                            Span::raw(certificate.subject.clone()),
+                        ]),
+                        Spans::from(vec![label("Issuer"), Span::raw(certificate.issuer.clone())]),
+                        Spans::from(vec![
+                            label("Valid from"),
+                            Span::raw(certificate.not_before.clone()),
+                        ]),
+                        Spans::from(vec![
+                            label("Valid until"),
+                            Span::raw(certificate.not_after.clone()),
+                        ]),
+                        Spans::from(vec![
+                            label("SHA-256"),
+                            Span::raw(certificate.fingerprint.clone()),
+                        ]),
+                        Spans::from(""),
+                    ]
+                })
+                .collect(),
+            Some(Ok(_)) => vec![Spans::from("The server presented no certificates")],
+            Some(Err(err)) => vec![Spans::from(err.clone())],
+            None => vec![Spans::from("No TLS connection was made")],
         }
     }
 
     fn next_tab(&mut self) {
-        if self.tab_index < 3 {
+        if self.tab_index < REQUEST_TAB_COUNT - 1 {
             self.tab_index += 1;
         } else {
             self.tab_index = 0;
@@ -152,14 +1340,16 @@ impl RequestWidget {
         if self.tab_index > 0 {
             self.tab_index -= 1;
         } else {
-            self.tab_index = 3;
+            self.tab_index = REQUEST_TAB_COUNT - 1;
         }
     }
 
     fn move_down(&mut self) {
         match self.tab_index {
             0 => match self.request_details_table_state.selected() {
-                Some(0) => self.request_details_table_state.select(Some(1)),
+                Some(current) if current < DETAIL_ROWS - 1 => {
+                    self.request_details_table_state.select(Some(current + 1))
+                }
                 Some(_) | None => self.request_details_table_state.select(Some(0)),
             },
             1 => {
@@ -173,6 +1363,12 @@ impl RequestWidget {
                     }
                 }
             }
+            4 if !self.form_parts.is_empty() => match self.form_table_state.selected() {
+                Some(current) if current < self.form_parts.len() - 1 => {
+                    self.form_table_state.select(Some(current + 1))
+                }
+                _ => self.form_table_state.select(Some(0)),
+            },
             _ => (),
         }
     }
@@ -180,8 +1376,12 @@ impl RequestWidget {
     fn move_up(&mut self) {
         match self.tab_index {
             0 => match self.request_details_table_state.selected() {
-                Some(0) => self.request_details_table_state.select(Some(1)),
-                Some(_) | None => self.request_details_table_state.select(Some(0)),
+                Some(current) if current > 0 => {
+                    self.request_details_table_state.select(Some(current - 1))
+                }
+                Some(_) | None => self
+                    .request_details_table_state
+                    .select(Some(DETAIL_ROWS - 1)),
             },
             1 => {
                 if !self.headers.is_empty() {
@@ -194,11 +1394,358 @@ impl RequestWidget {
                     }
                 }
             }
+            4 if !self.form_parts.is_empty() => match self.form_table_state.selected() {
+                Some(current) if current > 0 => self.form_table_state.select(Some(current - 1)),
+                _ => self
+                    .form_table_state
+                    .select(Some(self.form_parts.len() - 1)),
+            },
+            _ => (),
+        }
+    }
+
+    fn selected_form_part(&self) -> Option<&FormPart> {
+        self.form_table_state
+            .selected()
+            .and_then(|index| self.form_parts.get(index))
+    }
+
+    fn start_form_edit(&mut self, edit: FormEdit) {
+        let initial = match (&edit, self.selected_form_part()) {
+            (FormEdit::NewText, _) | (FormEdit::NewFile, _) => String::new(),
+            (FormEdit::Name, Some(form_part)) => form_part.name.clone(),
+            (FormEdit::Value, Some(form_part)) => form_part.value.clone(),
+            (FormEdit::ContentType, Some(form_part)) => {
+                form_part.content_type.clone().unwrap_or_default()
+            }
+            (_, None) => return,
+        };
+
+        self.input.replace(initial);
+        self.form_edit.replace(edit);
+    }
+
+    fn apply_form_edit(&mut self, input: &str) {
+        let edit = match self.form_edit.take() {
+            Some(edit) => edit,
+            None => return,
+        };
+
+        match edit {
+            FormEdit::NewText => {
+                if !input.is_empty() {
+                    form_part_service::create_new_form_part(NewFormPart {
+                        name: input,
+                        kind: FormPartKind::Text,
+                        value: "",
+                        content_type: None,
+                        request_id: self.request.id,
+                    });
+                }
+            }
+            FormEdit::NewFile => {
+                if !input.is_empty() {
+                    let name = Path::new(input)
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .unwrap_or("file");
+                    form_part_service::create_new_form_part(NewFormPart {
+                        name,
+                        kind: FormPartKind::File,
+                        value: input,
+                        content_type: None,
+                        request_id: self.request.id,
+                    });
+                }
+            }
+            FormEdit::Name | FormEdit::Value | FormEdit::ContentType => {
+                if let Some(form_part) = self.selected_form_part() {
+                    let mut changes = form_part.changes();
+                    match edit {
+                        FormEdit::Name => changes.name = input,
+                        FormEdit::Value => changes.value = input,
+                        _ => changes.content_type = optional(input),
+                    }
+                    form_part_service::update_form_part(form_part, &changes);
+                }
+            }
+        }
+
+        self.form_parts = form_part_service::get_form_parts_for_request(self.request.id);
+    }
+
+    fn delete_selected_form_part(&mut self) {
+        if let Some(form_part) = self.selected_form_part() {
+            form_part_service::delete_form_part(form_part);
+            self.form_parts = form_part_service::get_form_parts_for_request(self.request.id);
+
+            let selected = match self.form_table_state.selected() {
+                _ if self.form_parts.is_empty() => None,
+                Some(index) => Some(index.min(self.form_parts.len() - 1)),
+                None => None,
+            };
+            self.form_table_state.select(selected);
+        }
+    }
+
+    /// Whether the text input currently holds a file path, which enables
+    /// tab completion.
+    fn editing_path(&self) -> bool {
+        match self.form_edit {
+            Some(FormEdit::NewFile) => true,
+            Some(FormEdit::Value) => self
+                .selected_form_part()
+                .is_some_and(|form_part| form_part.kind == FormPartKind::File),
+            _ => false,
+        }
+    }
+
+    fn form_parts_table(&self) -> Table<'_> {
+        let block = Block::default().title("Form Parts").borders(Borders::ALL);
+
+        let highlight_style = if self.input.is_some() {
+            Style::default().bg(Color::Cyan).fg(Color::Black)
+        } else {
+            Style::default().fg(Color::Black).bg(Color::Yellow)
+        };
+
+        let header = Row::new(["Name", "Type", "Value", "Content-Type"]).style(
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+
+        let selected = self.form_table_state.selected();
+        let mut rows: Vec<_> = self
+            .form_parts
+            .iter()
+            .enumerate()
+            .map(|(index, form_part)| {
+                let mut name = form_part.name.clone();
+                let mut value = form_part.value.clone();
+                let mut content_type = form_part.content_type.clone().unwrap_or_default();
+
+                if let (Some(input), true) = (&self.input, selected == Some(index)) {
+                    match self.form_edit {
+                        Some(FormEdit::Name) => name = input.clone(),
+                        Some(FormEdit::Value) => value = input.clone(),
+                        Some(FormEdit::ContentType) => content_type = input.clone(),
+                        _ => (),
+                    }
+                }
+
+                Row::new([
+                    Cell::from(name),
+                    Cell::from(form_part.kind.to_string()),
+                    Cell::from(value),
+                    Cell::from(content_type),
+                ])
+            })
+            .collect();
+
+        let prompt = match self.form_edit {
+            Some(FormEdit::NewText) => Some("New text field name: "),
+            Some(FormEdit::NewFile) => Some("File path: "),
+            _ => None,
+        };
+        if let (Some(prompt), Some(input)) = (prompt, &self.input) {
+            rows.push(
+                Row::new([Cell::from(format!("{}{}", prompt, input))])
+                    .style(Style::default().bg(Color::Cyan).fg(Color::Black)),
+            );
+        }
+
+        Table::new(rows)
+            .header(header)
+            .block(block)
+            .highlight_style(highlight_style)
+            .widths(&[
+                Constraint::Percentage(20),
+                Constraint::Percentage(10),
+                Constraint::Percentage(45),
+                Constraint::Percentage(25),
+            ])
+    }
+
+    fn open_method_picker(&mut self) {
+        let methods = HttpMethod::standard();
+        let selected = methods
+            .iter()
+            .position(|method| method == &self.request.method)
+            .unwrap_or(methods.len());
+
+        let mut picker_state = ListState::default();
+        picker_state.select(Some(selected));
+        self.method_picker.replace(picker_state);
+    }
+
+    fn handle_method_picker_input(&mut self, key: KeyCode) -> RightInputResult {
+        let option_count = HttpMethod::standard().len() + 1;
+        let picker_state = match &mut self.method_picker {
+            Some(picker_state) => picker_state,
+            None => return RightInputResult::None,
+        };
+        let selected = picker_state.selected().unwrap_or(0);
+
+        match key {
+            KeyCode::Char('j') | KeyCode::Down => {
+                picker_state.select(Some((selected + 1) % option_count));
+                RightInputResult::None
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                picker_state.select(Some((selected + option_count - 1) % option_count));
+                RightInputResult::None
+            }
+            KeyCode::Enter => {
+                self.method_picker.take();
+                if let Some(new_method) = HttpMethod::standard().into_iter().nth(selected) {
+                    let updated_request =
+                        request_service::update_request_method(&self.request, new_method);
+                    self.request = updated_request;
+                    RightInputResult::RefreshRequests
+                } else {
+                    let current_verb = match &self.request.method {
+                        HttpMethod::Custom(verb) => verb.clone(),
+                        _ => String::new(),
+                    };
+                    self.input.replace(current_verb);
+                    RightInputResult::None
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => {
+                self.method_picker.take();
+                RightInputResult::None
+            }
+            _ => RightInputResult::None,
+        }
+    }
+
+    fn method_picker(&self) -> List<'_> {
+        let mut items: Vec<_> = HttpMethod::standard()
+            .iter()
+            .map(|method| ListItem::new(method.to_string()))
+            .collect();
+        items.push(ListItem::new("Custom..."));
+
+        let block = Block::default()
+            .title("Http Method")
+            .borders(Borders::ALL)
+            .border_type(BorderType::Plain);
+
+        List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(Color::Yellow)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        )
+    }
+
+    fn handle_copy_as_input(&mut self, key: KeyCode) -> RightInputResult {
+        let copy_as = match &self.copy_as {
+            Some(copy_as) => copy_as,
+            None => return RightInputResult::None,
+        };
+        let count = Language::ALL.len();
+
+        match key {
+            KeyCode::Char('l') | KeyCode::Right | KeyCode::Tab => {
+                self.copy_as = Some(CopyAs::new(self.request.id, (copy_as.language + 1) % count));
+            }
+            KeyCode::Char('h') | KeyCode::Left | KeyCode::BackTab => {
+                self.copy_as = Some(CopyAs::new(
+                    self.request.id,
+                    (copy_as.language + count - 1) % count,
+                ));
+            }
+            KeyCode::Char('y') | KeyCode::Enter => {
+                if let Ok(snippet) = &copy_as.snippet {
+                    let name = Language::ALL[copy_as.language].name();
+                    let message = match copy_to_clipboard(snippet) {
+                        Ok(()) => format!("Copied as {}", name),
+                        Err(err) => format!("Could not copy: {}", err),
+                    };
+                    self.status_message.replace(message);
+                    self.copy_as.take();
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('q') => {
+                self.copy_as.take();
+            }
             _ => (),
         }
+        RightInputResult::None
+    }
+
+    fn copy_as_popup(copy_as: &CopyAs) -> Paragraph<'_> {
+        let titles: Vec<Span> = Language::ALL
+            .iter()
+            .enumerate()
+            .flat_map(|(index, language)| {
+                let style = if index == copy_as.language {
+                    Style::default()
+                        .bg(Color::Yellow)
+                        .fg(Color::Black)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                [Span::raw(" "), Span::styled(language.name(), style)]
+            })
+            .collect();
+
+        let mut lines = vec![
+            Spans::from(titles),
+            Spans::from(Span::styled(
+                "h/l language, y copy, Esc close",
+                Style::default().fg(Color::DarkGray),
+            )),
+            Spans::from(""),
+        ];
+        match &copy_as.snippet {
+            Ok(snippet) => lines.extend(snippet.lines().map(Spans::from)),
+            Err(err) => lines.push(Spans::from(Span::styled(
+                err.as_str(),
+                Style::default().fg(Color::Red),
+            ))),
+        }
+
+        Paragraph::new(lines).block(
+            Block::default()
+                .title("Copy as")
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
     }
 
     fn handle_input(&mut self, key: KeyCode) -> RightInputResult {
+        if self.method_picker.is_some() {
+            return self.handle_method_picker_input(key);
+        }
+
+        if self.copy_as.is_some() {
+            return self.handle_copy_as_input(key);
+        }
+
+        if self.save_path.is_some() {
+            return self.handle_save_input(key);
+        }
+
+        if self.tab_index == 5 && self.request.kind == RequestKind::Graphql {
+            if let Some(result) = self.graphql.handle_input(key) {
+                return result;
+            }
+        }
+
+        if self.description_input.is_some() {
+            if let Some(description) = handle_description_input(&mut self.description_input, key) {
+                self.request = request_service::update_request_description(
+                    &self.request,
+                    optional(&description),
+                );
+            }
+            return RightInputResult::None;
+        }
+
         match key {
             KeyCode::Char(character) => {
                 if let Some(input) = &mut self.input {
@@ -226,6 +1773,7 @@ impl RequestWidget {
                                         self.input.replace(String::new());
                                     }
                                 }
+                                4 => self.start_form_edit(FormEdit::NewText),
                                 _ => (),
                             }
                             RightInputResult::None
@@ -247,14 +1795,58 @@ impl RequestWidget {
                             RightInputResult::None
                         }
                         'r' => {
-                            let response =
-                                http::make_request(self.request.id, "https://dummyjson.com");
-
-                            self.response = Some(response.text().unwrap());
+                            self.send(false);
+                            RightInputResult::None
+                        }
+                        'S' => {
+                            if !self.stop_stream() {
+                                self.send(true);
+                            }
+                            RightInputResult::None
+                        }
+                        'c' => {
+                            self.show_certificates = !self.show_certificates;
+                            if self.show_certificates {
+                                self.fetch_certificates();
+                            }
+                            RightInputResult::None
+                        }
+                        'x' => {
+                            self.show_hex = !self.show_hex;
+                            RightInputResult::None
+                        }
+                        'y' => {
+                            self.copy_as = Some(CopyAs::new(self.request.id, 0));
+                            RightInputResult::None
+                        }
+                        's' => {
+                            if let Some(Ok(_)) = self.response {
+                                self.save_path.replace(String::new());
+                            }
+                            RightInputResult::None
+                        }
+                        'f' if self.tab_index == 4 => {
+                            self.start_form_edit(FormEdit::NewFile);
+                            RightInputResult::None
+                        }
+                        'n' if self.tab_index == 4 => {
+                            self.start_form_edit(FormEdit::Name);
+                            RightInputResult::None
+                        }
+                        't' if self.tab_index == 4 => {
+                            self.start_form_edit(FormEdit::ContentType);
+                            RightInputResult::None
+                        }
+                        'd' if self.tab_index == 4 => {
+                            self.delete_selected_form_part();
+                            RightInputResult::None
+                        }
+                        'i' if self.tab_index == 5 => {
+                            self.graphql.introspect(&self.request);
                             RightInputResult::None
                         }
-                        'D' | 'H' | 'P' | 'B' => {
-                            let options = ['D', 'H', 'P', 'B'];
+                        'D' | 'H' | 'P' | 'B' | 'F' | 'G' => {
+                            let options = ['D', 'H', 'P', 'B', 'F', 'G'];
                             self.tab_index = options.iter().position(|x| x == &character).unwrap();
                             RightInputResult::None
                         }
@@ -270,21 +1862,73 @@ impl RequestWidget {
                     RightInputResult::LoseFocus
                 }
             }
+            KeyCode::Tab => {
+                if self.editing_path() {
+                    if let Some(input) = &mut self.input {
+                        *input = complete_path(input);
+                    }
+                }
+                RightInputResult::None
+            }
+            KeyCode::Esc => {
+                if self.input.is_some() {
+                    self.input.take();
+                    self.form_edit.take();
+                } else {
+                    self.stop_stream();
+                }
+                RightInputResult::None
+            }
+            KeyCode::Enter if self.tab_index == 4 => {
+                if let Some(input) = self.input.take() {
+                    self.apply_form_edit(&input);
+                } else {
+                    self.start_form_edit(FormEdit::Value);
+                }
+                RightInputResult::None
+            }
             KeyCode::Enter => {
                 if let Some(input) = &self.input {
                     let result = match self.tab_index {
-                        0 => {
-                            if let Some(0) = self.request_details_table_state.selected() {
+                        0 => match self.request_details_table_state.selected() {
+                            Some(0) => {
                                 let updated_request = request_service::update_request_route(
                                     &self.request,
                                     input.as_str(),
                                 );
                                 self.request = updated_request;
                                 RightInputResult::RefreshRequests
-                            } else {
-                                RightInputResult::None
                             }
-                        }
+                            Some(1) => match input.parse::<HttpMethod>() {
+                                Ok(new_method) => {
+                                    let updated_request = request_service::update_request_method(
+                                        &self.request,
+                                        new_method,
+                                    );
+                                    self.request = updated_request;
+                                    self.status_message.take();
+                                    RightInputResult::RefreshRequests
+                                }
+                                // Kept open to be corrected.
+                                Err(err) => {
+                                    self.status_message.replace(err);
+                                    return RightInputResult::None;
+                                }
+                            },
+                            Some(3) => {
+                                self.request = request_service::update_request_name(
+                                    &self.request,
+                                    optional(input),
+                                );
+                                RightInputResult::RefreshRequests
+                            }
+                            Some(5) => {
+                                let names: Vec<_> = input.split(',').collect();
+                                self.tags = tag_service::set_tags(self.request.id, &names);
+                                RightInputResult::RefreshRequests
+                            }
+                            _ => RightInputResult::None,
+                        },
                         1 => {
                             if let Some(selected_header_index) = self.header_table_state.selected()
                             {
@@ -319,19 +1963,29 @@ impl RequestWidget {
                                 RightInputResult::None
                             }
                             Some(1) => {
-                                let new_method = match self.request.method {
-                                    HttpMethod::Get => HttpMethod::Post,
-                                    HttpMethod::Post => HttpMethod::Put,
-                                    HttpMethod::Put => HttpMethod::Patch,
-                                    HttpMethod::Patch => HttpMethod::Delete,
-                                    HttpMethod::Delete => HttpMethod::Get,
-                                };
-                                let updated_request = request_service::update_request_method(
+                                self.open_method_picker();
+                                RightInputResult::None
+                            }
+                            Some(2) => self.toggle_graphql(),
+                            Some(3) => {
+                                self.input
+                                    .replace(self.request.name.clone().unwrap_or_default());
+                                RightInputResult::None
+                            }
+                            Some(4) => {
+                                self.description_input
+                                    .replace(self.request.description.clone().unwrap_or_default());
+                                RightInputResult::None
+                            }
+                            Some(5) => {
+                                self.input.replace(tag_names(&self.tags));
+                                RightInputResult::None
+                            }
+                            Some(6) => {
+                                self.request = request_service::update_request_favourite(
                                     &self.request,
-                                    new_method,
+                                    !self.request.favourite,
                                 );
-                                self.request = updated_request;
-
                                 RightInputResult::RefreshRequests
                             }
                             _ => RightInputResult::None,
@@ -349,10 +2003,14 @@ impl RequestWidget {
             .title("Request Details")
             .borders(Borders::ALL);
 
-        let route_value = if let Some(input) = &self.input {
-            input.clone()
-        } else {
-            self.request.route.clone()
+        let selected_row = self.request_details_table_state.selected();
+        let route_value = match &self.input {
+            Some(input) if selected_row == Some(0) => input.clone(),
+            _ => self.request.route.clone(),
+        };
+        let method_value = match &self.input {
+            Some(input) if selected_row == Some(1) => input.clone(),
+            _ => self.request.method.to_string(),
         };
 
         let highlight_style = if self.input.is_some() {
@@ -362,21 +2020,55 @@ impl RequestWidget {
         };
 
         let route_row = Row::new([Cell::from("Route"), Cell::from(route_value)]).height(1);
-        let method_row = Row::new([
-            Cell::from("Http Method"),
-            Cell::from(self.request.method.to_string()),
-        ])
-        .height(1);
+        let method_row = Row::new([Cell::from("Http Method"), Cell::from(method_value)]).height(1);
+        let kind_row = Row::new([
+            Cell::from("Kind"),
+            Cell::from(self.request.kind.to_string()),
+        ]);
+        let name_value = match &self.input {
+            Some(input) if selected_row == Some(3) => input.clone(),
+            _ => self.request.name.clone().unwrap_or_default(),
+        };
+        let name_row = Row::new([Cell::from("Name"), Cell::from(name_value)]);
+        let description_value = self
+            .request
+            .description
+            .as_deref()
+            .and_then(|description| description.lines().next())
+            .unwrap_or_default()
+            .to_string();
+        let description_row = Row::new([Cell::from("Description"), Cell::from(description_value)]);
+        let tags_value = match &self.input {
+            Some(input) if selected_row == Some(5) => input.clone(),
+            _ => tag_names(&self.tags),
+        };
+        let tags_row = Row::new([Cell::from("Tags"), Cell::from(tags_value)]);
+        let favourite_row = Row::new([
+            Cell::from("Favourite"),
+            Cell::from(if self.request.favourite {
+                "★ yes"
+            } else {
+                "no"
+            }),
+        ]);
 
-        Table::new([route_row, method_row])
-            .block(block)
-            .highlight_style(highlight_style)
-            .widths(&[Constraint::Percentage(50), Constraint::Percentage(50)])
+        Table::new([
+            route_row,
+            method_row,
+            kind_row,
+            name_row,
+            description_row,
+            tags_row,
+            favourite_row,
+        ])
+        .block(block)
+        .highlight_style(highlight_style)
+        .widths(&[Constraint::Percentage(50), Constraint::Percentage(50)])
     }
 }
 
-impl Drawable for RequestWidget {
-    fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
+impl RequestWidget {
+    fn draw_panes(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
         let right_chunks = Layout::default()
             .direction(Direction::Horizontal)
             .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
@@ -400,7 +2092,7 @@ impl Drawable for RequestWidget {
             Span::styled("D", Style::default().add_modifier(Modifier::UNDERLINED)),
             Span::raw("etails"),
         ]);
-        let mut titles: Vec<Spans> = ["Headers", "Params", "Body"]
+        let mut titles: Vec<Spans> = ["Headers", "Params", "Body", "Form", "GraphQL"]
             .iter()
             .map(|t| {
                 let (first, rest) = t.split_at(1);
@@ -428,12 +2120,27 @@ impl Drawable for RequestWidget {
 
         match self.tab_index {
             0 => {
+                let description = description_pane(
+                    self.request.description.as_deref(),
+                    self.description_input.as_deref(),
+                );
+                let details_chunks = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([
+                        Constraint::Length(DETAIL_ROWS as u16 + 2),
+                        Constraint::Min(0),
+                    ])
+                    .split(request_chunks[1]);
+
                 let widget = self.request_details();
                 frame.render_stateful_widget(
                     widget,
-                    request_chunks[1],
+                    details_chunks[0],
                     &mut self.request_details_table_state.clone(),
                 );
+                if let Some((_, description)) = description {
+                    frame.render_widget(description, details_chunks[1]);
+                }
             }
             1 => {
                 let widget = Block::default()
@@ -451,14 +2158,92 @@ impl Drawable for RequestWidget {
                 let widget = Block::default().title("Request Body").borders(Borders::ALL);
                 frame.render_widget(widget, request_chunks[1]);
             }
+            4 => {
+                let widget = self.form_parts_table();
+                frame.render_stateful_widget(
+                    widget,
+                    request_chunks[1],
+                    &mut self.form_table_state.clone(),
+                );
+            }
+            5 => self
+                .graphql
+                .draw(frame, request_chunks[1], self.request.kind),
             _ => (),
         };
 
-        let response_block = Block::default().title("Response").borders(Borders::ALL);
+        if let Some(picker_state) = &self.method_picker {
+            let height = HttpMethod::standard().len() as u16 + 3;
+            let popup = centered_rect(24, height, request_chunks[1]);
+            frame.render_widget(Clear, popup);
+            frame.render_stateful_widget(self.method_picker(), popup, &mut picker_state.clone());
+        }
+
+        if self.show_certificates {
+            let certificates_block = Block::default()
+                .title("Peer Certificate Chain")
+                .borders(Borders::ALL);
+            let certificates_para = Paragraph::new(self.certificate_chain())
+                .block(certificates_block)
+                .wrap(Wrap { trim: false });
+            frame.render_widget(certificates_para, response_chunk);
+            return;
+        }
+
+        if let Some(stream) = &self.stream {
+            stream_view::draw(frame, response_chunk, stream);
+            return;
+        }
+
+        let response_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(response_chunk);
+
+        let (body_chunk, prompt_chunk) =
+            if self.save_path.is_some() || self.status_message.is_some() {
+                (response_chunks[0], Some(response_chunks[1]))
+            } else {
+                (response_chunk, None)
+            };
+
+        let response_block = Block::default()
+            .title(self.response_title())
+            .borders(Borders::ALL);
+
+        if self.response.is_some() {
+            let response_para = Paragraph::new(self.response_lines()).block(response_block);
+            frame.render_widget(response_para, body_chunk);
+        }
+
+        if let Some(prompt_chunk) = prompt_chunk {
+            let prompt = match (&self.save_path, &self.status_message) {
+                (Some(save_path), _) => Paragraph::new(save_path.as_str())
+                    .style(Style::default().fg(Color::Black).bg(Color::Cyan))
+                    .block(Block::default().title("Save body to").borders(Borders::ALL)),
+                (None, Some(message)) => {
+                    Paragraph::new(message.as_str()).block(Block::default().borders(Borders::ALL))
+                }
+                (None, None) => Paragraph::new(""),
+            };
+            frame.render_widget(prompt, prompt_chunk);
+        }
+    }
+}
+
+impl Drawable for RequestWidget {
+    fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
+        let rect = draw_insecure_banner(frame, rect, self.tls.as_ref());
+        self.draw_panes(frame, rect);
 
-        if let Some(response) = &mut self.response {
-            let response_para = Paragraph::new(response.as_str()).block(response_block);
-            frame.render_widget(response_para, response_chunk);
+        if let Some(copy_as) = &self.copy_as {
+            let popup = centered_rect(
+                rect.width.saturating_sub(4),
+                rect.height.saturating_sub(2),
+                rect,
+            );
+            frame.render_widget(Clear, popup);
+            frame.render_widget(Self::copy_as_popup(copy_as), popup);
         }
     }
 }
@@ -488,21 +2273,54 @@ impl RightWidget {
             RightType::Api(api_id) => {
                 let api = api_service::get_api_by_id(*api_id).unwrap();
                 let resources = resource_service::get_resources_for_api(*api_id);
+                let tls = tls_service::get_tls_config_for_api(*api_id);
 
-                Some(Content::Api(ApiWidget { api, resources }))
+                Some(Content::Api(ApiWidget::new(api, resources, tls)))
             }
             RightType::Resource(resource_id) => {
                 let resource = resource_service::get_resource_by_id(*resource_id).unwrap();
+                let folders = resource_service::get_child_resources(*resource_id);
                 let requests = request_service::get_requests_for_resource(*resource_id).unwrap();
 
-                Some(Content::Resource(ResourceWidget { resource, requests }))
+                Some(Content::Resource(ResourceWidget {
+                    resource,
+                    folders,
+                    requests,
+                    description_input: None,
+                }))
             }
             RightType::Request(request_id) => {
                 let request = request_service::get_request_by_id(*request_id).unwrap();
                 let headers = header_service::get_headers_for_request(*request_id);
+                let tls = http::tls_config_for_resource(request.resource_id);
+
+                if request.kind == RequestKind::Websocket {
+                    let templates =
+                        message_template_service::get_templates_for_request(*request_id);
+
+                    return Some(Content::WebSocket(Box::new(WebSocketWidget::new(
+                        request, headers, templates, tls,
+                    ))));
+                }
 
-                Some(Content::Request(RequestWidget::new(request, headers)))
+                if request.kind == RequestKind::Grpc {
+                    return Some(Content::Grpc(Box::new(GrpcWidget::new(
+                        request, headers, tls,
+                    ))));
+                }
+
+                let form_parts = form_part_service::get_form_parts_for_request(*request_id);
+                let api_id = resource_service::get_resource_by_id(request.resource_id)
+                    .map(|resource| resource.api_id);
+                let graphql = GraphqlPane::new(*request_id, api_id);
+
+                Some(Content::Request(Box::new(RequestWidget::new(
+                    request, headers, form_parts, tls, graphql,
+                ))))
             }
+            RightType::HarImport(path, api_id) => Some(Content::HarImport(Box::new(
+                HarImportWidget::new(path, *api_id),
+            ))),
             RightType::None => None,
         }
     }
@@ -513,13 +2331,30 @@ impl RightWidget {
                 Content::Api(api_widget) => api_widget.draw(frame, rect),
                 Content::Resource(resource_widget) => resource_widget.draw(frame, rect),
                 Content::Request(request_widget) => request_widget.draw(frame, rect),
+                Content::WebSocket(websocket_widget) => websocket_widget.draw(frame, rect),
+                Content::Grpc(grpc_widget) => grpc_widget.draw(frame, rect),
+                Content::HarImport(har_widget) => har_widget.draw(frame, rect),
             }
         }
     }
 
+    pub fn tick(&mut self) {
+        match &mut self.content {
+            Some(Content::Request(request_widget)) => request_widget.tick(),
+            Some(Content::WebSocket(websocket_widget)) => websocket_widget.tick(),
+            Some(Content::Grpc(grpc_widget)) => grpc_widget.tick(),
+            _ => (),
+        }
+    }
+
     pub fn handle_input(&mut self, key_code: KeyCode) -> RightInputResult {
         match &mut self.content {
             Some(Content::Request(request_widget)) => request_widget.handle_input(key_code),
+            Some(Content::Api(api_widget)) => api_widget.handle_input(key_code),
+            Some(Content::Resource(resource_widget)) => resource_widget.handle_input(key_code),
+            Some(Content::WebSocket(websocket_widget)) => websocket_widget.handle_input(key_code),
+            Some(Content::Grpc(grpc_widget)) => grpc_widget.handle_input(key_code),
+            Some(Content::HarImport(har_widget)) => har_widget.handle_input(key_code),
             _ => RightInputResult::None,
         }
     }
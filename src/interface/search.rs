@@ -0,0 +1,157 @@
+use std::{collections::HashMap, io::Stdout};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{List, ListItem, ListState},
+    Frame,
+};
+
+use super::finder::{draw_popup, request_spans, resource_paths, FinderResult};
+use crate::{
+    models::Request,
+    services::{
+        request_service,
+        search_service::{self, SearchHit, MATCH_END, MATCH_START},
+    },
+};
+
+/// A popup searching the bodies, headers, params and recorded responses of
+/// every request for words, showing where they were found.
+pub struct Search {
+    query: String,
+    hits: Vec<SearchHit>,
+    list_state: ListState,
+    requests: HashMap<i32, Request>,
+    /// The API and folders of every resource.
+    paths: HashMap<i32, String>,
+}
+
+impl Search {
+    pub fn new() -> Self {
+        Self {
+            query: String::new(),
+            hits: vec![],
+            list_state: ListState::default(),
+            requests: request_service::get_all_requests()
+                .into_iter()
+                .map(|request| (request.id, request))
+                .collect(),
+            paths: resource_paths(),
+        }
+    }
+
+    fn update_hits(&mut self) {
+        self.hits = search_service::search(&self.query)
+            .into_iter()
+            .filter(|hit| self.requests.contains_key(&hit.request_id))
+            .collect();
+        self.list_state
+            .select(if self.hits.is_empty() { None } else { Some(0) });
+    }
+
+    fn select_next(&mut self, forward: bool) {
+        let count = self.hits.len();
+        if count == 0 {
+            return;
+        }
+        let selected = self.list_state.selected().unwrap_or(0);
+        self.list_state.select(Some(if forward {
+            (selected + 1) % count
+        } else {
+            (selected + count - 1) % count
+        }));
+    }
+
+    pub fn handle_input(&mut self, key: KeyEvent) -> FinderResult {
+        let control = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => return FinderResult::Close,
+            KeyCode::Enter => {
+                return match self
+                    .list_state
+                    .selected()
+                    .and_then(|selected| self.hits.get(selected))
+                {
+                    Some(hit) => FinderResult::Open(hit.request_id),
+                    None => FinderResult::None,
+                }
+            }
+            KeyCode::Down => self.select_next(true),
+            KeyCode::Up => self.select_next(false),
+            KeyCode::Char('n') if control => self.select_next(true),
+            KeyCode::Char('p') if control => self.select_next(false),
+            KeyCode::Char(c) if !control => {
+                self.query.push(c);
+                self.update_hits();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.update_hits();
+            }
+            _ => (),
+        }
+        FinderResult::None
+    }
+
+    pub fn draw(&self, frame: &mut Frame<CrosstermBackend<Stdout>>, area: Rect) {
+        let title = format!(
+            "Search bodies, headers, params and responses - {} found",
+            self.hits.len()
+        );
+        let list_area = draw_popup(frame, area, &title, &self.query);
+
+        let items: Vec<_> = self
+            .hits
+            .iter()
+            .filter_map(|hit| {
+                let request = self.requests.get(&hit.request_id)?;
+                let path = self
+                    .paths
+                    .get(&request.resource_id)
+                    .map(String::as_str)
+                    .unwrap_or_default();
+                let mut snippet = vec![Span::styled(
+                    format!("    {}: ", hit.source),
+                    Style::default().fg(Color::DarkGray),
+                )];
+                snippet.extend(snippet_spans(&hit.snippet));
+                Some(ListItem::new(Text::from(vec![
+                    Spans::from(request_spans(request, path)),
+                    Spans::from(snippet),
+                ])))
+            })
+            .collect();
+        let list = List::new(items).highlight_style(
+            Style::default()
+                .bg(Color::Yellow)
+                .fg(Color::Black)
+                .add_modifier(Modifier::BOLD),
+        );
+        frame.render_stateful_widget(list, list_area, &mut self.list_state.clone());
+    }
+}
+
+/// A snippet on one line, the words found in it highlighted.
+fn snippet_spans(snippet: &str) -> Vec<Span<'static>> {
+    let mut spans = vec![];
+    let mut found = false;
+    for part in snippet.split([MATCH_START, MATCH_END]) {
+        let text = part.replace(['\n', '\r', '\t'], " ");
+        if found {
+            spans.push(Span::styled(
+                text,
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        } else {
+            spans.push(Span::raw(text));
+        }
+        found = !found;
+    }
+    spans
+}
@@ -0,0 +1,92 @@
+use std::io::Stdout;
+
+use tui::{
+    backend::CrosstermBackend,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use crate::stream::{ResponseStream, StreamFormat, StreamItem, StreamState};
+
+/// Draws the entries of a streamed response, keeping the newest in view.
+pub(super) fn draw(
+    frame: &mut Frame<CrosstermBackend<Stdout>>,
+    rect: Rect,
+    stream: &ResponseStream,
+) {
+    let block = Block::default().title(title(stream)).borders(Borders::ALL);
+    let lines = lines(stream);
+    let visible = rect.height.saturating_sub(2) as usize;
+    let scroll = lines.len().saturating_sub(visible) as u16;
+    let para = Paragraph::new(lines).block(block).scroll((scroll, 0));
+    frame.render_widget(para, rect);
+}
+
+fn title(stream: &ResponseStream) -> String {
+    let state = match &stream.state {
+        StreamState::Receiving => String::from("receiving, S to stop"),
+        StreamState::Finished => String::from("finished"),
+        StreamState::Stopped => String::from("stopped"),
+        StreamState::Failed(err) => format!("failed: {}", err),
+    };
+    let unit = match stream.format {
+        StreamFormat::Sse => "events",
+        StreamFormat::Lines => "lines",
+    };
+
+    format!(
+        "Stream - {} - {} - {} {} - {}",
+        stream.status,
+        stream.content_type.as_deref().unwrap_or("-"),
+        stream.entries.len(),
+        unit,
+        state
+    )
+}
+
+fn lines(stream: &ResponseStream) -> Vec<Spans<'_>> {
+    let time_style = Style::default().fg(Color::DarkGray);
+    let field_style = Style::default()
+        .fg(Color::Green)
+        .add_modifier(Modifier::BOLD);
+
+    stream
+        .entries
+        .iter()
+        .flat_map(|entry| {
+            let time = Span::styled(
+                entry.received_at.format("%H:%M:%S%.3f ").to_string(),
+                time_style,
+            );
+
+            match &entry.item {
+                StreamItem::Line(line) => vec![Spans::from(vec![time, Span::raw(line)])],
+                StreamItem::Event(event) => {
+                    let mut header = vec![time];
+                    header.push(Span::styled(
+                        event.event.as_deref().unwrap_or("message"),
+                        field_style,
+                    ));
+                    if let Some(id) = &event.id {
+                        header.push(Span::styled(format!("  id: {}", id), time_style));
+                    }
+                    if let Some(retry) = event.retry {
+                        header.push(Span::styled(format!("  retry: {}ms", retry), time_style));
+                    }
+
+                    let mut lines = vec![Spans::from(header)];
+                    lines.extend(
+                        event
+                            .data
+                            .lines()
+                            .map(|line| Spans::from(format!("    {}", line))),
+                    );
+                    lines
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,433 @@
+use std::io::Stdout;
+
+use crossterm::event::KeyCode;
+use tui::{
+    backend::CrosstermBackend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph},
+    Frame,
+};
+
+use super::right_widget::{draw_insecure_banner, Drawable, RightInputResult};
+use crate::{
+    http::{self, HOSTNAME},
+    models::{Header, MessageTemplate, NewMessageTemplate, Request, TlsConfig},
+    services::{message_template_service, request_service},
+    websocket::{self, LogDirection, SessionState, WebSocketSession},
+};
+
+pub(super) struct WebSocketWidget {
+    request: Request,
+    headers: Vec<Header>,
+    tls: Option<TlsConfig>,
+    session: Option<WebSocketSession>,
+    templates: Vec<MessageTemplate>,
+    template_list_state: ListState,
+    compose: Option<String>,
+    compose_binary: bool,
+    url_input: Option<String>,
+    status_message: Option<String>,
+}
+
+impl WebSocketWidget {
+    pub(super) fn new(
+        request: Request,
+        headers: Vec<Header>,
+        templates: Vec<MessageTemplate>,
+        tls: Option<TlsConfig>,
+    ) -> Self {
+        Self {
+            request,
+            headers,
+            tls,
+            session: None,
+            templates,
+            template_list_state: ListState::default(),
+            compose: None,
+            compose_binary: false,
+            url_input: None,
+            status_message: None,
+        }
+    }
+
+    /// Relative routes get the scheme of the hostname swapped for its
+    /// WebSocket counterpart.
+    fn url(&self) -> String {
+        let variables = http::variables_for_resource(self.request.resource_id);
+        let route = http::expand_variables(&self.request.route, &variables);
+        let url = http::request_url(HOSTNAME, &route);
+
+        if let Some(rest) = url.strip_prefix("https://") {
+            format!("wss://{}", rest)
+        } else if let Some(rest) = url.strip_prefix("http://") {
+            format!("ws://{}", rest)
+        } else {
+            url
+        }
+    }
+
+    fn is_open(&self) -> bool {
+        self.session
+            .as_ref()
+            .is_some_and(|session| session.state != SessionState::Closed)
+    }
+
+    fn toggle_connection(&mut self) {
+        if self.is_open() {
+            if let Some(session) = &self.session {
+                session.close();
+            }
+            return;
+        }
+
+        let headers = self
+            .headers
+            .iter()
+            .map(|header| (header.key.clone(), header.value.clone()))
+            .collect();
+        request_service::mark_run(self.request.id);
+        self.session = Some(WebSocketSession::connect(
+            self.url(),
+            headers,
+            self.tls.clone(),
+        ));
+    }
+
+    pub(super) fn tick(&mut self) {
+        if let Some(session) = &mut self.session {
+            session.poll();
+        }
+    }
+
+    fn send(&mut self, body: &str, binary: bool) {
+        let session = match &self.session {
+            Some(session) if session.state == SessionState::Connected => session,
+            _ => {
+                self.status_message
+                    .replace(String::from("Not connected, press c to connect"));
+                return;
+            }
+        };
+
+        if binary {
+            match websocket::parse_hex(body) {
+                Ok(bytes) => session.send_binary(bytes),
+                Err(err) => {
+                    self.status_message.replace(err);
+                    return;
+                }
+            }
+        } else {
+            session.send_text(body.to_string());
+        }
+        self.status_message.take();
+    }
+
+    fn selected_template(&self) -> Option<&MessageTemplate> {
+        self.template_list_state
+            .selected()
+            .and_then(|index| self.templates.get(index))
+    }
+
+    fn refresh_templates(&mut self) {
+        self.templates = message_template_service::get_templates_for_request(self.request.id);
+
+        if self.templates.is_empty() {
+            self.template_list_state.select(None);
+        } else if let Some(selected) = self.template_list_state.selected() {
+            self.template_list_state
+                .select(Some(selected.min(self.templates.len() - 1)));
+        }
+    }
+
+    /// Stores the message being composed as a template named after its
+    /// first line.
+    fn save_template(&mut self) {
+        let body = match &self.compose {
+            Some(body) if !body.is_empty() => body.clone(),
+            _ => return,
+        };
+        let name: String = body.lines().next().unwrap_or("").chars().take(32).collect();
+
+        message_template_service::create_new_template(NewMessageTemplate {
+            name: &name,
+            body: &body,
+            binary: self.compose_binary,
+            request_id: self.request.id,
+        });
+        self.refresh_templates();
+        self.status_message
+            .replace(format!("Saved template \"{}\"", name));
+    }
+
+    fn move_down(&mut self) {
+        if self.templates.is_empty() {
+            return;
+        }
+        let next = match self.template_list_state.selected() {
+            Some(index) if index + 1 < self.templates.len() => index + 1,
+            Some(index) => index,
+            None => 0,
+        };
+        self.template_list_state.select(Some(next));
+    }
+
+    fn move_up(&mut self) {
+        if let Some(index) = self.template_list_state.selected() {
+            self.template_list_state
+                .select(Some(index.saturating_sub(1)));
+        }
+    }
+
+    fn handle_compose_input(&mut self, key: KeyCode) -> RightInputResult {
+        match key {
+            KeyCode::Char(character) => {
+                if let Some(compose) = &mut self.compose {
+                    compose.push(character);
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(compose) = &mut self.compose {
+                    compose.pop();
+                }
+            }
+            KeyCode::Tab => self.save_template(),
+            KeyCode::Enter => {
+                if let Some(body) = self.compose.clone() {
+                    self.send(&body, self.compose_binary);
+                    if self.status_message.is_none() {
+                        self.compose.take();
+                    }
+                }
+            }
+            KeyCode::Esc => {
+                self.compose.take();
+            }
+            _ => (),
+        }
+        RightInputResult::None
+    }
+
+    fn handle_url_input(&mut self, key: KeyCode) -> RightInputResult {
+        match key {
+            KeyCode::Char(character) => {
+                if let Some(input) = &mut self.url_input {
+                    input.push(character);
+                }
+                RightInputResult::None
+            }
+            KeyCode::Backspace => {
+                if let Some(input) = &mut self.url_input {
+                    input.pop();
+                }
+                RightInputResult::None
+            }
+            KeyCode::Enter => {
+                let input = self.url_input.take().unwrap_or_default();
+                self.request = request_service::update_request_route(&self.request, &input);
+                RightInputResult::RefreshRequests
+            }
+            KeyCode::Esc => {
+                self.url_input.take();
+                RightInputResult::None
+            }
+            _ => RightInputResult::None,
+        }
+    }
+
+    pub(super) fn handle_input(&mut self, key: KeyCode) -> RightInputResult {
+        if self.compose.is_some() {
+            return self.handle_compose_input(key);
+        }
+
+        if self.url_input.is_some() {
+            return self.handle_url_input(key);
+        }
+
+        match key {
+            KeyCode::Char('q') => RightInputResult::Exit,
+            KeyCode::Char('c') => {
+                self.toggle_connection();
+                RightInputResult::None
+            }
+            KeyCode::Char('m') => {
+                self.compose.replace(String::new());
+                self.compose_binary = false;
+                RightInputResult::None
+            }
+            KeyCode::Char('b') => {
+                self.compose.replace(String::new());
+                self.compose_binary = true;
+                RightInputResult::None
+            }
+            KeyCode::Char('e') => {
+                self.url_input.replace(self.request.route.clone());
+                RightInputResult::None
+            }
+            KeyCode::Char('o') => {
+                if let Some(template) = self.selected_template() {
+                    let (body, binary) = (template.body.clone(), template.binary);
+                    self.compose.replace(body);
+                    self.compose_binary = binary;
+                }
+                RightInputResult::None
+            }
+            KeyCode::Char('d') => {
+                if let Some(template) = self.selected_template() {
+                    message_template_service::delete_template(template);
+                    self.refresh_templates();
+                }
+                RightInputResult::None
+            }
+            KeyCode::Char('j') => {
+                self.move_down();
+                RightInputResult::None
+            }
+            KeyCode::Char('k') => {
+                self.move_up();
+                RightInputResult::None
+            }
+            KeyCode::Enter => {
+                if let Some(template) = self.selected_template() {
+                    let (body, binary) = (template.body.clone(), template.binary);
+                    self.send(&body, binary);
+                }
+                RightInputResult::None
+            }
+            KeyCode::Backspace => RightInputResult::LoseFocus,
+            _ => RightInputResult::None,
+        }
+    }
+
+    fn session_title(&self) -> String {
+        let state = match &self.session {
+            None => "disconnected, c to connect",
+            Some(session) => match session.state {
+                SessionState::Connecting => "connecting",
+                SessionState::Connected => "connected, c to close",
+                SessionState::Closed => "closed, c to reconnect",
+            },
+        };
+
+        format!("Session ({})", state)
+    }
+
+    fn log_lines(&self) -> Vec<Spans<'_>> {
+        let session = match &self.session {
+            Some(session) => session,
+            None => return vec![],
+        };
+
+        session
+            .log
+            .iter()
+            .map(|entry| {
+                let (marker, color) = match entry.direction {
+                    LogDirection::Sent => ("-> ", Color::Cyan),
+                    LogDirection::Received => ("<- ", Color::Green),
+                    LogDirection::Info => ("-- ", Color::DarkGray),
+                };
+                Spans::from(vec![
+                    Span::styled(
+                        entry.at.format("%H:%M:%S%.3f ").to_string(),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(marker, Style::default().fg(color)),
+                    Span::raw(entry.text.as_str()),
+                ])
+            })
+            .collect()
+    }
+
+    fn template_list(&self) -> List<'_> {
+        let items: Vec<_> = self
+            .templates
+            .iter()
+            .map(|template| {
+                let badge = if template.binary { "BIN " } else { "TXT " };
+                ListItem::new(Spans::from(vec![
+                    Span::styled(badge, Style::default().fg(Color::DarkGray)),
+                    Span::raw(template.name.as_str()),
+                ]))
+            })
+            .collect();
+
+        List::new(items)
+            .block(Block::default().title("Templates").borders(Borders::ALL))
+            .highlight_style(Style::default().fg(Color::Black).bg(Color::Yellow))
+    }
+}
+
+impl Drawable for WebSocketWidget {
+    fn draw(&mut self, frame: &mut Frame<CrosstermBackend<Stdout>>, rect: Rect) {
+        let rect = draw_insecure_banner(frame, rect, self.tls.as_ref());
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Min(0),
+                Constraint::Length(3),
+            ])
+            .split(rect);
+
+        let url_para = match &self.url_input {
+            Some(input) => Paragraph::new(input.as_str())
+                .style(Style::default().fg(Color::Black).bg(Color::Cyan)),
+            None => Paragraph::new(self.url()),
+        };
+        frame.render_widget(
+            url_para.block(
+                Block::default()
+                    .title("WebSocket URL")
+                    .borders(Borders::ALL),
+            ),
+            chunks[0],
+        );
+
+        let body_chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+            .split(chunks[1]);
+
+        let lines = self.log_lines();
+        let visible = body_chunks[0].height.saturating_sub(2) as usize;
+        let scroll = lines.len().saturating_sub(visible) as u16;
+        let log_para = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .title(self.session_title())
+                    .borders(Borders::ALL),
+            )
+            .scroll((scroll, 0));
+        frame.render_widget(log_para, body_chunks[0]);
+
+        frame.render_stateful_widget(
+            self.template_list(),
+            body_chunks[1],
+            &mut self.template_list_state.clone(),
+        );
+
+        let compose_para = match (&self.compose, &self.status_message) {
+            (Some(compose), _) => {
+                let title = if self.compose_binary {
+                    "Binary message (hex), Enter to send, Tab to save"
+                } else {
+                    "Text message, Enter to send, Tab to save"
+                };
+                Paragraph::new(compose.as_str())
+                    .style(Style::default().fg(Color::Black).bg(Color::Cyan))
+                    .block(Block::default().title(title).borders(Borders::ALL))
+            }
+            (None, Some(message)) => {
+                Paragraph::new(message.as_str()).block(Block::default().borders(Borders::ALL))
+            }
+            (None, None) => Paragraph::new("m text, b binary, o open template, d delete template")
+                .style(Style::default().fg(Color::DarkGray))
+                .block(Block::default().borders(Borders::ALL)),
+        };
+        frame.render_widget(compose_para, chunks[2]);
+    }
+}
@@ -1,22 +1,41 @@
 #[macro_use]
 extern crate diesel;
+#[macro_use]
+extern crate diesel_migrations;
 
 use std::{fs::File, io, path::Path};
 
+use backup::RestoreMode;
 use config::ReqstrConfig;
 use crossterm::terminal::enable_raw_mode;
-use diesel::prelude::*;
-use diesel::sqlite::SqliteConnection;
 use interface::main_window::MainWindow;
 use lazy_static::lazy_static;
+use storage::{Storage, StorageError};
 
+mod backup;
+mod codegen;
 mod config;
+mod curl;
+mod docs;
+mod filter;
+mod graphql;
+mod grpc;
+mod har;
 mod http;
+mod http_file;
 mod interface;
+mod markdown;
 mod models;
+mod openapi;
+mod postman;
+mod proto;
+mod response;
 mod schema;
 mod services;
+mod storage;
+mod stream;
 mod types;
+mod websocket;
 
 lazy_static! {
     static ref CONFIG: ReqstrConfig = {
@@ -28,11 +47,44 @@ lazy_static! {
             ReqstrConfig::default()
         }
     };
+    static ref STORAGE: Box<dyn Storage> = storage::open(&CONFIG).expect("Error opening storage");
+}
+
+/// Where the services keep everything.
+#[cfg(not(test))]
+pub(crate) fn storage() -> &'static dyn Storage {
+    STORAGE.as_ref()
+}
+
+/// Each test thread gets storage of its own in a temporary directory, so
+/// tests going through the services don't see each other's rows.
+#[cfg(test)]
+pub(crate) fn storage() -> &'static dyn Storage {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static TEST_STORAGE: &'static dyn Storage = {
+            let root = std::env::temp_dir().join(format!(
+                "reqstr-test-storage-{}-{}",
+                std::process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            let _ = std::fs::remove_dir_all(&root);
+            let config = ReqstrConfig {
+                storage_location: root.to_string_lossy().to_string(),
+                storage_backend: config::StorageBackend::Files,
+                tree_view: false,
+            };
+            Box::leak(storage::open(&config).expect("Error opening test storage"))
+        };
+    }
+    TEST_STORAGE.with(|storage| *storage)
 }
 
-pub fn establish_connection() -> SqliteConnection {
-    SqliteConnection::establish(&CONFIG.storage_location)
-        .expect(format!("Error connecting to {}", CONFIG.storage_location).as_str())
+/// Mirrors changes to backends that keep collections outside the database.
+pub fn flush_storage() -> Result<(), StorageError> {
+    storage().flush()
 }
 
 pub fn start() {
@@ -40,3 +92,35 @@ pub fn start() {
 
     MainWindow::new().run();
 }
+
+/// Runs a command given on the command line instead of the interface,
+/// returning what to print.
+pub fn run_command(args: &[String]) -> Result<String, String> {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        ["backup", path] => backup::export_to_file(path)
+            .map(|()| format!("Backed up to {}", path))
+            .map_err(|err| err.to_string()),
+        ["restore", path] | ["restore", path, "--replace"] => {
+            let mode = if args.len() == 3 {
+                RestoreMode::Replace
+            } else {
+                RestoreMode::Merge
+            };
+            let summary = backup::restore(path, mode).map_err(|err| err.to_string())?;
+            flush_storage().map_err(|err| err.to_string())?;
+
+            let mut output = format!("Restored {}: {}", path, summary);
+            for conflict in &summary.conflicts {
+                output.push_str(&format!("\nkept {} as it is", conflict));
+            }
+            Ok(output)
+        }
+        ["docs", api, dir] => docs::export_named_to_dir(api, dir)
+            .map(|pages| format!("Wrote {} pages to {}", pages, dir))
+            .map_err(|err| err.to_string()),
+        _ => Err(String::from(
+            "Usage: http-client [backup <file> | restore <file> [--replace] | docs <api> <dir>]",
+        )),
+    }
+}
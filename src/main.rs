@@ -1,5 +1,17 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    http_client::start();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        http_client::start();
+        return Ok(());
+    }
+
+    match http_client::run_command(&args) {
+        Ok(output) => println!("{}", output),
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    }
 
     Ok(())
 }
@@ -0,0 +1,416 @@
+use std::mem;
+
+use tui::{
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+};
+
+/// What a line of Markdown is, its marker taken off.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineKind {
+    Heading(usize),
+    Item,
+    Numbered(usize),
+    Quote,
+    Code,
+    Text,
+    Blank,
+}
+
+/// A run of text with the same emphasis.
+#[derive(Default)]
+pub struct Piece {
+    pub text: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub code: bool,
+    pub link: Option<String>,
+}
+
+/// Splits Markdown into lines and tells them apart. Only the part of
+/// Markdown descriptions are written in is known: headings, lists, quotes,
+/// fenced code, `code`, **bold**, *emphasis* and [links](url). The lines
+/// fencing code are left out.
+pub fn lines(text: &str) -> Vec<(LineKind, &str)> {
+    let mut lines = vec![];
+    let mut in_code = false;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") {
+            in_code = !in_code;
+            continue;
+        }
+        if in_code {
+            lines.push((LineKind::Code, line));
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|c| *c == '#').count();
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        let line = if trimmed.is_empty() {
+            (LineKind::Blank, "")
+        } else if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            (LineKind::Heading(level), trimmed[level..].trim())
+        } else if let Some(item) = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker))
+        {
+            (LineKind::Item, item.trim())
+        } else if digits > 0 && trimmed[digits..].starts_with(". ") {
+            let number = trimmed[..digits].parse().unwrap_or(1);
+            (LineKind::Numbered(number), trimmed[digits + 2..].trim())
+        } else if let Some(quote) = trimmed.strip_prefix('>') {
+            (LineKind::Quote, quote.trim())
+        } else {
+            (LineKind::Text, trimmed)
+        };
+        lines.push(line);
+    }
+    lines
+}
+
+/// Splits a line into runs of text by their emphasis. Markers that are
+/// never closed are kept as they are.
+pub fn inline(text: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut current = Piece::default();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        let after = &rest[c.len_utf8()..];
+        if current.code {
+            if c == '`' {
+                flush(&mut pieces, &mut current);
+                current.code = false;
+            } else {
+                current.text.push(c);
+            }
+            rest = after;
+            continue;
+        }
+
+        if c == '`' && after.contains('`') {
+            flush(&mut pieces, &mut current);
+            current.code = true;
+        } else if let Some(bold) = rest.strip_prefix("**") {
+            if !current.bold && !bold.contains("**") {
+                current.text.push_str("**");
+            } else {
+                flush(&mut pieces, &mut current);
+                current.bold = !current.bold;
+            }
+            rest = bold;
+            continue;
+        } else if c == '*' && (current.italic || (after.contains('*') && !after.starts_with(' '))) {
+            flush(&mut pieces, &mut current);
+            current.italic = !current.italic;
+        } else if let Some((label, url, length)) = link(rest) {
+            flush(&mut pieces, &mut current);
+            pieces.push(Piece {
+                text: label.to_string(),
+                link: Some(url.to_string()),
+                bold: current.bold,
+                italic: current.italic,
+                code: false,
+            });
+            rest = &rest[length..];
+            continue;
+        } else {
+            current.text.push(c);
+        }
+        rest = after;
+    }
+    flush(&mut pieces, &mut current);
+    pieces
+}
+
+fn flush(pieces: &mut Vec<Piece>, current: &mut Piece) {
+    if current.text.is_empty() {
+        return;
+    }
+    pieces.push(Piece {
+        text: mem::take(&mut current.text),
+        bold: current.bold,
+        italic: current.italic,
+        code: current.code,
+        link: None,
+    });
+}
+
+/// The label and URL of a `[label](url)` link at the start of `text`, and
+/// how long it is.
+fn link(text: &str) -> Option<(&str, &str, usize)> {
+    let label_end = text.strip_prefix('[')?.find("](")? + 1;
+    let url_end = text[label_end + 2..].find(')')? + label_end + 2;
+    Some((
+        &text[1..label_end],
+        &text[label_end + 2..url_end],
+        url_end + 1,
+    ))
+}
+
+/// Markdown as lines for the terminal.
+pub fn to_spans(text: &str) -> Vec<Spans<'static>> {
+    lines(text)
+        .into_iter()
+        .map(|(kind, line)| {
+            let mut spans = match kind {
+                LineKind::Heading(level) => {
+                    let mut style = Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD);
+                    if level == 1 {
+                        style = style.add_modifier(Modifier::UNDERLINED);
+                    }
+                    return Spans::from(Span::styled(line.to_string(), style));
+                }
+                LineKind::Code => {
+                    return Spans::from(Span::styled(
+                        line.to_string(),
+                        Style::default().fg(Color::Yellow),
+                    ))
+                }
+                LineKind::Blank => return Spans::default(),
+                LineKind::Item => vec![Span::raw("  • ")],
+                LineKind::Numbered(number) => vec![Span::raw(format!("  {}. ", number))],
+                LineKind::Quote => vec![Span::styled("│ ", Style::default().fg(Color::DarkGray))],
+                LineKind::Text => vec![],
+            };
+            spans.extend(inline(line).into_iter().map(|piece| {
+                let mut style = Style::default();
+                if piece.bold {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                if piece.italic || kind == LineKind::Quote {
+                    style = style.add_modifier(Modifier::ITALIC);
+                }
+                if piece.code {
+                    style = style.fg(Color::Yellow);
+                }
+                if piece.link.is_some() {
+                    style = style.fg(Color::Blue).add_modifier(Modifier::UNDERLINED);
+                }
+                Span::styled(piece.text, style)
+            }));
+            Spans::from(spans)
+        })
+        .collect()
+}
+
+/// Markdown as HTML. Consecutive lines of text make one paragraph, and
+/// list items one list.
+pub fn to_html(text: &str) -> String {
+    let mut html = String::new();
+    // The element open around the last lines, closed when the kind changes.
+    let mut open: Option<&str> = None;
+
+    for (kind, line) in lines(text) {
+        let wanted = match kind {
+            LineKind::Item => Some("ul"),
+            LineKind::Numbered(_) => Some("ol"),
+            LineKind::Quote => Some("blockquote"),
+            LineKind::Code => Some("pre"),
+            LineKind::Text => Some("p"),
+            LineKind::Heading(_) | LineKind::Blank => None,
+        };
+        if open != wanted {
+            if let Some(tag) = open.take() {
+                html.push_str(close(tag));
+            }
+            if let Some(tag) = wanted {
+                html.push_str(match tag {
+                    "pre" => "<pre><code>",
+                    "ul" => "<ul>\n",
+                    "ol" => "<ol>\n",
+                    "blockquote" => "<blockquote>",
+                    _ => "<p>",
+                });
+            }
+            open = wanted;
+        } else if matches!(kind, LineKind::Text | LineKind::Quote) {
+            html.push('\n');
+        }
+
+        match kind {
+            LineKind::Heading(level) => html.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                inline_html(line),
+                level = level
+            )),
+            LineKind::Item | LineKind::Numbered(_) => {
+                html.push_str(&format!("<li>{}</li>\n", inline_html(line)))
+            }
+            LineKind::Code => {
+                html.push_str(&escape(line));
+                html.push('\n');
+            }
+            LineKind::Quote | LineKind::Text => html.push_str(&inline_html(line)),
+            LineKind::Blank => (),
+        }
+    }
+    if let Some(tag) = open {
+        html.push_str(close(tag));
+    }
+    html
+}
+
+fn close(tag: &str) -> &'static str {
+    match tag {
+        "ul" => "</ul>\n",
+        "ol" => "</ol>\n",
+        "blockquote" => "</blockquote>\n",
+        "pre" => "</code></pre>\n",
+        _ => "</p>\n",
+    }
+}
+
+/// A line of Markdown as HTML, its emphasis and links kept.
+pub fn inline_html(text: &str) -> String {
+    inline(text)
+        .into_iter()
+        .map(|piece| {
+            let mut html = escape(&piece.text);
+            if piece.code {
+                html = format!("<code>{}</code>", html);
+            }
+            if piece.italic {
+                html = format!("<em>{}</em>", html);
+            }
+            if piece.bold {
+                html = format!("<strong>{}</strong>", html);
+            }
+            match piece.link {
+                Some(link) if is_safe_link(&link) => {
+                    format!("<a href=\"{}\">{}</a>", escape(link.trim()), html)
+                }
+                _ => html,
+            }
+        })
+        .collect()
+}
+
+/// Whether a link may be followed from documentation: web and mail links,
+/// and links relative to the page. Any other scheme, such as `javascript:`
+/// or `data:`, is left as text.
+fn is_safe_link(link: &str) -> bool {
+    let link = link.trim();
+    let scheme = link
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "+-.".contains(c)))
+        .filter(|end| link[*end..].starts_with(':'))
+        .map(|end| link[..end].to_ascii_lowercase());
+    match scheme {
+        Some(scheme) => ["http", "https", "mailto"].contains(&scheme.as_str()),
+        None => !link.contains(':') || link.starts_with(['/', '.', '#', '?']),
+    }
+}
+
+/// Text with the characters HTML gives a meaning escaped.
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lines_are_told_apart() {
+        let text = "# Title\n- item\n2. second\n> quote\n```\n# not a heading\n```\n\ntext";
+        let kinds: Vec<_> = lines(text).into_iter().collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (LineKind::Heading(1), "Title"),
+                (LineKind::Item, "item"),
+                (LineKind::Numbered(2), "second"),
+                (LineKind::Quote, "quote"),
+                (LineKind::Code, "# not a heading"),
+                (LineKind::Blank, ""),
+                (LineKind::Text, "text"),
+            ]
+        );
+    }
+
+    #[test]
+    fn inline_emphasis_and_code() {
+        let pieces = inline("a **b** *c* `d*e` [f](g) 2 * 3");
+        let found: Vec<_> = pieces
+            .iter()
+            .map(|piece| {
+                (
+                    piece.text.as_str(),
+                    piece.bold,
+                    piece.italic,
+                    piece.code,
+                    piece.link.as_deref(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("a ", false, false, false, None),
+                ("b", true, false, false, None),
+                (" ", false, false, false, None),
+                ("c", false, true, false, None),
+                (" ", false, false, false, None),
+                ("d*e", false, false, true, None),
+                (" ", false, false, false, None),
+                ("f", false, false, false, Some("g")),
+                (" 2 * 3", false, false, false, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unclosed_bold_is_kept() {
+        assert_eq!(inline_html("**not bold"), "**not bold");
+    }
+
+    #[test]
+    fn html_paragraphs_and_lists() {
+        assert_eq!(
+            to_html("one\ntwo\n\n- a\n- b\n\n```\n<x>\n```"),
+            "<p>one\ntwo</p>\n<ul>\n<li>a</li>\n<li>b</li>\n</ul>\n<pre><code>&lt;x&gt;\n</code></pre>\n"
+        );
+    }
+
+    #[test]
+    fn only_web_mail_and_relative_links_are_kept() {
+        for link in [
+            "https://example.com",
+            "HTTP://example.com",
+            "mailto:api@example.com",
+            "pets.html",
+            "../index.html#top",
+            "/docs?page=1",
+        ] {
+            assert!(is_safe_link(link), "{}", link);
+        }
+        for link in [
+            "javascript:alert(1)",
+            " JavaScript:alert(1)",
+            "data:text/html,<script>alert(1)</script>",
+            "vbscript:msgbox",
+            "file:///etc/passwd",
+        ] {
+            assert!(!is_safe_link(link), "{}", link);
+        }
+        assert_eq!(
+            inline_html("[x](data:text/html,hi) [y](https://a.b/?q=\"1\")"),
+            "x <a href=\"https://a.b/?q=&quot;1&quot;\">y</a>"
+        );
+    }
+}
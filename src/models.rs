@@ -1,12 +1,18 @@
 use crate::schema::*;
-use crate::types::HttpMethod;
+use crate::types::{FormPartKind, HttpMethod, RequestKind, TlsVersion, TreeNodeKind};
 
+use chrono::{DateTime, FixedOffset};
 use diesel::Queryable;
 
-#[derive(Queryable)]
+#[derive(Queryable, Identifiable)]
+#[table_name = "api"]
 pub struct Api {
     pub id: i32,
     pub name: String,
+    /// Markdown shown with the API.
+    pub description: Option<String>,
+    /// Where the API is listed, by its id when it was never moved.
+    pub position: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -15,11 +21,37 @@ pub struct NewApi<'a> {
     pub name: &'a str,
 }
 
-#[derive(Queryable)]
+/// A `{{key}}` placeholder that routes, headers, params and bodies of the
+/// API's requests can refer to.
+#[derive(Queryable, Identifiable)]
+#[table_name = "api_variable"]
+pub struct ApiVariable {
+    pub id: i32,
+    pub api_id: i32,
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "api_variable"]
+pub struct NewApiVariable<'a> {
+    pub api_id: i32,
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+#[derive(Queryable, Identifiable)]
+#[table_name = "resource"]
 pub struct Resource {
     pub id: i32,
     pub name: String,
     pub api_id: i32,
+    /// The folder this one is in, none for the folders at the top of an
+    /// API.
+    pub parent_id: Option<i32>,
+    pub description: Option<String>,
+    /// Where the folder is listed, by its id when it was never moved.
+    pub position: Option<i32>,
 }
 
 #[derive(Insertable)]
@@ -27,6 +59,7 @@ pub struct Resource {
 pub struct NewResource<'a> {
     pub name: &'a str,
     pub api_id: i32,
+    pub parent_id: Option<i32>,
 }
 
 #[derive(Queryable, Identifiable)]
@@ -37,6 +70,34 @@ pub struct Request {
     pub method: HttpMethod,
     pub body: Option<String>,
     pub resource_id: i32,
+    pub kind: RequestKind,
+    /// What the request is listed as, its route when there is none.
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// Where the request is listed, by its id when it was never moved.
+    pub position: Option<i32>,
+    /// When the request was last sent, in RFC 3339.
+    pub last_run_at: Option<String>,
+    /// Starred to be listed above the APIs.
+    pub favourite: bool,
+}
+
+impl Request {
+    /// The name of the request, or else its method and route.
+    pub fn title(&self) -> String {
+        match &self.name {
+            Some(name) if !name.is_empty() => name.clone(),
+            _ => format!("{} {}", self.method, self.route),
+        }
+    }
+
+    /// When the request was last sent. Stored with the local offset of the
+    /// time, so it has to be parsed to be compared.
+    pub fn last_run(&self) -> Option<DateTime<FixedOffset>> {
+        self.last_run_at
+            .as_deref()
+            .and_then(|time| DateTime::parse_from_rfc3339(time).ok())
+    }
 }
 
 #[derive(Insertable, AsChangeset)]
@@ -46,6 +107,7 @@ pub struct NewRequest<'a> {
     pub route: &'a str,
     pub method: HttpMethod,
     pub body: Option<&'a str>,
+    pub kind: RequestKind,
 }
 
 #[derive(Queryable, Identifiable)]
@@ -81,3 +143,332 @@ pub struct NewParam<'a> {
     pub value: &'a str,
     pub request_id: i32,
 }
+
+/// A free-form label of a request, to filter by.
+#[derive(Queryable, Identifiable)]
+#[table_name = "tag"]
+pub struct Tag {
+    pub id: i32,
+    pub request_id: i32,
+    pub name: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "tag"]
+pub struct NewTag<'a> {
+    pub request_id: i32,
+    pub name: &'a str,
+}
+
+#[derive(Queryable, Identifiable)]
+#[table_name = "form_part"]
+pub struct FormPart {
+    pub id: i32,
+    pub name: String,
+    pub kind: FormPartKind,
+    pub value: String,
+    pub content_type: Option<String>,
+    pub request_id: i32,
+}
+
+impl FormPart {
+    pub fn changes(&self) -> NewFormPart<'_> {
+        NewFormPart {
+            name: &self.name,
+            kind: self.kind,
+            value: &self.value,
+            content_type: self.content_type.as_deref(),
+            request_id: self.request_id,
+        }
+    }
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "form_part"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewFormPart<'a> {
+    pub name: &'a str,
+    pub kind: FormPartKind,
+    pub value: &'a str,
+    pub content_type: Option<&'a str>,
+    pub request_id: i32,
+}
+
+#[derive(Queryable, Identifiable)]
+#[table_name = "graphql_query"]
+pub struct GraphqlQuery {
+    pub id: i32,
+    pub request_id: i32,
+    pub query: String,
+    pub variables: String,
+    pub operation_name: Option<String>,
+}
+
+impl GraphqlQuery {
+    pub fn changes(&self) -> NewGraphqlQuery<'_> {
+        NewGraphqlQuery {
+            request_id: self.request_id,
+            query: &self.query,
+            variables: &self.variables,
+            operation_name: self.operation_name.as_deref(),
+        }
+    }
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "graphql_query"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewGraphqlQuery<'a> {
+    pub request_id: i32,
+    pub query: &'a str,
+    pub variables: &'a str,
+    pub operation_name: Option<&'a str>,
+}
+
+#[derive(Queryable, Identifiable)]
+#[table_name = "graphql_schema"]
+pub struct GraphqlSchema {
+    pub id: i32,
+    pub api_id: i32,
+    pub introspection: String,
+    pub fetched_at: String,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "graphql_schema"]
+pub struct NewGraphqlSchema<'a> {
+    pub api_id: i32,
+    pub introspection: &'a str,
+    pub fetched_at: &'a str,
+}
+
+#[derive(Queryable, Identifiable)]
+#[table_name = "grpc_call"]
+pub struct GrpcCall {
+    pub id: i32,
+    pub request_id: i32,
+    pub proto_files: String,
+    pub method: Option<String>,
+    pub message: String,
+}
+
+impl GrpcCall {
+    pub fn changes(&self) -> NewGrpcCall<'_> {
+        NewGrpcCall {
+            request_id: self.request_id,
+            proto_files: &self.proto_files,
+            method: self.method.as_deref(),
+            message: &self.message,
+        }
+    }
+
+    /// The `.proto` files to load, or none to use server reflection.
+    pub fn proto_paths(&self) -> Vec<&str> {
+        self.proto_files
+            .split(';')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .collect()
+    }
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "grpc_call"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewGrpcCall<'a> {
+    pub request_id: i32,
+    pub proto_files: &'a str,
+    pub method: Option<&'a str>,
+    pub message: &'a str,
+}
+
+/// The values a request had when it was last imported, so a later import
+/// can tell local edits apart from upstream changes.
+#[derive(Queryable, Identifiable)]
+#[table_name = "import_snapshot"]
+pub struct ImportSnapshot {
+    pub id: i32,
+    pub request_id: i32,
+    pub source_key: String,
+    pub route: String,
+    pub method: HttpMethod,
+    pub body: Option<String>,
+    pub headers: String,
+    pub params: String,
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "import_snapshot"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewImportSnapshot<'a> {
+    pub request_id: i32,
+    pub source_key: &'a str,
+    pub route: &'a str,
+    pub method: HttpMethod,
+    pub body: Option<&'a str>,
+    pub headers: &'a str,
+    pub params: &'a str,
+}
+
+/// An exchange of a request with the server: the request as it was sent
+/// and the response it got.
+#[derive(Queryable, Identifiable)]
+#[table_name = "recorded_response"]
+pub struct RecordedResponse {
+    pub id: i32,
+    pub request_id: i32,
+    pub status: i32,
+    pub content_type: Option<String>,
+    /// The body as text, or base64 encoded when `body_base64` is set.
+    pub body: String,
+    pub recorded_at: String,
+    /// The response headers, as a JSON list of name and value pairs.
+    pub headers: String,
+    pub body_base64: bool,
+    /// How long it took until the whole body was read.
+    pub duration_ms: i32,
+    pub method: String,
+    /// The URL that was requested, with variables expanded and the query
+    /// string added.
+    pub url: String,
+    /// The request headers, as a JSON list of name and value pairs.
+    pub request_headers: String,
+    /// The request body, unless there was none or it was a multipart form.
+    pub request_body: Option<String>,
+}
+
+impl RecordedResponse {
+    /// The body as text, unless it was binary.
+    pub fn text(&self) -> Option<&str> {
+        if self.body_base64 {
+            None
+        } else {
+            Some(&self.body)
+        }
+    }
+
+    pub fn response_headers(&self) -> Vec<(String, String)> {
+        serde_json::from_str(&self.headers).unwrap_or_default()
+    }
+
+    pub fn sent_headers(&self) -> Vec<(String, String)> {
+        serde_json::from_str(&self.request_headers).unwrap_or_default()
+    }
+}
+
+#[derive(Insertable)]
+#[table_name = "recorded_response"]
+pub struct NewRecordedResponse<'a> {
+    pub request_id: i32,
+    pub status: i32,
+    pub content_type: Option<&'a str>,
+    pub body: &'a str,
+    pub recorded_at: &'a str,
+    pub headers: &'a str,
+    pub body_base64: bool,
+    pub duration_ms: i32,
+    pub method: &'a str,
+    pub url: &'a str,
+    pub request_headers: &'a str,
+    pub request_body: Option<&'a str>,
+}
+
+#[derive(Queryable, Identifiable)]
+#[table_name = "message_template"]
+pub struct MessageTemplate {
+    pub id: i32,
+    pub name: String,
+    pub body: String,
+    pub binary: bool,
+    pub request_id: i32,
+}
+
+#[derive(Insertable)]
+#[table_name = "message_template"]
+pub struct NewMessageTemplate<'a> {
+    pub name: &'a str,
+    pub body: &'a str,
+    pub binary: bool,
+    pub request_id: i32,
+}
+
+#[derive(Queryable, Identifiable, Clone)]
+#[table_name = "tls_config"]
+pub struct TlsConfig {
+    pub id: i32,
+    pub api_id: i32,
+    pub ca_bundles: String,
+    pub client_certificate: Option<String>,
+    pub client_key: Option<String>,
+    /// The environment variable holding the password of a PKCS#12
+    /// certificate, so the password itself is never stored.
+    pub client_password_env: Option<String>,
+    pub min_tls_version: Option<TlsVersion>,
+    pub accept_invalid_certs: bool,
+}
+
+impl TlsConfig {
+    /// Paths of the extra PEM root certificate bundles, stored `;`-separated.
+    pub fn ca_bundle_paths(&self) -> Vec<&str> {
+        self.ca_bundles
+            .split(';')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .collect()
+    }
+
+    pub fn changes(&self) -> NewTlsConfig<'_> {
+        NewTlsConfig {
+            api_id: self.api_id,
+            ca_bundles: &self.ca_bundles,
+            client_certificate: self.client_certificate.as_deref(),
+            client_key: self.client_key.as_deref(),
+            client_password_env: self.client_password_env.as_deref(),
+            min_tls_version: self.min_tls_version,
+            accept_invalid_certs: self.accept_invalid_certs,
+        }
+    }
+}
+
+#[derive(Insertable, AsChangeset)]
+#[table_name = "tls_config"]
+#[changeset_options(treat_none_as_null = "true")]
+pub struct NewTlsConfig<'a> {
+    pub api_id: i32,
+    pub ca_bundles: &'a str,
+    pub client_certificate: Option<&'a str>,
+    pub client_key: Option<&'a str>,
+    pub client_password_env: Option<&'a str>,
+    pub min_tls_version: Option<TlsVersion>,
+    pub accept_invalid_certs: bool,
+}
+
+impl<'a> NewTlsConfig<'a> {
+    pub fn for_api(api_id: i32) -> Self {
+        Self {
+            api_id,
+            ca_bundles: "",
+            client_certificate: None,
+            client_key: None,
+            client_password_env: None,
+            min_tls_version: None,
+            accept_invalid_certs: false,
+        }
+    }
+}
+
+/// An API or folder left open in the collection tree.
+#[derive(Queryable, Identifiable)]
+#[table_name = "expanded_node"]
+pub struct ExpandedNode {
+    pub id: i32,
+    pub kind: TreeNodeKind,
+    pub node_id: i32,
+}
+
+#[derive(Insertable)]
+#[table_name = "expanded_node"]
+pub struct NewExpandedNode {
+    pub kind: TreeNodeKind,
+    pub node_id: i32,
+}
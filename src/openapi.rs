@@ -0,0 +1,1044 @@
+use std::{collections::HashSet, fs};
+
+use ::http::StatusCode;
+use chrono::{DateTime, NaiveDate};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{
+    http,
+    models::Request,
+    services::{
+        api_service, form_part_service, header_service,
+        import_service::{self, ImportedApi, ImportedRequest, ImportedResource},
+        param_service, recorded_response_service, request_service, resource_service,
+        variable_service,
+    },
+    types::{FormPartKind, HttpMethod, RequestKind},
+};
+
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+
+/// How deep example bodies are built from nested schemas.
+const SAMPLE_DEPTH: usize = 8;
+
+#[derive(Debug, Error)]
+pub enum OpenApiError {
+    #[error("Not a valid YAML or JSON document: {0}")]
+    Syntax(String),
+    #[error("Not an OpenAPI 3 or Swagger 2 document")]
+    UnknownFormat,
+    #[error("API {0} not found")]
+    UnknownApi(i32),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+}
+
+/// Reads an OpenAPI 3 or Swagger 2 document, YAML or JSON. Operations are
+/// grouped into resources by their first tag, or by the first segment of
+/// their path when untagged. Routes start with a `{{baseUrl}}` variable
+/// set to the first server, so it can be pointed elsewhere.
+pub fn parse(source: &str) -> Result<ImportedApi, OpenApiError> {
+    let document: Value = match serde_json::from_str(source) {
+        Ok(document) => document,
+        Err(_) => {
+            serde_yaml::from_str(source).map_err(|err| OpenApiError::Syntax(err.to_string()))?
+        }
+    };
+
+    let version = |key: &str| document.get(key).and_then(Value::as_str).unwrap_or("");
+    let swagger = version("swagger").starts_with('2');
+    if !swagger && !version("openapi").starts_with('3') {
+        return Err(OpenApiError::UnknownFormat);
+    }
+
+    let spec = Spec {
+        document: &document,
+        swagger,
+    };
+    let base_url = spec.base_url();
+    let mut resources: Vec<ImportedResource> = vec![];
+
+    let paths = document.get("paths").and_then(Value::as_object);
+    for (path, item) in paths.into_iter().flatten() {
+        let item = spec.resolve(item);
+
+        for method in METHODS {
+            let operation = match item.get(method) {
+                Some(operation) if operation.is_object() => operation,
+                _ => continue,
+            };
+
+            let group = operation
+                .get("tags")
+                .and_then(|tags| tags.get(0))
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| path_group(path));
+            let request = spec.request(&base_url, path, method, item, operation);
+
+            match resources.iter_mut().find(|resource| resource.name == group) {
+                Some(resource) => resource.requests.push(request),
+                None => resources.push(ImportedResource {
+                    name: group,
+                    requests: vec![request],
+                }),
+            }
+        }
+    }
+
+    let name = document
+        .pointer("/info/title")
+        .and_then(Value::as_str)
+        .unwrap_or("Imported API")
+        .to_string();
+
+    let description = document
+        .pointer("/info/description")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+
+    let variables = if base_url.is_empty() {
+        vec![]
+    } else {
+        vec![(String::from("baseUrl"), base_url)]
+    };
+
+    Ok(ImportedApi {
+        name,
+        description,
+        resources,
+        variables,
+        complete: true,
+    })
+}
+
+fn path_group(path: &str) -> String {
+    path.split('/')
+        .find(|segment| !segment.is_empty() && !segment.starts_with('{'))
+        .unwrap_or("default")
+        .to_string()
+}
+
+/// Renders an example value the way it is stored in a row or a body.
+fn to_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Null => String::new(),
+        Value::Object(_) | Value::Array(_) => {
+            serde_json::to_string_pretty(value).unwrap_or_default()
+        }
+        other => other.to_string(),
+    }
+}
+
+struct Spec<'a> {
+    document: &'a Value,
+    swagger: bool,
+}
+
+impl<'a> Spec<'a> {
+    /// Follows local `$ref`s. Anything pointing outside the document is
+    /// left as is.
+    fn resolve(&self, mut value: &'a Value) -> &'a Value {
+        for _ in 0..16 {
+            let target = value
+                .get("$ref")
+                .and_then(Value::as_str)
+                .and_then(|reference| reference.strip_prefix('#'))
+                .and_then(|pointer| self.document.pointer(pointer));
+            match target {
+                Some(target) => value = target,
+                None => break,
+            }
+        }
+        value
+    }
+
+    fn base_url(&self) -> String {
+        let url = if self.swagger {
+            let base_path = self
+                .document
+                .get("basePath")
+                .and_then(Value::as_str)
+                .unwrap_or("");
+            match self.document.get("host").and_then(Value::as_str) {
+                Some(host) => {
+                    let scheme = self
+                        .document
+                        .pointer("/schemes/0")
+                        .and_then(Value::as_str)
+                        .unwrap_or("https");
+                    format!("{}://{}{}", scheme, host, base_path)
+                }
+                None => base_path.to_string(),
+            }
+        } else {
+            let server = self.document.pointer("/servers/0");
+            let mut url = server
+                .and_then(|server| server.get("url"))
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            let variables = server
+                .and_then(|server| server.get("variables"))
+                .and_then(Value::as_object);
+            for (name, variable) in variables.into_iter().flatten() {
+                let default = variable.get("default").map(to_text).unwrap_or_default();
+                url = url.replace(&format!("{{{}}}", name), &default);
+            }
+            url
+        };
+
+        url.trim_end_matches('/').to_string()
+    }
+
+    /// Path-level parameters, overridden by the operation's own.
+    fn parameters(&self, item: &'a Value, operation: &'a Value) -> Vec<&'a Value> {
+        let mut parameters: Vec<&Value> = vec![];
+        let declared = [item, operation]
+            .into_iter()
+            .filter_map(|owner| owner.get("parameters").and_then(Value::as_array))
+            .flatten()
+            .map(|parameter| self.resolve(parameter));
+
+        for parameter in declared {
+            let same = |other: &&Value| {
+                other.get("name") == parameter.get("name") && other.get("in") == parameter.get("in")
+            };
+            parameters.retain(|other| !same(other));
+            parameters.push(parameter);
+        }
+        parameters
+    }
+
+    fn parameter_example(&self, parameter: &Value) -> Option<String> {
+        let schema = parameter.get("schema").map(|schema| self.resolve(schema));
+        let first_enum =
+            |owner: &Value| owner.get("enum").and_then(|values| values.get(0)).cloned();
+
+        parameter
+            .get("example")
+            .cloned()
+            .or_else(|| schema.and_then(|schema| schema.get("example")).cloned())
+            .or_else(|| schema.and_then(|schema| schema.get("default")).cloned())
+            .or_else(|| parameter.get("default").cloned())
+            .or_else(|| schema.and_then(first_enum))
+            .or_else(|| first_enum(parameter))
+            .map(|value| to_text(&value))
+    }
+
+    fn request(
+        &self,
+        base_url: &str,
+        path: &str,
+        method: &str,
+        item: &'a Value,
+        operation: &'a Value,
+    ) -> ImportedRequest {
+        let mut route = if base_url.is_empty() {
+            path.to_string()
+        } else {
+            format!("{{{{baseUrl}}}}{}", path)
+        };
+        let mut headers = vec![];
+        let mut params = vec![];
+        let mut body = None;
+
+        for parameter in self.parameters(item, operation) {
+            let name = parameter.get("name").and_then(Value::as_str).unwrap_or("");
+            match parameter.get("in").and_then(Value::as_str) {
+                Some("query") => {
+                    let value = self.parameter_example(parameter).unwrap_or_default();
+                    params.push((name.to_string(), value));
+                }
+                Some("header") => {
+                    let value = self.parameter_example(parameter).unwrap_or_default();
+                    headers.push((name.to_string(), value));
+                }
+                // Paths keep their `{placeholder}` unless there is an example
+                // to fill in.
+                Some("path") => {
+                    if let Some(value) = self.parameter_example(parameter) {
+                        route = route.replace(&format!("{{{}}}", name), &value);
+                    }
+                }
+                Some("body") => {
+                    let schema = parameter.get("schema").unwrap_or(&Value::Null);
+                    body = Some(to_text(&self.sample(schema, 0)));
+                }
+                _ => (),
+            }
+        }
+
+        let (content_type, example) = if self.swagger {
+            let content_type = operation
+                .pointer("/consumes/0")
+                .or_else(|| self.document.pointer("/consumes/0"))
+                .and_then(Value::as_str)
+                .unwrap_or("application/json");
+            (body.is_some().then(|| content_type.to_string()), body)
+        } else {
+            match operation.get("requestBody") {
+                Some(request_body) => self.request_body(self.resolve(request_body)),
+                None => (None, None),
+            }
+        };
+
+        if let Some(content_type) = content_type {
+            if !headers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+            {
+                headers.push((String::from("Content-Type"), content_type));
+            }
+        }
+
+        let key = operation
+            .get("operationId")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} {}", method.to_uppercase(), path));
+
+        let text = |key: &str| {
+            operation
+                .get(key)
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        };
+
+        ImportedRequest {
+            key,
+            name: text("summary"),
+            description: text("description"),
+            route,
+            method: method.parse().unwrap_or(HttpMethod::Get),
+            body: example,
+            headers,
+            params,
+            form_parts: vec![],
+        }
+    }
+
+    /// Picks a JSON media type when there is one and returns it with an
+    /// example body.
+    fn request_body(&self, request_body: &'a Value) -> (Option<String>, Option<String>) {
+        let content = match request_body.get("content").and_then(Value::as_object) {
+            Some(content) if !content.is_empty() => content,
+            _ => return (None, None),
+        };
+        let (media_type, media) = content
+            .iter()
+            .find(|(media_type, _)| media_type.contains("json"))
+            .or_else(|| content.iter().next())
+            .unwrap();
+
+        let example = media
+            .get("example")
+            .cloned()
+            .or_else(|| {
+                media
+                    .get("examples")
+                    .and_then(Value::as_object)
+                    .and_then(|examples| examples.values().next())
+                    .and_then(|example| self.resolve(example).get("value"))
+                    .cloned()
+            })
+            .or_else(|| media.get("schema").map(|schema| self.sample(schema, 0)));
+
+        (
+            Some(media_type.clone()),
+            example.map(|example| to_text(&example)),
+        )
+    }
+
+    /// Builds an example value from a schema, preferring the examples and
+    /// defaults it declares.
+    fn sample(&self, schema: &'a Value, depth: usize) -> Value {
+        let schema = self.resolve(schema);
+        if depth > SAMPLE_DEPTH {
+            return Value::Null;
+        }
+
+        if let Some(example) = schema.get("example").or_else(|| schema.get("default")) {
+            return example.clone();
+        }
+        if let Some(first) = schema.get("enum").and_then(|values| values.get(0)) {
+            return first.clone();
+        }
+        if let Some(all_of) = schema.get("allOf").and_then(Value::as_array) {
+            let mut merged = Map::new();
+            for part in all_of {
+                if let Value::Object(fields) = self.sample(part, depth + 1) {
+                    merged.extend(fields);
+                }
+            }
+            return Value::Object(merged);
+        }
+        if let Some(first) = ["oneOf", "anyOf"]
+            .iter()
+            .find_map(|key| schema.get(key).and_then(|options| options.get(0)))
+        {
+            return self.sample(first, depth + 1);
+        }
+
+        let kind = schema.get("type").and_then(Value::as_str);
+        let properties = schema.get("properties").and_then(Value::as_object);
+        match (kind, properties) {
+            (Some("object"), _) | (None, Some(_)) => Value::Object(
+                properties
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), self.sample(property, depth + 1)))
+                    .collect(),
+            ),
+            (Some("array"), _) => match schema.get("items") {
+                Some(items) => Value::Array(vec![self.sample(items, depth + 1)]),
+                None => Value::Array(vec![]),
+            },
+            (Some("string"), _) => {
+                let format = schema.get("format").and_then(Value::as_str);
+                Value::from(match format {
+                    Some("date-time") => "2024-01-01T00:00:00Z",
+                    Some("date") => "2024-01-01",
+                    Some("email") => "user@example.com",
+                    Some("uuid") => "00000000-0000-0000-0000-000000000000",
+                    Some("uri") | Some("url") => "https://example.com",
+                    _ => "string",
+                })
+            }
+            (Some("integer"), _) | (Some("number"), _) => Value::from(0),
+            (Some("boolean"), _) => Value::from(false),
+            _ => Value::Null,
+        }
+    }
+}
+
+/// Builds an OpenAPI 3 document from a stored API. Requests become
+/// operations tagged with their resource, and the responses recorded for
+/// them become examples with schemas inferred from their JSON.
+pub fn export(api_id: i32) -> Result<Value, OpenApiError> {
+    let api = api_service::get_api_by_id(api_id).ok_or(OpenApiError::UnknownApi(api_id))?;
+    let resources = resource_service::get_resources_for_api(api_id);
+    // Folders in folders are tagged with their whole path.
+    let tags: Vec<String> = resources
+        .iter()
+        .map(|resource| resource_service::path_name(&resources, resource))
+        .collect();
+
+    // Routes like `{{baseUrl}}/pets` are exported with the server filled in.
+    let variables = variable_service::get_variables_for_api(api_id);
+    let mut requests = vec![];
+    for (resource, tag) in resources.iter().zip(&tags) {
+        let stored = request_service::get_requests_for_resource(resource.id).unwrap_or_default();
+        requests.extend(
+            stored
+                .into_iter()
+                .filter(|request| matches!(request.kind, RequestKind::Http | RequestKind::Graphql))
+                .map(|mut request| {
+                    request.route = http::expand_variables(&request.route, &variables);
+                    (tag.as_str(), request)
+                }),
+        );
+    }
+
+    // The most common origin becomes the server, others are set per
+    // operation.
+    let mut origins: Vec<(&str, usize)> = vec![];
+    for (_, request) in &requests {
+        if let (Some(origin), _, _) = split_route(&request.route) {
+            match origins.iter_mut().find(|(known, _)| *known == origin) {
+                Some((_, count)) => *count += 1,
+                None => origins.push((origin, 1)),
+            }
+        }
+    }
+    let server = origins
+        .iter()
+        .max_by_key(|(_, count)| *count)
+        .map(|(origin, _)| *origin);
+
+    let mut paths = Map::new();
+    let mut operation_ids = HashSet::new();
+    for (tag, request) in &requests {
+        let method = request.method.as_str().to_lowercase();
+        if !METHODS.contains(&method.as_str()) {
+            continue;
+        }
+
+        let (origin, path, _) = split_route(&request.route);
+        let mut operation = export_operation(tag, request, &path);
+        let operation_id = unique_operation_id(request, &method, &path, &mut operation_ids);
+        operation["operationId"] = Value::from(operation_id);
+        if let Some(name) = request.name.as_deref().filter(|name| !name.is_empty()) {
+            operation["summary"] = Value::from(name);
+        }
+        if let Some(description) = &request.description {
+            operation["description"] = Value::from(description.as_str());
+        }
+        if let Some(origin) = origin.filter(|origin| Some(*origin) != server) {
+            operation["servers"] = json!([{ "url": origin }]);
+        }
+
+        let item = paths
+            .entry(path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        item[method.as_str()] = operation;
+    }
+
+    let tags: Vec<Value> = tags.iter().map(|tag| json!({ "name": tag })).collect();
+    let servers: Vec<Value> = server
+        .map(|server| json!({ "url": server }))
+        .into_iter()
+        .collect();
+
+    let mut info = json!({ "title": api.name, "version": "1.0.0" });
+    if let Some(description) = &api.description {
+        info["description"] = Value::from(description.as_str());
+    }
+
+    Ok(json!({
+        "openapi": "3.0.3",
+        "info": info,
+        "servers": servers,
+        "tags": tags,
+        "paths": paths,
+    }))
+}
+
+/// Writes `export` as JSON when the path ends in `.json`, YAML otherwise.
+pub fn export_to_file(api_id: i32, path: &str) -> Result<(), OpenApiError> {
+    let document = export(api_id)?;
+    let text = if path.ends_with(".json") {
+        serde_json::to_string_pretty(&document).map_err(|err| err.to_string())
+    } else {
+        serde_yaml::to_string(&document).map_err(|err| err.to_string())
+    }
+    .map_err(OpenApiError::Syntax)?;
+
+    fs::write(path, text).map_err(|err| OpenApiError::Write(path.to_string(), err))
+}
+
+/// Splits a route into its origin (absolute routes only), its path and
+/// its query string pairs.
+fn split_route(route: &str) -> (Option<&str>, String, Vec<(String, String)>) {
+    let (origin, rest) = match route.find("://") {
+        Some(scheme_end) => {
+            let path_start = route[scheme_end + 3..]
+                .find(['/', '?'])
+                .map(|index| index + scheme_end + 3)
+                .unwrap_or(route.len());
+            (Some(&route[..path_start]), &route[path_start..])
+        }
+        None => (None, route),
+    };
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+
+    let path = if path.starts_with('/') {
+        path.to_string()
+    } else {
+        format!("/{}", path)
+    };
+    let query = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_string(), value.to_string())
+        })
+        .collect();
+
+    (origin, path, query)
+}
+
+fn export_operation(tag: &str, request: &Request, path: &str) -> Value {
+    let headers = header_service::get_headers_for_request(request.id);
+    let params = param_service::get_params_for_request(request.id);
+    let form_parts = form_part_service::get_form_parts_for_request(request.id);
+    let (_, _, query) = split_route(&request.route);
+
+    let mut parameters = vec![];
+    for segment in path.split('/') {
+        if let Some(name) = segment
+            .strip_prefix('{')
+            .and_then(|rest| rest.strip_suffix('}'))
+        {
+            parameters.push(json!({
+                "name": name,
+                "in": "path",
+                "required": true,
+                "schema": { "type": "string" },
+            }));
+        }
+    }
+    let query = query
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_str()))
+        .chain(
+            params
+                .iter()
+                .map(|row| (row.key.as_str(), row.value.as_str())),
+        );
+    for (key, value) in query {
+        parameters.push(json!({
+            "name": key,
+            "in": "query",
+            "schema": infer_text_schema(value),
+            "example": value,
+        }));
+    }
+    // OpenAPI describes these three elsewhere and ignores them here.
+    let ignored = ["content-type", "accept", "authorization"];
+    for header in &headers {
+        if ignored.contains(&header.key.to_lowercase().as_str()) {
+            continue;
+        }
+        parameters.push(json!({
+            "name": header.key,
+            "in": "header",
+            "schema": infer_text_schema(&header.value),
+            "example": header.value,
+        }));
+    }
+
+    let mut operation = json!({ "tags": [tag] });
+    if !parameters.is_empty() {
+        operation["parameters"] = Value::Array(parameters);
+    }
+
+    let content_type = headers
+        .iter()
+        .find(|header| header.key.eq_ignore_ascii_case("content-type"))
+        .map(|header| header.value.as_str());
+    if !form_parts.is_empty() {
+        let properties: Map<String, Value> = form_parts
+            .iter()
+            .map(|part| {
+                let schema = match part.kind {
+                    FormPartKind::Text => json!({ "type": "string" }),
+                    FormPartKind::File => json!({ "type": "string", "format": "binary" }),
+                };
+                (part.name.clone(), schema)
+            })
+            .collect();
+        operation["requestBody"] = json!({
+            "content": {
+                "multipart/form-data": {
+                    "schema": { "type": "object", "properties": properties },
+                },
+            },
+        });
+    } else if let Some(body) = request
+        .body
+        .as_deref()
+        .filter(|body| !body.trim().is_empty())
+    {
+        operation["requestBody"] = json!({ "content": media(content_type, body) });
+    }
+
+    let recorded = recorded_response_service::get_examples_for_request(request.id);
+    let mut responses = Map::new();
+    for response in &recorded {
+        let reason = u16::try_from(response.status)
+            .ok()
+            .and_then(|code| StatusCode::from_u16(code).ok())
+            .and_then(|code| code.canonical_reason())
+            .unwrap_or("Response");
+        let mut described = json!({ "description": reason });
+        if !response.body.is_empty() {
+            described["content"] = media(response.content_type.as_deref(), &response.body);
+        }
+        responses.insert(response.status.to_string(), described);
+    }
+    if responses.is_empty() {
+        responses.insert(
+            String::from("default"),
+            json!({ "description": "No recorded response" }),
+        );
+    }
+    operation["responses"] = Value::Object(responses);
+
+    operation
+}
+
+/// A content map for one body, with a schema inferred from JSON bodies.
+fn media(content_type: Option<&str>, body: &str) -> Value {
+    let parsed: Option<Value> = serde_json::from_str(body).ok();
+    let content_type = content_type
+        .and_then(|content_type| content_type.split(';').next())
+        .map(str::trim)
+        .filter(|content_type| !content_type.is_empty())
+        .unwrap_or(if parsed.is_some() {
+            "application/json"
+        } else {
+            "text/plain"
+        });
+
+    let described = match parsed {
+        Some(example) => json!({ "schema": infer_schema(&example), "example": example }),
+        None => json!({ "schema": { "type": "string" }, "example": body }),
+    };
+    json!({ content_type: described })
+}
+
+/// Keeps imported `operationId`s, and otherwise names operations after
+/// their method and path, e.g. `getPetsPetId`.
+fn unique_operation_id(
+    request: &Request,
+    method: &str,
+    path: &str,
+    taken: &mut HashSet<String>,
+) -> String {
+    let imported = import_service::get_source_key(request.id)
+        .filter(|key| !key.is_empty() && !key.contains(' '));
+
+    let base = imported.unwrap_or_else(|| {
+        let mut name = method.to_string();
+        for word in path.split(|c: char| !c.is_ascii_alphanumeric()) {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                name.push(first.to_ascii_uppercase());
+                name.extend(chars);
+            }
+        }
+        name
+    });
+
+    let mut candidate = base.clone();
+    let mut suffix = 2;
+    while !taken.insert(candidate.clone()) {
+        candidate = format!("{}{}", base, suffix);
+        suffix += 1;
+    }
+    candidate
+}
+
+/// Guesses the type of a header, query parameter or other text value.
+fn infer_text_schema(text: &str) -> Value {
+    if text.parse::<i64>().is_ok() {
+        json!({ "type": "integer" })
+    } else if text.parse::<f64>().is_ok() {
+        json!({ "type": "number" })
+    } else if text == "true" || text == "false" {
+        json!({ "type": "boolean" })
+    } else {
+        infer_schema(&Value::from(text))
+    }
+}
+
+fn infer_schema(value: &Value) -> Value {
+    match value {
+        Value::Null => json!({ "nullable": true }),
+        Value::Bool(_) => json!({ "type": "boolean" }),
+        Value::Number(number) if number.is_f64() => json!({ "type": "number" }),
+        Value::Number(_) => json!({ "type": "integer" }),
+        Value::String(text) => match string_format(text) {
+            Some(format) => json!({ "type": "string", "format": format }),
+            None => json!({ "type": "string" }),
+        },
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(infer_schema)
+                .reduce(merge_schemas)
+                .unwrap_or_else(|| json!({}));
+            json!({ "type": "array", "items": items })
+        }
+        Value::Object(fields) => {
+            let properties: Map<String, Value> = fields
+                .iter()
+                .map(|(name, field)| (name.clone(), infer_schema(field)))
+                .collect();
+            let required: Vec<&String> = fields.keys().collect();
+            json!({ "type": "object", "properties": properties, "required": required })
+        }
+    }
+}
+
+fn string_format(text: &str) -> Option<&'static str> {
+    if DateTime::parse_from_rfc3339(text).is_ok() {
+        Some("date-time")
+    } else if NaiveDate::parse_from_str(text, "%Y-%m-%d").is_ok() {
+        Some("date")
+    } else if text.len() == 36 && Uuid::parse_str(text).is_ok() {
+        Some("uuid")
+    } else if !text.contains(char::is_whitespace)
+        && text
+            .split_once('@')
+            .is_some_and(|(user, domain)| !user.is_empty() && domain.contains('.'))
+    {
+        Some("email")
+    } else {
+        None
+    }
+}
+
+/// Combines the schemas of two array items. Objects keep every property
+/// and only require those both have, integers widen to numbers and
+/// anything else that differs becomes a `oneOf`.
+fn merge_schemas(left: Value, right: Value) -> Value {
+    if left == right {
+        return left;
+    }
+
+    let kind = |schema: &Value| {
+        schema
+            .get("type")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    };
+    let nullable = |schema: &Value| schema.get("nullable") == Some(&Value::Bool(true));
+
+    match (kind(&left).as_deref(), kind(&right).as_deref()) {
+        (None, Some(_)) if nullable(&left) => {
+            let mut right = right;
+            right["nullable"] = Value::Bool(true);
+            right
+        }
+        (Some(_), None) if nullable(&right) => merge_schemas(right, left),
+        (Some("integer"), Some("number")) | (Some("number"), Some("integer")) => {
+            json!({ "type": "number" })
+        }
+        (Some("string"), Some("string")) => json!({ "type": "string" }),
+        (Some("array"), Some("array")) => {
+            let items = merge_schemas(left["items"].clone(), right["items"].clone());
+            json!({ "type": "array", "items": items })
+        }
+        (Some("object"), Some("object")) => {
+            let mut properties = left["properties"].as_object().cloned().unwrap_or_default();
+            for (name, schema) in right["properties"].as_object().into_iter().flatten() {
+                let merged = match properties.remove(name) {
+                    Some(existing) => merge_schemas(existing, schema.clone()),
+                    None => schema.clone(),
+                };
+                properties.insert(name.clone(), merged);
+            }
+            let required_in = |schema: &Value, name: &Value| {
+                schema["required"]
+                    .as_array()
+                    .is_some_and(|required| required.contains(name))
+            };
+            let required: Vec<Value> = left["required"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|name| required_in(&right, name))
+                .cloned()
+                .collect();
+            json!({ "type": "object", "properties": properties, "required": required })
+        }
+        _ => {
+            let mut options = match left.get("oneOf").and_then(Value::as_array) {
+                Some(options) => options.clone(),
+                None => vec![left],
+            };
+            if !options.contains(&right) {
+                options.push(right);
+            }
+            json!({ "oneOf": options })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_server_in_a_base_url_variable() {
+        let imported = parse(
+            r#"{
+                "openapi": "3.0.0",
+                "info": { "title": "Shop" },
+                "servers": [{ "url": "https://{region}.shop.test/v1/", "variables": { "region": { "default": "eu" } } }],
+                "paths": { "/orders": { "get": { "operationId": "listOrders" } } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imported.variables,
+            vec![(
+                String::from("baseUrl"),
+                String::from("https://eu.shop.test/v1")
+            )]
+        );
+        assert_eq!(
+            imported.resources[0].requests[0].route,
+            "{{baseUrl}}/orders"
+        );
+        assert!(imported.complete);
+    }
+
+    #[test]
+    fn builds_requests_from_parameters_and_bodies() {
+        let imported = parse(
+            r##"
+openapi: 3.0.3
+info:
+  title: Shop
+paths:
+  /orders/{orderId}:
+    parameters:
+      - name: orderId
+        in: path
+        example: 7
+    put:
+      tags: [orders]
+      summary: Replace an order
+      parameters:
+        - name: dryRun
+          in: query
+          schema: { type: boolean, default: false }
+        - $ref: "#/components/parameters/Trace"
+      requestBody:
+        content:
+          application/xml: {}
+          application/json:
+            schema: { $ref: "#/components/schemas/Order" }
+  /health:
+    get: {}
+components:
+  parameters:
+    Trace:
+      name: X-Trace
+      in: header
+      schema: { type: string, enum: [abc] }
+  schemas:
+    Order:
+      type: object
+      properties:
+        id: { type: integer }
+        placed: { type: string, format: date-time }
+        items:
+          type: array
+          items: { type: string }
+"##,
+        )
+        .unwrap();
+        assert!(imported.variables.is_empty());
+
+        let orders = imported
+            .resources
+            .iter()
+            .find(|resource| resource.name == "orders")
+            .unwrap();
+        let request = &orders.requests[0];
+        assert_eq!(request.key, "PUT /orders/{orderId}");
+        assert_eq!(request.name.as_deref(), Some("Replace an order"));
+        assert_eq!(request.method, HttpMethod::Put);
+        assert_eq!(request.route, "/orders/7");
+        assert_eq!(
+            request.params,
+            vec![(String::from("dryRun"), String::from("false"))]
+        );
+        assert_eq!(
+            request.headers,
+            vec![
+                (String::from("X-Trace"), String::from("abc")),
+                (
+                    String::from("Content-Type"),
+                    String::from("application/json")
+                )
+            ]
+        );
+        let body: Value = serde_json::from_str(request.body.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            body,
+            json!({ "id": 0, "placed": "2024-01-01T00:00:00Z", "items": ["string"] })
+        );
+
+        // Untagged operations are grouped by the first segment of their path.
+        assert!(imported
+            .resources
+            .iter()
+            .any(|resource| resource.name == "health"));
+    }
+
+    #[test]
+    fn reads_swagger_hosts_and_body_parameters() {
+        let imported = parse(
+            r#"{
+                "swagger": "2.0",
+                "info": { "title": "Legacy" },
+                "host": "legacy.test",
+                "basePath": "/api",
+                "schemes": ["http"],
+                "consumes": ["application/json"],
+                "paths": {
+                    "/pets": {
+                        "post": {
+                            "parameters": [
+                                { "name": "pet", "in": "body", "schema": { "properties": { "name": { "example": "Rex" } } } }
+                            ]
+                        }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            imported.variables,
+            vec![(
+                String::from("baseUrl"),
+                String::from("http://legacy.test/api")
+            )]
+        );
+        let request = &imported.resources[0].requests[0];
+        assert_eq!(request.body.as_deref(), Some("{\n  \"name\": \"Rex\"\n}"));
+        assert_eq!(
+            request.headers,
+            vec![(
+                String::from("Content-Type"),
+                String::from("application/json")
+            )]
+        );
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(matches!(
+            parse(r#"{ "info": { "title": "x" } }"#),
+            Err(OpenApiError::UnknownFormat)
+        ));
+        assert!(matches!(parse("a: [b"), Err(OpenApiError::Syntax(_))));
+    }
+
+    #[test]
+    fn infers_schemas_from_examples() {
+        assert_eq!(
+            infer_schema(&json!([{ "id": 1, "at": "2024-05-01" }])),
+            json!({
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "id": { "type": "integer" },
+                        "at": { "type": "string", "format": "date" }
+                    },
+                    "required": ["at", "id"]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn splits_routes_into_origin_path_and_query() {
+        assert_eq!(
+            split_route("https://shop.test/orders?page=2"),
+            (
+                Some("https://shop.test"),
+                String::from("/orders"),
+                vec![(String::from("page"), String::from("2"))]
+            )
+        );
+        assert_eq!(
+            split_route("orders"),
+            (None, String::from("/orders"), vec![])
+        );
+    }
+}
@@ -0,0 +1,905 @@
+use std::fs;
+
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+use url::form_urlencoded;
+use uuid::Uuid;
+
+use crate::{
+    http::HOSTNAME,
+    models::{Header, Param, Request},
+    services::{
+        api_service, form_part_service, graphql_service, header_service,
+        import_service::{
+            self, ImportedApi, ImportedFormPart, ImportedRequest, ImportedResource, PATH_SEPARATOR,
+        },
+        param_service, request_service, resource_service, variable_service,
+    },
+    types::{FormPartKind, HttpMethod, RequestKind},
+};
+
+const SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// Requests at the top of a collection, outside any folder, are stored in
+/// a resource of this name.
+const ROOT_RESOURCE: &str = "default";
+
+#[derive(Debug, Error)]
+pub enum PostmanError {
+    #[error("Not a valid JSON document: {0}")]
+    Syntax(String),
+    #[error("Not a Postman v2 collection")]
+    UnknownFormat,
+    #[error("API {0} not found")]
+    UnknownApi(i32),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+}
+
+/// Whether `source` looks like a Postman collection rather than some
+/// other document.
+pub fn is_collection(source: &str) -> bool {
+    serde_json::from_str::<Value>(source)
+        .map(|document| {
+            document
+                .pointer("/info/schema")
+                .and_then(Value::as_str)
+                .is_some_and(|schema| schema.contains("getpostman.com"))
+        })
+        .unwrap_or(false)
+}
+
+/// Reads a Postman v2.0 or v2.1 collection. Nested folders become
+/// resources named after their path, e.g. `Pets / Photos`, and auth set
+/// on the collection or a folder is turned into headers or params of the
+/// requests inheriting it.
+pub fn parse(source: &str) -> Result<ImportedApi, PostmanError> {
+    let document: Value =
+        serde_json::from_str(source).map_err(|err| PostmanError::Syntax(err.to_string()))?;
+    if !document.get("item").is_some_and(Value::is_array) || document.get("info").is_none() {
+        return Err(PostmanError::UnknownFormat);
+    }
+
+    let variables: Vec<(String, String)> = document
+        .get("variable")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|variable| !is_disabled(variable))
+        .filter_map(|variable| {
+            let key = variable.get("key").and_then(Value::as_str)?;
+            Some((key.to_string(), text(variable.get("value"))))
+        })
+        .collect();
+
+    let mut resources = vec![];
+    collect_items(&document["item"], &[], document.get("auth"), &mut resources);
+
+    let name = document
+        .pointer("/info/name")
+        .and_then(Value::as_str)
+        .unwrap_or("Imported collection")
+        .to_string();
+
+    Ok(ImportedApi {
+        name,
+        description: description(document.pointer("/info/description")),
+        resources,
+        variables,
+        complete: true,
+    })
+}
+
+/// Walks the items of a collection or folder, adding requests to the
+/// resource of their folder path.
+fn collect_items(
+    items: &Value,
+    folders: &[&str],
+    auth: Option<&Value>,
+    resources: &mut Vec<ImportedResource>,
+) {
+    for item in items.as_array().into_iter().flatten() {
+        let name = item.get("name").and_then(Value::as_str).unwrap_or("");
+        let auth = item.get("auth").or(auth);
+
+        if let Some(children) = item.get("item") {
+            let mut path = folders.to_vec();
+            path.push(name);
+            collect_items(children, &path, auth, resources);
+            continue;
+        }
+
+        let request = match item.get("request") {
+            Some(request) => request,
+            None => continue,
+        };
+        let resource_name = if folders.is_empty() {
+            ROOT_RESOURCE.to_string()
+        } else {
+            folders.join(PATH_SEPARATOR)
+        };
+        let mut key = folders.to_vec();
+        key.push(name);
+        let mut imported = import_request(
+            key.join(PATH_SEPARATOR),
+            request,
+            request.get("auth").or(auth),
+        );
+        imported.name = Some(name.to_string()).filter(|name| !name.is_empty());
+
+        match resources
+            .iter_mut()
+            .find(|resource| resource.name == resource_name)
+        {
+            Some(resource) => resource.requests.push(imported),
+            None => resources.push(ImportedResource {
+                name: resource_name,
+                requests: vec![imported],
+            }),
+        }
+    }
+}
+
+fn import_request(key: String, request: &Value, auth: Option<&Value>) -> ImportedRequest {
+    // A request may be given as nothing but its URL.
+    let url = match request {
+        Value::String(_) => request,
+        _ => request.get("url").unwrap_or(&Value::Null),
+    };
+    let (route, mut params) = import_url(url);
+
+    let method = request
+        .get("method")
+        .and_then(Value::as_str)
+        .and_then(|method| method.parse().ok())
+        .unwrap_or(HttpMethod::Get);
+
+    let mut headers: Vec<(String, String)> = request
+        .get("header")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|header| !is_disabled(header))
+        .filter_map(|header| {
+            let key = header.get("key").and_then(Value::as_str)?;
+            Some((key.to_string(), text(header.get("value"))))
+        })
+        .collect();
+
+    let mut body = None;
+    let mut form_parts = vec![];
+    let body_value = request.get("body").unwrap_or(&Value::Null);
+    let content_type = match body_value.get("mode").and_then(Value::as_str) {
+        Some("raw") => {
+            body = body_value
+                .get("raw")
+                .and_then(Value::as_str)
+                .filter(|raw| !raw.is_empty())
+                .map(str::to_string);
+            let language = body_value
+                .pointer("/options/raw/language")
+                .and_then(Value::as_str);
+            match language {
+                Some("json") => Some("application/json"),
+                Some("xml") => Some("application/xml"),
+                Some("html") => Some("text/html"),
+                Some("javascript") => Some("application/javascript"),
+                Some("text") => Some("text/plain"),
+                _ => None,
+            }
+        }
+        Some("urlencoded") => {
+            let pairs = enabled_pairs(body_value.get("urlencoded"));
+            let encoded: Vec<String> = pairs
+                .iter()
+                .map(|(key, value)| format!("{}={}", encode(key), encode(value)))
+                .collect();
+            body = Some(encoded.join("&"));
+            Some("application/x-www-form-urlencoded")
+        }
+        Some("formdata") => {
+            for part in body_value
+                .get("formdata")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter(|part| !is_disabled(part))
+            {
+                let (kind, value) = match part.get("type").and_then(Value::as_str) {
+                    // `src` is a list when several files were picked.
+                    Some("file") => {
+                        let src = part.get("src").unwrap_or(&Value::Null);
+                        let src = src.get(0).unwrap_or(src);
+                        (FormPartKind::File, text(Some(src)))
+                    }
+                    _ => (FormPartKind::Text, text(part.get("value"))),
+                };
+                form_parts.push(ImportedFormPart {
+                    name: text(part.get("key")),
+                    kind,
+                    value,
+                    content_type: part
+                        .get("contentType")
+                        .and_then(Value::as_str)
+                        .filter(|content_type| !content_type.is_empty())
+                        .map(str::to_string),
+                });
+            }
+            None
+        }
+        Some("graphql") => {
+            let graphql = body_value.get("graphql").unwrap_or(&Value::Null);
+            let variables = graphql
+                .get("variables")
+                .and_then(Value::as_str)
+                .and_then(|variables| serde_json::from_str(variables).ok())
+                .unwrap_or_else(|| json!({}));
+            body = Some(
+                json!({
+                    "query": text(graphql.get("query")),
+                    "variables": variables,
+                })
+                .to_string(),
+            );
+            Some("application/json")
+        }
+        _ => None,
+    };
+
+    if let Some(content_type) = content_type {
+        if !headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("content-type"))
+        {
+            headers.push((String::from("Content-Type"), content_type.to_string()));
+        }
+    }
+
+    if let Some(auth) = auth {
+        apply_auth(auth, &mut headers, &mut params);
+    }
+
+    ImportedRequest {
+        key,
+        name: None,
+        description: description(request.get("description")),
+        route,
+        method,
+        body,
+        headers,
+        params,
+        form_parts,
+    }
+}
+
+/// Splits a Postman URL into the route and its query params. Path
+/// variables such as `:id` are filled in when they have a value.
+fn import_url(url: &Value) -> (String, Vec<(String, String)>) {
+    let raw = match url {
+        Value::String(raw) => raw.clone(),
+        _ => match url.get("raw").and_then(Value::as_str) {
+            Some(raw) => raw.to_string(),
+            None => build_raw_url(url),
+        },
+    };
+    let (mut route, query) = match raw.split_once('?') {
+        Some((route, query)) => (route.to_string(), Some(query)),
+        None => (raw.clone(), None),
+    };
+
+    let params = match url.get("query") {
+        Some(query) => enabled_pairs(Some(query)),
+        None => query
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (key.to_string(), value.to_string())
+            })
+            .collect(),
+    };
+
+    for (key, value) in enabled_pairs(url.get("variable")) {
+        if !value.is_empty() {
+            route = route.replace(&format!(":{}", key), &value);
+        }
+    }
+
+    (route, params)
+}
+
+fn build_raw_url(url: &Value) -> String {
+    let join = |value: Option<&Value>, separator: &str| match value {
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .map(|part| text(Some(part)))
+            .collect::<Vec<_>>()
+            .join(separator),
+        value => text(value),
+    };
+
+    let mut raw = String::new();
+    if let Some(protocol) = url.get("protocol").and_then(Value::as_str) {
+        raw.push_str(protocol);
+        raw.push_str("://");
+    }
+    raw.push_str(&join(url.get("host"), "."));
+    if let Some(port) = url.get("port") {
+        raw.push(':');
+        raw.push_str(&text(Some(port)));
+    }
+    let path = join(url.get("path"), "/");
+    if !path.is_empty() {
+        raw.push('/');
+        raw.push_str(path.trim_start_matches('/'));
+    }
+    raw
+}
+
+/// Turns an auth block into the header or param that carries it. Only
+/// bearer, basic and API key auth can be expressed that way; other kinds
+/// are left out.
+fn apply_auth(
+    auth: &Value,
+    headers: &mut Vec<(String, String)>,
+    params: &mut Vec<(String, String)>,
+) {
+    let kind = auth.get("type").and_then(Value::as_str).unwrap_or("noauth");
+    let field = |key: &str| auth_field(auth, kind, key);
+
+    match kind {
+        "bearer" => headers.push((
+            String::from("Authorization"),
+            format!("Bearer {}", field("token")),
+        )),
+        // Kept as typed, they are encoded when the request is sent, once
+        // placeholders in them are filled in.
+        "basic" => headers.push((
+            String::from("Authorization"),
+            format!("Basic {}:{}", field("username"), field("password")),
+        )),
+        "apikey" => {
+            let pair = (field("key"), field("value"));
+            if field("in") == "query" {
+                params.push(pair);
+            } else {
+                headers.push(pair);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Auth settings are a list of `{key, value}` in v2.1 and a plain object
+/// in v2.0.
+fn auth_field(auth: &Value, kind: &str, key: &str) -> String {
+    match auth.get(kind) {
+        Some(Value::Array(fields)) => fields
+            .iter()
+            .find(|field| field.get("key").and_then(Value::as_str) == Some(key))
+            .map(|field| text(field.get("value")))
+            .unwrap_or_default(),
+        Some(fields) => text(fields.get(key)),
+        None => String::new(),
+    }
+}
+
+fn enabled_pairs(list: Option<&Value>) -> Vec<(String, String)> {
+    list.and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter(|pair| !is_disabled(pair))
+        .filter_map(|pair| {
+            let key = pair.get("key").and_then(Value::as_str)?;
+            Some((key.to_string(), text(pair.get("value"))))
+        })
+        .collect()
+}
+
+fn is_disabled(value: &Value) -> bool {
+    value.get("disabled").and_then(Value::as_bool) == Some(true)
+}
+
+/// A description, given as text or as an object with its `content`.
+fn description(value: Option<&Value>) -> Option<String> {
+    let value = value?;
+    value
+        .as_str()
+        .or_else(|| value.get("content").and_then(Value::as_str))
+        .filter(|description| !description.trim().is_empty())
+        .map(str::to_string)
+}
+
+fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(value) => value.to_string(),
+    }
+}
+
+/// Form-encodes a value, keeping `{{key}}` placeholders readable so they
+/// are still filled in when the request is sent.
+fn encode(value: &str) -> String {
+    let mut encoded = String::new();
+    let mut rest = value;
+
+    while let Some(start) = rest.find("{{") {
+        let end = match rest[start..].find("}}") {
+            Some(end) => start + end + 2,
+            None => break,
+        };
+        encoded.extend(form_urlencoded::byte_serialize(&rest.as_bytes()[..start]));
+        encoded.push_str(&rest[start..end]);
+        rest = &rest[end..];
+    }
+
+    encoded.extend(form_urlencoded::byte_serialize(rest.as_bytes()));
+    encoded
+}
+
+/// Builds a Postman v2.1 collection from a stored API. Resources become
+/// folders, nested again when their names are folder paths, and the API's
+/// variables become collection variables. WebSocket and gRPC requests
+/// have no place in the format and are left out.
+pub fn export(api_id: i32) -> Result<Value, PostmanError> {
+    let api = api_service::get_api_by_id(api_id).ok_or(PostmanError::UnknownApi(api_id))?;
+
+    let mut variables: Vec<Value> = variable_service::get_variables_for_api(api_id)
+        .iter()
+        .map(|variable| json!({ "key": variable.key, "value": variable.value }))
+        .collect();
+    let mut uses_hostname = false;
+
+    let mut items = vec![];
+    let resources = resource_service::get_resources_for_api(api_id);
+    for resource in &resources {
+        let requests: Vec<Value> = request_service::get_requests_for_resource(resource.id)
+            .unwrap_or_default()
+            .iter()
+            .filter(|request| matches!(request.kind, RequestKind::Http | RequestKind::Graphql))
+            .map(|request| {
+                uses_hostname |= !request.route.contains("://") && !request.route.starts_with("{{");
+                export_request(request)
+            })
+            .collect();
+
+        if resource.name == ROOT_RESOURCE {
+            items.extend(requests);
+        } else {
+            let path = resource_service::path_name(&resources, resource);
+            let path: Vec<&str> = path.split(PATH_SEPARATOR).collect();
+            insert_folder(&mut items, &path, requests);
+        }
+    }
+
+    if uses_hostname
+        && !variables
+            .iter()
+            .any(|variable| variable["key"] == "baseUrl")
+    {
+        variables.push(json!({ "key": "baseUrl", "value": HOSTNAME }));
+    }
+
+    let mut info = json!({
+        "_postman_id": Uuid::new_v4().to_string(),
+        "name": api.name,
+        "schema": SCHEMA,
+    });
+    if let Some(description) = &api.description {
+        info["description"] = Value::from(description.as_str());
+    }
+
+    Ok(json!({
+        "info": info,
+        "item": items,
+        "variable": variables,
+    }))
+}
+
+pub fn export_to_file(api_id: i32, path: &str) -> Result<(), PostmanError> {
+    let collection = export(api_id)?;
+    let text = serde_json::to_string_pretty(&collection)
+        .map_err(|err| PostmanError::Syntax(err.to_string()))?;
+
+    fs::write(path, text).map_err(|err| PostmanError::Write(path.to_string(), err))
+}
+
+/// Adds requests to the folder at `path`, creating the folders on the way.
+fn insert_folder(items: &mut Vec<Value>, path: &[&str], requests: Vec<Value>) {
+    let (name, rest) = match path.split_first() {
+        Some(split) => split,
+        None => {
+            items.extend(requests);
+            return;
+        }
+    };
+
+    let index = match items
+        .iter()
+        .position(|item| item.get("item").is_some() && item["name"] == *name)
+    {
+        Some(index) => index,
+        None => {
+            items.push(json!({ "name": name, "item": [] }));
+            items.len() - 1
+        }
+    };
+
+    if let Some(Value::Array(children)) = items[index].get_mut("item") {
+        insert_folder(children, rest, requests);
+    }
+}
+
+fn export_request(request: &Request) -> Value {
+    let headers = header_service::get_headers_for_request(request.id);
+    let params = param_service::get_params_for_request(request.id);
+    let form_parts = form_part_service::get_form_parts_for_request(request.id);
+
+    let name = import_service::get_request_name(request)
+        .unwrap_or_else(|| format!("{} {}", request.method, request.route));
+
+    let content_type = headers
+        .iter()
+        .find(|header| header.key.eq_ignore_ascii_case("content-type"))
+        .map(|header| header.value.as_str());
+
+    let body = if request.kind == RequestKind::Graphql {
+        graphql_service::get_query_for_request(request.id).map(|query| {
+            json!({
+                "mode": "graphql",
+                "graphql": { "query": query.query, "variables": query.variables },
+            })
+        })
+    } else if !form_parts.is_empty() {
+        let parts: Vec<Value> = form_parts
+            .iter()
+            .map(|part| {
+                let mut exported = match part.kind {
+                    FormPartKind::Text => {
+                        json!({ "key": part.name, "value": part.value, "type": "text" })
+                    }
+                    FormPartKind::File => {
+                        json!({ "key": part.name, "src": part.value, "type": "file" })
+                    }
+                };
+                if let Some(content_type) = &part.content_type {
+                    exported["contentType"] = Value::from(content_type.as_str());
+                }
+                exported
+            })
+            .collect();
+        Some(json!({ "mode": "formdata", "formdata": parts }))
+    } else {
+        request.body.as_ref().map(|body| match content_type {
+            Some(content_type) if content_type.contains("x-www-form-urlencoded") => {
+                let pairs: Vec<Value> = form_urlencoded::parse(body.as_bytes())
+                    .map(|(key, value)| json!({ "key": key, "value": value }))
+                    .collect();
+                json!({ "mode": "urlencoded", "urlencoded": pairs })
+            }
+            _ => {
+                let language = match content_type {
+                    Some(content_type) if content_type.contains("json") => "json",
+                    Some(content_type) if content_type.contains("xml") => "xml",
+                    Some(content_type) if content_type.contains("html") => "html",
+                    Some(content_type) if content_type.contains("javascript") => "javascript",
+                    None if serde_json::from_str::<Value>(body).is_ok() => "json",
+                    _ => "text",
+                };
+                json!({
+                    "mode": "raw",
+                    "raw": body,
+                    "options": { "raw": { "language": language } },
+                })
+            }
+        })
+    };
+
+    let credentials = headers.iter().find_map(basic_credentials);
+    let headers: Vec<Value> = headers
+        .iter()
+        .filter(|header| credentials.is_none() || basic_credentials(header).is_none())
+        .map(|header| json!({ "key": header.key, "value": header.value }))
+        .collect();
+
+    let mut exported_request = Map::new();
+    exported_request.insert("method".into(), Value::from(request.method.as_str()));
+    exported_request.insert("header".into(), Value::from(headers));
+    exported_request.insert("url".into(), export_url(&request.route, &params));
+    if let Some(body) = body {
+        exported_request.insert("body".into(), body);
+    }
+    if let Some(description) = &request.description {
+        exported_request.insert("description".into(), Value::from(description.as_str()));
+    }
+    if let Some((username, password)) = credentials {
+        exported_request.insert(
+            "auth".into(),
+            json!({
+                "type": "basic",
+                "basic": [
+                    { "key": "username", "value": username },
+                    { "key": "password", "value": password },
+                ],
+            }),
+        );
+    }
+
+    json!({ "name": name, "request": exported_request })
+}
+
+/// The user and password of an `Authorization: Basic user:password`
+/// header, which is how basic auth is kept until it is sent.
+fn basic_credentials(header: &Header) -> Option<(&str, &str)> {
+    if !header.key.eq_ignore_ascii_case("authorization") {
+        return None;
+    }
+    header.value.strip_prefix("Basic ")?.split_once(':')
+}
+
+/// Postman shows the `host`, `path` and `query` parts, `raw` is what it
+/// sends. Relative routes are made absolute with a `{{baseUrl}}` variable.
+fn export_url(route: &str, params: &[Param]) -> Value {
+    let route = if route.contains("://") || route.starts_with("{{") {
+        route.to_string()
+    } else {
+        format!("{{{{baseUrl}}}}{}", route)
+    };
+    let (location, inline_query) = match route.split_once('?') {
+        Some((location, query)) => (location, Some(query)),
+        None => (route.as_str(), None),
+    };
+
+    let mut query: Vec<(String, String)> = inline_query
+        .into_iter()
+        .flat_map(|query| query.split('&'))
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (key.to_string(), value.to_string())
+        })
+        .collect();
+    query.extend(
+        params
+            .iter()
+            .map(|param| (param.key.clone(), param.value.clone())),
+    );
+
+    let (protocol, rest) = match location.split_once("://") {
+        Some((protocol, rest)) => (Some(protocol), rest),
+        None => (None, location),
+    };
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) if port.chars().all(|c| c.is_ascii_digit()) => (host, Some(port)),
+        _ => (authority, None),
+    };
+
+    let mut raw = location.to_string();
+    if !query.is_empty() {
+        let pairs: Vec<String> = query
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        raw = format!("{}?{}", raw, pairs.join("&"));
+    }
+
+    let mut url = Map::new();
+    url.insert("raw".into(), Value::from(raw));
+    if let Some(protocol) = protocol {
+        url.insert("protocol".into(), Value::from(protocol));
+    }
+    let host: Vec<&str> = if host.starts_with("{{") {
+        vec![host]
+    } else {
+        host.split('.').collect()
+    };
+    url.insert("host".into(), Value::from(host));
+    if let Some(port) = port {
+        url.insert("port".into(), Value::from(port));
+    }
+    let path: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    url.insert("path".into(), Value::from(path));
+    if !query.is_empty() {
+        let query: Vec<Value> = query
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": value }))
+            .collect();
+        url.insert("query".into(), Value::from(query));
+    }
+
+    Value::Object(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION: &str = r#"{
+        "info": {
+            "name": "Shop",
+            "schema": "https://schema.getpostman.com/json/collection/v2.1.0/collection.json"
+        },
+        "auth": { "type": "bearer", "bearer": [{ "key": "token", "value": "{{token}}" }] },
+        "variable": [
+            { "key": "baseUrl", "value": "https://shop.test" },
+            { "key": "old", "value": "x", "disabled": true }
+        ],
+        "item": [
+            {
+                "name": "Orders",
+                "item": [{
+                    "name": "Photos",
+                    "auth": { "type": "basic", "basic": { "username": "ada", "password": "{{password}}" } },
+                    "item": [{
+                        "name": "Upload photo",
+                        "request": {
+                            "method": "POST",
+                            "url": {
+                                "raw": "{{baseUrl}}/orders/:id/photos?size=large",
+                                "query": [
+                                    { "key": "size", "value": "large" },
+                                    { "key": "debug", "value": "1", "disabled": true }
+                                ],
+                                "variable": [{ "key": "id", "value": "7" }]
+                            },
+                            "body": {
+                                "mode": "formdata",
+                                "formdata": [
+                                    { "key": "title", "value": "Holiday", "type": "text" },
+                                    { "key": "photo", "src": ["cat.png"], "type": "file" }
+                                ]
+                            }
+                        }
+                    }]
+                }]
+            },
+            {
+                "name": "Health",
+                "request": "{{baseUrl}}/health"
+            },
+            {
+                "name": "Search",
+                "request": {
+                    "method": "POST",
+                    "url": "{{baseUrl}}/search",
+                    "body": { "mode": "urlencoded", "urlencoded": [{ "key": "q", "value": "a b&c" }] }
+                }
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn recognises_collections() {
+        assert!(is_collection(COLLECTION));
+        assert!(!is_collection(r#"{ "openapi": "3.0.0" }"#));
+    }
+
+    #[test]
+    fn reads_folders_requests_and_variables() {
+        let imported = parse(COLLECTION).unwrap();
+
+        assert_eq!(imported.name, "Shop");
+        assert!(imported.complete);
+        assert_eq!(
+            imported.variables,
+            vec![(String::from("baseUrl"), String::from("https://shop.test"))]
+        );
+
+        let names: Vec<&str> = imported
+            .resources
+            .iter()
+            .map(|resource| resource.name.as_str())
+            .collect();
+        assert_eq!(names, ["Orders / Photos", "default"]);
+
+        let upload = &imported.resources[0].requests[0];
+        assert_eq!(upload.key, "Orders / Photos / Upload photo");
+        assert_eq!(upload.method, HttpMethod::Post);
+        assert_eq!(upload.route, "{{baseUrl}}/orders/7/photos");
+        assert_eq!(
+            upload.params,
+            vec![(String::from("size"), String::from("large"))]
+        );
+        assert_eq!(upload.form_parts.len(), 2);
+        assert_eq!(upload.form_parts[1].kind, FormPartKind::File);
+        assert_eq!(upload.form_parts[1].value, "cat.png");
+
+        let health = &imported.resources[1].requests[0];
+        assert_eq!(health.route, "{{baseUrl}}/health");
+        assert_eq!(health.method, HttpMethod::Get);
+    }
+
+    #[test]
+    fn turns_auth_into_headers() {
+        let imported = parse(COLLECTION).unwrap();
+
+        // Basic credentials are encoded when sent, once placeholders in
+        // them are filled in.
+        let upload = &imported.resources[0].requests[0];
+        assert!(upload.headers.contains(&(
+            String::from("Authorization"),
+            String::from("Basic ada:{{password}}")
+        )));
+
+        let health = &imported.resources[1].requests[0];
+        assert_eq!(
+            health.headers,
+            vec![(
+                String::from("Authorization"),
+                String::from("Bearer {{token}}")
+            )]
+        );
+    }
+
+    #[test]
+    fn exports_basic_credentials_as_auth() {
+        let summary = import_service::import_api(&parse(COLLECTION).unwrap()).unwrap();
+        let exported = export(summary.api_id).unwrap();
+
+        let find = |items: &Value, name: &str| {
+            items["item"]
+                .as_array()
+                .and_then(|items| items.iter().find(|item| item["name"] == name))
+                .cloned()
+                .unwrap()
+        };
+        let photos = find(&find(&exported, "Orders"), "Photos");
+        let upload = &find(&photos, "Upload photo")["request"];
+        assert_eq!(upload["auth"]["type"], "basic");
+        assert_eq!(
+            upload["auth"]["basic"],
+            json!([
+                { "key": "username", "value": "ada" },
+                { "key": "password", "value": "{{password}}" },
+            ])
+        );
+        assert!(upload["header"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .all(|header| header["key"] != "Authorization"));
+
+        let health = &find(&exported, "Health")["request"];
+        assert_eq!(
+            health["header"],
+            json!([{ "key": "Authorization", "value": "Bearer {{token}}" }])
+        );
+
+        // And is read back the same.
+        let again = parse(&exported.to_string()).unwrap();
+        let upload = again
+            .resources
+            .iter()
+            .find(|resource| resource.name == "Orders / Photos")
+            .map(|resource| &resource.requests[0])
+            .unwrap();
+        assert!(upload.headers.contains(&(
+            String::from("Authorization"),
+            String::from("Basic ada:{{password}}")
+        )));
+    }
+
+    #[test]
+    fn encodes_urlencoded_bodies() {
+        let imported = parse(COLLECTION).unwrap();
+        let search = &imported.resources[1].requests[1];
+
+        assert_eq!(search.body.as_deref(), Some("q=a+b%26c"));
+        assert!(search.headers.contains(&(
+            String::from("Content-Type"),
+            String::from("application/x-www-form-urlencoded")
+        )));
+    }
+
+    #[test]
+    fn rejects_other_documents() {
+        assert!(matches!(
+            parse(r#"{ "info": {} }"#),
+            Err(PostmanError::UnknownFormat)
+        ));
+        assert!(matches!(parse("{"), Err(PostmanError::Syntax(_))));
+    }
+}
@@ -0,0 +1,842 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use prost_reflect::DescriptorPool;
+use prost_types::{
+    field_descriptor_proto::{Label, Type},
+    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+    FileDescriptorProto, MessageOptions, MethodDescriptorProto, OneofDescriptorProto,
+    ServiceDescriptorProto,
+};
+use thiserror::Error;
+
+/// A problem in a `.proto` file, with the line it was found on.
+#[derive(Debug, Error)]
+#[error("{file}:{line}: {message}")]
+pub struct ProtoError {
+    pub file: String,
+    pub line: usize,
+    pub message: String,
+}
+
+/// Parses `paths` and everything they import into a descriptor pool. The
+/// Google well-known types are always available. Imports are looked up
+/// next to the importing file first and then next to each of `paths`.
+pub fn load_files(paths: &[&str]) -> Result<DescriptorPool, ProtoError> {
+    let mut pool = DescriptorPool::global();
+    let mut loaded: HashMap<String, FileDescriptorProto> = HashMap::new();
+    let roots: Vec<PathBuf> = paths
+        .iter()
+        .map(|path| {
+            Path::new(path)
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_default()
+        })
+        .collect();
+
+    let mut pending: Vec<(String, PathBuf)> = paths
+        .iter()
+        .map(|path| {
+            let name = Path::new(path)
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| path.to_string());
+            (name, PathBuf::from(path))
+        })
+        .collect();
+
+    while let Some((name, path)) = pending.pop() {
+        if loaded.contains_key(&name) || pool.get_file_by_name(&name).is_some() {
+            continue;
+        }
+
+        let source = fs::read_to_string(&path).map_err(|err| ProtoError {
+            file: name.clone(),
+            line: 0,
+            message: err.to_string(),
+        })?;
+        let file = parse(&name, &source)?;
+
+        let directory = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        for dependency in &file.dependency {
+            if loaded.contains_key(dependency) || pool.get_file_by_name(dependency).is_some() {
+                continue;
+            }
+            let found = std::iter::once(&directory)
+                .chain(roots.iter())
+                .map(|root| root.join(dependency))
+                .find(|candidate| candidate.is_file())
+                .ok_or_else(|| ProtoError {
+                    file: name.clone(),
+                    line: 0,
+                    message: format!("Import {} not found", dependency),
+                })?;
+            pending.push((dependency.clone(), found));
+        }
+
+        loaded.insert(name, file);
+    }
+
+    pool.add_file_descriptor_protos(loaded.into_values())
+        .map_err(|err| ProtoError {
+            file: String::from("<descriptors>"),
+            line: 0,
+            message: err.to_string(),
+        })?;
+
+    Ok(pool)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    Int(i64),
+    Float,
+    Str(String),
+    Symbol(char),
+}
+
+fn tokenize(file: &str, source: &str) -> Result<Vec<(Token, usize)>, ProtoError> {
+    let mut tokens = vec![];
+    let mut chars = source.chars().peekable();
+    let mut line = 1;
+    let error = |line, message: String| ProtoError {
+        file: file.to_string(),
+        line,
+        message,
+    };
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\n' => line += 1,
+            c if c.is_whitespace() => (),
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&next| next != '\n') {
+                    chars.next();
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut previous = ' ';
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        line += 1;
+                    }
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '"' | '\'' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some(next) if next == c => break,
+                        Some('\\') => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(match escaped {
+                                    'n' => '\n',
+                                    't' => '\t',
+                                    other => other,
+                                });
+                            }
+                        }
+                        Some('\n') | None => {
+                            return Err(error(line, String::from("Unterminated string")))
+                        }
+                        Some(next) => value.push(next),
+                    }
+                }
+                tokens.push((Token::Str(value), line));
+            }
+            c if c.is_ascii_digit() => {
+                let mut literal = String::from(c);
+                while let Some(next) =
+                    chars.next_if(|next| next.is_ascii_alphanumeric() || *next == '.')
+                {
+                    literal.push(next);
+                }
+                let parsed = if let Some(hex) = literal
+                    .strip_prefix("0x")
+                    .or_else(|| literal.strip_prefix("0X"))
+                {
+                    i64::from_str_radix(hex, 16).ok()
+                } else if literal.len() > 1 && literal.starts_with('0') {
+                    i64::from_str_radix(&literal[1..], 8).ok()
+                } else {
+                    literal.parse().ok()
+                };
+                tokens.push((parsed.map(Token::Int).unwrap_or(Token::Float), line));
+            }
+            c if c.is_alphabetic() || c == '_' || c == '.' => {
+                let mut ident = String::from(c);
+                while let Some(next) =
+                    chars.next_if(|next| next.is_alphanumeric() || *next == '_' || *next == '.')
+                {
+                    ident.push(next);
+                }
+                tokens.push((Token::Ident(ident), line));
+            }
+            c => tokens.push((Token::Symbol(c), line)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses the source of a single `.proto` file. Type references are kept
+/// as written; the descriptor pool resolves them against the scopes.
+pub fn parse(file: &str, source: &str) -> Result<FileDescriptorProto, ProtoError> {
+    let tokens = tokenize(file, source)?;
+    let mut parser = Parser {
+        file,
+        tokens: &tokens,
+        position: 0,
+    };
+    parser.file()
+}
+
+struct Parser<'a> {
+    file: &'a str,
+    tokens: &'a [(Token, usize)],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ProtoError> {
+        let line = self
+            .tokens
+            .get(self.position.min(self.tokens.len().saturating_sub(1)))
+            .map(|(_, line)| *line)
+            .unwrap_or(0);
+
+        Err(ProtoError {
+            file: self.file.to_string(),
+            line,
+            message: message.into(),
+        })
+    }
+
+    fn peek(&self) -> Option<&'a Token> {
+        self.tokens.get(self.position).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<&'a Token> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn peek_ident(&self) -> Option<&'a str> {
+        match self.peek() {
+            Some(Token::Ident(ident)) => Some(ident),
+            _ => None,
+        }
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.position += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), ProtoError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            self.error(format!("Expected {}", symbol))
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, ProtoError> {
+        match self.next() {
+            Some(Token::Ident(ident)) => Ok(ident.clone()),
+            _ => {
+                self.position -= 1;
+                self.error("Expected a name")
+            }
+        }
+    }
+
+    fn int(&mut self) -> Result<i32, ProtoError> {
+        let negative = self.eat('-');
+        match self.next() {
+            Some(Token::Int(value)) => {
+                let value = if negative { -value } else { *value };
+                i32::try_from(value).or_else(|_| self.error("Number out of range"))
+            }
+            _ => {
+                self.position -= 1;
+                self.error("Expected a number")
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ProtoError> {
+        match self.next() {
+            Some(Token::Str(value)) => Ok(value.clone()),
+            _ => {
+                self.position -= 1;
+                self.error("Expected a string")
+            }
+        }
+    }
+
+    /// Skips up to and including the next `;` at the current nesting
+    /// level, so aggregate option values in braces are skipped as well.
+    fn skip_statement(&mut self) -> Result<(), ProtoError> {
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some(Token::Symbol('{')) => depth += 1,
+                Some(Token::Symbol('}')) if depth > 0 => depth -= 1,
+                Some(Token::Symbol(';')) if depth == 0 => return Ok(()),
+                Some(_) => (),
+                None => return self.error("Unexpected end of file"),
+            }
+        }
+    }
+
+    fn skip_block(&mut self) -> Result<(), ProtoError> {
+        self.expect('{')?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next() {
+                Some(Token::Symbol('{')) => depth += 1,
+                Some(Token::Symbol('}')) => depth -= 1,
+                Some(_) => (),
+                None => return self.error("Unexpected end of file"),
+            }
+        }
+        Ok(())
+    }
+
+    /// Skips `[...]` field options.
+    fn skip_field_options(&mut self) -> Result<(), ProtoError> {
+        if !self.eat('[') {
+            return Ok(());
+        }
+        loop {
+            match self.next() {
+                Some(Token::Symbol(']')) => return Ok(()),
+                Some(_) => (),
+                None => return self.error("Unexpected end of file"),
+            }
+        }
+    }
+
+    fn file(&mut self) -> Result<FileDescriptorProto, ProtoError> {
+        let mut file = FileDescriptorProto {
+            name: Some(self.file.to_string()),
+            ..Default::default()
+        };
+
+        while let Some(token) = self.peek() {
+            match token {
+                Token::Symbol(';') => self.position += 1,
+                Token::Ident(keyword) => {
+                    self.position += 1;
+                    match keyword.as_str() {
+                        "syntax" | "edition" => {
+                            self.expect('=')?;
+                            let syntax = self.string()?;
+                            if syntax != "proto2" {
+                                file.syntax = Some(syntax);
+                            }
+                            self.expect(';')?;
+                        }
+                        "package" => {
+                            file.package = Some(self.ident()?);
+                            self.expect(';')?;
+                        }
+                        "import" => {
+                            let index = file.dependency.len() as i32;
+                            match self.peek_ident() {
+                                Some("public") => {
+                                    self.position += 1;
+                                    file.public_dependency.push(index);
+                                }
+                                Some("weak") => {
+                                    self.position += 1;
+                                    file.weak_dependency.push(index);
+                                }
+                                _ => (),
+                            }
+                            file.dependency.push(self.string()?);
+                            self.expect(';')?;
+                        }
+                        "option" => self.skip_statement()?,
+                        "message" => {
+                            let message = self.message(file.syntax.is_some())?;
+                            file.message_type.push(message);
+                        }
+                        "enum" => file.enum_type.push(self.enumeration()?),
+                        "service" => file.service.push(self.service()?),
+                        "extend" => {
+                            self.ident()?;
+                            self.skip_block()?;
+                        }
+                        other => return self.error(format!("Unexpected {}", other)),
+                    }
+                }
+                _ => return self.error("Expected a declaration"),
+            }
+        }
+
+        Ok(file)
+    }
+
+    fn message(&mut self, proto3: bool) -> Result<DescriptorProto, ProtoError> {
+        let mut message = DescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect('{')?;
+        // `optional` proto3 fields each get a synthetic oneof, which must
+        // come after the declared ones.
+        let mut proto3_optional: Vec<usize> = vec![];
+
+        loop {
+            match self.peek() {
+                Some(Token::Symbol('}')) => {
+                    self.position += 1;
+                    for field_index in proto3_optional {
+                        let field = &mut message.field[field_index];
+                        field.oneof_index = Some(message.oneof_decl.len() as i32);
+                        message.oneof_decl.push(OneofDescriptorProto {
+                            name: Some(format!("_{}", field.name())),
+                            ..Default::default()
+                        });
+                    }
+                    return Ok(message);
+                }
+                Some(Token::Symbol(';')) => self.position += 1,
+                Some(Token::Ident(keyword)) => match keyword.as_str() {
+                    "message" => {
+                        self.position += 1;
+                        let nested = self.message(proto3)?;
+                        message.nested_type.push(nested);
+                    }
+                    "enum" => {
+                        self.position += 1;
+                        message.enum_type.push(self.enumeration()?);
+                    }
+                    "option" | "reserved" | "extensions" => self.skip_statement()?,
+                    "extend" => {
+                        self.position += 1;
+                        self.ident()?;
+                        self.skip_block()?;
+                    }
+                    "oneof" => {
+                        self.position += 1;
+                        let index = message.oneof_decl.len() as i32;
+                        message.oneof_decl.push(OneofDescriptorProto {
+                            name: Some(self.ident()?),
+                            ..Default::default()
+                        });
+                        self.expect('{')?;
+                        while !self.eat('}') {
+                            if self.peek_ident() == Some("option") {
+                                self.skip_statement()?;
+                                continue;
+                            }
+                            let mut field = self.field(None)?;
+                            field.oneof_index = Some(index);
+                            message.field.push(field);
+                        }
+                    }
+                    "map" => {
+                        self.position += 1;
+                        let field = self.map_field(&mut message)?;
+                        message.field.push(field);
+                    }
+                    "group" => return self.error("Groups are not supported"),
+                    label @ ("optional" | "required" | "repeated") => {
+                        self.position += 1;
+                        let label = match label {
+                            "repeated" => Label::Repeated,
+                            "required" => Label::Required,
+                            _ => Label::Optional,
+                        };
+                        let mut field = self.field(Some(label))?;
+                        if proto3 && label == Label::Optional {
+                            field.proto3_optional = Some(true);
+                            proto3_optional.push(message.field.len());
+                        }
+                        message.field.push(field);
+                    }
+                    _ => {
+                        let field = self.field(None)?;
+                        message.field.push(field);
+                    }
+                },
+                Some(_) => return self.error("Expected a field"),
+                None => return self.error("Unexpected end of file"),
+            }
+        }
+    }
+
+    fn field(&mut self, label: Option<Label>) -> Result<FieldDescriptorProto, ProtoError> {
+        let type_name = self.ident()?;
+        if type_name == "group" {
+            return self.error("Groups are not supported");
+        }
+        let name = self.ident()?;
+        self.expect('=')?;
+        let number = self.int()?;
+        self.skip_field_options()?;
+        self.expect(';')?;
+
+        let mut field = FieldDescriptorProto {
+            name: Some(name),
+            number: Some(number),
+            ..Default::default()
+        };
+        field.set_label(label.unwrap_or(Label::Optional));
+        set_field_type(&mut field, &type_name);
+
+        Ok(field)
+    }
+
+    /// `map<K, V> name = n;` becomes a repeated field of a synthesized
+    /// `NameEntry` message, the same way protoc represents maps.
+    fn map_field(
+        &mut self,
+        message: &mut DescriptorProto,
+    ) -> Result<FieldDescriptorProto, ProtoError> {
+        self.expect('<')?;
+        let key_type = self.ident()?;
+        self.expect(',')?;
+        let value_type = self.ident()?;
+        self.expect('>')?;
+        let name = self.ident()?;
+        self.expect('=')?;
+        let number = self.int()?;
+        self.skip_field_options()?;
+        self.expect(';')?;
+
+        let entry_name = format!("{}Entry", to_pascal_case(&name));
+        let entry_field = |name: &str, number, type_name: &str| {
+            let mut field = FieldDescriptorProto {
+                name: Some(name.to_string()),
+                number: Some(number),
+                ..Default::default()
+            };
+            field.set_label(Label::Optional);
+            set_field_type(&mut field, type_name);
+            field
+        };
+        message.nested_type.push(DescriptorProto {
+            name: Some(entry_name.clone()),
+            field: vec![
+                entry_field("key", 1, &key_type),
+                entry_field("value", 2, &value_type),
+            ],
+            options: Some(MessageOptions {
+                map_entry: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+
+        let mut field = FieldDescriptorProto {
+            name: Some(name),
+            number: Some(number),
+            type_name: Some(entry_name),
+            ..Default::default()
+        };
+        field.set_label(Label::Repeated);
+        field.set_type(Type::Message);
+
+        Ok(field)
+    }
+
+    fn enumeration(&mut self) -> Result<EnumDescriptorProto, ProtoError> {
+        let mut enumeration = EnumDescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect('{')?;
+
+        while !self.eat('}') {
+            match self.peek_ident() {
+                Some("option") | Some("reserved") => self.skip_statement()?,
+                Some(_) => {
+                    let name = self.ident()?;
+                    self.expect('=')?;
+                    let number = self.int()?;
+                    self.skip_field_options()?;
+                    self.expect(';')?;
+                    enumeration.value.push(EnumValueDescriptorProto {
+                        name: Some(name),
+                        number: Some(number),
+                        ..Default::default()
+                    });
+                }
+                None if self.eat(';') => (),
+                None => return self.error("Expected an enum value"),
+            }
+        }
+
+        Ok(enumeration)
+    }
+
+    fn service(&mut self) -> Result<ServiceDescriptorProto, ProtoError> {
+        let mut service = ServiceDescriptorProto {
+            name: Some(self.ident()?),
+            ..Default::default()
+        };
+        self.expect('{')?;
+
+        while !self.eat('}') {
+            match self.peek_ident() {
+                Some("option") => self.skip_statement()?,
+                Some("rpc") => {
+                    self.position += 1;
+                    service.method.push(self.method()?);
+                }
+                _ if self.eat(';') => (),
+                _ => return self.error("Expected rpc"),
+            }
+        }
+
+        Ok(service)
+    }
+
+    fn method(&mut self) -> Result<MethodDescriptorProto, ProtoError> {
+        let name = self.ident()?;
+        let (client_streaming, input_type) = self.method_type()?;
+        if self.ident()? != "returns" {
+            return self.error("Expected returns");
+        }
+        let (server_streaming, output_type) = self.method_type()?;
+
+        if self.peek() == Some(&Token::Symbol('{')) {
+            self.skip_block()?;
+        } else {
+            self.expect(';')?;
+        }
+
+        Ok(MethodDescriptorProto {
+            name: Some(name),
+            input_type: Some(input_type),
+            output_type: Some(output_type),
+            client_streaming: Some(client_streaming),
+            server_streaming: Some(server_streaming),
+            ..Default::default()
+        })
+    }
+
+    fn method_type(&mut self) -> Result<(bool, String), ProtoError> {
+        self.expect('(')?;
+        let mut streaming = false;
+        let mut type_name = self.ident()?;
+        if type_name == "stream" && self.peek() != Some(&Token::Symbol(')')) {
+            streaming = true;
+            type_name = self.ident()?;
+        }
+        self.expect(')')?;
+        Ok((streaming, type_name))
+    }
+}
+
+fn set_field_type(field: &mut FieldDescriptorProto, type_name: &str) {
+    let scalar = match type_name {
+        "double" => Type::Double,
+        "float" => Type::Float,
+        "int32" => Type::Int32,
+        "int64" => Type::Int64,
+        "uint32" => Type::Uint32,
+        "uint64" => Type::Uint64,
+        "sint32" => Type::Sint32,
+        "sint64" => Type::Sint64,
+        "fixed32" => Type::Fixed32,
+        "fixed64" => Type::Fixed64,
+        "sfixed32" => Type::Sfixed32,
+        "sfixed64" => Type::Sfixed64,
+        "bool" => Type::Bool,
+        "string" => Type::String,
+        "bytes" => Type::Bytes,
+        _ => {
+            // Message or enum, which the pool works out when resolving.
+            field.type_name = Some(type_name.to_string());
+            return;
+        }
+    };
+    field.set_type(scalar);
+}
+
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nested_messages_and_enums() {
+        let source = r#"
+            syntax = "proto3";
+            package shop;
+
+            message Order {
+                message Line {
+                    string sku = 1;
+                    uint32 quantity = 2;
+                }
+                enum State {
+                    OPEN = 0;
+                    SHIPPED = 1;
+                }
+                repeated Line lines = 1;
+                State state = 2;
+                optional string note = 3;
+                map<string, int64> totals = 4;
+            }
+
+            enum Currency { EUR = 0; USD = -1; }
+        "#;
+        let file = parse("shop.proto", source).unwrap();
+
+        assert_eq!(file.package(), "shop");
+        assert_eq!(file.syntax(), "proto3");
+        let order = &file.message_type[0];
+        assert_eq!(order.nested_type[0].name(), "Line");
+        assert_eq!(order.nested_type[0].field[1].r#type(), Type::Uint32);
+        assert_eq!(order.nested_type[1].name(), "TotalsEntry");
+        assert_eq!(order.enum_type[0].value[1].name(), "SHIPPED");
+
+        let lines = &order.field[0];
+        assert_eq!(lines.label(), Label::Repeated);
+        assert_eq!(lines.type_name(), "Line");
+        let note = &order.field[2];
+        assert_eq!(note.proto3_optional, Some(true));
+        assert_eq!(order.oneof_decl[0].name(), "_note");
+        assert_eq!(order.field[3].type_name(), "TotalsEntry");
+
+        assert_eq!(file.enum_type[0].value[1].number(), -1);
+    }
+
+    #[test]
+    fn skips_comments_and_options() {
+        let source = r#"
+            // Line comment with a "quote" and a brace {
+            syntax = "proto3";
+            /* Block comment
+               message Hidden {} */
+            option java_package = "com.example";
+            option (custom.file) = { name: "a;b" nested { x: 1 } };
+
+            message Item {
+                option deprecated = true;
+                reserved 5, 6 to 8;
+                string name = 1 [deprecated = true, json_name = "label"]; // trailing
+            }
+
+            service Store {
+                option (custom.service) = "x";
+                rpc Get (Item) returns (stream Item) {
+                    option idempotency_level = NO_SIDE_EFFECTS;
+                }
+                rpc Put (stream Item) returns (Item);
+            }
+        "#;
+        let file = parse("store.proto", source).unwrap();
+
+        assert_eq!(file.message_type.len(), 1);
+        assert_eq!(file.message_type[0].field[0].name(), "name");
+        let methods = &file.service[0].method;
+        assert_eq!(methods[0].name(), "Get");
+        assert!(methods[0].server_streaming() && !methods[0].client_streaming());
+        assert!(methods[1].client_streaming() && !methods[1].server_streaming());
+    }
+
+    #[test]
+    fn records_imports() {
+        let source = r#"
+            syntax = "proto3";
+            import "common.proto";
+            import public "google/protobuf/timestamp.proto";
+            import weak "legacy.proto";
+        "#;
+        let file = parse("main.proto", source).unwrap();
+
+        assert_eq!(
+            file.dependency,
+            vec![
+                "common.proto",
+                "google/protobuf/timestamp.proto",
+                "legacy.proto"
+            ]
+        );
+        assert_eq!(file.public_dependency, vec![1]);
+        assert_eq!(file.weak_dependency, vec![2]);
+    }
+
+    #[test]
+    fn loads_imports_next_to_the_file() {
+        let directory = std::env::temp_dir().join("reqstr-proto-test");
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(
+            directory.join("common.proto"),
+            "syntax = \"proto3\"; package common; message Money { int64 cents = 1; }",
+        )
+        .unwrap();
+        let main = directory.join("main.proto");
+        fs::write(
+            &main,
+            r#"syntax = "proto3";
+            package shop;
+            import "common.proto";
+            import "google/protobuf/timestamp.proto";
+            message Price {
+                common.Money amount = 1;
+                google.protobuf.Timestamp valid_from = 2;
+            }"#,
+        )
+        .unwrap();
+
+        let pool = load_files(&[main.to_str().unwrap()]).unwrap();
+        let price = pool.get_message_by_name("shop.Price").unwrap();
+        let amount = price.get_field_by_name("amount").unwrap();
+        assert_eq!(
+            amount.kind().as_message().unwrap().full_name(),
+            "common.Money"
+        );
+
+        fs::remove_file(directory.join("common.proto")).unwrap();
+        let err = load_files(&[main.to_str().unwrap()]).unwrap_err();
+        assert_eq!(err.message, "Import common.proto not found");
+    }
+
+    #[test]
+    fn reports_the_line_of_an_error() {
+        let err = parse(
+            "bad.proto",
+            "syntax = \"proto3\";\n\nmessage A {\n  string = 1;\n}",
+        )
+        .unwrap_err();
+        assert_eq!(err.line, 4);
+        assert_eq!(err.to_string(), "bad.proto:4: Expected a name");
+
+        let err = parse("bad.proto", "message A { group G = 1 {} }").unwrap_err();
+        assert_eq!(err.message, "Groups are not supported");
+    }
+}
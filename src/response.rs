@@ -0,0 +1,261 @@
+use std::time::Duration;
+
+use crate::http::{HttpError, OpenResponse, SentRequest};
+
+/// Signatures of common binary formats, matched against the start of a body.
+const MAGIC_NUMBERS: [(&[u8], &str); 14] = [
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"BM", "image/bmp"),
+    (b"\x00\x00\x01\x00", "image/x-icon"),
+    (b"%PDF-", "application/pdf"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"BZh", "application/x-bzip2"),
+    (b"\x28\xb5\x2f\xfd", "application/zstd"),
+    (b"7z\xbc\xaf\x27\x1c", "application/x-7z-compressed"),
+    (b"\x00asm", "application/wasm"),
+    (b"SQLite format 3\x00", "application/vnd.sqlite3"),
+];
+
+/// Signatures at least this long are trusted even when the body reads as
+/// text.
+const MIN_TRUSTED_MAGIC: usize = 5;
+
+/// The media type of a body, as detected from its bytes and the
+/// `Content-Type` header.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContentType {
+    pub mime: String,
+    pub textual: bool,
+}
+
+/// A response that has been read in full. The body is kept as raw bytes so
+/// binary payloads survive untouched.
+pub struct HttpResponse {
+    pub status: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+    pub content_type: ContentType,
+    pub sent: SentRequest,
+    /// From sending the request until the whole body was read.
+    pub elapsed: Duration,
+}
+
+impl HttpResponse {
+    pub fn read(response: OpenResponse) -> Result<Self, HttpError> {
+        let sent = response.sent.clone();
+        let started = response.started;
+        let status = response.response.status().to_string();
+        let headers: Vec<_> = response
+            .response
+            .headers()
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let body = response.bytes()?;
+        let content_type = sniff(header_value(&headers, "content-type"), &body);
+
+        Ok(Self {
+            status,
+            headers,
+            body,
+            content_type,
+            sent,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// The body as text, if it was detected as textual.
+    pub fn text(&self) -> Option<&str> {
+        if self.content_type.textual {
+            std::str::from_utf8(&self.body).ok()
+        } else {
+            None
+        }
+    }
+
+    pub fn header(&self, key: &str) -> Option<&str> {
+        header_value(&self.headers, key)
+    }
+}
+
+fn header_value<'a>(headers: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Detects the type of `body`. Known magic numbers take precedence over the
+/// declared header, since servers regularly label binary payloads as
+/// `application/octet-stream` or even `text/plain`. Signatures of a few
+/// bytes, like the `BM` of bitmaps, also start plenty of text, so they only
+/// count for bodies that are not text.
+pub fn sniff(declared: Option<&str>, body: &[u8]) -> ContentType {
+    let is_utf8 = std::str::from_utf8(body).is_ok();
+    let has_control_characters = body
+        .iter()
+        .any(|byte| byte.is_ascii_control() && !b"\t\n\r\x0c".contains(byte));
+    let reads_as_text = is_utf8 && !has_control_characters;
+
+    if let Some((_, mime)) = MAGIC_NUMBERS.iter().find(|(magic, _)| {
+        body.starts_with(magic) && (magic.len() >= MIN_TRUSTED_MAGIC || !reads_as_text)
+    }) {
+        return ContentType {
+            mime: mime.to_string(),
+            textual: false,
+        };
+    }
+
+    let declared = declared
+        .and_then(|value| value.split(';').next())
+        .map(|mime| mime.trim().to_lowercase())
+        .filter(|mime| !mime.is_empty());
+
+    match declared {
+        Some(mime) => {
+            let textual = is_text_mime(&mime) && is_utf8;
+            ContentType { mime, textual }
+        }
+        None if reads_as_text => ContentType {
+            mime: String::from("text/plain"),
+            textual: true,
+        },
+        None => ContentType {
+            mime: String::from("application/octet-stream"),
+            textual: false,
+        },
+    }
+}
+
+fn is_text_mime(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || mime.ends_with("+json")
+        || mime.ends_with("+xml")
+        || [
+            "application/json",
+            "application/xml",
+            "application/javascript",
+            "application/ecmascript",
+            "application/x-www-form-urlencoded",
+            "application/x-ndjson",
+            "application/graphql",
+            "application/yaml",
+            "application/x-yaml",
+        ]
+        .contains(&mime)
+}
+
+/// Formats `bytes` as a classic hex dump: offset, sixteen hex bytes and
+/// their printable ASCII characters.
+pub fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .map(|(line, chunk)| {
+            let hex: Vec<_> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&byte| {
+                    if byte.is_ascii_graphic() || byte == b' ' {
+                        byte as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+
+            format!("{:08x}  {:<47}  |{}|", line * 16, hex.join(" "), ascii)
+        })
+        .collect()
+}
+
+/// Formats a byte count for display, e.g. `1.4 KiB`.
+pub fn human_size(size: usize) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut value = size as f64;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < units.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", size, units[0])
+    } else {
+        format!("{:.1} {}", value, units[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_starting_like_a_short_signature_stays_text() {
+        let content_type = sniff(Some("text/plain; charset=utf-8"), b"BMW 320i, 2004");
+        assert_eq!(content_type.mime, "text/plain");
+        assert!(content_type.textual);
+
+        let content_type = sniff(Some("application/json"), b"BZh is not a number");
+        assert_eq!(content_type.mime, "application/json");
+        assert!(content_type.textual);
+
+        assert!(sniff(None, b"BM is a fine start").textual);
+    }
+
+    #[test]
+    fn short_signatures_still_win_for_binary_bodies() {
+        let bitmap = b"BM\x36\x00\x0c\x00\x00\x00\x00\x00\x36\x00";
+        assert_eq!(sniff(Some("text/plain"), bitmap).mime, "image/bmp");
+
+        let icon = b"\x00\x00\x01\x00\x01\x00\x10\x10";
+        assert_eq!(sniff(None, icon).mime, "image/x-icon");
+
+        let gzip = b"\x1f\x8b\x08\x00\x00\x00";
+        assert_eq!(
+            sniff(Some("application/json"), gzip).mime,
+            "application/gzip"
+        );
+    }
+
+    #[test]
+    fn long_signatures_win_over_the_header() {
+        let png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR";
+        let content_type = sniff(Some("text/plain"), png);
+        assert_eq!(content_type.mime, "image/png");
+        assert!(!content_type.textual);
+
+        assert_eq!(
+            sniff(Some("text/plain"), b"%PDF-1.7").mime,
+            "application/pdf"
+        );
+    }
+
+    #[test]
+    fn declared_types_decide_without_a_signature() {
+        assert!(sniff(Some("application/problem+json"), b"{}").textual);
+        assert!(!sniff(Some("application/octet-stream"), b"hello").textual);
+        assert!(!sniff(Some("text/plain"), b"\xff\xfe").textual);
+        assert_eq!(sniff(None, b"\x01\x02").mime, "application/octet-stream");
+    }
+
+    #[test]
+    fn sizes_and_hex_dumps() {
+        assert_eq!(human_size(512), "512 B");
+        assert_eq!(human_size(1536), "1.5 KiB");
+        assert_eq!(
+            hex_dump(b"AB\n"),
+            vec![format!("00000000  {:<47}  |AB.|", "41 42 0a")]
+        );
+    }
+}
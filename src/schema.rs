@@ -2,6 +2,69 @@ table! {
     api (id) {
         id -> Integer,
         name -> Text,
+        description -> Nullable<Text>,
+        position -> Nullable<Integer>,
+    }
+}
+
+table! {
+    api_variable (id) {
+        id -> Integer,
+        api_id -> Integer,
+        key -> Text,
+        value -> Text,
+    }
+}
+
+table! {
+    use diesel::sql_types::Integer;
+    use crate::types::TreeNodeKindMapping;
+    expanded_node (id) {
+        id -> Integer,
+        kind -> TreeNodeKindMapping,
+        node_id -> Integer,
+    }
+}
+
+table! {
+    use diesel::sql_types::{Integer, Text, Nullable};
+    use crate::types::FormPartKindMapping;
+    form_part (id) {
+        id -> Integer,
+        name -> Text,
+        kind -> FormPartKindMapping,
+        value -> Text,
+        content_type -> Nullable<Text>,
+        request_id -> Integer,
+    }
+}
+
+table! {
+    graphql_query (id) {
+        id -> Integer,
+        request_id -> Integer,
+        query -> Text,
+        variables -> Text,
+        operation_name -> Nullable<Text>,
+    }
+}
+
+table! {
+    graphql_schema (id) {
+        id -> Integer,
+        api_id -> Integer,
+        introspection -> Text,
+        fetched_at -> Text,
+    }
+}
+
+table! {
+    grpc_call (id) {
+        id -> Integer,
+        request_id -> Integer,
+        proto_files -> Text,
+        method -> Nullable<Text>,
+        message -> Text,
     }
 }
 
@@ -14,6 +77,29 @@ table! {
     }
 }
 
+table! {
+    import_snapshot (id) {
+        id -> Integer,
+        request_id -> Integer,
+        source_key -> Text,
+        route -> Text,
+        method -> Text,
+        body -> Nullable<Text>,
+        headers -> Text,
+        params -> Text,
+    }
+}
+
+table! {
+    message_template (id) {
+        id -> Integer,
+        name -> Text,
+        body -> Text,
+        binary -> Bool,
+        request_id -> Integer,
+    }
+}
+
 table! {
     param (id) {
         id -> Integer,
@@ -24,14 +110,38 @@ table! {
 }
 
 table! {
-    use diesel::sql_types::{Integer, Text, Nullable};
-    use crate::types::HttpMethodMapping;
+    recorded_response (id) {
+        id -> Integer,
+        request_id -> Integer,
+        status -> Integer,
+        content_type -> Nullable<Text>,
+        body -> Text,
+        recorded_at -> Text,
+        headers -> Text,
+        body_base64 -> Bool,
+        duration_ms -> Integer,
+        method -> Text,
+        url -> Text,
+        request_headers -> Text,
+        request_body -> Nullable<Text>,
+    }
+}
+
+table! {
+    use diesel::sql_types::{Bool, Integer, Text, Nullable};
+    use crate::types::RequestKindMapping;
     request (id) {
         id -> Integer,
         route -> Text,
-        method -> HttpMethodMapping,
+        method -> Text,
         body -> Nullable<Text>,
         resource_id -> Integer,
+        kind -> RequestKindMapping,
+        name -> Nullable<Text>,
+        description -> Nullable<Text>,
+        position -> Nullable<Integer>,
+        last_run_at -> Nullable<Text>,
+        favourite -> Bool,
     }
 }
 
@@ -40,12 +150,65 @@ table! {
         id -> Integer,
         name -> Text,
         api_id -> Integer,
+        parent_id -> Nullable<Integer>,
+        description -> Nullable<Text>,
+        position -> Nullable<Integer>,
+    }
+}
+
+table! {
+    tag (id) {
+        id -> Integer,
+        request_id -> Integer,
+        name -> Text,
+    }
+}
+
+table! {
+    use diesel::sql_types::{Bool, Integer, Text, Nullable};
+    use crate::types::TlsVersionMapping;
+    tls_config (id) {
+        id -> Integer,
+        api_id -> Integer,
+        ca_bundles -> Text,
+        client_certificate -> Nullable<Text>,
+        client_key -> Nullable<Text>,
+        client_password_env -> Nullable<Text>,
+        min_tls_version -> Nullable<TlsVersionMapping>,
+        accept_invalid_certs -> Bool,
     }
 }
 
+joinable!(api_variable -> api (api_id));
+joinable!(form_part -> request (request_id));
+joinable!(graphql_query -> request (request_id));
+joinable!(graphql_schema -> api (api_id));
+joinable!(grpc_call -> request (request_id));
 joinable!(header -> request (request_id));
+joinable!(import_snapshot -> request (request_id));
+joinable!(message_template -> request (request_id));
 joinable!(param -> request (request_id));
+joinable!(recorded_response -> request (request_id));
 joinable!(request -> resource (resource_id));
 joinable!(resource -> api (api_id));
+joinable!(tag -> request (request_id));
+joinable!(tls_config -> api (api_id));
 
-allow_tables_to_appear_in_same_query!(api, header, param, request, resource,);
+allow_tables_to_appear_in_same_query!(
+    api,
+    api_variable,
+    expanded_node,
+    form_part,
+    graphql_query,
+    graphql_schema,
+    grpc_call,
+    header,
+    import_snapshot,
+    message_template,
+    param,
+    recorded_response,
+    request,
+    resource,
+    tag,
+    tls_config,
+);
@@ -1,39 +1,25 @@
 use crate::{
-    establish_connection,
     models::{Api, NewApi},
+    storage,
 };
-use diesel::{insert_into, prelude::*};
 
 pub fn get_all_apis() -> Vec<Api> {
-    use crate::schema::api::dsl::*;
-
-    let connection = establish_connection();
-    let results = api.load::<Api>(&connection).unwrap();
-
-    results
+    storage().get_all_apis()
 }
 
 pub fn get_api_by_id(api_id: i32) -> Option<Api> {
-    use crate::schema::api::dsl::*;
-
-    let connection = establish_connection();
-
-    let result = api.find(api_id).first(&connection);
-
-    if let Ok(matching) = result {
-        Some(matching)
-    } else {
-        None
-    }
+    storage().get_api_by_id(api_id)
 }
 
 pub fn create_new_api(new_api: NewApi) {
-    use crate::schema::api::dsl::*;
+    storage().create_new_api(new_api)
+}
 
-    let connection = establish_connection();
+pub fn update_api_description(obj: &Api, new_description: Option<&str>) -> Api {
+    storage().update_api_description(obj, new_description)
+}
 
-    insert_into(api)
-        .values(&new_api)
-        .execute(&connection)
-        .unwrap();
+/// Moves an API one place up or down the list, returning whether it could.
+pub fn move_api(api_id: i32, up: bool) -> bool {
+    storage().move_api(api_id, up)
 }
@@ -0,0 +1,11 @@
+use crate::{models::ExpandedNode, storage, types::TreeNodeKind};
+
+/// The APIs and folders left open in the collection tree.
+pub fn get_expanded_nodes() -> Vec<ExpandedNode> {
+    storage().get_expanded_nodes()
+}
+
+/// Remembers whether a row of the tree is open, for the next start.
+pub fn set_expanded(node_kind: TreeNodeKind, related_id: i32, expanded: bool) {
+    storage().set_expanded(node_kind, related_id, expanded)
+}
@@ -0,0 +1,20 @@
+use crate::{
+    models::{FormPart, NewFormPart},
+    storage,
+};
+
+pub fn get_form_parts_for_request(related_id: i32) -> Vec<FormPart> {
+    storage().get_form_parts_for_request(related_id)
+}
+
+pub fn create_new_form_part(new_form_part: NewFormPart) {
+    storage().create_new_form_part(new_form_part)
+}
+
+pub fn update_form_part(target: &FormPart, changes: &NewFormPart) {
+    storage().update_form_part(target, changes)
+}
+
+pub fn delete_form_part(target: &FormPart) {
+    storage().delete_form_part(target)
+}
@@ -0,0 +1,20 @@
+use crate::{
+    models::{GraphqlQuery, GraphqlSchema, NewGraphqlQuery, NewGraphqlSchema},
+    storage,
+};
+
+pub fn get_query_for_request(related_id: i32) -> Option<GraphqlQuery> {
+    storage().get_graphql_query_for_request(related_id)
+}
+
+pub fn save_query(changes: &NewGraphqlQuery) -> GraphqlQuery {
+    storage().save_graphql_query(changes)
+}
+
+pub fn get_schema_for_api(related_id: i32) -> Option<GraphqlSchema> {
+    storage().get_graphql_schema_for_api(related_id)
+}
+
+pub fn save_schema(changes: &NewGraphqlSchema) -> GraphqlSchema {
+    storage().save_graphql_schema(changes)
+}
@@ -0,0 +1,12 @@
+use crate::{
+    models::{GrpcCall, NewGrpcCall},
+    storage,
+};
+
+pub fn get_call_for_request(related_id: i32) -> Option<GrpcCall> {
+    storage().get_grpc_call_for_request(related_id)
+}
+
+pub fn save_call(changes: &NewGrpcCall) -> GrpcCall {
+    storage().save_grpc_call(changes)
+}
@@ -0,0 +1,229 @@
+use std::fmt;
+
+use crate::{
+    models::Request,
+    storage,
+    storage::StorageError,
+    types::{FormPartKind, HttpMethod},
+};
+
+/// Separates folder names, and the request name after them, in resource
+/// names and request keys, e.g. `Pets / Photos / Upload photo`.
+pub const PATH_SEPARATOR: &str = " / ";
+
+/// Tags requests that were imported before and are missing from a later
+/// import of the whole document. Taken off again when they come back.
+pub const REMOVED_TAG: &str = "removed-upstream";
+
+/// An API read from an external document, before it is stored.
+pub struct ImportedApi {
+    pub name: String,
+    /// Given to the API when it has no description yet.
+    pub description: Option<String>,
+    pub resources: Vec<ImportedResource>,
+    /// `{{key}}` variables the requests refer to, with their default values.
+    pub variables: Vec<(String, String)>,
+    /// Whether the document lists every request of the API, so that
+    /// requests imported before and missing now were removed upstream.
+    pub complete: bool,
+}
+
+pub struct ImportedResource {
+    pub name: String,
+    pub requests: Vec<ImportedRequest>,
+}
+
+pub struct ImportedRequest {
+    /// Identifies the request across imports of the same document, e.g. an
+    /// OpenAPI `operationId`.
+    pub key: String,
+    /// The name and description are only stored when the request is
+    /// created, like the form parts.
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub route: String,
+    pub method: HttpMethod,
+    pub body: Option<String>,
+    pub headers: Vec<(String, String)>,
+    pub params: Vec<(String, String)>,
+    /// Only stored when the request is created; later imports leave the
+    /// parts alone.
+    pub form_parts: Vec<ImportedFormPart>,
+}
+
+pub struct ImportedFormPart {
+    pub name: String,
+    pub kind: FormPartKind,
+    pub value: String,
+    pub content_type: Option<String>,
+}
+
+#[derive(Default)]
+pub struct ImportSummary {
+    pub api_id: i32,
+    pub created: usize,
+    pub updated: usize,
+    /// Values left alone because they were edited since the last import.
+    pub kept: usize,
+    /// Requests imported before that the document no longer has, tagged
+    /// with `REMOVED_TAG`.
+    pub removed: usize,
+}
+
+impl fmt::Display for ImportSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} new, {} updated, {} kept",
+            self.created, self.updated, self.kept
+        )?;
+        if self.removed > 0 {
+            write!(f, ", {} removed upstream", self.removed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Stores an imported API. When an API of the same name was imported
+/// before, requests are matched by their key and only the values nobody
+/// touched since are replaced. Requests sharing a key are matched in the
+/// order they were imported.
+pub fn import_api(imported: &ImportedApi) -> Result<ImportSummary, StorageError> {
+    storage().import_api(imported)
+}
+
+/// The key a request was imported under, e.g. its `operationId`.
+pub fn get_source_key(related_id: i32) -> Option<String> {
+    storage().get_source_key(related_id)
+}
+
+/// The name of a request, or else the one it was imported under, the last
+/// part of its key.
+pub fn get_request_name(request: &Request) -> Option<String> {
+    match &request.name {
+        Some(name) if !name.is_empty() => Some(name.clone()),
+        _ => get_source_key(request.id)
+            .and_then(|key| key.rsplit(PATH_SEPARATOR).next().map(str::to_string)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        models::NewHeader,
+        services::{header_service, request_service, resource_service, tag_service},
+    };
+
+    fn request(key: &str, method: HttpMethod, route: &str, body: Option<&str>) -> ImportedRequest {
+        ImportedRequest {
+            key: key.to_string(),
+            name: None,
+            description: None,
+            route: route.to_string(),
+            method,
+            body: body.map(str::to_string),
+            headers: vec![(String::from("Accept"), String::from("application/json"))],
+            params: vec![],
+            form_parts: vec![],
+        }
+    }
+
+    fn api(requests: Vec<ImportedRequest>) -> ImportedApi {
+        ImportedApi {
+            name: String::from("Merged pets"),
+            description: None,
+            resources: vec![ImportedResource {
+                name: String::from("Pets"),
+                requests,
+            }],
+            variables: vec![],
+            complete: true,
+        }
+    }
+
+    /// The request of an imported API that was imported under `key`.
+    fn by_key(api_id: i32, key: &str) -> Request {
+        resource_service::get_resources_for_api(api_id)
+            .iter()
+            .flat_map(|resource| request_service::get_requests_for_resource(resource.id).unwrap())
+            .find(|request| get_source_key(request.id).as_deref() == Some(key))
+            .unwrap()
+    }
+
+    fn tags(request: &Request) -> Vec<String> {
+        tag_service::get_tags_for_request(request.id)
+            .into_iter()
+            .map(|tag| tag.name)
+            .collect()
+    }
+
+    #[test]
+    fn merges_re_imports_and_tags_removed_requests() {
+        let first = || {
+            api(vec![
+                request("listPets", HttpMethod::Get, "/pets", None),
+                request("deletePet", HttpMethod::Delete, "/pets/{id}", None),
+            ])
+        };
+        let summary = import_api(&first()).unwrap();
+        assert_eq!(summary.created, 2);
+        let api_id = summary.api_id;
+
+        // Edited here since.
+        let listed = by_key(api_id, "listPets");
+        request_service::update_request_route(&listed, "/pets/all");
+        let accept = &header_service::get_headers_for_request(listed.id)[0];
+        header_service::update_header(
+            accept,
+            &NewHeader {
+                key: "Accept",
+                value: "text/plain",
+                request_id: listed.id,
+            },
+        );
+
+        let mut changed = request("listPets", HttpMethod::Get, "/pets", Some("{}"));
+        changed.headers[0].1 = String::from("application/xml");
+        let summary = import_api(&api(vec![
+            changed,
+            request("createPet", HttpMethod::Post, "/pets", None),
+        ]))
+        .unwrap();
+        assert_eq!(summary.api_id, api_id);
+        assert_eq!(summary.created, 1);
+        assert_eq!(summary.updated, 1);
+        assert_eq!(summary.kept, 2);
+        assert_eq!(summary.removed, 1);
+
+        // What was edited here is kept, what wasn't follows the document.
+        let listed = by_key(api_id, "listPets");
+        assert_eq!(listed.route, "/pets/all");
+        assert_eq!(listed.body.as_deref(), Some("{}"));
+        assert_eq!(
+            header_service::get_headers_for_request(listed.id)[0].value,
+            "text/plain"
+        );
+        let deleted = by_key(api_id, "deletePet");
+        assert_eq!(tags(&deleted), [REMOVED_TAG]);
+
+        // The tag goes once the request is back.
+        let summary = import_api(&first()).unwrap();
+        assert_eq!(summary.removed, 1);
+        assert!(tags(&deleted).is_empty());
+        assert_eq!(tags(&by_key(api_id, "createPet")), [REMOVED_TAG]);
+    }
+
+    #[test]
+    fn reports_requests_removed_upstream() {
+        let summary = ImportSummary {
+            created: 1,
+            removed: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            summary.to_string(),
+            "1 new, 0 updated, 0 kept, 2 removed upstream"
+        );
+    }
+}
@@ -0,0 +1,16 @@
+use crate::{
+    models::{MessageTemplate, NewMessageTemplate},
+    storage,
+};
+
+pub fn get_templates_for_request(related_id: i32) -> Vec<MessageTemplate> {
+    storage().get_templates_for_request(related_id)
+}
+
+pub fn create_new_template(new_template: NewMessageTemplate) {
+    storage().create_new_template(new_template)
+}
+
+pub fn delete_template(target: &MessageTemplate) {
+    storage().delete_template(target)
+}
@@ -1,5 +1,16 @@
 pub mod api_service;
+pub mod expanded_node_service;
+pub mod form_part_service;
+pub mod graphql_service;
+pub mod grpc_service;
 pub mod header_service;
+pub mod import_service;
+pub mod message_template_service;
 pub mod param_service;
+pub mod recorded_response_service;
 pub mod request_service;
 pub mod resource_service;
+pub mod search_service;
+pub mod tag_service;
+pub mod tls_service;
+pub mod variable_service;
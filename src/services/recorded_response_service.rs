@@ -0,0 +1,41 @@
+use crate::{
+    models::{NewRecordedResponse, RecordedResponse},
+    storage,
+};
+
+/// How many exchanges are kept per request, the oldest go first.
+pub const HISTORY_LIMIT: i64 = 50;
+
+/// Every exchange kept for a request, the latest first.
+pub fn get_responses_for_request(related_id: i32) -> Vec<RecordedResponse> {
+    storage().get_responses_for_request(related_id)
+}
+
+/// The latest textual response for each status code of a request, ordered
+/// by status, to use as examples.
+pub fn get_examples_for_request(related_id: i32) -> Vec<RecordedResponse> {
+    let mut examples: Vec<RecordedResponse> = vec![];
+    for response in get_responses_for_request(related_id) {
+        if response.text().is_some()
+            && !examples
+                .iter()
+                .any(|example| example.status == response.status)
+        {
+            examples.push(response);
+        }
+    }
+    examples.sort_by_key(|example| example.status);
+    examples
+}
+
+/// The request and status of the latest recorded response of each
+/// request.
+pub fn get_latest_statuses() -> Vec<(i32, i32)> {
+    storage().get_latest_statuses()
+}
+
+/// Adds an exchange to the history of its request, dropping the oldest
+/// beyond `HISTORY_LIMIT`.
+pub fn record_response(changes: &NewRecordedResponse) {
+    storage().record_response(changes)
+}
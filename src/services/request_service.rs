@@ -1,74 +1,67 @@
-use diesel::{insert_into, prelude::*};
-
 use crate::{
-    establish_connection,
     models::{NewRequest, Request},
-    types::HttpMethod,
+    storage,
+    types::{HttpMethod, RequestKind},
 };
 
 pub fn get_requests_for_resource(related_id: i32) -> Result<Vec<Request>, String> {
-    use crate::schema::request::dsl::*;
-    let conn = establish_connection();
+    storage().get_requests_for_resource(related_id)
+}
 
-    let result = request
-        .filter(resource_id.eq(related_id))
-        .load::<Request>(&conn);
+/// Every request of every API.
+pub fn get_all_requests() -> Vec<Request> {
+    storage().get_all_requests()
+}
 
-    match result {
-        Ok(requests) => Ok(requests),
-        Err(err) => Err(format!("{}", err)),
-    }
+/// The requests starred as favourites, of every API.
+pub fn get_favourite_requests() -> Vec<Request> {
+    storage().get_favourite_requests()
 }
 
 pub fn get_request_by_id(relevant_id: i32) -> Option<Request> {
-    use crate::schema::request::dsl::*;
-
-    let conn = establish_connection();
-
-    request.find(relevant_id).first(&conn).ok()
+    storage().get_request_by_id(relevant_id)
 }
 
 pub fn create_new_request(new_request: NewRequest) {
-    use crate::schema::request::dsl::*;
-
-    let conn = establish_connection();
-
-    insert_into(request)
-        .values(&new_request)
-        .execute(&conn)
-        .unwrap();
+    storage().create_new_request(new_request)
 }
 
-pub fn update_request(target: &Request, changes: &NewRequest) {
-    use crate::schema::request::dsl::*;
-
-    let conn = establish_connection();
-
-    diesel::update(target).set(changes).execute(&conn).unwrap();
+/// Like `create_new_request`, returning the request that was created.
+pub fn create_request(new_request: NewRequest) -> Request {
+    storage().create_request(new_request)
 }
 
 pub fn update_request_method(obj: &Request, new_method: HttpMethod) -> Request {
-    use crate::schema::request::dsl::*;
+    storage().update_request_method(obj, new_method)
+}
 
-    let conn = establish_connection();
+pub fn update_request_kind(obj: &Request, new_kind: RequestKind) -> Request {
+    storage().update_request_kind(obj, new_kind)
+}
 
-    diesel::update(obj)
-        .set(method.eq(new_method))
-        .execute(&conn)
-        .unwrap();
+pub fn update_request_route(obj: &Request, new_route: &str) -> Request {
+    storage().update_request_route(obj, new_route)
+}
 
-    request.find(obj.id).first(&conn).unwrap()
+pub fn update_request_name(obj: &Request, new_name: Option<&str>) -> Request {
+    storage().update_request_name(obj, new_name)
 }
 
-pub fn update_request_route(obj: &Request, new_route: &str) -> Request {
-    use crate::schema::request::dsl::*;
+pub fn update_request_description(obj: &Request, new_description: Option<&str>) -> Request {
+    storage().update_request_description(obj, new_description)
+}
 
-    let conn = establish_connection();
+/// Moves a request one place up or down its folder, returning whether it
+/// could.
+pub fn move_request(obj: &Request, up: bool) -> bool {
+    storage().move_request(obj, up)
+}
 
-    diesel::update(obj)
-        .set(route.eq(new_route))
-        .execute(&conn)
-        .unwrap();
+/// Notes that a request was sent just now.
+pub fn mark_run(request_id: i32) {
+    storage().mark_run(request_id)
+}
 
-    request.find(obj.id).first(&conn).unwrap()
+pub fn update_request_favourite(obj: &Request, new_favourite: bool) -> Request {
+    storage().update_request_favourite(obj, new_favourite)
 }
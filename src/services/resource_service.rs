@@ -1,41 +1,71 @@
-use diesel::{insert_into, prelude::*};
-
 use crate::{
-    establish_connection,
     models::{NewResource, Resource},
+    services::import_service::PATH_SEPARATOR,
+    storage,
 };
 
 pub fn get_resources_for_api(related_id: i32) -> Vec<Resource> {
-    use crate::schema::resource::dsl::*;
+    storage().get_resources_for_api(related_id)
+}
 
-    let conn = establish_connection();
+/// The folders at the top of an API.
+pub fn get_root_resources(related_id: i32) -> Vec<Resource> {
+    storage().get_root_resources(related_id)
+}
 
-    let results = resource
-        .filter(api_id.eq(related_id))
-        .load::<Resource>(&conn);
+/// The folders directly inside a folder.
+pub fn get_child_resources(related_id: i32) -> Vec<Resource> {
+    storage().get_child_resources(related_id)
+}
 
-    if let Ok(resources) = results {
-        resources
-    } else {
-        vec![]
+/// A folder and the folders it is in, outermost first.
+pub fn get_resource_path(resource_id: i32) -> Vec<Resource> {
+    let mut path = vec![];
+    let mut next = Some(resource_id);
+    while let Some(id) = next {
+        match get_resource_by_id(id) {
+            // A folder can't be in itself, the loop would never end.
+            Some(found) if !path.iter().any(|known: &Resource| known.id == found.id) => {
+                next = found.parent_id;
+                path.push(found);
+            }
+            _ => break,
+        }
     }
+    path.reverse();
+    path
 }
 
-pub fn get_resource_by_id(resource_id: i32) -> Option<Resource> {
-    use crate::schema::resource::dsl::*;
-
-    let conn = establish_connection();
+/// The names of the folders a resource is in and its own, joined like
+/// `Pets / Photos`, looked up among all the resources of its API.
+pub fn path_name(resources: &[Resource], resource: &Resource) -> String {
+    let mut names = vec![resource.name.as_str()];
+    let mut parent_id = resource.parent_id;
+    while let Some(parent) = parent_id.and_then(|id| resources.iter().find(|r| r.id == id)) {
+        if names.len() > resources.len() {
+            break;
+        }
+        names.push(&parent.name);
+        parent_id = parent.parent_id;
+    }
+    names.reverse();
+    names.join(PATH_SEPARATOR)
+}
 
-    resource.find(resource_id).first(&conn).ok()
+pub fn get_resource_by_id(resource_id: i32) -> Option<Resource> {
+    storage().get_resource_by_id(resource_id)
 }
 
 pub fn create_new_resource(new_resource: NewResource) {
-    use crate::schema::resource::dsl::*;
+    storage().create_new_resource(new_resource)
+}
 
-    let conn = establish_connection();
+pub fn update_resource_description(obj: &Resource, new_description: Option<&str>) -> Resource {
+    storage().update_resource_description(obj, new_description)
+}
 
-    insert_into(resource)
-        .values(&new_resource)
-        .execute(&conn)
-        .unwrap();
+/// Moves a folder one place up or down among the folders next to it,
+/// returning whether it could.
+pub fn move_resource(obj: &Resource, up: bool) -> bool {
+    storage().move_resource(obj, up)
 }
@@ -0,0 +1,27 @@
+use diesel::sql_types::{Integer, Text};
+
+use crate::storage;
+
+/// Put around the words found in a snippet.
+pub const MATCH_START: char = '\u{2}';
+pub const MATCH_END: char = '\u{3}';
+
+/// A request whose body, a header, a param or a recorded response has the
+/// words searched for.
+#[derive(QueryableByName)]
+pub struct SearchHit {
+    /// `body`, `header`, `param` or `response`.
+    #[sql_type = "Text"]
+    pub source: String,
+    #[sql_type = "Integer"]
+    pub request_id: i32,
+    /// The part around the words found, which are marked with
+    /// `MATCH_START` and `MATCH_END`.
+    #[sql_type = "Text"]
+    pub snippet: String,
+}
+
+/// The best matches for the words typed, each taken as the start of a word.
+pub fn search(text: &str) -> Vec<SearchHit> {
+    storage().search(text)
+}
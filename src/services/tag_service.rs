@@ -0,0 +1,15 @@
+use crate::{models::Tag, storage};
+
+pub fn get_tags_for_request(related_id: i32) -> Vec<Tag> {
+    storage().get_tags_for_request(related_id)
+}
+
+/// The tags of every request.
+pub fn get_all_tags() -> Vec<Tag> {
+    storage().get_all_tags()
+}
+
+/// Replaces the tags of a request, leaving out blank and repeated names.
+pub fn set_tags(related_id: i32, names: &[&str]) -> Vec<Tag> {
+    storage().set_tags(related_id, names)
+}
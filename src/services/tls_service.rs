@@ -0,0 +1,12 @@
+use crate::{
+    models::{NewTlsConfig, TlsConfig},
+    storage,
+};
+
+pub fn get_tls_config_for_api(related_id: i32) -> Option<TlsConfig> {
+    storage().get_tls_config_for_api(related_id)
+}
+
+pub fn save_tls_config(changes: &NewTlsConfig) -> TlsConfig {
+    storage().save_tls_config(changes)
+}
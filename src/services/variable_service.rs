@@ -0,0 +1,18 @@
+use crate::{
+    models::{ApiVariable, NewApiVariable},
+    storage,
+};
+
+pub fn get_variables_for_api(related_id: i32) -> Vec<ApiVariable> {
+    storage().get_variables_for_api(related_id)
+}
+
+/// Sets the value of a variable, adding it when the API has none by that
+/// key.
+pub fn save_variable(changes: &NewApiVariable) {
+    storage().save_variable(changes)
+}
+
+pub fn delete_variable(target: &ApiVariable) {
+    storage().delete_variable(target)
+}
@@ -0,0 +1,560 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use diesel::{
+    prelude::*,
+    sql_query,
+    sql_types::{BigInt, Integer, Nullable, Text},
+    sqlite::SqliteConnection,
+};
+use serde_json::{Map, Value};
+use serde_yaml::Mapping;
+
+use super::StorageError;
+
+/// A table stored inside the document of the row it belongs to, under `key`.
+/// Rows nested this way are the shape both the YAML directory backend and
+/// backups are written in.
+pub struct Nested {
+    pub table: &'static str,
+    pub parent_column: &'static str,
+    pub key: &'static str,
+    /// Whether there is at most one row per parent.
+    pub single: bool,
+    /// Whether the rows belong to one machine, like recorded responses, or
+    /// may hold secrets, like variables and TLS settings, and are left out
+    /// of files meant to be shared.
+    pub local: bool,
+}
+
+pub const API_TABLES: [Nested; 3] = [
+    Nested {
+        table: "api_variable",
+        parent_column: "api_id",
+        key: "variables",
+        single: false,
+        local: true,
+    },
+    Nested {
+        table: "tls_config",
+        parent_column: "api_id",
+        key: "tls",
+        single: true,
+        local: true,
+    },
+    Nested {
+        table: "graphql_schema",
+        parent_column: "api_id",
+        key: "graphql_schema",
+        single: true,
+        local: true,
+    },
+];
+
+pub const REQUEST_TABLES: [Nested; 9] = [
+    Nested {
+        table: "header",
+        parent_column: "request_id",
+        key: "headers",
+        single: false,
+        local: false,
+    },
+    Nested {
+        table: "param",
+        parent_column: "request_id",
+        key: "params",
+        single: false,
+        local: false,
+    },
+    Nested {
+        table: "form_part",
+        parent_column: "request_id",
+        key: "form_parts",
+        single: false,
+        local: false,
+    },
+    Nested {
+        table: "graphql_query",
+        parent_column: "request_id",
+        key: "graphql",
+        single: true,
+        local: false,
+    },
+    Nested {
+        table: "grpc_call",
+        parent_column: "request_id",
+        key: "grpc",
+        single: true,
+        local: false,
+    },
+    Nested {
+        table: "message_template",
+        parent_column: "request_id",
+        key: "message_templates",
+        single: false,
+        local: false,
+    },
+    Nested {
+        table: "tag",
+        parent_column: "request_id",
+        key: "tags",
+        single: false,
+        local: false,
+    },
+    Nested {
+        table: "import_snapshot",
+        parent_column: "request_id",
+        key: "import_snapshot",
+        single: true,
+        local: false,
+    },
+    Nested {
+        table: "recorded_response",
+        parent_column: "request_id",
+        key: "recorded_responses",
+        single: false,
+        local: true,
+    },
+];
+
+/// Columns that only mean something on one machine, like when a request
+/// was last sent, by table. They are left out of files meant to be shared,
+/// so using a request does not change its file.
+pub const LOCAL_COLUMNS: [(&str, &str); 1] = [("request", "last_run_at")];
+
+/// The local columns of a table.
+pub fn local_columns(table: &str) -> Vec<&'static str> {
+    LOCAL_COLUMNS
+        .iter()
+        .filter(|(local_table, _)| *local_table == table)
+        .map(|(_, column)| *column)
+        .collect()
+}
+
+/// The nested tables to read or write, with or without local ones.
+pub fn nested(tables: &'static [Nested], with_local: bool) -> Vec<&'static Nested> {
+    tables
+        .iter()
+        .filter(|nested| with_local || !nested.local)
+        .collect()
+}
+
+/// Every table documents are made of, parents first.
+pub fn tables(with_local: bool) -> Vec<&'static str> {
+    let mut tables = vec!["api"];
+    tables.extend(
+        nested(&API_TABLES, with_local)
+            .iter()
+            .map(|nested| nested.table),
+    );
+    tables.extend(["resource", "request"]);
+    tables.extend(
+        nested(&REQUEST_TABLES, with_local)
+            .iter()
+            .map(|nested| nested.table),
+    );
+    tables
+}
+
+#[derive(QueryableByName)]
+struct Column {
+    #[sql_type = "Text"]
+    name: String,
+    #[sql_type = "Text"]
+    #[column_name = "type"]
+    kind: String,
+    #[sql_type = "Nullable<Text>"]
+    dflt_value: Option<String>,
+}
+
+#[derive(QueryableByName)]
+struct NextId {
+    #[sql_type = "BigInt"]
+    next: i64,
+}
+
+#[derive(QueryableByName)]
+struct JsonRow {
+    #[sql_type = "Text"]
+    json: String,
+}
+
+fn columns(conn: &SqliteConnection, table: &str) -> Result<Vec<Column>, StorageError> {
+    Ok(sql_query(format!(
+        "SELECT name, type, dflt_value FROM pragma_table_info('{}') ORDER BY cid",
+        table
+    ))
+    .load(conn)?)
+}
+
+/// The rows of a table, or those belonging to one parent, oldest first.
+/// Each row is a mapping in column order, without the `skip` columns. Tables are read
+/// column by column, so columns added by later migrations are carried
+/// along.
+pub fn rows(
+    conn: &SqliteConnection,
+    table: &str,
+    parent: Option<(&str, i32)>,
+    skip: &[&str],
+) -> Result<Vec<Mapping>, StorageError> {
+    let columns = columns(conn, table)?;
+    let pairs: Vec<String> = columns
+        .iter()
+        .map(|column| format!("'{name}', \"{name}\"", name = column.name))
+        .collect();
+    let filter = match parent {
+        Some((column, _)) => format!("WHERE \"{}\" = ?", column),
+        None => String::new(),
+    };
+    let query = sql_query(format!(
+        "SELECT json_object({}) AS json FROM {} {} ORDER BY id",
+        pairs.join(", "),
+        table,
+        filter
+    ));
+    let found: Vec<JsonRow> = match parent {
+        Some((_, id)) => query.bind::<Integer, _>(id).load(conn)?,
+        None => query.load(conn)?,
+    };
+
+    let mut mappings = vec![];
+    for row in found {
+        let row: Map<String, Value> = serde_json::from_str(&row.json)
+            .map_err(|err| StorageError::Syntax(table.to_string(), err.to_string()))?;
+        let mut mapping = Mapping::new();
+        for column in columns
+            .iter()
+            .filter(|column| !skip.contains(&column.name.as_str()))
+        {
+            let mut value = row.get(&column.name).cloned().unwrap_or(Value::Null);
+            if column.kind.to_uppercase().contains("BOOL") {
+                if let Some(number) = value.as_i64() {
+                    value = Value::Bool(number != 0);
+                }
+            }
+            mapping.insert(
+                serde_yaml::Value::String(column.name.clone()),
+                serde_yaml::to_value(value).unwrap_or(serde_yaml::Value::Null),
+            );
+        }
+        mappings.push(mapping);
+    }
+    Ok(mappings)
+}
+
+/// Adds the rows of nested tables to a parent's mapping. Empty lists are
+/// left out to keep documents short.
+pub fn add_nested(
+    conn: &SqliteConnection,
+    mapping: &mut Mapping,
+    nested: &[&Nested],
+    parent_id: i32,
+) -> Result<(), StorageError> {
+    for nested in nested {
+        let children = rows(
+            conn,
+            nested.table,
+            Some((nested.parent_column, parent_id)),
+            &[nested.parent_column],
+        )?;
+        let value = if nested.single {
+            match children.into_iter().next() {
+                Some(child) => serde_yaml::Value::Mapping(child),
+                None => continue,
+            }
+        } else if children.is_empty() {
+            continue;
+        } else {
+            serde_yaml::Value::Sequence(
+                children
+                    .into_iter()
+                    .map(serde_yaml::Value::Mapping)
+                    .collect(),
+            )
+        };
+        insert(mapping, nested.key, value);
+    }
+    Ok(())
+}
+
+pub fn insert(mapping: &mut Mapping, key: &str, value: serde_yaml::Value) {
+    mapping.insert(serde_yaml::Value::String(key.to_string()), value);
+}
+
+pub fn id_of(mapping: &Mapping) -> i32 {
+    mapping
+        .get(&serde_yaml::Value::String(String::from("id")))
+        .and_then(serde_yaml::Value::as_i64)
+        .unwrap_or(0) as i32
+}
+
+pub fn text_of(mapping: &Mapping, key: &str) -> String {
+    mapping
+        .get(&serde_yaml::Value::String(key.to_string()))
+        .and_then(serde_yaml::Value::as_str)
+        .unwrap_or("")
+        .to_string()
+}
+
+/// A folder of an API, with the folders in it.
+pub struct Folder {
+    /// The resource row, without the `api_id` and `parent_id` the tree
+    /// stands for.
+    pub row: Mapping,
+    pub children: Vec<Folder>,
+}
+
+/// The folders of an API as a tree, oldest first. Folders whose parent is
+/// missing are put at the top.
+pub fn folders(conn: &SqliteConnection, api_id: i32) -> Result<Vec<Folder>, StorageError> {
+    let resources = rows(conn, "resource", Some(("api_id", api_id)), &["api_id"])?;
+    let ids: Vec<i64> = resources.iter().map(|row| id_of(row) as i64).collect();
+
+    let mut by_parent: HashMap<Option<i64>, Vec<Mapping>> = HashMap::new();
+    for mut row in resources {
+        let parent_id = row
+            .remove(&serde_yaml::Value::String(String::from("parent_id")))
+            .and_then(|parent_id| parent_id.as_i64())
+            .filter(|parent_id| ids.contains(parent_id));
+        by_parent.entry(parent_id).or_default().push(row);
+    }
+    Ok(folder_tree(&mut by_parent, None))
+}
+
+fn folder_tree(
+    by_parent: &mut HashMap<Option<i64>, Vec<Mapping>>,
+    parent_id: Option<i64>,
+) -> Vec<Folder> {
+    by_parent
+        .remove(&parent_id)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|row| Folder {
+            children: folder_tree(by_parent, Some(id_of(&row) as i64)),
+            row,
+        })
+        .collect()
+}
+
+/// Every API with its local tables, its resources as a tree under
+/// `resources` and their requests under `requests`, as backups are
+/// written.
+pub fn dump(conn: &SqliteConnection) -> Result<Vec<Mapping>, StorageError> {
+    let mut apis = vec![];
+    for mut api in rows(conn, "api", None, &[])? {
+        let api_id = id_of(&api);
+        add_nested(conn, &mut api, &nested(&API_TABLES, true), api_id)?;
+
+        let mut resources = vec![];
+        for folder in folders(conn, api_id)? {
+            resources.push(serde_yaml::Value::Mapping(dump_resource(conn, folder)?));
+        }
+        insert(
+            &mut api,
+            "resources",
+            serde_yaml::Value::Sequence(resources),
+        );
+        apis.push(api);
+    }
+    Ok(apis)
+}
+
+/// A resource with its requests, and the resources in it under
+/// `resources`.
+fn dump_resource(conn: &SqliteConnection, folder: Folder) -> Result<Mapping, StorageError> {
+    let mut resource = folder.row;
+    let resource_id = id_of(&resource);
+
+    let mut requests = vec![];
+    for mut request in rows(
+        conn,
+        "request",
+        Some(("resource_id", resource_id)),
+        &["resource_id"],
+    )? {
+        let request_id = id_of(&request);
+        add_nested(
+            conn,
+            &mut request,
+            &nested(&REQUEST_TABLES, true),
+            request_id,
+        )?;
+        requests.push(serde_yaml::Value::Mapping(request));
+    }
+    insert(
+        &mut resource,
+        "requests",
+        serde_yaml::Value::Sequence(requests),
+    );
+
+    if !folder.children.is_empty() {
+        let mut children = vec![];
+        for child in folder.children {
+            children.push(serde_yaml::Value::Mapping(dump_resource(conn, child)?));
+        }
+        insert(
+            &mut resource,
+            "resources",
+            serde_yaml::Value::Sequence(children),
+        );
+    }
+    Ok(resource)
+}
+
+/// A row read from a document, before it is given its ids.
+pub struct LoadedRow {
+    pub table: &'static str,
+    pub row: Map<String, Value>,
+    /// The indexes of the parent rows, and the columns that refer to them.
+    pub parents: Vec<(usize, &'static str)>,
+}
+
+/// Adds a row read from a document, and the rows nested in it, returning
+/// the index of the row.
+pub fn push_document(
+    rows: &mut Vec<LoadedRow>,
+    table: &'static str,
+    mut document: Map<String, Value>,
+    parents: &[(usize, &'static str)],
+    nested: &[&'static Nested],
+) -> usize {
+    let index = rows.len();
+    let children: Vec<(&Nested, Value)> = nested
+        .iter()
+        .filter_map(|nested| Some((*nested, document.remove(nested.key)?)))
+        .collect();
+    rows.push(LoadedRow {
+        table,
+        row: document,
+        parents: parents.to_vec(),
+    });
+
+    for (nested, value) in children {
+        let children = match value {
+            Value::Array(children) => children,
+            Value::Null => vec![],
+            child => vec![child],
+        };
+        for child in children {
+            if let Value::Object(child) = child {
+                rows.push(LoadedRow {
+                    table: nested.table,
+                    row: child,
+                    parents: vec![(index, nested.parent_column)],
+                });
+            }
+        }
+    }
+    index
+}
+
+/// Gives rows without an id the next free one of their table, counting
+/// from `next_ids` where given, and links every row to its parent. A row
+/// whose id an earlier row of its table already has, as when two branches
+/// each added a request, is given a new one too. Parents always come
+/// before their children. Returns whether any id was given.
+pub fn assign_ids(rows: &mut [LoadedRow], mut next_ids: HashMap<&'static str, i64>) -> bool {
+    for loaded in rows.iter() {
+        let id = loaded.row.get("id").and_then(Value::as_i64).unwrap_or(0);
+        let next = next_ids.entry(loaded.table).or_insert(1);
+        *next = (*next).max(id + 1);
+    }
+
+    let mut taken: HashSet<(&str, i64)> = HashSet::new();
+    let mut assigned = false;
+    for index in 0..rows.len() {
+        let table = rows[index].table;
+        let id = rows[index].row.get("id").and_then(Value::as_i64);
+        if !id.is_some_and(|id| taken.insert((table, id))) {
+            let next = next_ids.entry(table).or_insert(1);
+            rows[index]
+                .row
+                .insert(String::from("id"), Value::from(*next));
+            taken.insert((table, *next));
+            *next += 1;
+            assigned = true;
+        }
+        for (parent, column) in rows[index].parents.clone() {
+            let parent_id = rows[parent].row.get("id").cloned().unwrap_or(Value::Null);
+            rows[index].row.insert(column.to_string(), parent_id);
+        }
+    }
+    assigned
+}
+
+/// Adds rows read from a backup. With `replace`, every table is emptied
+/// first and the rows keep their ids; otherwise they are given ids after
+/// the last of their table.
+pub fn restore(
+    conn: &SqliteConnection,
+    mut rows: Vec<LoadedRow>,
+    replace: bool,
+) -> Result<(), StorageError> {
+    let mut next_ids = HashMap::new();
+    if !replace {
+        for table in tables(true) {
+            let next = sql_query(format!(
+                "SELECT coalesce(max(id), 0) + 1 AS next FROM {}",
+                table
+            ))
+            .get_result::<NextId>(conn)?
+            .next;
+            next_ids.insert(table, next);
+        }
+    }
+    assign_ids(&mut rows, next_ids);
+
+    conn.transaction::<_, StorageError, _>(|| {
+        if replace {
+            for table in tables(true).into_iter().rev() {
+                sql_query(format!("DELETE FROM {}", table)).execute(conn)?;
+            }
+        }
+        insert_loaded(conn, rows)
+    })
+}
+
+/// Inserts loaded rows, a statement per table.
+pub fn insert_loaded(conn: &SqliteConnection, rows: Vec<LoadedRow>) -> Result<(), StorageError> {
+    let mut by_table: BTreeMap<&str, Vec<Value>> = BTreeMap::new();
+    for loaded in rows {
+        by_table
+            .entry(loaded.table)
+            .or_default()
+            .push(Value::Object(loaded.row));
+    }
+    for (table, rows) in by_table {
+        insert_rows(conn, table, &rows)?;
+    }
+    Ok(())
+}
+
+/// Inserts rows given as JSON objects. Columns missing from a row get
+/// their default, keys that are not columns are ignored.
+fn insert_rows(conn: &SqliteConnection, table: &str, rows: &[Value]) -> Result<(), StorageError> {
+    let columns = columns(conn, table)?;
+    let names: Vec<String> = columns
+        .iter()
+        .map(|column| format!("\"{}\"", column.name))
+        .collect();
+    let values: Vec<String> = columns
+        .iter()
+        .map(|column| {
+            format!(
+                "CASE WHEN json_type(value, '$.{name}') IS NULL THEN {default} \
+                 ELSE json_extract(value, '$.{name}') END",
+                name = column.name,
+                default = column.dflt_value.as_deref().unwrap_or("NULL"),
+            )
+        })
+        .collect();
+
+    sql_query(format!(
+        "INSERT INTO {} ({}) SELECT {} FROM json_each(?)",
+        table,
+        names.join(", "),
+        values.join(", ")
+    ))
+    .bind::<Text, _>(Value::Array(rows.to_vec()).to_string())
+    .execute(conn)?;
+    Ok(())
+}
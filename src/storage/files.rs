@@ -0,0 +1,732 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use diesel::{prelude::*, sql_query, sql_types::BigInt, sqlite::SqliteConnection};
+use serde_json::{Map, Value};
+use serde_yaml::Mapping;
+
+use super::{
+    document::{
+        self, add_nested, assign_ids, id_of, insert_loaded, push_document, rows, text_of, Folder,
+        LoadedRow, API_TABLES, REQUEST_TABLES,
+    },
+    SqliteStorage, Storage, StorageError,
+};
+use crate::{
+    models::{
+        Api, ApiVariable, ExpandedNode, FormPart, GraphqlQuery, GraphqlSchema, GrpcCall, Header,
+        MessageTemplate, NewApi, NewApiVariable, NewFormPart, NewGraphqlQuery, NewGraphqlSchema,
+        NewGrpcCall, NewHeader, NewMessageTemplate, NewParam, NewRecordedResponse, NewRequest,
+        NewResource, NewTlsConfig, Param, RecordedResponse, Request, Resource, Tag, TlsConfig,
+    },
+    services::{
+        import_service::{ImportSummary, ImportedApi},
+        search_service::SearchHit,
+    },
+    types::{HttpMethod, RequestKind, TreeNodeKind},
+};
+
+embed_migrations!();
+
+/// Kept next to the files and out of version control.
+const CACHE_DIR: &str = ".reqstr";
+const CACHE_FILE: &str = "cache.sqlite3";
+const API_FILE: &str = "api.yaml";
+const RESOURCE_FILE: &str = "resource.yaml";
+
+/// A directory of YAML files that can be committed with the code it tests:
+/// a folder per API and resource, resources nested in the resources they
+/// are in, and a file per request, with ids kept in the
+/// files so they stay the same on every machine.
+///
+/// Queries run on a cache database rebuilt from the files on start. It also
+/// keeps what belongs to one machine only: API variables, TLS settings,
+/// recorded responses and fetched GraphQL schemas.
+pub struct FileStorage {
+    root: PathBuf,
+    cache: SqliteStorage,
+    watch: Mutex<Watch>,
+}
+
+/// A connection kept open to notice when other connections wrote to the
+/// cache, and the `data_version` it saw after the last flush.
+struct Watch {
+    conn: SqliteConnection,
+    version: i64,
+}
+
+/// Implements the operations of `Storage` by running them on the cache,
+/// whose changes `flush` then writes to the files.
+macro_rules! forward_to_cache {
+    ($(fn $name:ident(&self $(, $arg:ident: $ty:ty)*) $(-> $ret:ty)?;)*) => {
+        $(
+            fn $name(&self $(, $arg: $ty)*) $(-> $ret)? {
+                self.cache.$name($($arg),*)
+            }
+        )*
+    };
+}
+
+#[derive(QueryableByName)]
+struct Count {
+    #[sql_type = "BigInt"]
+    count: i64,
+}
+
+#[derive(QueryableByName)]
+struct DataVersion {
+    #[sql_type = "BigInt"]
+    data_version: i64,
+}
+
+impl Storage for FileStorage {
+    forward_to_cache! {
+        fn get_all_apis(&self) -> Vec<Api>;
+        fn get_api_by_id(&self, api_id: i32) -> Option<Api>;
+        fn create_new_api(&self, new_api: NewApi);
+        fn update_api_description(&self, obj: &Api, new_description: Option<&str>) -> Api;
+        fn move_api(&self, api_id: i32, up: bool) -> bool;
+        fn get_variables_for_api(&self, related_id: i32) -> Vec<ApiVariable>;
+        fn save_variable(&self, changes: &NewApiVariable);
+        fn delete_variable(&self, target: &ApiVariable);
+        fn get_tls_config_for_api(&self, related_id: i32) -> Option<TlsConfig>;
+        fn save_tls_config(&self, changes: &NewTlsConfig) -> TlsConfig;
+        fn get_graphql_schema_for_api(&self, related_id: i32) -> Option<GraphqlSchema>;
+        fn save_graphql_schema(&self, changes: &NewGraphqlSchema) -> GraphqlSchema;
+        fn get_resources_for_api(&self, related_id: i32) -> Vec<Resource>;
+        fn get_root_resources(&self, related_id: i32) -> Vec<Resource>;
+        fn get_child_resources(&self, related_id: i32) -> Vec<Resource>;
+        fn get_resource_by_id(&self, resource_id: i32) -> Option<Resource>;
+        fn create_new_resource(&self, new_resource: NewResource);
+        fn update_resource_description(&self, obj: &Resource, new_description: Option<&str>) -> Resource;
+        fn move_resource(&self, obj: &Resource, up: bool) -> bool;
+        fn get_requests_for_resource(&self, related_id: i32) -> Result<Vec<Request>, String>;
+        fn get_all_requests(&self) -> Vec<Request>;
+        fn get_favourite_requests(&self) -> Vec<Request>;
+        fn get_request_by_id(&self, relevant_id: i32) -> Option<Request>;
+        fn create_new_request(&self, new_request: NewRequest);
+        fn create_request(&self, new_request: NewRequest) -> Request;
+        fn update_request_method(&self, obj: &Request, new_method: HttpMethod) -> Request;
+        fn update_request_kind(&self, obj: &Request, new_kind: RequestKind) -> Request;
+        fn update_request_route(&self, obj: &Request, new_route: &str) -> Request;
+        fn update_request_name(&self, obj: &Request, new_name: Option<&str>) -> Request;
+        fn update_request_description(&self, obj: &Request, new_description: Option<&str>) -> Request;
+        fn update_request_favourite(&self, obj: &Request, new_favourite: bool) -> Request;
+        fn move_request(&self, obj: &Request, up: bool) -> bool;
+        fn mark_run(&self, request_id: i32);
+        fn get_headers_for_request(&self, related_id: i32) -> Vec<Header>;
+        fn create_new_header(&self, new_header: NewHeader);
+        fn update_header(&self, target: &Header, changes: &NewHeader);
+        fn get_params_for_request(&self, related_id: i32) -> Vec<Param>;
+        fn create_new_param(&self, new_param: NewParam);
+        fn get_form_parts_for_request(&self, related_id: i32) -> Vec<FormPart>;
+        fn create_new_form_part(&self, new_form_part: NewFormPart);
+        fn update_form_part(&self, target: &FormPart, changes: &NewFormPart);
+        fn delete_form_part(&self, target: &FormPart);
+        fn get_graphql_query_for_request(&self, related_id: i32) -> Option<GraphqlQuery>;
+        fn save_graphql_query(&self, changes: &NewGraphqlQuery) -> GraphqlQuery;
+        fn get_grpc_call_for_request(&self, related_id: i32) -> Option<GrpcCall>;
+        fn save_grpc_call(&self, changes: &NewGrpcCall) -> GrpcCall;
+        fn get_templates_for_request(&self, related_id: i32) -> Vec<MessageTemplate>;
+        fn create_new_template(&self, new_template: NewMessageTemplate);
+        fn delete_template(&self, target: &MessageTemplate);
+        fn get_tags_for_request(&self, related_id: i32) -> Vec<Tag>;
+        fn get_all_tags(&self) -> Vec<Tag>;
+        fn set_tags(&self, related_id: i32, names: &[&str]) -> Vec<Tag>;
+        fn get_responses_for_request(&self, related_id: i32) -> Vec<RecordedResponse>;
+        fn get_latest_statuses(&self) -> Vec<(i32, i32)>;
+        fn record_response(&self, changes: &NewRecordedResponse);
+        fn get_expanded_nodes(&self) -> Vec<ExpandedNode>;
+        fn set_expanded(&self, node_kind: TreeNodeKind, related_id: i32, expanded: bool);
+        fn search(&self, text: &str) -> Vec<SearchHit>;
+        fn import_api(&self, imported: &ImportedApi) -> Result<ImportSummary, StorageError>;
+        fn get_source_key(&self, related_id: i32) -> Option<String>;
+        fn dump(&self) -> Result<Vec<Mapping>, StorageError>;
+        fn restore(&self, rows: Vec<LoadedRow>, replace: bool) -> Result<(), StorageError>;
+    }
+
+    fn flush(&self) -> Result<(), StorageError> {
+        let mut watch = self.watch.lock().unwrap_or_else(|err| err.into_inner());
+        let version = data_version(&watch.conn)?;
+        if version == watch.version {
+            return Ok(());
+        }
+
+        // The changes are only forgotten once the files are written, so a
+        // failed write is tried again on the next flush.
+        watch.conn.transaction::<_, StorageError, _>(|| {
+            let changes = sql_query("SELECT count(*) AS count FROM storage_change")
+                .get_result::<Count>(&watch.conn)?
+                .count;
+            if changes > 0 {
+                sql_query("DELETE FROM storage_change").execute(&watch.conn)?;
+                self.write(&watch.conn)?;
+            }
+            Ok(())
+        })?;
+        watch.version = version;
+        Ok(())
+    }
+}
+
+impl FileStorage {
+    pub fn open(root: &str) -> Result<Self, StorageError> {
+        let root = PathBuf::from(root);
+        let cache_dir = root.join(CACHE_DIR);
+        fs::create_dir_all(&cache_dir).map_err(|err| write_error(&cache_dir, err))?;
+
+        let ignore = root.join(".gitignore");
+        if !ignore.exists() {
+            fs::write(&ignore, format!("{}/\n", CACHE_DIR))
+                .map_err(|err| write_error(&ignore, err))?;
+        }
+
+        let cache = cache_dir.join(CACHE_FILE).to_string_lossy().to_string();
+        let conn = SqliteConnection::establish(&cache)
+            .map_err(|err| StorageError::Connection(cache.clone(), err))?;
+        embedded_migrations::run(&conn)
+            .map_err(|err| StorageError::Migration(cache.clone(), err))?;
+        track_changes(&conn)?;
+
+        let storage = Self {
+            root,
+            cache: SqliteStorage::new(&cache),
+            watch: Mutex::new(Watch {
+                version: data_version(&conn)?,
+                conn,
+            }),
+        };
+        {
+            let watch = storage.watch.lock().unwrap_or_else(|err| err.into_inner());
+            let assigned_ids = storage.load(&watch.conn)?;
+            sql_query("DELETE FROM storage_change").execute(&watch.conn)?;
+            if assigned_ids {
+                storage.write(&watch.conn)?;
+            }
+        }
+
+        Ok(storage)
+    }
+
+    /// Replaces what the cache holds with the files. Returns whether rows
+    /// were given an id, either because they had none, like requests
+    /// written by hand, or because another row already had theirs.
+    fn load(&self, conn: &SqliteConnection) -> Result<bool, StorageError> {
+        let mut rows = vec![];
+
+        for api_dir in sorted_entries(&self.root)? {
+            let api_file = api_dir.join(API_FILE);
+            if !api_file.is_file() {
+                continue;
+            }
+            let api = read_document(&api_file)?;
+            let api_index = push_document(
+                &mut rows,
+                "api",
+                api,
+                &[],
+                &document::nested(&API_TABLES, false),
+            );
+
+            for resource_dir in sorted_entries(&api_dir)? {
+                if resource_dir.join(RESOURCE_FILE).is_file() {
+                    load_resource(&mut rows, &resource_dir, api_index, None)?;
+                }
+            }
+        }
+
+        let assigned_ids = assign_ids(&mut rows, HashMap::new());
+
+        conn.transaction::<_, StorageError, _>(|| {
+            // Local columns are not in the files, they are kept from the
+            // cache for the rows that are still there.
+            for (table, column) in document::LOCAL_COLUMNS {
+                sql_query(format!(
+                    "CREATE TEMP TABLE local_{table}_{column} AS \
+                     SELECT id, \"{column}\" AS value FROM {table}",
+                    table = table,
+                    column = column,
+                ))
+                .execute(conn)?;
+            }
+            for table in document::tables(false) {
+                sql_query(format!("DELETE FROM {}", table)).execute(conn)?;
+            }
+
+            insert_loaded(conn, rows)?;
+
+            for (table, column) in document::LOCAL_COLUMNS {
+                sql_query(format!(
+                    "UPDATE {table} SET \"{column}\" = coalesce(\
+                     (SELECT value FROM local_{table}_{column} \
+                     WHERE local_{table}_{column}.id = {table}.id), \"{column}\")",
+                    table = table,
+                    column = column,
+                ))
+                .execute(conn)?;
+                sql_query(format!("DROP TABLE local_{}_{}", table, column)).execute(conn)?;
+            }
+
+            // What this machine kept for APIs and requests that are gone
+            // from the files.
+            for (tables, parent) in [(&API_TABLES[..], "api"), (&REQUEST_TABLES[..], "request")] {
+                for nested in tables.iter().filter(|nested| nested.local) {
+                    sql_query(format!(
+                        "DELETE FROM {} WHERE {} NOT IN (SELECT id FROM {})",
+                        nested.table, nested.parent_column, parent
+                    ))
+                    .execute(conn)?;
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(assigned_ids)
+    }
+
+    /// Writes every API to the files, leaving files that did not change
+    /// untouched and removing those of rows that are gone.
+    fn write(&self, conn: &SqliteConnection) -> Result<(), StorageError> {
+        let documents = documents(conn)?;
+
+        for (relative, content) in &documents {
+            let path = self.root.join(relative);
+            if fs::read_to_string(&path).ok().as_ref() == Some(content) {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|err| write_error(parent, err))?;
+            }
+            fs::write(&path, content).map_err(|err| write_error(&path, err))?;
+        }
+
+        // Only folders of APIs are looked at, other files may live next to
+        // them.
+        for api_dir in sorted_entries(&self.root)? {
+            if api_dir.join(API_FILE).is_file() {
+                remove_stale(&self.root, &api_dir, &documents)?;
+                remove_if_empty(&api_dir)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Adds a resource read from its folder, with its requests and the
+/// resources in it.
+fn load_resource(
+    rows: &mut Vec<LoadedRow>,
+    dir: &Path,
+    api_index: usize,
+    parent_index: Option<usize>,
+) -> Result<(), StorageError> {
+    let mut resource = read_document(&dir.join(RESOURCE_FILE))?;
+    let mut parents = vec![(api_index, "api_id")];
+    match parent_index {
+        Some(parent_index) => parents.push((parent_index, "parent_id")),
+        None => {
+            resource.insert(String::from("parent_id"), Value::Null);
+        }
+    }
+    let resource_index = push_document(rows, "resource", resource, &parents, &[]);
+
+    for path in sorted_entries(dir)? {
+        if path.join(RESOURCE_FILE).is_file() {
+            load_resource(rows, &path, api_index, Some(resource_index))?;
+            continue;
+        }
+
+        let is_request = path.is_file()
+            && path.extension().is_some_and(|ext| ext == "yaml")
+            && path.file_name().is_some_and(|name| name != RESOURCE_FILE);
+        if is_request {
+            let request = read_document(&path)?;
+            push_document(
+                rows,
+                "request",
+                request,
+                &[(resource_index, "resource_id")],
+                &document::nested(&REQUEST_TABLES, false),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Marks the cache as changed whenever a stored table is written to, so
+/// `flush` knows when to write the files.
+fn track_changes(conn: &SqliteConnection) -> Result<(), StorageError> {
+    sql_query("CREATE TABLE IF NOT EXISTS storage_change (changed INTEGER NOT NULL)")
+        .execute(conn)?;
+
+    let shared = document::tables(false);
+    for table in document::tables(true) {
+        for event in ["INSERT", "UPDATE", "DELETE"] {
+            // Caches made before a table became local still have its
+            // triggers.
+            if !shared.contains(&table) {
+                sql_query(format!(
+                    "DROP TRIGGER IF EXISTS {}_{}_change",
+                    table,
+                    event.to_lowercase()
+                ))
+                .execute(conn)?;
+                continue;
+            }
+            sql_query(format!(
+                "CREATE TRIGGER IF NOT EXISTS {table}_{event}_change AFTER {event} ON {table} \
+                 BEGIN INSERT INTO storage_change SELECT 1 \
+                 WHERE NOT EXISTS (SELECT 1 FROM storage_change); END",
+                table = table,
+                event = event.to_lowercase(),
+            ))
+            .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// The content of every file, by path relative to the root.
+fn documents(conn: &SqliteConnection) -> Result<BTreeMap<PathBuf, String>, StorageError> {
+    let mut documents = BTreeMap::new();
+    let mut api_names = HashMap::new();
+
+    for mut api in rows(conn, "api", None, &[])? {
+        let api_id = id_of(&api);
+        let api_dir = PathBuf::from(unique_name(
+            &mut api_names,
+            &slug(&text_of(&api, "name"), "api"),
+            api_id,
+        ));
+        add_nested(
+            conn,
+            &mut api,
+            &document::nested(&API_TABLES, false),
+            api_id,
+        )?;
+        documents.insert(api_dir.join(API_FILE), to_yaml(api)?);
+
+        let mut resource_names = HashMap::new();
+        for folder in document::folders(conn, api_id)? {
+            add_resource(conn, &mut documents, &api_dir, &mut resource_names, folder)?;
+        }
+    }
+
+    Ok(documents)
+}
+
+/// Adds the files of a resource, its requests and the resources in it.
+fn add_resource(
+    conn: &SqliteConnection,
+    documents: &mut BTreeMap<PathBuf, String>,
+    parent_dir: &Path,
+    resource_names: &mut HashMap<String, i32>,
+    folder: Folder,
+) -> Result<(), StorageError> {
+    let resource_id = id_of(&folder.row);
+    let resource_dir = parent_dir.join(unique_name(
+        resource_names,
+        &slug(&text_of(&folder.row, "name"), "resource"),
+        resource_id,
+    ));
+    documents.insert(resource_dir.join(RESOURCE_FILE), to_yaml(folder.row)?);
+
+    // `resource` is taken by the resource file.
+    let mut request_names = HashMap::from([(String::from("resource"), 0)]);
+    let mut skip = vec!["resource_id"];
+    skip.extend(document::local_columns("request"));
+    for mut request in rows(conn, "request", Some(("resource_id", resource_id)), &skip)? {
+        let request_id = id_of(&request);
+        let route = text_of(&request, "route");
+        // The host of absolute URLs would make every name start the same.
+        let path = match route.split_once("://") {
+            Some((_, rest)) => rest.find('/').map_or("", |start| &rest[start..]),
+            None => route.as_str(),
+        };
+        let name = match text_of(&request, "name") {
+            name if name.is_empty() => format!("{} {}", text_of(&request, "method"), path),
+            name => name,
+        };
+        let file_name = unique_name(&mut request_names, &slug(&name, "request"), request_id);
+        add_nested(
+            conn,
+            &mut request,
+            &document::nested(&REQUEST_TABLES, false),
+            request_id,
+        )?;
+        documents.insert(
+            resource_dir.join(format!("{}.yaml", file_name)),
+            to_yaml(request)?,
+        );
+    }
+
+    let mut child_names = HashMap::new();
+    for child in folder.children {
+        add_resource(conn, documents, &resource_dir, &mut child_names, child)?;
+    }
+    Ok(())
+}
+
+fn to_yaml(mapping: serde_yaml::Mapping) -> Result<String, StorageError> {
+    serde_yaml::to_string(&mapping)
+        .map_err(|err| StorageError::Syntax(String::from("YAML"), err.to_string()))
+}
+
+/// A lowercase name for a file or folder, made of letters, digits and
+/// dashes.
+fn slug(name: &str, fallback: &str) -> String {
+    let slug = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '-' })
+        .collect::<String>()
+        .to_lowercase()
+        .split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+
+    if slug.is_empty() {
+        fallback.to_string()
+    } else {
+        slug
+    }
+}
+
+/// The oldest row keeps the plain name, later ones with the same name get
+/// their id added.
+fn unique_name(taken: &mut HashMap<String, i32>, name: &str, id: i32) -> String {
+    match taken.get(name) {
+        Some(_) => format!("{}-{}", name, id),
+        None => {
+            taken.insert(name.to_string(), id);
+            name.to_string()
+        }
+    }
+}
+
+fn data_version(conn: &SqliteConnection) -> Result<i64, StorageError> {
+    Ok(sql_query("PRAGMA data_version")
+        .get_result::<DataVersion>(conn)?
+        .data_version)
+}
+
+fn write_error(path: &Path, err: std::io::Error) -> StorageError {
+    StorageError::Write(path.to_string_lossy().to_string(), err)
+}
+
+fn read_error(path: &Path, err: std::io::Error) -> StorageError {
+    StorageError::Read(path.to_string_lossy().to_string(), err)
+}
+
+/// The entries of a folder by name, leaving out hidden ones such as the
+/// cache and `.git`.
+fn sorted_entries(dir: &Path) -> Result<Vec<PathBuf>, StorageError> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|err| read_error(dir, err))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            !path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with('.'))
+        })
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+fn read_document(path: &Path) -> Result<Map<String, Value>, StorageError> {
+    let source = fs::read_to_string(path).map_err(|err| read_error(path, err))?;
+    let syntax_error =
+        |message: String| StorageError::Syntax(path.to_string_lossy().to_string(), message);
+
+    let document: Value =
+        serde_yaml::from_str(&source).map_err(|err| syntax_error(err.to_string()))?;
+    match document {
+        Value::Object(document) => Ok(document),
+        _ => Err(syntax_error(String::from("expected a mapping"))),
+    }
+}
+
+/// Removes YAML files no row was written to, and folders left empty.
+fn remove_stale(
+    root: &Path,
+    dir: &Path,
+    documents: &BTreeMap<PathBuf, String>,
+) -> Result<(), StorageError> {
+    for path in sorted_entries(dir)? {
+        if path.is_dir() {
+            remove_stale(root, &path, documents)?;
+            remove_if_empty(&path)?;
+        } else if path.extension().is_some_and(|ext| ext == "yaml") {
+            let relative = path.strip_prefix(root).unwrap_or(&path);
+            if !documents.contains_key(relative) {
+                fs::remove_file(&path).map_err(|err| write_error(&path, err))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn remove_if_empty(dir: &Path) -> Result<(), StorageError> {
+    let is_empty = fs::read_dir(dir)
+        .map(|mut entries| entries.next().is_none())
+        .unwrap_or(false);
+    if is_empty {
+        fs::remove_dir(dir).map_err(|err| write_error(dir, err))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_empty(name: &str) -> (PathBuf, FileStorage) {
+        let root = std::env::temp_dir().join(format!("reqstr-files-test-{}", name));
+        let _ = fs::remove_dir_all(&root);
+        let storage = FileStorage::open(&root.to_string_lossy()).unwrap();
+        (root, storage)
+    }
+
+    #[test]
+    fn keeps_changes_until_the_files_are_written() {
+        let (root, storage) = open_empty("retry");
+        sql_query("INSERT INTO api (id, name) VALUES (1, 'Shop')")
+            .execute(&storage.cache.connect())
+            .unwrap();
+
+        // A file where the folder of the API goes makes the write fail.
+        fs::write(root.join("shop"), "").unwrap();
+        assert!(storage.flush().is_err());
+
+        fs::remove_file(root.join("shop")).unwrap();
+        storage.flush().unwrap();
+        assert!(root.join("shop").join(API_FILE).is_file());
+    }
+
+    #[test]
+    fn leaves_variables_and_tls_settings_out_of_the_files() {
+        let (root, storage) = open_empty("local");
+        let conn = storage.cache.connect();
+        sql_query("INSERT INTO api (id, name) VALUES (1, 'Shop')")
+            .execute(&conn)
+            .unwrap();
+        sql_query("INSERT INTO api_variable (api_id, key, value) VALUES (1, 'token', 'secret')")
+            .execute(&conn)
+            .unwrap();
+        sql_query("INSERT INTO tls_config (api_id, ca_bundles) VALUES (1, '')")
+            .execute(&conn)
+            .unwrap();
+        storage.flush().unwrap();
+
+        let api = fs::read_to_string(root.join("shop").join(API_FILE)).unwrap();
+        assert!(!api.contains("secret"));
+        assert!(!api.contains("tls"));
+
+        // They stay in the cache when it is loaded from the files again.
+        drop(storage);
+        let storage = FileStorage::open(&root.to_string_lossy()).unwrap();
+        let variables = sql_query("SELECT count(*) AS count FROM api_variable")
+            .get_result::<Count>(&storage.cache.connect())
+            .unwrap();
+        assert_eq!(variables.count, 1);
+    }
+
+    #[test]
+    fn only_writes_after_changes() {
+        let (root, storage) = open_empty("changes");
+        sql_query("INSERT INTO api (id, name) VALUES (1, 'Shop')")
+            .execute(&storage.cache.connect())
+            .unwrap();
+        storage.flush().unwrap();
+
+        // Removed by hand, it is not written again until something changes.
+        fs::remove_dir_all(root.join("shop")).unwrap();
+        storage.flush().unwrap();
+        assert!(!root.join("shop").exists());
+
+        sql_query("UPDATE api SET description = 'Orders'")
+            .execute(&storage.cache.connect())
+            .unwrap();
+        storage.flush().unwrap();
+        assert!(root.join("shop").join(API_FILE).is_file());
+    }
+
+    #[test]
+    fn renumbers_rows_added_on_two_branches() {
+        let (root, storage) = open_empty("merge");
+        let conn = storage.cache.connect();
+        for statement in [
+            "INSERT INTO api (id, name) VALUES (1, 'Shop')",
+            "INSERT INTO resource (id, name, api_id) VALUES (1, 'Orders', 1)",
+            "INSERT INTO request (id, route, method, resource_id, name) \
+             VALUES (1, '/orders', 'GET', 1, 'List orders')",
+            "INSERT INTO header (id, key, value, request_id) VALUES (1, 'Accept', 'json', 1)",
+        ] {
+            sql_query(statement).execute(&conn).unwrap();
+        }
+        storage.flush().unwrap();
+        drop(conn);
+        drop(storage);
+
+        // Both branches gave their new request the next id.
+        let orders = root.join("shop").join("orders");
+        let listed = fs::read_to_string(orders.join("list-orders.yaml")).unwrap();
+        fs::write(
+            orders.join("update-order.yaml"),
+            listed.replace("List orders", "Update order"),
+        )
+        .unwrap();
+
+        let storage = FileStorage::open(&root.to_string_lossy()).unwrap();
+        let conn = storage.cache.connect();
+        let count = |table: &str| {
+            sql_query(format!("SELECT count(DISTINCT id) AS count FROM {}", table))
+                .get_result::<Count>(&conn)
+                .unwrap()
+                .count
+        };
+        assert_eq!(count("request"), 2);
+        assert_eq!(count("header"), 2);
+
+        let updated = fs::read_to_string(orders.join("update-order.yaml")).unwrap();
+        assert!(updated.starts_with("---\nid: 2\n"));
+        assert_eq!(
+            fs::read_to_string(orders.join("list-orders.yaml")).unwrap(),
+            listed
+        );
+    }
+
+    #[test]
+    fn keeps_when_requests_were_sent_out_of_the_files() {
+        let (root, storage) = open_empty("last-run");
+        let conn = storage.cache.connect();
+        for statement in [
+            "INSERT INTO api (id, name) VALUES (1, 'Shop')",
+            "INSERT INTO resource (id, name, api_id) VALUES (1, 'Orders', 1)",
+            "INSERT INTO request (id, route, method, resource_id, name) \
+             VALUES (1, '/orders', 'GET', 1, 'List orders')",
+        ] {
+            sql_query(statement).execute(&conn).unwrap();
+        }
+        storage.flush().unwrap();
+        let path = root.join("shop").join("orders").join("list-orders.yaml");
+        let written = fs::read_to_string(&path).unwrap();
+
+        storage.mark_run(1);
+        storage.flush().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), written);
+        assert!(!written.contains("last_run_at"));
+
+        // It is still known when the cache is loaded from the files again.
+        drop(conn);
+        drop(storage);
+        let storage = FileStorage::open(&root.to_string_lossy()).unwrap();
+        let request = storage.get_request_by_id(1).unwrap();
+        assert!(request.last_run_at.is_some());
+    }
+}
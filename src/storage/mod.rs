@@ -0,0 +1,149 @@
+use diesel::ConnectionError;
+use serde_yaml::Mapping;
+use thiserror::Error;
+
+use crate::{
+    config::{ReqstrConfig, StorageBackend},
+    models::{
+        Api, ApiVariable, ExpandedNode, FormPart, GraphqlQuery, GraphqlSchema, GrpcCall, Header,
+        MessageTemplate, NewApi, NewApiVariable, NewFormPart, NewGraphqlQuery, NewGraphqlSchema,
+        NewGrpcCall, NewHeader, NewMessageTemplate, NewParam, NewRecordedResponse, NewRequest,
+        NewResource, NewTlsConfig, Param, RecordedResponse, Request, Resource, Tag, TlsConfig,
+    },
+    services::{
+        import_service::{ImportSummary, ImportedApi},
+        search_service::SearchHit,
+    },
+    types::{HttpMethod, RequestKind, TreeNodeKind},
+};
+
+use document::LoadedRow;
+
+pub mod document;
+mod files;
+mod sqlite;
+
+pub use files::FileStorage;
+pub use sqlite::SqliteStorage;
+
+#[derive(Debug, Error)]
+pub enum StorageError {
+    #[error("Could not open {0}: {1}")]
+    Connection(String, ConnectionError),
+    #[error(transparent)]
+    Query(#[from] diesel::result::Error),
+    #[error("Could not set up {0}: {1}")]
+    Migration(String, diesel_migrations::RunMigrationsError),
+    #[error("Could not read {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Could not write {0}: {1}")]
+    Write(String, std::io::Error),
+    #[error("{0} is not valid: {1}")]
+    Syntax(String, String),
+}
+
+/// Where collections are kept. The services only go through these
+/// operations, each backend decides how they are stored; see the service of
+/// the same name for what each one does.
+pub trait Storage: Send + Sync {
+    fn get_all_apis(&self) -> Vec<Api>;
+    fn get_api_by_id(&self, api_id: i32) -> Option<Api>;
+    fn create_new_api(&self, new_api: NewApi);
+    fn update_api_description(&self, obj: &Api, new_description: Option<&str>) -> Api;
+    fn move_api(&self, api_id: i32, up: bool) -> bool;
+
+    fn get_variables_for_api(&self, related_id: i32) -> Vec<ApiVariable>;
+    fn save_variable(&self, changes: &NewApiVariable);
+    fn delete_variable(&self, target: &ApiVariable);
+
+    fn get_tls_config_for_api(&self, related_id: i32) -> Option<TlsConfig>;
+    fn save_tls_config(&self, changes: &NewTlsConfig) -> TlsConfig;
+
+    fn get_graphql_schema_for_api(&self, related_id: i32) -> Option<GraphqlSchema>;
+    fn save_graphql_schema(&self, changes: &NewGraphqlSchema) -> GraphqlSchema;
+
+    fn get_resources_for_api(&self, related_id: i32) -> Vec<Resource>;
+    fn get_root_resources(&self, related_id: i32) -> Vec<Resource>;
+    fn get_child_resources(&self, related_id: i32) -> Vec<Resource>;
+    fn get_resource_by_id(&self, resource_id: i32) -> Option<Resource>;
+    fn create_new_resource(&self, new_resource: NewResource);
+    fn update_resource_description(
+        &self,
+        obj: &Resource,
+        new_description: Option<&str>,
+    ) -> Resource;
+    fn move_resource(&self, obj: &Resource, up: bool) -> bool;
+
+    fn get_requests_for_resource(&self, related_id: i32) -> Result<Vec<Request>, String>;
+    fn get_all_requests(&self) -> Vec<Request>;
+    fn get_favourite_requests(&self) -> Vec<Request>;
+    fn get_request_by_id(&self, relevant_id: i32) -> Option<Request>;
+    fn create_new_request(&self, new_request: NewRequest);
+    fn create_request(&self, new_request: NewRequest) -> Request;
+    fn update_request_method(&self, obj: &Request, new_method: HttpMethod) -> Request;
+    fn update_request_kind(&self, obj: &Request, new_kind: RequestKind) -> Request;
+    fn update_request_route(&self, obj: &Request, new_route: &str) -> Request;
+    fn update_request_name(&self, obj: &Request, new_name: Option<&str>) -> Request;
+    fn update_request_description(&self, obj: &Request, new_description: Option<&str>) -> Request;
+    fn update_request_favourite(&self, obj: &Request, new_favourite: bool) -> Request;
+    fn move_request(&self, obj: &Request, up: bool) -> bool;
+    fn mark_run(&self, request_id: i32);
+
+    fn get_headers_for_request(&self, related_id: i32) -> Vec<Header>;
+    fn create_new_header(&self, new_header: NewHeader);
+    fn update_header(&self, target: &Header, changes: &NewHeader);
+
+    fn get_params_for_request(&self, related_id: i32) -> Vec<Param>;
+    fn create_new_param(&self, new_param: NewParam);
+
+    fn get_form_parts_for_request(&self, related_id: i32) -> Vec<FormPart>;
+    fn create_new_form_part(&self, new_form_part: NewFormPart);
+    fn update_form_part(&self, target: &FormPart, changes: &NewFormPart);
+    fn delete_form_part(&self, target: &FormPart);
+
+    fn get_graphql_query_for_request(&self, related_id: i32) -> Option<GraphqlQuery>;
+    fn save_graphql_query(&self, changes: &NewGraphqlQuery) -> GraphqlQuery;
+
+    fn get_grpc_call_for_request(&self, related_id: i32) -> Option<GrpcCall>;
+    fn save_grpc_call(&self, changes: &NewGrpcCall) -> GrpcCall;
+
+    fn get_templates_for_request(&self, related_id: i32) -> Vec<MessageTemplate>;
+    fn create_new_template(&self, new_template: NewMessageTemplate);
+    fn delete_template(&self, target: &MessageTemplate);
+
+    fn get_tags_for_request(&self, related_id: i32) -> Vec<Tag>;
+    fn get_all_tags(&self) -> Vec<Tag>;
+    fn set_tags(&self, related_id: i32, names: &[&str]) -> Vec<Tag>;
+
+    fn get_responses_for_request(&self, related_id: i32) -> Vec<RecordedResponse>;
+    fn get_latest_statuses(&self) -> Vec<(i32, i32)>;
+    fn record_response(&self, changes: &NewRecordedResponse);
+
+    fn get_expanded_nodes(&self) -> Vec<ExpandedNode>;
+    fn set_expanded(&self, node_kind: TreeNodeKind, related_id: i32, expanded: bool);
+
+    fn search(&self, text: &str) -> Vec<SearchHit>;
+
+    fn import_api(&self, imported: &ImportedApi) -> Result<ImportSummary, StorageError>;
+    fn get_source_key(&self, related_id: i32) -> Option<String>;
+
+    /// Every API with everything in it, local tables included, as backups
+    /// are written.
+    fn dump(&self) -> Result<Vec<Mapping>, StorageError>;
+
+    /// Adds rows read from a backup, linked by `document::LoadedRow::parents`.
+    /// With `replace`, everything is deleted first and the rows keep their
+    /// ids; otherwise rows without one get the next free id.
+    fn restore(&self, rows: Vec<LoadedRow>, replace: bool) -> Result<(), StorageError>;
+
+    /// Saves what was changed since the last flush. Cheap when nothing
+    /// changed, so it can run after every event.
+    fn flush(&self) -> Result<(), StorageError>;
+}
+
+pub fn open(config: &ReqstrConfig) -> Result<Box<dyn Storage>, StorageError> {
+    match config.storage_backend {
+        StorageBackend::Sqlite => Ok(Box::new(SqliteStorage::new(&config.storage_location))),
+        StorageBackend::Files => Ok(Box::new(FileStorage::open(&config.storage_location)?)),
+    }
+}
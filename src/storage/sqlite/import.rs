@@ -0,0 +1,432 @@
+use diesel::{insert_into, insert_or_ignore_into, prelude::*, result::Error};
+
+use crate::{
+    models::{
+        Header, ImportSnapshot, NewApi, NewApiVariable, NewFormPart, NewHeader, NewImportSnapshot,
+        NewParam, NewRequest, NewResource, NewTag, Param, Request,
+    },
+    schema::{
+        api, api_variable, form_part, header, import_snapshot, param, request, resource, tag,
+    },
+    services::import_service::{
+        ImportSummary, ImportedApi, ImportedRequest, PATH_SEPARATOR, REMOVED_TAG,
+    },
+    types::RequestKind,
+};
+
+/// Stores an imported API. When an API of the same name was imported
+/// before, requests are matched by their key and only the values nobody
+/// touched since are replaced. Requests sharing a key are matched in the
+/// order they were imported.
+pub fn import_api(conn: &SqliteConnection, imported: &ImportedApi) -> Result<ImportSummary, Error> {
+    conn.transaction(|| {
+        let api_id = find_or_create_api(conn, &imported.name)?;
+        if let Some(description) = &imported.description {
+            diesel::update(api::table.find(api_id))
+                .filter(api::description.is_null())
+                .set(api::description.eq(description))
+                .execute(conn)?;
+        }
+        let mut summary = ImportSummary {
+            api_id,
+            ..Default::default()
+        };
+
+        for (key, value) in &imported.variables {
+            add_variable(conn, api_id, key, value)?;
+        }
+
+        let existing: Vec<(ImportSnapshot, Request)> = import_snapshot::table
+            .inner_join(request::table.inner_join(resource::table))
+            .filter(resource::api_id.eq(api_id))
+            .select((import_snapshot::all_columns, request::all_columns))
+            .load(conn)?;
+
+        let mut matched_ids = vec![];
+        for imported_resource in &imported.resources {
+            let mut resource_id = None;
+
+            for imported_request in &imported_resource.requests {
+                let matched = existing.iter().find(|(snapshot, current)| {
+                    snapshot.source_key == imported_request.key
+                        && !matched_ids.contains(&current.id)
+                });
+
+                match matched {
+                    Some((snapshot, current)) => {
+                        matched_ids.push(current.id);
+                        diesel::delete(
+                            tag::table
+                                .filter(tag::request_id.eq(current.id))
+                                .filter(tag::name.eq(REMOVED_TAG)),
+                        )
+                        .execute(conn)?;
+                        if merge_request(conn, snapshot, current, imported_request, &mut summary)? {
+                            summary.updated += 1;
+                        }
+                    }
+                    None => {
+                        let resource_id = match resource_id {
+                            Some(resource_id) => resource_id,
+                            None => {
+                                let id =
+                                    find_or_create_resource(conn, api_id, &imported_resource.name)?;
+                                *resource_id.insert(id)
+                            }
+                        };
+                        create_request(conn, resource_id, imported_request)?;
+                        summary.created += 1;
+                    }
+                }
+            }
+        }
+
+        if imported.complete {
+            for (_, current) in &existing {
+                if !matched_ids.contains(&current.id) {
+                    insert_or_ignore_into(tag::table)
+                        .values(&NewTag {
+                            request_id: current.id,
+                            name: REMOVED_TAG,
+                        })
+                        .execute(conn)?;
+                    summary.removed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    })
+}
+
+fn find_or_create_api(conn: &SqliteConnection, name: &str) -> Result<i32, Error> {
+    let found = api::table
+        .filter(api::name.eq(name))
+        .select(api::id)
+        .first(conn)
+        .optional()?;
+    if let Some(id) = found {
+        return Ok(id);
+    }
+
+    insert_into(api::table)
+        .values(&NewApi { name })
+        .execute(conn)?;
+    api::table.select(api::id).order(api::id.desc()).first(conn)
+}
+
+/// Finds the folder at a path like `Pets / Photos`, creating the folders
+/// that are missing.
+fn find_or_create_resource(conn: &SqliteConnection, api_id: i32, path: &str) -> Result<i32, Error> {
+    let mut parent_id = None;
+    for name in path.split(PATH_SEPARATOR) {
+        let query = resource::table
+            .filter(resource::api_id.eq(api_id))
+            .filter(resource::name.eq(name))
+            .select(resource::id)
+            .into_boxed();
+        let query = match parent_id {
+            Some(id) => query.filter(resource::parent_id.eq(id)),
+            None => query.filter(resource::parent_id.is_null()),
+        };
+        let found = query.first(conn).optional()?;
+        let id = match found {
+            Some(id) => id,
+            None => {
+                insert_into(resource::table)
+                    .values(&NewResource {
+                        name,
+                        api_id,
+                        parent_id,
+                    })
+                    .execute(conn)?;
+                resource::table
+                    .select(resource::id)
+                    .order(resource::id.desc())
+                    .first(conn)?
+            }
+        };
+        parent_id = Some(id);
+    }
+    Ok(parent_id.unwrap_or_default())
+}
+
+fn create_request(
+    conn: &SqliteConnection,
+    resource_id: i32,
+    imported: &ImportedRequest,
+) -> Result<(), Error> {
+    insert_into(request::table)
+        .values(&NewRequest {
+            resource_id,
+            route: &imported.route,
+            method: imported.method.clone(),
+            body: imported.body.as_deref(),
+            kind: RequestKind::for_route(&imported.route),
+        })
+        .execute(conn)?;
+    let request_id = request::table
+        .select(request::id)
+        .order(request::id.desc())
+        .first(conn)?;
+    diesel::update(request::table.find(request_id))
+        .set((
+            request::name.eq(&imported.name),
+            request::description.eq(&imported.description),
+        ))
+        .execute(conn)?;
+
+    for (key, value) in &imported.headers {
+        insert_into(header::table)
+            .values(&NewHeader {
+                key,
+                value,
+                request_id,
+            })
+            .execute(conn)?;
+    }
+    for (key, value) in &imported.params {
+        insert_into(param::table)
+            .values(&NewParam {
+                key,
+                value,
+                request_id,
+            })
+            .execute(conn)?;
+    }
+
+    for part in &imported.form_parts {
+        insert_into(form_part::table)
+            .values(&NewFormPart {
+                name: &part.name,
+                kind: part.kind,
+                value: &part.value,
+                content_type: part.content_type.as_deref(),
+                request_id,
+            })
+            .execute(conn)?;
+    }
+
+    save_snapshot(conn, None, request_id, imported)
+}
+
+/// Adds a variable the API does not have yet. Existing values are never
+/// overwritten, they are usually local settings such as tokens.
+fn add_variable(conn: &SqliteConnection, api_id: i32, key: &str, value: &str) -> Result<(), Error> {
+    let found = api_variable::table
+        .filter(api_variable::api_id.eq(api_id))
+        .filter(api_variable::key.eq(key))
+        .select(api_variable::id)
+        .first::<i32>(conn)
+        .optional()?;
+
+    if found.is_none() {
+        insert_into(api_variable::table)
+            .values(&NewApiVariable { api_id, key, value })
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Returns whether anything changed.
+fn merge_request(
+    conn: &SqliteConnection,
+    snapshot: &ImportSnapshot,
+    current: &Request,
+    imported: &ImportedRequest,
+    summary: &mut ImportSummary,
+) -> Result<bool, Error> {
+    let route = merge_value(&current.route, &snapshot.route, &imported.route, summary);
+    let method = merge_value(&current.method, &snapshot.method, &imported.method, summary);
+    let body = merge_value(&current.body, &snapshot.body, &imported.body, summary);
+    let mut changed = route != &current.route || method != &current.method || body != &current.body;
+
+    if changed {
+        diesel::update(current)
+            .set(&NewRequest {
+                resource_id: current.resource_id,
+                route,
+                method: method.clone(),
+                body: body.as_deref(),
+                kind: current.kind,
+            })
+            .execute(conn)?;
+    }
+
+    let headers: Vec<Header> = header::table
+        .filter(header::request_id.eq(current.id))
+        .load(conn)?;
+    let rows: Vec<_> = headers
+        .iter()
+        .map(|row| (row.id, row.key.as_str(), row.value.as_str()))
+        .collect();
+    let (updates, inserts) = merge_rows(&rows, &snapshot.headers, &imported.headers, summary);
+    changed |= !updates.is_empty() || !inserts.is_empty();
+    for (id, value) in updates {
+        diesel::update(header::table.find(id))
+            .set(header::value.eq(value))
+            .execute(conn)?;
+    }
+    for (key, value) in inserts {
+        insert_into(header::table)
+            .values(&NewHeader {
+                key,
+                value,
+                request_id: current.id,
+            })
+            .execute(conn)?;
+    }
+
+    let params: Vec<Param> = param::table
+        .filter(param::request_id.eq(current.id))
+        .load(conn)?;
+    let rows: Vec<_> = params
+        .iter()
+        .map(|row| (row.id, row.key.as_str(), row.value.as_str()))
+        .collect();
+    let (updates, inserts) = merge_rows(&rows, &snapshot.params, &imported.params, summary);
+    changed |= !updates.is_empty() || !inserts.is_empty();
+    for (id, value) in updates {
+        diesel::update(param::table.find(id))
+            .set(param::value.eq(value))
+            .execute(conn)?;
+    }
+    for (key, value) in inserts {
+        insert_into(param::table)
+            .values(&NewParam {
+                key,
+                value,
+                request_id: current.id,
+            })
+            .execute(conn)?;
+    }
+
+    save_snapshot(conn, Some(snapshot), current.id, imported)?;
+    Ok(changed)
+}
+
+/// Takes the incoming value unless the current one was edited locally.
+fn merge_value<'a, T: PartialEq>(
+    current: &'a T,
+    previous: &T,
+    incoming: &'a T,
+    summary: &mut ImportSummary,
+) -> &'a T {
+    if current == previous || current == incoming {
+        incoming
+    } else {
+        summary.kept += 1;
+        current
+    }
+}
+
+/// Rows to update by id, and rows to add.
+type RowChanges<'a> = (Vec<(i32, &'a str)>, Vec<(&'a str, &'a str)>);
+
+/// Works out which key/value rows to update and which to add. Rows that
+/// were imported before and deleted since stay deleted. Repeated keys, like
+/// `tag=a&tag=b`, are matched in order: the second incoming `tag` with the
+/// second current and previous one.
+fn merge_rows<'a>(
+    current: &[(i32, &str, &str)],
+    previous: &str,
+    incoming: &'a [(String, String)],
+    summary: &mut ImportSummary,
+) -> RowChanges<'a> {
+    let previous: Vec<(String, String)> = serde_json::from_str(previous).unwrap_or_default();
+    let mut updates = vec![];
+    let mut inserts = vec![];
+
+    for (index, (key, value)) in incoming.iter().enumerate() {
+        let occurrence = incoming[..index]
+            .iter()
+            .filter(|(earlier, _)| earlier == key)
+            .count();
+        let previous_value = previous
+            .iter()
+            .filter(|(previous_key, _)| previous_key == key)
+            .nth(occurrence)
+            .map(|(_, value)| value.as_str());
+
+        match current
+            .iter()
+            .filter(|(_, current_key, _)| current_key == key)
+            .nth(occurrence)
+        {
+            Some((_, _, current_value)) if current_value == value => (),
+            Some((id, _, current_value)) if Some(*current_value) == previous_value => {
+                updates.push((*id, value.as_str()))
+            }
+            Some(_) => summary.kept += 1,
+            None if previous_value.is_some() => summary.kept += 1,
+            None => inserts.push((key.as_str(), value.as_str())),
+        }
+    }
+
+    (updates, inserts)
+}
+
+fn save_snapshot(
+    conn: &SqliteConnection,
+    existing: Option<&ImportSnapshot>,
+    request_id: i32,
+    imported: &ImportedRequest,
+) -> Result<(), Error> {
+    let headers = serde_json::to_string(&imported.headers).unwrap_or_default();
+    let params = serde_json::to_string(&imported.params).unwrap_or_default();
+    let changes = NewImportSnapshot {
+        request_id,
+        source_key: &imported.key,
+        route: &imported.route,
+        method: imported.method.clone(),
+        body: imported.body.as_deref(),
+        headers: &headers,
+        params: &params,
+    };
+
+    match existing {
+        Some(snapshot) => diesel::update(snapshot).set(&changes).execute(conn)?,
+        None => insert_into(import_snapshot::table)
+            .values(&changes)
+            .execute(conn)?,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn matches_repeated_keys_in_order() {
+        let current = [(1, "tag", "a"), (2, "tag", "b")];
+        let previous = serde_json::to_string(&pairs(&[("tag", "a"), ("tag", "b")])).unwrap();
+        let incoming = pairs(&[("tag", "a"), ("tag", "c"), ("tag", "d")]);
+        let mut summary = ImportSummary::default();
+
+        let (updates, inserts) = merge_rows(&current, &previous, &incoming, &mut summary);
+        assert_eq!(updates, vec![(2, "c")]);
+        assert_eq!(inserts, vec![("tag", "d")]);
+        assert_eq!(summary.kept, 0);
+    }
+
+    #[test]
+    fn keeps_edited_and_deleted_rows() {
+        let current = [(1, "limit", "50")];
+        let previous = serde_json::to_string(&pairs(&[("limit", "10"), ("page", "1")])).unwrap();
+        let incoming = pairs(&[("limit", "20"), ("page", "2")]);
+        let mut summary = ImportSummary::default();
+
+        let (updates, inserts) = merge_rows(&current, &previous, &incoming, &mut summary);
+        assert!(updates.is_empty());
+        assert!(inserts.is_empty());
+        assert_eq!(summary.kept, 2);
+    }
+}
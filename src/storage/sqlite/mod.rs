@@ -0,0 +1,840 @@
+use std::collections::HashSet;
+
+use chrono::Local;
+use diesel::{
+    insert_into, insert_or_ignore_into,
+    prelude::*,
+    sql_query,
+    sql_types::{Integer, Nullable, Text},
+    sqlite::SqliteConnection,
+};
+use serde_yaml::Mapping;
+
+use super::{
+    document::{self, LoadedRow},
+    Storage, StorageError,
+};
+use crate::{
+    models::{
+        Api, ApiVariable, ExpandedNode, FormPart, GraphqlQuery, GraphqlSchema, GrpcCall, Header,
+        MessageTemplate, NewApi, NewApiVariable, NewExpandedNode, NewFormPart, NewGraphqlQuery,
+        NewGraphqlSchema, NewGrpcCall, NewHeader, NewMessageTemplate, NewParam,
+        NewRecordedResponse, NewRequest, NewResource, NewTag, NewTlsConfig, Param,
+        RecordedResponse, Request, Resource, Tag, TlsConfig,
+    },
+    services::{
+        import_service::{ImportSummary, ImportedApi},
+        recorded_response_service::HISTORY_LIMIT,
+        search_service::SearchHit,
+    },
+    types::{HttpMethod, RequestKind, TreeNodeKind},
+};
+
+mod import;
+
+sql_function! {
+    /// The first of its arguments that is not null.
+    fn coalesce(x: Nullable<Integer>, y: Integer) -> Integer;
+}
+
+/// A single SQLite database file, which is where the data lives.
+pub struct SqliteStorage {
+    path: String,
+}
+
+impl SqliteStorage {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: path.to_string(),
+        }
+    }
+
+    pub fn connect(&self) -> SqliteConnection {
+        SqliteConnection::establish(&self.path)
+            .unwrap_or_else(|_| panic!("Error connecting to {}", self.path))
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn get_all_apis(&self) -> Vec<Api> {
+        use crate::schema::api::dsl::*;
+
+        api.order((coalesce(position, id), id))
+            .load::<Api>(&self.connect())
+            .unwrap()
+    }
+
+    fn get_api_by_id(&self, api_id: i32) -> Option<Api> {
+        use crate::schema::api::dsl::*;
+
+        api.find(api_id).first(&self.connect()).ok()
+    }
+
+    fn create_new_api(&self, new_api: NewApi) {
+        use crate::schema::api::dsl::*;
+
+        insert_into(api)
+            .values(&new_api)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn update_api_description(&self, obj: &Api, new_description: Option<&str>) -> Api {
+        use crate::schema::api::dsl::*;
+
+        let connection = self.connect();
+
+        diesel::update(api.find(obj.id))
+            .set(description.eq(new_description))
+            .execute(&connection)
+            .unwrap();
+
+        api.find(obj.id).first(&connection).unwrap()
+    }
+
+    fn move_api(&self, api_id: i32, up: bool) -> bool {
+        use crate::schema::api::dsl::*;
+
+        let connection = self.connect();
+
+        let mut ids: Vec<i32> = api
+            .select(id)
+            .order((coalesce(position, id), id))
+            .load(&connection)
+            .unwrap_or_default();
+        if !reorder(&mut ids, api_id, up) {
+            return false;
+        }
+
+        connection
+            .transaction::<_, diesel::result::Error, _>(|| {
+                for (index, moved) in (1..).zip(&ids) {
+                    diesel::update(api.find(moved))
+                        .set(position.eq(index))
+                        .execute(&connection)?;
+                }
+                Ok(())
+            })
+            .is_ok()
+    }
+
+    fn get_variables_for_api(&self, related_id: i32) -> Vec<ApiVariable> {
+        use crate::schema::api_variable::dsl::*;
+
+        api_variable
+            .filter(api_id.eq(related_id))
+            .order(key)
+            .load(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn save_variable(&self, changes: &NewApiVariable) {
+        use crate::schema::api_variable::dsl::*;
+
+        let conn = self.connect();
+
+        let existing = api_variable
+            .filter(api_id.eq(changes.api_id))
+            .filter(key.eq(changes.key))
+            .first::<ApiVariable>(&conn);
+
+        if let Ok(existing) = existing {
+            diesel::update(&existing)
+                .set(changes)
+                .execute(&conn)
+                .unwrap();
+        } else {
+            insert_into(api_variable)
+                .values(changes)
+                .execute(&conn)
+                .unwrap();
+        }
+    }
+
+    fn delete_variable(&self, target: &ApiVariable) {
+        diesel::delete(target).execute(&self.connect()).unwrap();
+    }
+
+    fn get_tls_config_for_api(&self, related_id: i32) -> Option<TlsConfig> {
+        use crate::schema::tls_config::dsl::*;
+
+        tls_config
+            .filter(api_id.eq(related_id))
+            .first(&self.connect())
+            .ok()
+    }
+
+    fn save_tls_config(&self, changes: &NewTlsConfig) -> TlsConfig {
+        use crate::schema::tls_config::dsl::*;
+
+        let conn = self.connect();
+
+        if let Some(existing) = self.get_tls_config_for_api(changes.api_id) {
+            diesel::update(&existing)
+                .set(changes)
+                .execute(&conn)
+                .unwrap();
+        } else {
+            insert_into(tls_config)
+                .values(changes)
+                .execute(&conn)
+                .unwrap();
+        }
+
+        tls_config
+            .filter(api_id.eq(changes.api_id))
+            .first(&conn)
+            .unwrap()
+    }
+
+    fn get_graphql_schema_for_api(&self, related_id: i32) -> Option<GraphqlSchema> {
+        use crate::schema::graphql_schema::dsl::*;
+
+        graphql_schema
+            .filter(api_id.eq(related_id))
+            .first(&self.connect())
+            .ok()
+    }
+
+    fn save_graphql_schema(&self, changes: &NewGraphqlSchema) -> GraphqlSchema {
+        use crate::schema::graphql_schema::dsl::*;
+
+        let conn = self.connect();
+
+        if let Some(existing) = self.get_graphql_schema_for_api(changes.api_id) {
+            diesel::update(&existing)
+                .set(changes)
+                .execute(&conn)
+                .unwrap();
+        } else {
+            insert_into(graphql_schema)
+                .values(changes)
+                .execute(&conn)
+                .unwrap();
+        }
+
+        graphql_schema
+            .filter(api_id.eq(changes.api_id))
+            .first(&conn)
+            .unwrap()
+    }
+
+    fn get_resources_for_api(&self, related_id: i32) -> Vec<Resource> {
+        use crate::schema::resource::dsl::*;
+
+        resource
+            .filter(api_id.eq(related_id))
+            .order((coalesce(position, id), id))
+            .load::<Resource>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_root_resources(&self, related_id: i32) -> Vec<Resource> {
+        use crate::schema::resource::dsl::*;
+
+        resource
+            .filter(api_id.eq(related_id))
+            .filter(parent_id.is_null())
+            .order((coalesce(position, id), id))
+            .load::<Resource>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_child_resources(&self, related_id: i32) -> Vec<Resource> {
+        use crate::schema::resource::dsl::*;
+
+        resource
+            .filter(parent_id.eq(related_id))
+            .order((coalesce(position, id), id))
+            .load::<Resource>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_resource_by_id(&self, resource_id: i32) -> Option<Resource> {
+        use crate::schema::resource::dsl::*;
+
+        resource.find(resource_id).first(&self.connect()).ok()
+    }
+
+    fn create_new_resource(&self, new_resource: NewResource) {
+        use crate::schema::resource::dsl::*;
+
+        insert_into(resource)
+            .values(&new_resource)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn update_resource_description(
+        &self,
+        obj: &Resource,
+        new_description: Option<&str>,
+    ) -> Resource {
+        use crate::schema::resource::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(resource.find(obj.id))
+            .set(description.eq(new_description))
+            .execute(&conn)
+            .unwrap();
+
+        resource.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn move_resource(&self, obj: &Resource, up: bool) -> bool {
+        use crate::schema::resource::dsl::*;
+
+        let conn = self.connect();
+
+        let siblings = resource
+            .select(id)
+            .filter(api_id.eq(obj.api_id))
+            .order((coalesce(position, id), id))
+            .into_boxed();
+        let siblings = match obj.parent_id {
+            Some(parent) => siblings.filter(parent_id.eq(parent)),
+            None => siblings.filter(parent_id.is_null()),
+        };
+        let mut ids: Vec<i32> = siblings.load(&conn).unwrap_or_default();
+        if !reorder(&mut ids, obj.id, up) {
+            return false;
+        }
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            for (index, moved) in (1..).zip(&ids) {
+                diesel::update(resource.find(moved))
+                    .set(position.eq(index))
+                    .execute(&conn)?;
+            }
+            Ok(())
+        })
+        .is_ok()
+    }
+
+    fn get_requests_for_resource(&self, related_id: i32) -> Result<Vec<Request>, String> {
+        use crate::schema::request::dsl::*;
+
+        request
+            .filter(resource_id.eq(related_id))
+            .order((coalesce(position, id), id))
+            .load::<Request>(&self.connect())
+            .map_err(|err| format!("{}", err))
+    }
+
+    fn get_all_requests(&self) -> Vec<Request> {
+        use crate::schema::request::dsl::*;
+
+        request
+            .order((coalesce(position, id), id))
+            .load(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_favourite_requests(&self) -> Vec<Request> {
+        use crate::schema::request::dsl::*;
+
+        request
+            .filter(favourite.eq(true))
+            .order((coalesce(position, id), id))
+            .load(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_request_by_id(&self, relevant_id: i32) -> Option<Request> {
+        use crate::schema::request::dsl::*;
+
+        request.find(relevant_id).first(&self.connect()).ok()
+    }
+
+    fn create_new_request(&self, new_request: NewRequest) {
+        use crate::schema::request::dsl::*;
+
+        insert_into(request)
+            .values(&new_request)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn create_request(&self, new_request: NewRequest) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        insert_into(request)
+            .values(&new_request)
+            .execute(&conn)
+            .unwrap();
+
+        request.order(id.desc()).first(&conn).unwrap()
+    }
+
+    fn update_request_method(&self, obj: &Request, new_method: HttpMethod) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(obj)
+            .set(method.eq(new_method))
+            .execute(&conn)
+            .unwrap();
+
+        request.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn update_request_kind(&self, obj: &Request, new_kind: RequestKind) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(obj)
+            .set(kind.eq(new_kind))
+            .execute(&conn)
+            .unwrap();
+
+        request.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn update_request_route(&self, obj: &Request, new_route: &str) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(obj)
+            .set(route.eq(new_route))
+            .execute(&conn)
+            .unwrap();
+
+        request.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn update_request_name(&self, obj: &Request, new_name: Option<&str>) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(obj)
+            .set(name.eq(new_name))
+            .execute(&conn)
+            .unwrap();
+
+        request.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn update_request_description(&self, obj: &Request, new_description: Option<&str>) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(obj)
+            .set(description.eq(new_description))
+            .execute(&conn)
+            .unwrap();
+
+        request.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn update_request_favourite(&self, obj: &Request, new_favourite: bool) -> Request {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        diesel::update(obj)
+            .set(favourite.eq(new_favourite))
+            .execute(&conn)
+            .unwrap();
+
+        request.find(obj.id).first(&conn).unwrap()
+    }
+
+    fn move_request(&self, obj: &Request, up: bool) -> bool {
+        use crate::schema::request::dsl::*;
+
+        let conn = self.connect();
+
+        let mut ids: Vec<i32> = request
+            .select(id)
+            .filter(resource_id.eq(obj.resource_id))
+            .order((coalesce(position, id), id))
+            .load(&conn)
+            .unwrap_or_default();
+        if !reorder(&mut ids, obj.id, up) {
+            return false;
+        }
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            for (index, moved) in (1..).zip(&ids) {
+                diesel::update(request.find(moved))
+                    .set(position.eq(index))
+                    .execute(&conn)?;
+            }
+            Ok(())
+        })
+        .is_ok()
+    }
+
+    fn mark_run(&self, request_id: i32) {
+        use crate::schema::request::dsl::*;
+
+        diesel::update(request.find(request_id))
+            .set(last_run_at.eq(Local::now().to_rfc3339()))
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn get_headers_for_request(&self, related_id: i32) -> Vec<Header> {
+        use crate::schema::header::dsl::*;
+
+        header
+            .filter(request_id.eq(related_id))
+            .load::<Header>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn create_new_header(&self, new_header: NewHeader) {
+        use crate::schema::header::dsl::*;
+
+        insert_into(header)
+            .values(&new_header)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn update_header(&self, target: &Header, changes: &NewHeader) {
+        diesel::update(target)
+            .set(changes)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn get_params_for_request(&self, related_id: i32) -> Vec<Param> {
+        use crate::schema::param::dsl::*;
+
+        param
+            .filter(request_id.eq(related_id))
+            .load::<Param>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn create_new_param(&self, new_param: NewParam) {
+        use crate::schema::param::dsl::*;
+
+        insert_into(param)
+            .values(&new_param)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn get_form_parts_for_request(&self, related_id: i32) -> Vec<FormPart> {
+        use crate::schema::form_part::dsl::*;
+
+        form_part
+            .filter(request_id.eq(related_id))
+            .order(id)
+            .load::<FormPart>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn create_new_form_part(&self, new_form_part: NewFormPart) {
+        use crate::schema::form_part::dsl::*;
+
+        insert_into(form_part)
+            .values(&new_form_part)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn update_form_part(&self, target: &FormPart, changes: &NewFormPart) {
+        diesel::update(target)
+            .set(changes)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn delete_form_part(&self, target: &FormPart) {
+        diesel::delete(target).execute(&self.connect()).unwrap();
+    }
+
+    fn get_graphql_query_for_request(&self, related_id: i32) -> Option<GraphqlQuery> {
+        use crate::schema::graphql_query::dsl::*;
+
+        graphql_query
+            .filter(request_id.eq(related_id))
+            .first(&self.connect())
+            .ok()
+    }
+
+    fn save_graphql_query(&self, changes: &NewGraphqlQuery) -> GraphqlQuery {
+        use crate::schema::graphql_query::dsl::*;
+
+        let conn = self.connect();
+
+        if let Some(existing) = self.get_graphql_query_for_request(changes.request_id) {
+            diesel::update(&existing)
+                .set(changes)
+                .execute(&conn)
+                .unwrap();
+        } else {
+            insert_into(graphql_query)
+                .values(changes)
+                .execute(&conn)
+                .unwrap();
+        }
+
+        graphql_query
+            .filter(request_id.eq(changes.request_id))
+            .first(&conn)
+            .unwrap()
+    }
+
+    fn get_grpc_call_for_request(&self, related_id: i32) -> Option<GrpcCall> {
+        use crate::schema::grpc_call::dsl::*;
+
+        grpc_call
+            .filter(request_id.eq(related_id))
+            .first(&self.connect())
+            .ok()
+    }
+
+    fn save_grpc_call(&self, changes: &NewGrpcCall) -> GrpcCall {
+        use crate::schema::grpc_call::dsl::*;
+
+        let conn = self.connect();
+
+        if let Some(existing) = self.get_grpc_call_for_request(changes.request_id) {
+            diesel::update(&existing)
+                .set(changes)
+                .execute(&conn)
+                .unwrap();
+        } else {
+            insert_into(grpc_call)
+                .values(changes)
+                .execute(&conn)
+                .unwrap();
+        }
+
+        grpc_call
+            .filter(request_id.eq(changes.request_id))
+            .first(&conn)
+            .unwrap()
+    }
+
+    fn get_templates_for_request(&self, related_id: i32) -> Vec<MessageTemplate> {
+        use crate::schema::message_template::dsl::*;
+
+        message_template
+            .filter(request_id.eq(related_id))
+            .order(id)
+            .load::<MessageTemplate>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn create_new_template(&self, new_template: NewMessageTemplate) {
+        use crate::schema::message_template::dsl::*;
+
+        insert_into(message_template)
+            .values(&new_template)
+            .execute(&self.connect())
+            .unwrap();
+    }
+
+    fn delete_template(&self, target: &MessageTemplate) {
+        diesel::delete(target).execute(&self.connect()).unwrap();
+    }
+
+    fn get_tags_for_request(&self, related_id: i32) -> Vec<Tag> {
+        use crate::schema::tag::dsl::*;
+
+        tag.filter(request_id.eq(related_id))
+            .order(name)
+            .load(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_all_tags(&self) -> Vec<Tag> {
+        use crate::schema::tag::dsl::*;
+
+        tag.load(&self.connect()).unwrap_or_default()
+    }
+
+    fn set_tags(&self, related_id: i32, names: &[&str]) -> Vec<Tag> {
+        use crate::schema::tag::dsl::*;
+
+        let conn = self.connect();
+
+        conn.transaction::<_, diesel::result::Error, _>(|| {
+            diesel::delete(tag.filter(request_id.eq(related_id))).execute(&conn)?;
+            for new_name in names.iter().map(|new_name| new_name.trim()) {
+                if new_name.is_empty() {
+                    continue;
+                }
+                insert_or_ignore_into(tag)
+                    .values(&NewTag {
+                        request_id: related_id,
+                        name: new_name,
+                    })
+                    .execute(&conn)?;
+            }
+            Ok(())
+        })
+        .unwrap();
+
+        self.get_tags_for_request(related_id)
+    }
+
+    fn get_responses_for_request(&self, related_id: i32) -> Vec<RecordedResponse> {
+        use crate::schema::recorded_response::dsl::*;
+
+        recorded_response
+            .filter(request_id.eq(related_id))
+            .order(id.desc())
+            .load(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn get_latest_statuses(&self) -> Vec<(i32, i32)> {
+        use crate::schema::recorded_response::dsl::*;
+
+        let recorded: Vec<(i32, i32)> = recorded_response
+            .select((request_id, status))
+            .order(id.desc())
+            .load(&self.connect())
+            .unwrap_or_default();
+        let mut seen = HashSet::new();
+        recorded
+            .into_iter()
+            .filter(|(related_id, _)| seen.insert(*related_id))
+            .collect()
+    }
+
+    fn record_response(&self, changes: &NewRecordedResponse) {
+        use crate::schema::recorded_response::dsl::*;
+
+        let conn = self.connect();
+
+        insert_into(recorded_response)
+            .values(changes)
+            .execute(&conn)
+            .unwrap();
+
+        let kept: Vec<i32> = recorded_response
+            .select(id)
+            .filter(request_id.eq(changes.request_id))
+            .order(id.desc())
+            .limit(HISTORY_LIMIT)
+            .load(&conn)
+            .unwrap_or_default();
+        diesel::delete(
+            recorded_response
+                .filter(request_id.eq(changes.request_id))
+                .filter(id.ne_all(kept)),
+        )
+        .execute(&conn)
+        .unwrap();
+    }
+
+    fn get_expanded_nodes(&self) -> Vec<ExpandedNode> {
+        use crate::schema::expanded_node::dsl::*;
+
+        expanded_node
+            .load::<ExpandedNode>(&self.connect())
+            .unwrap_or_default()
+    }
+
+    fn set_expanded(&self, node_kind: TreeNodeKind, related_id: i32, expanded: bool) {
+        use crate::schema::expanded_node::dsl::*;
+
+        let conn = self.connect();
+
+        if expanded {
+            insert_or_ignore_into(expanded_node)
+                .values(&NewExpandedNode {
+                    kind: node_kind,
+                    node_id: related_id,
+                })
+                .execute(&conn)
+                .unwrap();
+        } else {
+            diesel::delete(
+                expanded_node
+                    .filter(kind.eq(node_kind))
+                    .filter(node_id.eq(related_id)),
+            )
+            .execute(&conn)
+            .unwrap();
+        }
+    }
+
+    fn search(&self, text: &str) -> Vec<SearchHit> {
+        let query = match_query(text);
+        if query.is_empty() {
+            return vec![];
+        }
+
+        // `char(2)` and `char(3)` are `MATCH_START` and `MATCH_END`.
+        sql_query(
+            "SELECT source, request_id, \
+             snippet(search_index, 3, char(2), char(3), '…', 12) AS snippet \
+             FROM search_index WHERE search_index MATCH ? ORDER BY rank LIMIT 100",
+        )
+        .bind::<Text, _>(query)
+        .load(&self.connect())
+        .unwrap_or_default()
+    }
+
+    fn import_api(&self, imported: &ImportedApi) -> Result<ImportSummary, StorageError> {
+        Ok(import::import_api(&self.connect(), imported)?)
+    }
+
+    fn get_source_key(&self, related_id: i32) -> Option<String> {
+        use crate::schema::import_snapshot::dsl::*;
+
+        import_snapshot
+            .filter(request_id.eq(related_id))
+            .select(source_key)
+            .first(&self.connect())
+            .ok()
+    }
+
+    fn dump(&self) -> Result<Vec<Mapping>, StorageError> {
+        document::dump(&self.connect())
+    }
+
+    fn restore(&self, rows: Vec<LoadedRow>, replace: bool) -> Result<(), StorageError> {
+        document::restore(&self.connect(), rows, replace)
+    }
+
+    fn flush(&self) -> Result<(), StorageError> {
+        Ok(())
+    }
+}
+
+/// Moves `moved` one place up or down `ids`, returning whether it could.
+fn reorder(ids: &mut [i32], moved: i32, up: bool) -> bool {
+    let index = match ids.iter().position(|id| *id == moved) {
+        Some(index) => index,
+        None => return false,
+    };
+    let other = if up {
+        index.checked_sub(1)
+    } else {
+        Some(index + 1).filter(|other| *other < ids.len())
+    };
+    match other {
+        Some(other) => {
+            ids.swap(index, other);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Quotes every word, so nothing typed is read as FTS5 syntax. Words
+/// without a letter or digit would never match and are left out.
+fn match_query(text: &str) -> String {
+    text.split_whitespace()
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
@@ -0,0 +1,529 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::http::{OpenResponse, SentRequest};
+
+/// How often a reader waiting for the next chunk checks whether it was
+/// stopped.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How the chunks of a streamed body are split into entries.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum StreamFormat {
+    /// `text/event-stream`, parsed into events.
+    Sse,
+    /// Newline-delimited records such as NDJSON or log output.
+    Lines,
+}
+
+impl StreamFormat {
+    pub fn for_content_type(content_type: Option<&str>) -> Option<StreamFormat> {
+        let mime = content_type?.split(';').next()?.trim().to_lowercase();
+
+        match mime.as_str() {
+            "text/event-stream" => Some(StreamFormat::Sse),
+            "application/x-ndjson"
+            | "application/ndjson"
+            | "application/jsonlines"
+            | "application/json-seq"
+            | "application/stream+json" => Some(StreamFormat::Lines),
+            _ => None,
+        }
+    }
+}
+
+/// A single Server-Sent Event.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SseEvent {
+    pub event: Option<String>,
+    pub id: Option<String>,
+    pub retry: Option<u64>,
+    pub data: String,
+}
+
+/// Incremental parser for `text/event-stream` bodies. Chunks may split
+/// lines (and UTF-8 sequences) anywhere, so incomplete input is buffered
+/// until the rest of it arrives.
+#[derive(Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+    current: SseEvent,
+    data_lines: Vec<String>,
+    has_fields: bool,
+}
+
+impl SseParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = vec![];
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if let Some(event) = self.process_line(line) {
+                events.push(event);
+            }
+        }
+
+        events
+    }
+
+    fn process_line(&mut self, line: &str) -> Option<SseEvent> {
+        if line.is_empty() {
+            if !self.has_fields {
+                return None;
+            }
+            self.has_fields = false;
+            let mut event = std::mem::take(&mut self.current);
+            event.data = std::mem::take(&mut self.data_lines).join("\n");
+            return Some(event);
+        }
+
+        if line.starts_with(':') {
+            return None;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+            None => (line, ""),
+        };
+
+        match field {
+            "event" => self.current.event = Some(value.to_string()),
+            "id" => self.current.id = Some(value.to_string()),
+            "retry" => self.current.retry = value.parse().ok(),
+            "data" => self.data_lines.push(value.to_string()),
+            _ => return None,
+        }
+        self.has_fields = true;
+
+        None
+    }
+}
+
+/// Splits a byte stream into lines, keeping the unterminated tail buffered.
+#[derive(Default)]
+pub struct LineParser {
+    buffer: Vec<u8>,
+}
+
+impl LineParser {
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut lines = vec![];
+        while let Some(end) = self.buffer.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            lines.push(
+                String::from_utf8_lossy(&line)
+                    .trim_end_matches(['\n', '\r'])
+                    .to_string(),
+            );
+        }
+
+        lines
+    }
+
+    pub fn finish(&mut self) -> Option<String> {
+        if self.buffer.is_empty() {
+            None
+        } else {
+            let rest = std::mem::take(&mut self.buffer);
+            Some(String::from_utf8_lossy(&rest).into_owned())
+        }
+    }
+}
+
+pub enum StreamItem {
+    Event(SseEvent),
+    Line(String),
+}
+
+pub struct StreamEntry {
+    pub received_at: DateTime<Local>,
+    pub item: StreamItem,
+}
+
+enum StreamMessage {
+    Item(StreamEntry),
+    Finished,
+    Failed(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamState {
+    Receiving,
+    Finished,
+    Stopped,
+    Failed(String),
+}
+
+/// A response body that is read on a background thread and handed over
+/// entry by entry.
+pub struct ResponseStream {
+    pub status: String,
+    pub headers: Vec<(String, String)>,
+    pub content_type: Option<String>,
+    pub sent: SentRequest,
+    pub started: Instant,
+    pub format: StreamFormat,
+    pub entries: Vec<StreamEntry>,
+    pub state: StreamState,
+    receiver: Receiver<StreamMessage>,
+    stop: Arc<AtomicBool>,
+}
+
+impl ResponseStream {
+    pub fn start(response: OpenResponse, format: StreamFormat) -> Self {
+        let OpenResponse {
+            runtime,
+            mut response,
+            sent,
+            started,
+        } = response;
+        let status = response.status().to_string();
+        let headers = response
+            .headers()
+            .iter()
+            .map(|(key, value)| {
+                (
+                    key.to_string(),
+                    String::from_utf8_lossy(value.as_bytes()).into_owned(),
+                )
+            })
+            .collect();
+        let content_type = response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok())
+            .map(String::from);
+
+        let (tx, rx) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = Arc::clone(&stop);
+
+        // Dropping the response when the thread returns closes the
+        // connection, so a stopped stream does not linger on the server.
+        thread::spawn(move || {
+            let mut sse = SseParser::default();
+            let mut lines = LineParser::default();
+
+            loop {
+                let chunk = match runtime.block_on(async {
+                    tokio::time::timeout(STOP_CHECK_INTERVAL, response.chunk()).await
+                }) {
+                    _ if stopped.load(Ordering::Relaxed) => return,
+                    Err(_) => continue,
+                    Ok(Ok(chunk)) => chunk,
+                    Ok(Err(err)) => {
+                        let _ = tx.send(StreamMessage::Failed(err.to_string()));
+                        return;
+                    }
+                };
+
+                // An event cut off by the end of the body is dropped, as
+                // the event-stream spec asks.
+                let ended = chunk.is_none();
+                let items: Vec<_> = match (chunk, format) {
+                    (Some(chunk), StreamFormat::Sse) => sse
+                        .push(&chunk)
+                        .into_iter()
+                        .map(StreamItem::Event)
+                        .collect(),
+                    (Some(chunk), StreamFormat::Lines) => lines
+                        .push(&chunk)
+                        .into_iter()
+                        .map(StreamItem::Line)
+                        .collect(),
+                    (None, StreamFormat::Sse) => vec![],
+                    (None, StreamFormat::Lines) => {
+                        lines.finish().map(StreamItem::Line).into_iter().collect()
+                    }
+                };
+
+                let received_at = Local::now();
+                for item in items {
+                    let entry = StreamEntry { received_at, item };
+                    if tx.send(StreamMessage::Item(entry)).is_err() {
+                        return;
+                    }
+                }
+
+                if ended {
+                    let _ = tx.send(StreamMessage::Finished);
+                    return;
+                }
+            }
+        });
+
+        Self {
+            status,
+            headers,
+            content_type,
+            sent,
+            started,
+            format,
+            entries: vec![],
+            state: StreamState::Receiving,
+            receiver: rx,
+            stop,
+        }
+    }
+
+    /// Moves everything received since the last call into `entries`.
+    pub fn poll(&mut self) {
+        if self.state != StreamState::Receiving {
+            return;
+        }
+
+        loop {
+            match self.receiver.try_recv() {
+                Ok(StreamMessage::Item(entry)) => self.entries.push(entry),
+                Ok(StreamMessage::Finished) => {
+                    self.state = StreamState::Finished;
+                    break;
+                }
+                Ok(StreamMessage::Failed(err)) => {
+                    self.state = StreamState::Failed(err);
+                    break;
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.state = StreamState::Finished;
+                    break;
+                }
+            }
+        }
+    }
+
+    /// What was received so far, written the way it came in: events in the
+    /// event-stream format, lines one per line.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for entry in &self.entries {
+            match &entry.item {
+                StreamItem::Event(event) => {
+                    if let Some(name) = &event.event {
+                        text.push_str(&format!("event: {}\n", name));
+                    }
+                    if let Some(id) = &event.id {
+                        text.push_str(&format!("id: {}\n", id));
+                    }
+                    if let Some(retry) = event.retry {
+                        text.push_str(&format!("retry: {}\n", retry));
+                    }
+                    for line in event.data.split('\n') {
+                        text.push_str(&format!("data: {}\n", line));
+                    }
+                    text.push('\n');
+                }
+                StreamItem::Line(line) => {
+                    text.push_str(line);
+                    text.push('\n');
+                }
+            }
+        }
+        text
+    }
+
+    /// Stops listening right away. The reader notices within
+    /// `STOP_CHECK_INTERVAL` and closes the connection.
+    pub fn stop(&mut self) {
+        if self.state == StreamState::Receiving {
+            self.stop.store(true, Ordering::Relaxed);
+            self.state = StreamState::Stopped;
+        }
+    }
+}
+
+impl Drop for ResponseStream {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+#[allow(dead_code)]
+#[path = "../examples/stream_stub.rs"]
+mod stream_stub;
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        time::Instant,
+    };
+
+    use super::*;
+
+    fn open(url: &str) -> OpenResponse {
+        OpenResponse::send(reqwest::Client::new().get(url)).unwrap()
+    }
+
+    fn start_stub() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || stream_stub::run(listener));
+        format!("http://127.0.0.1:{}", port)
+    }
+
+    fn poll_until(stream: &mut ResponseStream, done: impl Fn(&ResponseStream) -> bool) {
+        let started = Instant::now();
+        while !done(stream) && started.elapsed() < Duration::from_secs(10) {
+            thread::sleep(Duration::from_millis(20));
+            stream.poll();
+        }
+    }
+
+    #[test]
+    fn splits_events_across_chunks() {
+        let mut parser = SseParser::default();
+
+        assert!(parser
+            .push(b": comment\n\nevent: tick\nid: 7\nda")
+            .is_empty());
+        let events = parser.push(b"ta: {\"a\": 1}\r\ndata:second\r\nretry: 300\r\n\r\ndata");
+        assert_eq!(
+            events,
+            vec![SseEvent {
+                event: Some(String::from("tick")),
+                id: Some(String::from("7")),
+                retry: Some(300),
+                data: String::from("{\"a\": 1}\nsecond"),
+            }]
+        );
+
+        assert!(parser.push(b": \xc3").is_empty());
+        let events = parser.push(b"\xa9\n\n");
+        assert_eq!(events[0].data, "\u{e9}");
+        assert_eq!(events[0].event, None);
+    }
+
+    #[test]
+    fn splits_lines_and_keeps_the_tail() {
+        let mut parser = LineParser::default();
+
+        assert_eq!(parser.push(b"{\"id\": 1}\r\n{\"id\""), vec!["{\"id\": 1}"]);
+        assert_eq!(parser.push(b": 2}\n"), vec!["{\"id\": 2}"]);
+        assert_eq!(parser.push(b"tail"), Vec::<String>::new());
+        assert_eq!(parser.finish(), Some(String::from("tail")));
+        assert_eq!(parser.finish(), None);
+    }
+
+    #[test]
+    fn detects_streamed_content_types() {
+        assert_eq!(
+            StreamFormat::for_content_type(Some("text/event-stream; charset=utf-8")),
+            Some(StreamFormat::Sse)
+        );
+        assert_eq!(
+            StreamFormat::for_content_type(Some("Application/X-NDJSON")),
+            Some(StreamFormat::Lines)
+        );
+        assert_eq!(
+            StreamFormat::for_content_type(Some("application/json")),
+            None
+        );
+    }
+
+    #[test]
+    fn hands_over_events_as_they_arrive() {
+        let url = start_stub();
+        let mut stream = ResponseStream::start(open(&format!("{}/sse", url)), StreamFormat::Sse);
+
+        poll_until(&mut stream, |stream| !stream.entries.is_empty());
+        assert_eq!(stream.state, StreamState::Receiving);
+        assert_eq!(stream.entries.len(), 1);
+        match &stream.entries[0].item {
+            StreamItem::Event(event) => assert_eq!(event.data, "message 1"),
+            StreamItem::Line(_) => panic!("expected an event"),
+        }
+
+        poll_until(&mut stream, |stream| stream.entries.len() >= 2);
+        assert!(stream.entries[1].received_at > stream.entries[0].received_at);
+        stream.stop();
+        assert_eq!(stream.state, StreamState::Stopped);
+    }
+
+    #[test]
+    fn finishes_when_the_body_ends() {
+        let url = start_stub();
+        let mut stream =
+            ResponseStream::start(open(&format!("{}/ndjson", url)), StreamFormat::Lines);
+
+        poll_until(&mut stream, |stream| stream.state != StreamState::Receiving);
+        assert_eq!(stream.state, StreamState::Finished);
+        assert_eq!(stream.entries.len(), 10);
+        assert!(stream
+            .text()
+            .ends_with("{\"id\": 10, \"status\": \"ok\"}\n"));
+        assert_eq!(stream.sent.method, "GET");
+    }
+
+    #[test]
+    fn writes_events_back_in_the_event_stream_format() {
+        let url = start_stub();
+        let mut stream = ResponseStream::start(open(&format!("{}/sse", url)), StreamFormat::Sse);
+
+        poll_until(&mut stream, |stream| !stream.entries.is_empty());
+        stream.stop();
+        assert!(stream.text().contains("data: message 1\n\n"));
+    }
+
+    #[test]
+    fn drops_an_unfinished_event_at_the_end() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let _ = socket.read(&mut [0; 1024]);
+            let body = "data: complete\n\ndata: cut off";
+            let _ = write!(
+                socket,
+                "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ncontent-length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            );
+        });
+        let mut stream = ResponseStream::start(open(&url), StreamFormat::Sse);
+
+        poll_until(&mut stream, |stream| stream.state != StreamState::Receiving);
+        assert_eq!(stream.state, StreamState::Finished);
+        assert_eq!(stream.entries.len(), 1);
+    }
+
+    #[test]
+    fn stop_closes_the_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let (closed_tx, closed_rx) = mpsc::channel();
+        thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let _ = socket.read(&mut [0; 1024]);
+            let _ = write!(
+                socket,
+                "HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\ntransfer-encoding: chunked\r\n\r\n"
+            );
+            // Nothing more is sent, so only the client can end this.
+            let closed = matches!(socket.read(&mut [0; 16]), Ok(0) | Err(_));
+            let _ = closed_tx.send(closed);
+        });
+
+        let mut stream = ResponseStream::start(open(&url), StreamFormat::Sse);
+        stream.stop();
+
+        assert_eq!(closed_rx.recv_timeout(Duration::from_secs(5)), Ok(true));
+    }
+}
@@ -1,26 +1,66 @@
 use core::fmt::{self, Display, Formatter};
 use core::str::FromStr;
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql},
+    serialize::{self, Output, ToSql},
+    sql_types::Text,
+};
 use diesel_derive_enum::DbEnum;
+use std::io::Write;
 
-#[derive(Debug, Copy, Clone, PartialEq, DbEnum)]
+/// An HTTP request method. The well-known methods are stored in lowercase,
+/// custom verbs such as `PROPFIND` are stored exactly as entered (uppercase).
+#[derive(Debug, Clone, PartialEq, AsExpression, FromSqlRow)]
+#[sql_type = "Text"]
 pub enum HttpMethod {
     Get,
     Post,
     Put,
     Patch,
     Delete,
+    Head,
+    Options,
+    Trace,
+    Connect,
+    Custom(String),
 }
 
-impl Display for HttpMethod {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        let repr = match self {
+impl HttpMethod {
+    /// Every method except `Custom`, in the order they are offered in the picker.
+    pub fn standard() -> Vec<HttpMethod> {
+        vec![
+            HttpMethod::Get,
+            HttpMethod::Post,
+            HttpMethod::Put,
+            HttpMethod::Patch,
+            HttpMethod::Delete,
+            HttpMethod::Head,
+            HttpMethod::Options,
+            HttpMethod::Trace,
+            HttpMethod::Connect,
+        ]
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
             HttpMethod::Get => "GET",
             HttpMethod::Post => "POST",
             HttpMethod::Put => "PUT",
             HttpMethod::Patch => "PATCH",
             HttpMethod::Delete => "DELETE",
-        };
-        write!(f, "{}", repr)
+            HttpMethod::Head => "HEAD",
+            HttpMethod::Options => "OPTIONS",
+            HttpMethod::Trace => "TRACE",
+            HttpMethod::Connect => "CONNECT",
+            HttpMethod::Custom(verb) => verb,
+        }
+    }
+}
+
+impl Display for HttpMethod {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.as_str())
     }
 }
 
@@ -29,26 +69,138 @@ type HttpMethodErr = String;
 impl FromStr for HttpMethod {
     type Err = HttpMethodErr;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
-            "GET" => Ok(HttpMethod::Get),
-            "POST" => Ok(HttpMethod::Post),
-            "PUT" => Ok(HttpMethod::Put),
-            "PATCH" => Ok(HttpMethod::Patch),
-            "DELETE" => Ok(HttpMethod::Delete),
-            x => Err(format!("Unrecognized HTTP method: {}", x).into()),
+        let verb = s.trim().to_uppercase();
+
+        if let Some(method) = HttpMethod::standard()
+            .into_iter()
+            .find(|method| method.as_str() == verb)
+        {
+            return Ok(method);
         }
+
+        // RFC 7230 `token` characters
+        let is_token = |c: char| c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c);
+
+        if !verb.is_empty() && verb.chars().all(is_token) {
+            Ok(HttpMethod::Custom(verb))
+        } else {
+            Err(format!("Unrecognized HTTP method: {}", s))
+        }
+    }
+}
+
+impl<DB> ToSql<Text, DB> for HttpMethod
+where
+    DB: Backend,
+    str: ToSql<Text, DB>,
+{
+    fn to_sql<W: Write>(&self, out: &mut Output<W, DB>) -> serialize::Result {
+        match self {
+            HttpMethod::Custom(verb) => verb.as_str().to_sql(out),
+            method => method.as_str().to_lowercase().as_str().to_sql(out),
+        }
+    }
+}
+
+impl<DB> FromSql<Text, DB> for HttpMethod
+where
+    DB: Backend,
+    String: FromSql<Text, DB>,
+{
+    fn from_sql(bytes: Option<&DB::RawValue>) -> deserialize::Result<Self> {
+        Ok(String::from_sql(bytes)?.parse()?)
+    }
+}
+
+/// What protocol a stored request speaks.
+#[derive(Debug, Copy, Clone, PartialEq, DbEnum)]
+pub enum RequestKind {
+    Http,
+    Graphql,
+    Websocket,
+    Grpc,
+}
+
+impl RequestKind {
+    /// Picks the kind from the scheme of an absolute URL.
+    pub fn for_route(route: &str) -> Self {
+        let route = route.trim().to_lowercase();
+        if route.starts_with("ws://") || route.starts_with("wss://") {
+            RequestKind::Websocket
+        } else if route.starts_with("grpc://") || route.starts_with("grpcs://") {
+            RequestKind::Grpc
+        } else {
+            RequestKind::Http
+        }
+    }
+}
+
+impl Display for RequestKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let repr = match self {
+            RequestKind::Http => "HTTP",
+            RequestKind::Graphql => "GraphQL",
+            RequestKind::Websocket => "WebSocket",
+            RequestKind::Grpc => "gRPC",
+        };
+        write!(f, "{}", repr)
     }
 }
 
-// impl<DB> FromSql<Text, DB> for HttpMethod where DB: Backend, String: FromSql<Text, DB> {
-//     fn from_sql(bytes: Option<&DB::RawValue>) -> diesel::deserialize::Result<Self> {
-//         match String::from_sql(bytes)?.as_str() {
-//             "GET" => Ok(HttpMethod::Get),
-//             "POST" => Ok(HttpMethod::Post),
-//             "PUT" => Ok(HttpMethod::Put),
-//             "PATCH" => Ok(HttpMethod::Patch),
-//             "DELETE" => Ok(HttpMethod::Delete),
-//             x => Err(format!("Unrecognized HTTP method: {}", x).into())
-//         }
-//     }
-// }
+/// What an expanded row of the collection tree stands for.
+#[derive(Debug, Copy, Clone, PartialEq, DbEnum)]
+pub enum TreeNodeKind {
+    Api,
+    Resource,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, DbEnum)]
+pub enum FormPartKind {
+    Text,
+    File,
+}
+
+impl Display for FormPartKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let repr = match self {
+            FormPartKind::Text => "Text",
+            FormPartKind::File => "File",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+/// The lowest TLS version a connection may use. There is no 1.3, as
+/// native-tls cannot require it.
+#[derive(Debug, Copy, Clone, PartialEq, DbEnum)]
+pub enum TlsVersion {
+    Tls10,
+    Tls11,
+    Tls12,
+}
+
+impl Display for TlsVersion {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let repr = match self {
+            TlsVersion::Tls10 => "TLS 1.0",
+            TlsVersion::Tls11 => "TLS 1.1",
+            TlsVersion::Tls12 => "TLS 1.2",
+        };
+        write!(f, "{}", repr)
+    }
+}
+
+impl FromStr for TlsVersion {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_uppercase().trim_start_matches("TLS").trim() {
+            "1.0" | "10" => Ok(TlsVersion::Tls10),
+            "1.1" | "11" => Ok(TlsVersion::Tls11),
+            "1.2" | "12" => Ok(TlsVersion::Tls12),
+            "1.3" | "13" => Err(String::from(
+                "TLS 1.3 cannot be required, the TLS library only enforces up to 1.2",
+            )),
+            x => Err(format!("Unrecognized TLS version: {}", x)),
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    io::ErrorKind,
+    net::TcpStream,
+    sync::mpsc::{self, Receiver, Sender, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local};
+use tungstenite::{
+    client::IntoClientRequest,
+    http::{HeaderName, HeaderValue},
+    stream::MaybeTlsStream,
+    Connector, Message, WebSocket,
+};
+
+use crate::{http, models::TlsConfig};
+
+/// How long a read on the socket waits before outgoing messages get a turn.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum LogDirection {
+    Sent,
+    Received,
+    Info,
+}
+
+pub struct LogEntry {
+    pub at: DateTime<Local>,
+    pub direction: LogDirection,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionState {
+    Connecting,
+    Connected,
+    Closed,
+}
+
+enum Outgoing {
+    Message(Message),
+    Close,
+}
+
+enum SessionEvent {
+    Connected,
+    Log(LogDirection, String),
+    Closed(Option<String>),
+}
+
+/// A WebSocket connection running on a background thread. Messages are
+/// queued with the `send_*` methods and everything that happens on the
+/// socket ends up in `log`.
+pub struct WebSocketSession {
+    pub state: SessionState,
+    pub log: Vec<LogEntry>,
+    outgoing: Sender<Outgoing>,
+    events: Receiver<SessionEvent>,
+}
+
+impl WebSocketSession {
+    pub fn connect(url: String, headers: Vec<(String, String)>, tls: Option<TlsConfig>) -> Self {
+        let (outgoing_tx, outgoing_rx) = mpsc::channel();
+        let (events_tx, events_rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            let mut socket = match open(&url, &headers, tls.as_ref()) {
+                Ok((socket, status)) => {
+                    let _ = events_tx.send(SessionEvent::Connected);
+                    let _ = events_tx.send(SessionEvent::Log(
+                        LogDirection::Info,
+                        format!("Connected to {} ({})", url, status),
+                    ));
+                    socket
+                }
+                Err(err) => {
+                    let _ = events_tx.send(SessionEvent::Closed(Some(err)));
+                    return;
+                }
+            };
+
+            let closed = run(&mut socket, &outgoing_rx, &events_tx);
+            let _ = events_tx.send(SessionEvent::Closed(closed));
+        });
+
+        let mut session = Self {
+            state: SessionState::Connecting,
+            log: vec![],
+            outgoing: outgoing_tx,
+            events: events_rx,
+        };
+        session.push_log(LogDirection::Info, String::from("Connecting..."));
+        session
+    }
+
+    pub fn send_text(&self, text: String) {
+        let _ = self.outgoing.send(Outgoing::Message(Message::Text(text)));
+    }
+
+    pub fn send_binary(&self, bytes: Vec<u8>) {
+        let _ = self
+            .outgoing
+            .send(Outgoing::Message(Message::Binary(bytes)));
+    }
+
+    pub fn close(&self) {
+        let _ = self.outgoing.send(Outgoing::Close);
+    }
+
+    /// Moves everything that happened since the last call into `log`.
+    pub fn poll(&mut self) {
+        loop {
+            match self.events.try_recv() {
+                Ok(SessionEvent::Connected) => self.state = SessionState::Connected,
+                Ok(SessionEvent::Log(direction, text)) => self.push_log(direction, text),
+                Ok(SessionEvent::Closed(reason)) => {
+                    self.state = SessionState::Closed;
+                    let text = match reason {
+                        Some(reason) => format!("Connection closed: {}", reason),
+                        None => String::from("Connection closed"),
+                    };
+                    self.push_log(LogDirection::Info, text);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.state = SessionState::Closed;
+                    break;
+                }
+            }
+        }
+    }
+
+    fn push_log(&mut self, direction: LogDirection, text: String) {
+        self.log.push(LogEntry {
+            at: Local::now(),
+            direction,
+            text,
+        });
+    }
+}
+
+impl Drop for WebSocketSession {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+type Socket = WebSocket<MaybeTlsStream<TcpStream>>;
+
+/// Performs the handshake, sending `headers` along with the upgrade request.
+fn open(
+    url: &str,
+    headers: &[(String, String)],
+    tls: Option<&TlsConfig>,
+) -> Result<(Socket, String), String> {
+    let mut request = url.into_client_request().map_err(|err| err.to_string())?;
+
+    for (key, value) in headers {
+        let name = HeaderName::from_bytes(key.as_bytes())
+            .map_err(|_| format!("Invalid header name {}", key))?;
+        let value = HeaderValue::from_str(value)
+            .map_err(|_| format!("Invalid value for header {}", key))?;
+        request.headers_mut().append(name, value);
+    }
+
+    let uri = request.uri().clone();
+    let secure = uri.scheme_str() == Some("wss");
+    let host = uri.host().ok_or_else(|| format!("No host in {}", url))?;
+    let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+
+    let stream = TcpStream::connect((host, port)).map_err(|err| err.to_string())?;
+
+    let connector = match tls {
+        Some(tls) if secure => Some(Connector::NativeTls(
+            http::native_tls_connector(tls).map_err(|err| err.to_string())?,
+        )),
+        _ => None,
+    };
+
+    let (mut socket, response) =
+        tungstenite::client_tls_with_config(request, stream, None, connector)
+            .map_err(|err| err.to_string())?;
+
+    let stream = match socket.get_mut() {
+        MaybeTlsStream::Plain(stream) => Some(stream),
+        MaybeTlsStream::NativeTls(stream) => Some(stream.get_mut()),
+        _ => None,
+    };
+    if let Some(stream) = stream {
+        stream
+            .set_read_timeout(Some(POLL_INTERVAL))
+            .map_err(|err| err.to_string())?;
+    }
+
+    Ok((socket, response.status().to_string()))
+}
+
+/// Alternates between flushing queued outgoing messages and reading from
+/// the socket until the connection ends. Returns why it ended, if it was
+/// not a regular close.
+fn run(
+    socket: &mut Socket,
+    outgoing: &Receiver<Outgoing>,
+    events: &Sender<SessionEvent>,
+) -> Option<String> {
+    let log = |direction, text| {
+        let _ = events.send(SessionEvent::Log(direction, text));
+    };
+
+    loop {
+        loop {
+            match outgoing.try_recv() {
+                Ok(Outgoing::Message(message)) => {
+                    let text = describe(&message);
+                    if let Err(err) = socket.send(message) {
+                        return Some(err.to_string());
+                    }
+                    log(LogDirection::Sent, text);
+                }
+                Ok(Outgoing::Close) | Err(TryRecvError::Disconnected) => {
+                    let _ = socket.close(None);
+                    let _ = socket.flush();
+                    return None;
+                }
+                Err(TryRecvError::Empty) => break,
+            }
+        }
+
+        match socket.read() {
+            Ok(Message::Close(frame)) => {
+                let reason = frame.map(|frame| format!("{} {}", frame.code, frame.reason));
+                log(
+                    LogDirection::Info,
+                    format!(
+                        "Server closed the connection {}",
+                        reason.unwrap_or_default()
+                    ),
+                );
+            }
+            Ok(Message::Frame(_)) => (),
+            Ok(message) => log(LogDirection::Received, describe(&message)),
+            Err(tungstenite::Error::Io(err))
+                if matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(tungstenite::Error::ConnectionClosed) | Err(tungstenite::Error::AlreadyClosed) => {
+                return None
+            }
+            Err(err) => return Some(err.to_string()),
+        }
+    }
+}
+
+fn describe(message: &Message) -> String {
+    match message {
+        Message::Text(text) => text.clone(),
+        Message::Binary(bytes) => format!("binary, {} bytes: {}", bytes.len(), to_hex(bytes, 32)),
+        Message::Ping(bytes) => format!("ping {}", to_hex(bytes, 16)),
+        Message::Pong(bytes) => format!("pong {}", to_hex(bytes, 16)),
+        Message::Close(_) => String::from("close"),
+        Message::Frame(_) => String::from("frame"),
+    }
+}
+
+fn to_hex(bytes: &[u8], limit: usize) -> String {
+    let mut hex: Vec<_> = bytes
+        .iter()
+        .take(limit)
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    if bytes.len() > limit {
+        hex.push(String::from("..."));
+    }
+    hex.join(" ")
+}
+
+/// Parses hex input such as `de ad be ef` or `deadbeef` into bytes.
+// `usize::is_multiple_of` needs Rust 1.87.
+#[allow(clippy::manual_is_multiple_of)]
+pub fn parse_hex(input: &str) -> Result<Vec<u8>, String> {
+    let digits: String = input.chars().filter(|c| !c.is_whitespace()).collect();
+
+    if !digits.is_ascii() {
+        return Err(String::from("Hex input may only contain 0-9 and a-f"));
+    }
+    if digits.len() % 2 != 0 {
+        return Err(String::from("Hex input needs an even number of digits"));
+    }
+
+    (0..digits.len())
+        .step_by(2)
+        .map(|index| {
+            u8::from_str_radix(&digits[index..index + 2], 16)
+                .map_err(|_| format!("Invalid hex byte {}", &digits[index..index + 2]))
+        })
+        .collect()
+}